@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{Money, Quantity};
+
+/// Formatos de código de barras GTIN, pelo número de dígitos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GtinKind {
+    /// EAN-8 (GTIN-8)
+    Ean8,
+    /// UPC-A (GTIN-12)
+    UpcA,
+    /// EAN-13 (GTIN-13)
+    Ean13,
+    /// DUN-14 / GTIN-14, usado em caixas
+    Gtin14,
+}
+
+impl GtinKind {
+    /// Formato pelo tamanho, sem conferir o dígito verificador
+    pub fn of(code: &str) -> Option<Self> {
+        if !code.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        match code.len() {
+            8 => Some(GtinKind::Ean8),
+            12 => Some(GtinKind::UpcA),
+            13 => Some(GtinKind::Ean13),
+            14 => Some(GtinKind::Gtin14),
+            _ => None,
+        }
+    }
+}
+
+/// Dígito verificador (módulo 10, pesos 3 e 1 a partir da direita) dos dígitos
+/// de `body`, que não inclui o próprio verificador
+pub fn check_digit(body: &str) -> Option<u32> {
+    let sum = body.bytes().rev().enumerate().try_fold(0u32, |sum, (i, b)| {
+        let digit = (b as char).to_digit(10)?;
+        Some(sum + if i % 2 == 0 { digit * 3 } else { digit })
+    })?;
+    Some((10 - sum % 10) % 10)
+}
+
+/// Confere formato e dígito verificador de um EAN-8, UPC-A, EAN-13 ou GTIN-14
+pub fn is_valid_gtin(code: &str) -> bool {
+    GtinKind::of(code).is_some() && {
+        let (body, check) = code.split_at(code.len() - 1);
+        check_digit(body) == check.parse().ok()
+    }
+}
+
+/// O que vem embutido numa etiqueta de balança
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaleValueKind {
+    /// Preço total em centavos
+    #[default]
+    Price,
+    /// Peso em gramas
+    Weight,
+}
+
+impl ScaleValueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScaleValueKind::Price => "price",
+            ScaleValueKind::Weight => "weight",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "price" => Some(ScaleValueKind::Price),
+            "weight" => Some(ScaleValueKind::Weight),
+            _ => None,
+        }
+    }
+}
+
+/// Leiaute das etiquetas de balança (EAN-13 iniciado em `2`).
+///
+/// O código do produto começa logo depois do prefixo e o valor termina logo
+/// antes do dígito verificador; o que sobrar entre os dois (ex. o dígito
+/// verificador do valor, em algumas balanças) é ignorado. O leiaute padrão,
+/// `2 CCCC 0 VVVVVV D`, é o das balanças Toledo e Filizola.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScaleLayout {
+    pub code_digits: usize,
+    pub value_digits: usize,
+    pub kind: ScaleValueKind,
+}
+
+impl Default for ScaleLayout {
+    fn default() -> Self {
+        Self { code_digits: 4, value_digits: 6, kind: ScaleValueKind::Price }
+    }
+}
+
+impl ScaleLayout {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(4..=6).contains(&self.code_digits) || !(4..=6).contains(&self.value_digits) {
+            return Err("Código e valor da etiqueta devem ter de 4 a 6 dígitos".to_string());
+        }
+        if self.code_digits + self.value_digits > 11 {
+            return Err("Código e valor da etiqueta não cabem em 11 dígitos".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Valor lido de uma etiqueta de balança
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum ScaleValue {
+    Price(Money),
+    Weight(Quantity),
+}
+
+/// Etiqueta de balança decodificada
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScaleLabel {
+    /// Código do produto como impresso (com zeros à esquerda)
+    pub code: String,
+    pub value: ScaleValue,
+}
+
+/// Decodifica uma etiqueta de balança: EAN-13 válido com prefixo `2`.
+/// Retorna `None` para qualquer outro código.
+pub fn decode_scale_label(barcode: &str, layout: &ScaleLayout) -> Option<ScaleLabel> {
+    if GtinKind::of(barcode) != Some(GtinKind::Ean13) || !barcode.starts_with('2') || !is_valid_gtin(barcode) {
+        return None;
+    }
+    layout.validate().ok()?;
+
+    let code = &barcode[1..1 + layout.code_digits];
+    let raw: i64 = barcode[12 - layout.value_digits..12].parse().ok()?;
+    let value = match layout.kind {
+        ScaleValueKind::Price => ScaleValue::Price(Money::from_cents(raw)),
+        ScaleValueKind::Weight => ScaleValue::Weight(Quantity::from_units(raw)),
+    };
+
+    Some(ScaleLabel { code: code.to_string(), value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gtin_check_digits() {
+        assert!(is_valid_gtin("7891000100103"));
+        assert!(is_valid_gtin("96385074"));
+        assert!(is_valid_gtin("036000291452"));
+        assert!(is_valid_gtin("17891000100100"));
+        assert!(!is_valid_gtin("7891000100104"));
+        assert!(!is_valid_gtin("789100010010"));
+        assert!(!is_valid_gtin("78910001001O3"));
+        assert_eq!(GtinKind::of("036000291452"), Some(GtinKind::UpcA));
+    }
+
+    #[test]
+    fn test_decode_scale_label() {
+        // 2 0123 0 001590 D: produto 0123, R$ 15,90
+        let body = "2012300015900";
+        let barcode = format!("{}{}", &body[..12], check_digit(&body[..12]).unwrap());
+        let label = decode_scale_label(&barcode, &ScaleLayout::default()).unwrap();
+        assert_eq!(label, ScaleLabel { code: "0123".to_string(), value: ScaleValue::Price(Money::from_cents(1590)) });
+
+        // Mesmo código lido como 5 dígitos de código e 5 de peso em gramas
+        let layout = ScaleLayout { code_digits: 5, value_digits: 5, kind: ScaleValueKind::Weight };
+        let label = decode_scale_label(&barcode, &layout).unwrap();
+        assert_eq!(label, ScaleLabel { code: "01230".to_string(), value: ScaleValue::Weight(Quantity::from_units(1590)) });
+
+        assert_eq!(decode_scale_label("7891000100103", &ScaleLayout::default()), None);
+        let invalid = format!("{}{}", &body[..12], (check_digit(&body[..12]).unwrap() + 1) % 10);
+        assert_eq!(decode_scale_label(&invalid, &ScaleLayout::default()), None);
+        assert!(ScaleLayout { code_digits: 6, value_digits: 6, kind: ScaleValueKind::Price }.validate().is_err());
+    }
+}
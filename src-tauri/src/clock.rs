@@ -0,0 +1,170 @@
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Fuso horário padrão da loja
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::America::Sao_Paulo;
+
+/// Fonte única de "agora" e do fuso horário da loja.
+///
+/// Todos os instantes são gravados em UTC (milissegundos desde a época); o fuso
+/// só é usado para decidir a que dia local um instante pertence, como em
+/// "vendas de hoje" ou nos intervalos `YYYY-MM-DD` das consultas.
+pub trait Clock: Send + Sync {
+    /// Instante atual
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Fuso horário da loja
+    fn timezone(&self) -> Tz;
+
+    /// Data local de um instante
+    fn local_date(&self, instant: DateTime<Utc>) -> NaiveDate {
+        instant.with_timezone(&self.timezone()).date_naive()
+    }
+
+    /// Data local de hoje
+    fn today(&self) -> NaiveDate {
+        self.local_date(self.now())
+    }
+
+    /// Primeiro instante do dia local `date`
+    fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.local_to_utc(date.and_time(NaiveTime::MIN))
+    }
+
+    /// Intervalo `[início de from, início do dia seguinte a to)` em UTC
+    fn day_range(&self, from: NaiveDate, to: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let end = to.checked_add_days(Days::new(1)).unwrap_or(to);
+        (self.start_of_day(from), self.start_of_day(end))
+    }
+
+    /// Converte uma data/hora local (sem fuso) para UTC.
+    ///
+    /// Horários inexistentes (início do horário de verão) avançam para o
+    /// primeiro instante válido; horários ambíguos usam a primeira ocorrência.
+    fn local_to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let tz = self.timezone();
+        let mut candidate = local;
+        for _ in 0..4 {
+            if let Some(instant) = tz.from_local_datetime(&candidate).earliest() {
+                return instant.with_timezone(&Utc);
+            }
+            candidate += chrono::Duration::minutes(30);
+        }
+        Utc.from_utc_datetime(&local)
+    }
+
+    /// Interpreta uma data/hora textual: RFC 3339 com fuso (`dh_emi` dos
+    /// documentos fiscais) ou data/hora local sem fuso
+    fn parse_instant(&self, value: &str) -> Option<DateTime<Utc>> {
+        let value = value.trim();
+        if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+            return Some(instant.with_timezone(&Utc));
+        }
+
+        const LOCAL_FORMATS: [&str; 4] = [
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+        ];
+        LOCAL_FORMATS.iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .or_else(|| parse_date(value).ok().map(|date| date.and_time(NaiveTime::MIN)))
+            .map(|local| self.local_to_utc(local))
+    }
+}
+
+/// Relógio do sistema com o fuso configurado
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    timezone: Tz,
+}
+
+impl SystemClock {
+    pub fn new(timezone: Tz) -> Self {
+        Self { timezone }
+    }
+}
+
+/// Interpreta o nome IANA de um fuso (ex. `America/Manaus`)
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim().parse::<Tz>()
+        .map_err(|_| format!("Fuso horário '{}' inválido; use um nome IANA, ex. America/Manaus", name))
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMEZONE)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn timezone(&self) -> Tz {
+        self.timezone
+    }
+}
+
+/// Relógio parado num instante, para testes
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    pub now: DateTime<Utc>,
+    pub timezone: Tz,
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    fn timezone(&self) -> Tz {
+        self.timezone
+    }
+}
+
+/// Converte milissegundos UTC (formato gravado no banco) em instante
+pub fn from_millis(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+/// Interpreta uma data `YYYY-MM-DD` dos filtros de intervalo
+pub fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock_at(rfc3339: &str) -> FixedClock {
+        FixedClock {
+            now: DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc),
+            timezone: DEFAULT_TIMEZONE,
+        }
+    }
+
+    #[test]
+    fn test_today_follows_store_timezone() {
+        // 22:30 em São Paulo já é o dia seguinte em UTC
+        let clock = clock_at("2024-06-16T01:30:00Z");
+        assert_eq!(clock.today(), NaiveDate::from_ymd_opt(2024, 6, 15).unwrap());
+        assert_eq!(clock.start_of_day(clock.today()).to_rfc3339(), "2024-06-15T03:00:00+00:00");
+    }
+
+    #[test]
+    fn test_day_range_is_half_open() {
+        let clock = clock_at("2024-06-15T12:00:00Z");
+        let day = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let (start, end) = clock.day_range(day, day);
+        assert_eq!(end - start, chrono::Duration::hours(24));
+
+        let late_sale = clock.parse_instant("2024-06-15T23:59:00-03:00").unwrap();
+        assert!(late_sale >= start && late_sale < end);
+        assert_eq!(clock.parse_instant("2024-06-15 10:30:00").unwrap().to_rfc3339(), "2024-06-15T13:30:00+00:00");
+        assert!(clock.parse_instant("ontem").is_none());
+    }
+}
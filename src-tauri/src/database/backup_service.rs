@@ -0,0 +1,436 @@
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{Connection, OpenFlags};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult, ResultExt};
+use super::migrations;
+use super::SqliteDbService;
+
+const BACKUP_PREFIX: &str = "rabbit-";
+const BACKUP_EXTENSION: &str = "sqlite";
+/// Sufixo das cópias feitas automaticamente antes de uma restauração
+const PRE_RESTORE_SUFFIX: &str = "-pre-restore";
+/// Espera entre tentativas quando o banco está ocupado
+const BUSY_WAIT: Duration = Duration::from_millis(50);
+/// Tentativas antes de desistir de um banco ocupado (30 s no total)
+const BUSY_RETRIES: u32 = 600;
+
+/// Configuração das cópias de segurança
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    /// Diretório onde as cópias são gravadas
+    pub dir: PathBuf,
+    /// Quantidade de cópias agendadas ou manuais mantidas (as mais antigas são removidas)
+    pub keep: usize,
+    /// Quantidade de cópias `pre-restore` mantidas, contadas à parte
+    pub keep_pre_restore: usize,
+    /// Intervalo entre cópias agendadas (`None` desativa o agendamento)
+    pub interval: Option<Duration>,
+}
+
+impl BackupConfig {
+    /// Lê a configuração das variáveis de ambiente:
+    /// `SQLITE_BACKUP_DIR` (padrão: pasta `backups` ao lado do banco),
+    /// `SQLITE_BACKUP_KEEP` (padrão: 14), `SQLITE_BACKUP_KEEP_PRE_RESTORE`
+    /// (padrão: 5) e `SQLITE_BACKUP_INTERVAL_MINUTES` (padrão: 360; 0 desativa
+    /// o agendamento)
+    pub fn from_env(db_path: &Path) -> Self {
+        let dir = std::env::var("SQLITE_BACKUP_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                db_path.parent().unwrap_or_else(|| Path::new(".")).join("backups")
+            });
+
+        let keep = std::env::var("SQLITE_BACKUP_KEEP")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(14);
+
+        let keep_pre_restore = std::env::var("SQLITE_BACKUP_KEEP_PRE_RESTORE")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(5);
+
+        let minutes: u64 = std::env::var("SQLITE_BACKUP_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(360);
+
+        Self {
+            dir,
+            keep: keep.max(1),
+            keep_pre_restore: keep_pre_restore.max(1),
+            interval: (minutes > 0).then(|| Duration::from_secs(minutes * 60)),
+        }
+    }
+}
+
+/// Uma cópia de segurança gravada em disco
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub schema_version: i64,
+    pub created_at: DateTime<Utc>,
+    /// Preenchido quando o arquivo não pôde ser lido (ex. corrompido)
+    pub error: Option<String>,
+}
+
+/// Cópias de segurança do banco usando a API de backup online do SQLite,
+/// que copia um snapshot consistente sem parar o aplicativo
+#[derive(Clone)]
+pub struct BackupService {
+    db: SqliteDbService,
+    config: BackupConfig,
+}
+
+impl BackupService {
+    pub fn new(db: SqliteDbService, config: BackupConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
+    /// Grava uma nova cópia, verifica a integridade do arquivo gerado e
+    /// aplica a rotação
+    pub fn create_backup(&self) -> AppResult<BackupInfo> {
+        let info = self.write_snapshot("")?;
+        self.rotate()?;
+        Ok(info)
+    }
+
+    /// Lista as cópias existentes, mais recentes primeiro. Arquivos ilegíveis
+    /// aparecem com `error` preenchido, sem interromper a listagem.
+    pub fn list_backups(&self) -> AppResult<Vec<BackupInfo>> {
+        if !self.config.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(&self.config.dir)
+            .context("Failed to read backup directory")?;
+
+        let mut backups = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if Self::is_backup_file(&path) {
+                backups.push(Self::describe_or_flag(&path));
+            }
+        }
+
+        backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+        Ok(backups)
+    }
+
+    /// Restaura uma cópia sobre o banco em uso.
+    ///
+    /// A cópia é verificada antes, e o estado atual é salvo numa cópia
+    /// `pre-restore` para que a operação possa ser desfeita. Ao final as
+    /// migrações são reaplicadas, trazendo cópias antigas para o schema atual.
+    pub fn restore_backup(&self, file_name: &str) -> AppResult<BackupInfo> {
+        let path = self.resolve(file_name)?;
+        let info = Self::verify(&path)?;
+
+        self.write_snapshot(PRE_RESTORE_SUFFIX)?;
+
+        let source = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| AppError::from(e).context(&format!("Failed to open backup {}", file_name)))?;
+        let mut target = self.db.get_connection()?;
+
+        {
+            let restore = Backup::new(&source, &mut target)
+                .context("Failed to start restore")?;
+            Self::copy_all_pages(&restore, BUSY_RETRIES)
+                .map_err(|e| e.context(&format!("Failed to restore backup {}", file_name)))?;
+        }
+
+        migrations::run_migrations(&mut target).map_err(AppError::Database)?;
+        self.rotate()?;
+
+        Ok(info)
+    }
+
+    /// Executa cópias periodicamente conforme `config.interval`
+    pub async fn run_scheduler(self) {
+        let Some(interval) = self.config.interval else {
+            return;
+        };
+
+        let mut ticker = tokio::time::interval(interval);
+        // O primeiro tick é imediato; a primeira cópia sai após um intervalo
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let service = self.clone();
+            match tokio::task::spawn_blocking(move || service.create_backup()).await {
+                Ok(Ok(info)) => println!("💾 Backup criado: {}", info.path),
+                Ok(Err(e)) => eprintln!("Erro ao criar backup agendado: {}", e),
+                Err(e) => eprintln!("Erro ao criar backup agendado: {}", e),
+            }
+        }
+    }
+
+    /// Grava um snapshot do banco num arquivo temporário e só o publica com o
+    /// nome definitivo depois de verificado
+    fn write_snapshot(&self, suffix: &str) -> AppResult<BackupInfo> {
+        std::fs::create_dir_all(&self.config.dir)
+            .context("Failed to create backup directory")?;
+
+        let file_name = format!(
+            "{}{}{}.{}",
+            BACKUP_PREFIX,
+            Utc::now().format("%Y%m%d-%H%M%S%3f"),
+            suffix,
+            BACKUP_EXTENSION
+        );
+        let final_path = self.config.dir.join(&file_name);
+        let tmp_path = self.config.dir.join(format!("{}.tmp", file_name));
+
+        let result = self.copy_to(&tmp_path).and_then(|_| Self::verify(&tmp_path));
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+
+        std::fs::rename(&tmp_path, &final_path)
+            .map_err(|e| AppError::from(e).context(&format!("Failed to publish backup {}", file_name)))?;
+
+        Self::describe(&final_path)
+    }
+
+    fn copy_to(&self, path: &Path) -> AppResult<()> {
+        let source = self.db.get_connection()?;
+        let mut target = Connection::open(path)
+            .context("Failed to create backup file")?;
+
+        {
+            let backup = Backup::new(&source, &mut target)
+                .context("Failed to start backup")?;
+            Self::copy_all_pages(&backup, BUSY_RETRIES)
+                .context("Failed to write backup")?;
+        }
+
+        // A cópia deve ser um arquivo único, sem depender de -wal/-shm
+        target.query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))
+            .context("Failed to finalize backup file")?;
+
+        Ok(())
+    }
+
+    /// Copia todas as páginas num único passo, mantendo o snapshot consistente.
+    /// Com o banco ocupado tenta de novo até `retries` vezes e então desiste.
+    fn copy_all_pages(backup: &Backup, retries: u32) -> AppResult<()> {
+        for _ in 0..=retries {
+            match backup.step(-1)? {
+                StepResult::Done => return Ok(()),
+                // More, Busy ou Locked: aguarda e tenta de novo
+                _ => thread::sleep(BUSY_WAIT),
+            }
+        }
+        Err(AppError::Database(format!("Database still busy after {} attempts", retries + 1)))
+    }
+
+    /// Confere a integridade de um arquivo de cópia e a versão do schema
+    pub fn verify(path: &Path) -> AppResult<BackupInfo> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| AppError::from(e).context(&format!("Failed to open backup {}", path.display())))?;
+
+        let check: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .context("Failed to check backup integrity")?;
+        if check != "ok" {
+            return Err(AppError::Database(format!("Backup {} failed integrity check: {}", path.display(), check)));
+        }
+
+        let version = Self::schema_version(&conn)?;
+        if version > migrations::latest_version() {
+            return Err(AppError::Database(format!(
+                "Backup {} has schema version {}, newer than supported ({})",
+                path.display(), version, migrations::latest_version()
+            )));
+        }
+
+        Self::describe(path)
+    }
+
+    fn schema_version(conn: &Connection) -> AppResult<i64> {
+        conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+            .or_else(|e| match e {
+                // Cópias anteriores ao controle de versão
+                rusqlite::Error::SqliteFailure(_, Some(ref msg)) if msg.contains("no such table") => Ok(0),
+                e => Err(e),
+            })
+            .context("Failed to read backup schema version")
+    }
+
+    fn describe(path: &Path) -> AppResult<BackupInfo> {
+        let metadata = std::fs::metadata(path)
+            .context("Failed to read backup metadata")?;
+        let schema_version = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| AppError::from(e).context(&format!("Failed to open backup {}", path.display())))
+            .and_then(|conn| Self::schema_version(&conn))?;
+
+        Ok(BackupInfo {
+            file_name: path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            size_bytes: metadata.len(),
+            schema_version,
+            created_at: metadata.modified()
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now()),
+            error: None,
+        })
+    }
+
+    /// Como `describe`, mas um arquivo ilegível vira uma entrada marcada com o erro
+    fn describe_or_flag(path: &Path) -> BackupInfo {
+        Self::describe(path).unwrap_or_else(|e| {
+            let metadata = std::fs::metadata(path).ok();
+            BackupInfo {
+                file_name: path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                path: path.to_string_lossy().to_string(),
+                size_bytes: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                schema_version: 0,
+                created_at: metadata
+                    .and_then(|m| m.modified().ok())
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(Utc::now),
+                error: Some(e.to_string()),
+            }
+        })
+    }
+
+    /// Resolve o nome de uma cópia dentro do diretório configurado,
+    /// recusando caminhos que apontem para fora dele
+    fn resolve(&self, file_name: &str) -> AppResult<PathBuf> {
+        let path = self.config.dir.join(file_name);
+        let is_plain_name = Path::new(file_name).file_name().map(|n| n == file_name).unwrap_or(false);
+
+        if !is_plain_name || !Self::is_backup_file(&path) || !path.exists() {
+            return Err(AppError::not_found(format!("Backup {} not found", file_name)));
+        }
+
+        Ok(path)
+    }
+
+    fn is_backup_file(path: &Path) -> bool {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        name.starts_with(BACKUP_PREFIX) && path.extension().map(|e| e == BACKUP_EXTENSION).unwrap_or(false)
+    }
+
+    fn is_pre_restore(info: &BackupInfo) -> bool {
+        info.file_name.ends_with(&format!("{}.{}", PRE_RESTORE_SUFFIX, BACKUP_EXTENSION))
+    }
+
+    /// Remove as cópias excedentes, mantendo as `keep` agendadas ou manuais
+    /// mais recentes e, à parte, as `keep_pre_restore` cópias `pre-restore`
+    fn rotate(&self) -> AppResult<()> {
+        let (pre_restore, regular): (Vec<_>, Vec<_>) = self.list_backups()?
+            .into_iter()
+            .partition(Self::is_pre_restore);
+
+        let excess = regular.into_iter().skip(self.config.keep)
+            .chain(pre_restore.into_iter().skip(self.config.keep_pre_restore));
+        for old in excess {
+            std::fs::remove_file(&old.path)
+                .map_err(|e| AppError::from(e).context(&format!("Failed to remove old backup {}", old.file_name)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::PoolConfig;
+
+    #[test]
+    fn test_backup_rotation_and_restore() {
+        let dir = std::env::temp_dir().join(format!("rabbit-backup-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let db = SqliteDbService::with_config(dir.join("db.sqlite"), &PoolConfig::default()).unwrap();
+        db.initialize().unwrap();
+        let service = BackupService::new(db.clone(), BackupConfig {
+            dir: dir.join("backups"),
+            keep: 2,
+            keep_pre_restore: 1,
+            interval: None,
+        });
+
+        let insert = |code: &str| {
+            db.get_connection().unwrap().execute(
+                "INSERT INTO produtos (code, name, created_at, updated_at) VALUES (?1, 'P', '', '')",
+                [code],
+            ).unwrap();
+        };
+        let count = || -> i64 {
+            db.get_connection().unwrap()
+                .query_row("SELECT COUNT(*) FROM produtos", [], |r| r.get(0)).unwrap()
+        };
+
+        insert("A");
+        let first = service.create_backup().unwrap();
+        assert_eq!(first.schema_version, migrations::latest_version());
+        insert("B");
+        service.create_backup().unwrap();
+        service.create_backup().unwrap();
+        assert_eq!(service.list_backups().unwrap().len(), 2);
+
+        let oldest_kept = service.list_backups().unwrap().pop().unwrap();
+        insert("C");
+        assert_eq!(count(), 3);
+
+        service.restore_backup(&oldest_kept.file_name).unwrap();
+        assert_eq!(count(), 2);
+
+        // A cópia pre-restore não toma o lugar das regulares e tem limite próprio
+        service.restore_backup(&oldest_kept.file_name).unwrap();
+        let backups = service.list_backups().unwrap();
+        let pre_restore = backups.iter().filter(|b| BackupService::is_pre_restore(b)).count();
+        assert_eq!((backups.len(), pre_restore), (3, 1));
+        assert!(backups.iter().any(|b| b.file_name == oldest_kept.file_name));
+
+        assert!(service.restore_backup("../db.sqlite").is_err());
+
+        // Um arquivo corrompido aparece marcado, sem esconder os demais
+        std::fs::write(dir.join("backups").join("rabbit-20000101-000000000.sqlite"), b"not a database").unwrap();
+        let backups = service.list_backups().unwrap();
+        assert_eq!(backups.len(), 4);
+        assert!(backups[..3].iter().all(|b| b.error.is_none()));
+        assert!(backups[3].error.is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_gives_up_on_busy_database() {
+        let dir = std::env::temp_dir().join(format!("rabbit-backup-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Uma transação exclusiva impede a leitura da origem
+        let writer = Connection::open(dir.join("db.sqlite")).unwrap();
+        writer.execute_batch("CREATE TABLE t (x INTEGER); BEGIN EXCLUSIVE; INSERT INTO t VALUES (1);").unwrap();
+
+        let source = Connection::open(dir.join("db.sqlite")).unwrap();
+        source.busy_timeout(Duration::ZERO).unwrap();
+        let mut target = Connection::open(dir.join("copy.sqlite")).unwrap();
+        let backup = Backup::new(&source, &mut target).unwrap();
+        let err = BackupService::copy_all_pages(&backup, 2).unwrap_err();
+        assert!(err.to_string().contains("busy after 3 attempts"), "{}", err);
+
+        drop(backup);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,316 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{BackupInfo, BackupService, SqliteDbService};
+use crate::entities::{Money, PaymentTypes};
+use crate::error::{AppError, AppResult, ResultExt};
+
+/// Violação reportada por `PRAGMA foreign_key_check`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+}
+
+/// Linha de `venda_itens` ou `venda_pagamentos` sem a venda correspondente
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrphanRow {
+    pub id: i64,
+    pub venda_id: i64,
+}
+
+/// Venda cujo total difere da soma dos itens
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TotalMismatch {
+    pub venda_id: i64,
+    pub total: Money,
+    pub items_total: Money,
+    pub difference: Money,
+}
+
+/// Resumo com código de pagamento que não existe em `PaymentTypes`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnknownResumeCode {
+    pub id: String,
+    pub code: String,
+    pub created_at: i64,
+}
+
+/// Resultado do diagnóstico do banco
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Mensagens de `PRAGMA integrity_check` (vazio quando o arquivo está íntegro)
+    pub integrity_errors: Vec<String>,
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+    pub orphan_items: Vec<OrphanRow>,
+    pub orphan_payments: Vec<OrphanRow>,
+    pub total_mismatches: Vec<TotalMismatch>,
+    pub unknown_resume_codes: Vec<UnknownResumeCode>,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.foreign_key_violations.is_empty()
+            && self.orphan_items.is_empty()
+            && self.orphan_payments.is_empty()
+            && self.total_mismatches.is_empty()
+            && self.unknown_resume_codes.is_empty()
+    }
+}
+
+/// Reparos a aplicar; todos desligados por padrão.
+///
+/// Divergências de total não têm reparo automático: o documento fiscal é a
+/// fonte da verdade e precisa ser conferido manualmente.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RepairOptions {
+    pub delete_orphan_items: bool,
+    pub delete_orphan_payments: bool,
+    pub delete_unknown_resumes: bool,
+}
+
+impl RepairOptions {
+    pub fn any(&self) -> bool {
+        self.delete_orphan_items || self.delete_orphan_payments || self.delete_unknown_resumes
+    }
+}
+
+/// Resultado de um reparo
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Cópia feita antes do reparo
+    pub backup: Option<BackupInfo>,
+    pub deleted_items: usize,
+    pub deleted_payments: usize,
+    pub deleted_resumes: usize,
+    /// Diagnóstico após o reparo
+    pub report: IntegrityReport,
+}
+
+/// Diagnóstico de consistência do banco SQLite
+#[derive(Clone)]
+pub struct IntegrityService {
+    db: SqliteDbService,
+}
+
+impl IntegrityService {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    /// Executa todas as verificações sem alterar o banco
+    pub fn check(&self) -> AppResult<IntegrityReport> {
+        let conn = self.db.get_connection()?;
+
+        Ok(IntegrityReport {
+            integrity_errors: Self::integrity_errors(&conn)?,
+            foreign_key_violations: Self::foreign_key_violations(&conn)?,
+            orphan_items: Self::orphans(&conn, "venda_itens")?,
+            orphan_payments: Self::orphans(&conn, "venda_pagamentos")?,
+            total_mismatches: Self::total_mismatches(&conn)?,
+            unknown_resume_codes: Self::unknown_resume_codes(&conn)?,
+        })
+    }
+
+    /// Aplica os reparos selecionados numa única transação.
+    ///
+    /// Quando algum reparo é pedido, uma cópia de segurança é feita antes.
+    pub fn repair(&self, backups: Option<&BackupService>, options: &RepairOptions) -> AppResult<RepairReport> {
+        let mut result = RepairReport::default();
+
+        if options.any() {
+            result.backup = backups.map(|b| b.create_backup()).transpose()?;
+
+            let mut conn = self.db.get_connection()?;
+            let tx = conn.transaction()
+                .context("Failed to start transaction")?;
+
+            if options.delete_orphan_items {
+                result.deleted_items = Self::delete_orphans(&tx, "venda_itens")?;
+            }
+            if options.delete_orphan_payments {
+                result.deleted_payments = Self::delete_orphans(&tx, "venda_pagamentos")?;
+            }
+            if options.delete_unknown_resumes {
+                for resume in Self::unknown_resume_codes(&tx)? {
+                    result.deleted_resumes += tx.execute("DELETE FROM resumes WHERE id = ?1", params![resume.id])
+                        .context("Failed to delete resume")?;
+                }
+            }
+
+            tx.commit()
+                .context("Failed to commit repair")?;
+        }
+
+        result.report = self.check()?;
+        Ok(result)
+    }
+
+    fn integrity_errors(conn: &Connection) -> AppResult<Vec<String>> {
+        let mut stmt = conn.prepare("PRAGMA integrity_check")
+            .context("Failed to prepare statement")?;
+
+        let messages = stmt.query_map([], |row| row.get::<_, String>(0))
+            .context("Failed to run integrity check")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect integrity check")?;
+
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    fn foreign_key_violations(conn: &Connection) -> AppResult<Vec<ForeignKeyViolation>> {
+        let mut stmt = conn.prepare("PRAGMA foreign_key_check")
+            .context("Failed to prepare statement")?;
+
+        let violations = stmt.query_map([], |row| {
+            Ok(ForeignKeyViolation {
+                table: row.get(0)?,
+                rowid: row.get(1)?,
+                parent: row.get(2)?,
+            })
+        })
+        .context("Failed to run foreign key check")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect foreign key check")?;
+
+        Ok(violations)
+    }
+
+    fn orphans(conn: &Connection, table: &str) -> AppResult<Vec<OrphanRow>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT t.id, t.venda_id FROM {} t
+             WHERE NOT EXISTS (SELECT 1 FROM vendas v WHERE v.id = t.venda_id)
+             ORDER BY t.id",
+            table
+        )).context("Failed to prepare statement")?;
+
+        let rows = stmt.query_map([], |row| Ok(OrphanRow { id: row.get(0)?, venda_id: row.get(1)? }))
+            .map_err(|e| AppError::from(e).context(&format!("Failed to query orphan {}", table)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::from(e).context(&format!("Failed to collect orphan {}", table)))?;
+
+        Ok(rows)
+    }
+
+    fn delete_orphans(conn: &Connection, table: &str) -> AppResult<usize> {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE NOT EXISTS (SELECT 1 FROM vendas v WHERE v.id = {}.venda_id)",
+                table, table
+            ),
+            [],
+        ).map_err(|e| AppError::from(e).context(&format!("Failed to delete orphan {}", table)))
+    }
+
+    /// Compara `vendas.total` com a soma de `preco_total` dos itens (o valor
+    /// líquido, `quantidade * preco_unitario - desconto + acrescimo`, como em
+    /// `VendaService::create_venda`) ajustada pelo desconto e acréscimo da venda
+    fn total_mismatches(conn: &Connection) -> AppResult<Vec<TotalMismatch>> {
+        let mut stmt = conn.prepare(
+            "SELECT v.id, v.total,
+                    COALESCE(SUM(vi.preco_total), 0) - v.discount + v.addition AS items_total
+             FROM vendas v
+             LEFT JOIN venda_itens vi ON vi.venda_id = v.id
+             GROUP BY v.id
+             HAVING v.total <> items_total
+             ORDER BY v.id"
+        ).context("Failed to prepare statement")?;
+
+        let rows = stmt.query_map([], |row| {
+            let total: Money = row.get(1)?;
+            let items_total: Money = row.get(2)?;
+            Ok(TotalMismatch {
+                venda_id: row.get(0)?,
+                total,
+                items_total,
+                difference: total - items_total,
+            })
+        })
+        .context("Failed to query venda totals")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect venda totals")?;
+
+        Ok(rows)
+    }
+
+    fn unknown_resume_codes(conn: &Connection) -> AppResult<Vec<UnknownResumeCode>> {
+        let mut stmt = conn.prepare("SELECT id, code, created_at FROM resumes ORDER BY created_at")
+            .context("Failed to prepare statement")?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(UnknownResumeCode {
+                id: row.get(0)?,
+                code: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .context("Failed to query resumes")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to collect resumes")?;
+
+        Ok(rows.into_iter().filter(|r| PaymentTypes::from_str(&r.code).is_none()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_and_repairs_inconsistencies() {
+        let db = SqliteDbService::new_in_memory().unwrap();
+        db.initialize().unwrap();
+        let service = IntegrityService::new(db.clone());
+
+        assert!(service.check().unwrap().is_healthy());
+
+        {
+            let conn = db.get_connection().unwrap();
+            conn.execute_batch(
+                "INSERT INTO vendas (id, tip, mod, serie, nr_nf, cnpj, dh_emi, total, chave, created_at, updated_at)
+                 VALUES (1, 1, 65, '1', 1, '', '2024-01-01T10:00:00Z', 5000, 'k', '', ''),
+                        (2, 1, 65, '1', 2, '', '2024-01-01T11:00:00Z', 1500, 'k2', '', '');
+                 INSERT INTO venda_itens (venda_id, produto_code, produto_description, produto_medida,
+                     quantidade, preco_unitario, preco_total, created_at, updated_at)
+                 VALUES (1, 'A', 'A', 'UN', 2000, 1000, 2000, '', '');
+                 PRAGMA foreign_keys = OFF;
+                 INSERT INTO venda_itens (venda_id, produto_code, produto_description, produto_medida,
+                     quantidade, preco_unitario, preco_total, created_at, updated_at)
+                 VALUES (99, 'B', 'B', 'UN', 1000, 500, 500, '', '');
+                 INSERT INTO venda_pagamentos (venda_id, code, name, total_pagamento, created_at, updated_at)
+                 VALUES (99, '01', 'Dinheiro', 500, '', '');
+                 PRAGMA foreign_keys = ON;
+                 INSERT INTO resumes (id, code, amount_s, amount_n, updated_at, created_at)
+                 VALUES ('r1', 'XX', 0, 0, 0, 0), ('r2', '01', 0, 0, 0, 0);"
+            ).unwrap();
+        }
+
+        let report = service.check().unwrap();
+        assert!(report.integrity_errors.is_empty());
+        assert_eq!(report.foreign_key_violations.len(), 2);
+        assert_eq!(report.orphan_items, vec![OrphanRow { id: 2, venda_id: 99 }]);
+        assert_eq!(report.orphan_payments.len(), 1);
+        // A venda 2 não tem itens e também diverge
+        assert_eq!(report.total_mismatches.len(), 2);
+        assert_eq!(report.total_mismatches[0].difference, Money::from_int(30));
+        assert_eq!((report.total_mismatches[1].venda_id, report.total_mismatches[1].items_total), (2, Money::ZERO));
+        assert_eq!(report.unknown_resume_codes[0].code, "XX");
+
+        // Sem opções nada é alterado
+        let untouched = service.repair(None, &RepairOptions::default()).unwrap();
+        assert_eq!(untouched.report.orphan_items.len(), 1);
+
+        let repaired = service.repair(None, &RepairOptions {
+            delete_orphan_items: true,
+            delete_orphan_payments: true,
+            delete_unknown_resumes: true,
+        }).unwrap();
+        assert_eq!((repaired.deleted_items, repaired.deleted_payments, repaired.deleted_resumes), (1, 1, 1));
+        assert!(repaired.report.foreign_key_violations.is_empty());
+        assert!(repaired.report.unknown_resume_codes.is_empty());
+        assert_eq!(repaired.report.total_mismatches.len(), 2);
+    }
+}
@@ -0,0 +1,1374 @@
+use rusqlite::{params, types::Value, Connection, OptionalExtension, Transaction};
+use chrono::Utc;
+use chrono_tz::Tz;
+
+use crate::clock::{parse_timezone, Clock, SystemClock, DEFAULT_TIMEZONE};
+use crate::entities::{Money, Quantity};
+use crate::entities::unit::DEFAULT_UNITS;
+
+/// Uma migração de schema versionada.
+///
+/// As migrações são aplicadas em ordem crescente de `version`, cada uma em sua
+/// própria transação, e registradas na tabela `schema_version`.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> Result<(), String>,
+}
+
+/// Lista ordenada de todas as migrações conhecidas por este binário.
+/// Novas migrações devem ser adicionadas sempre ao final, com a próxima versão.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create application schema",
+        up: m0001_create_schema,
+    },
+    Migration {
+        version: 2,
+        description: "import and drop legacy tables",
+        up: m0002_import_legacy_tables,
+    },
+    Migration {
+        version: 3,
+        description: "store money and quantities as fixed-point integers",
+        up: m0003_fixed_point_amounts,
+    },
+    Migration {
+        version: 4,
+        description: "store timestamps as UTC epoch milliseconds",
+        up: m0004_utc_millis_timestamps,
+    },
+    Migration {
+        version: 5,
+        description: "record change origin and text entity ids in history",
+        up: m0005_history_audit,
+    },
+    Migration {
+        version: 6,
+        description: "add fiscal catalog fields to products and sale items",
+        up: m0006_product_fiscal_fields,
+    },
+    Migration {
+        version: 7,
+        description: "create stock movement ledger",
+        up: m0007_stock_movements,
+    },
+    Migration {
+        version: 8,
+        description: "add negative stock policy to config",
+        up: m0008_negative_stock_policy,
+    },
+    Migration {
+        version: 9,
+        description: "full-text index of products",
+        up: m0009_product_search,
+    },
+    Migration {
+        version: 10,
+        description: "add scale label layout to config and index product gtin",
+        up: m0010_scale_labels,
+    },
+    Migration {
+        version: 11,
+        description: "create product categories",
+        up: m0011_categories,
+    },
+    Migration {
+        version: 12,
+        description: "create promotions",
+        up: m0012_promotions,
+    },
+    Migration {
+        version: 13,
+        description: "create product compositions (kits)",
+        up: m0013_compositions,
+    },
+    Migration {
+        version: 14,
+        description: "create inventory count sessions",
+        up: m0014_inventory,
+    },
+    Migration {
+        version: 15,
+        description: "create units of measure and product purchase unit",
+        up: m0015_units,
+    },
+    Migration {
+        version: 16,
+        description: "create product lots with expiry dates",
+        up: m0016_lots,
+    },
+    Migration {
+        version: 17,
+        description: "add product minimum and maximum stock levels",
+        up: m0017_stock_levels,
+    },
+    Migration {
+        version: 18,
+        description: "create persisted sale carts",
+        up: m0018_carts,
+    },
+    Migration {
+        version: 19,
+        description: "add sale total rounding tolerance",
+        up: m0019_total_tolerance,
+    },
+    Migration {
+        version: 20,
+        description: "add store timezone to config",
+        up: m0020_store_timezone,
+    },
+];
+
+/// Versão mais recente de schema suportada por este binário
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Retorna a versão atual do schema (0 para bancos sem `schema_version`)
+pub fn current_version(conn: &Connection) -> Result<i64, String> {
+    ensure_version_table(conn)?;
+
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    ).map_err(|e| format!("Failed to read schema version: {}", e))
+}
+
+/// Aplica todas as migrações pendentes.
+///
+/// Recusa bancos cuja versão seja maior que a suportada pelo binário, para que
+/// uma versão antiga do aplicativo não corrompa dados gravados por uma mais nova.
+pub fn run_migrations(conn: &mut Connection) -> Result<(), String> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(format!(
+            "Database schema version {} is newer than the version supported by this build ({})",
+            current, latest
+        ));
+    }
+
+    // As migrações podem remover ou recriar tabelas referenciadas por chaves
+    // estrangeiras; a verificação fica desligada enquanto elas são aplicadas
+    conn.execute_batch("PRAGMA foreign_keys = OFF")
+        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+
+    let result = apply_pending(conn, current);
+
+    conn.execute_batch("PRAGMA foreign_keys = ON")
+        .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
+
+    result
+}
+
+fn apply_pending(conn: &mut Connection, current: i64) -> Result<(), String> {
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.transaction()
+            .map_err(|e| format!("Failed to start migration transaction: {}", e))?;
+
+        (migration.up)(&tx).map_err(|e| {
+            format!("Migration {} ({}) failed: {}", migration.version, migration.description, e)
+        })?;
+
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.description, Utc::now().to_rfc3339()],
+        ).map_err(|e| format!("Failed to record migration {}: {}", migration.version, e))?;
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit migration {}: {}", migration.version, e))?;
+    }
+
+    Ok(())
+}
+
+fn ensure_version_table(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    ).map_err(|e| format!("Failed to create schema_version table: {}", e))?;
+
+    Ok(())
+}
+
+fn table_exists(tx: &Transaction, name: &str) -> Result<bool, String> {
+    tx.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![name],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(|e| format!("Failed to inspect table {}: {}", name, e))
+}
+
+fn table_is_empty(tx: &Transaction, name: &str) -> Result<bool, String> {
+    tx.query_row(&format!("SELECT COUNT(*) FROM {}", name), [], |row| row.get::<_, i64>(0))
+        .map(|count| count == 0)
+        .map_err(|e| format!("Failed to count rows in {}: {}", name, e))
+}
+
+/// v1: cria as tabelas exatamente como os serviços as consultam
+fn m0001_create_schema(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS config (
+            id TEXT PRIMARY KEY,
+            flowBaseUrl TEXT NOT NULL,
+            codeUf INTEGER NOT NULL DEFAULT 35,
+            nserieSAT TEXT NOT NULL DEFAULT '123456789',
+            nserieSATNao TEXT,
+            nrNfSim INTEGER NOT NULL DEFAULT 0,
+            nrNfNao INTEGER NOT NULL DEFAULT 0,
+            signAC TEXT,
+            regimeTributario TEXT NOT NULL DEFAULT '1',
+            cnpj TEXT NOT NULL DEFAULT '28095955000199',
+            name TEXT NOT NULL DEFAULT 'EMPRESA TESTE',
+            shortName TEXT,
+            zipcode TEXT NOT NULL DEFAULT '00000000',
+            addressName TEXT NOT NULL DEFAULT 'AV Paulista',
+            addressNumber TEXT NOT NULL DEFAULT '2000',
+            addressCity TEXT NOT NULL DEFAULT 'São Paulo',
+            addressCityCode TEXT,
+            tipoAmbiente TEXT NOT NULL DEFAULT '1',
+            addressCpl TEXT,
+            addressNeiborhood TEXT NOT NULL DEFAULT 'Consolação',
+            addressState TEXT NOT NULL DEFAULT 'SP',
+            fone TEXT,
+            createdAt INTEGER NOT NULL,
+            updatedAt INTEGER NOT NULL,
+            percentS INTEGER NOT NULL DEFAULT 50,
+            onlyMoney INTEGER NOT NULL DEFAULT 0,
+            errorAsSuccess INTEGER NOT NULL DEFAULT 0,
+            ie TEXT,
+            pagamentos TEXT,
+            ignoreCpf INTEGER NOT NULL DEFAULT 0,
+            numeroCaixa INTEGER NOT NULL DEFAULT 0,
+            emitirL INTEGER NOT NULL DEFAULT 0,
+            habilitarContador INTEGER NOT NULL DEFAULT 0,
+            habilitarContadorNao INTEGER NOT NULL DEFAULT 0,
+            controleEstoque INTEGER NOT NULL DEFAULT 0,
+            modelo INTEGER NOT NULL DEFAULT 59
+        );
+
+        CREATE TABLE IF NOT EXISTS history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            action TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            entity_id INTEGER,
+            details TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS produtos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL,
+            active INTEGER NOT NULL DEFAULT 1,
+            balance REAL NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS vendas (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tip INTEGER NOT NULL,
+            mod INTEGER NOT NULL,
+            serie_origin TEXT NOT NULL DEFAULT '0',
+            serie TEXT NOT NULL,
+            nr_nf_origin INTEGER NOT NULL DEFAULT 0,
+            nr_nf INTEGER NOT NULL,
+            cnpj TEXT NOT NULL,
+            doc_destinatario TEXT,
+            dh_emi TEXT NOT NULL,
+            dh_emi_canc TEXT,
+            total REAL NOT NULL DEFAULT 0,
+            addition REAL NOT NULL DEFAULT 0,
+            discount REAL NOT NULL DEFAULT 0,
+            chave TEXT NOT NULL,
+            chave_canc TEXT,
+            file_path TEXT,
+            cancel_file_path TEXT,
+            protocolo TEXT,
+            cancelled INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS venda_itens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            venda_id INTEGER NOT NULL,
+            produto_code TEXT NOT NULL,
+            produto_description TEXT NOT NULL,
+            produto_medida TEXT NOT NULL,
+            quantidade REAL NOT NULL,
+            preco_unitario REAL NOT NULL,
+            desconto REAL NOT NULL DEFAULT 0,
+            desconto_rat REAL NOT NULL DEFAULT 0,
+            acrescimo REAL NOT NULL DEFAULT 0,
+            acrescimo_rat REAL NOT NULL DEFAULT 0,
+            preco_total REAL NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (venda_id) REFERENCES vendas(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS venda_pagamentos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            venda_id INTEGER NOT NULL,
+            code TEXT NOT NULL,
+            name TEXT NOT NULL,
+            total_pagamento REAL NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (venda_id) REFERENCES vendas(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS resumes (
+            id TEXT PRIMARY KEY,
+            code TEXT NOT NULL,
+            amount_s REAL NOT NULL DEFAULT 0,
+            amount_n REAL NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_produtos_active ON produtos(active);
+        CREATE INDEX IF NOT EXISTS idx_vendas_dh_emi ON vendas(dh_emi);
+        CREATE INDEX IF NOT EXISTS idx_venda_itens_venda_id ON venda_itens(venda_id);
+        CREATE INDEX IF NOT EXISTS idx_venda_pagamentos_venda_id ON venda_pagamentos(venda_id);
+        CREATE INDEX IF NOT EXISTS idx_resumes_code_created_at ON resumes(code, created_at);
+        CREATE INDEX IF NOT EXISTS idx_history_entity ON history(entity_type, entity_id);",
+    ).map_err(|e| format!("Failed to create schema: {}", e))
+}
+
+/// v2: migra bancos criados pelo antigo `create_tables`.
+///
+/// Os produtos da tabela `product` são copiados para `produtos` (o código de
+/// barras vira o `code`, ou o id antigo quando não houver). Produtos com código
+/// repetido não são importados; nesse caso `product` é mantida como
+/// `legacy_product`. As demais tabelas legadas são removidas quando vazias; se
+/// tiverem dados, são renomeadas com o prefixo `legacy_` para conferência manual.
+fn m0002_import_legacy_tables(tx: &Transaction) -> Result<(), String> {
+    let mut skipped_products = 0;
+    if table_exists(tx, "product")? {
+        let imported = tx.execute(
+            "INSERT OR IGNORE INTO produtos (code, name, active, balance, created_at, updated_at)
+             SELECT COALESCE(NULLIF(TRIM(barcode), ''), CAST(id AS TEXT)), name, active, stock,
+                    created_at, updated_at
+             FROM product",
+            [],
+        ).map_err(|e| format!("Failed to import legacy products: {}", e))?;
+
+        let total: usize = tx.query_row("SELECT COUNT(*) FROM product", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count legacy products: {}", e))?;
+        skipped_products = total - imported;
+        if skipped_products > 0 {
+            eprintln!(
+                "Migração v2: {} produto(s) legado(s) com código repetido não importado(s); veja a tabela legacy_product",
+                skipped_products
+            );
+        }
+    }
+
+    // Ordem respeita as chaves estrangeiras (filhas antes das mães)
+    for table in ["venda_item", "venda_pagamento", "venda", "product", "resume"] {
+        if !table_exists(tx, table)? {
+            continue;
+        }
+
+        let imported = table == "product" && skipped_products == 0;
+        let sql = if imported || table_is_empty(tx, table)? {
+            format!("DROP TABLE {}", table)
+        } else {
+            format!("ALTER TABLE {} RENAME TO legacy_{}", table, table)
+        };
+
+        tx.execute(&sql, [])
+            .map_err(|e| format!("Failed to retire legacy table {}: {}", table, e))?;
+    }
+
+    Ok(())
+}
+
+/// v3: converte valores monetários (REAL) para centavos e quantidades para
+/// milésimos, em colunas INTEGER.
+///
+/// Colunas com afinidade REAL convertem inteiros para ponto flutuante ao
+/// gravar, então as tabelas são recriadas em vez de apenas atualizadas.
+fn m0003_fixed_point_amounts(tx: &Transaction) -> Result<(), String> {
+    let money: &Conversion = &|v| {
+        let value = value_as_f64(&v)?;
+        Money::from_f64(value).map(|m| Value::Integer(m.units())).ok_or_else(|| out_of_range(value))
+    };
+    let quantity: &Conversion = &|v| {
+        let value = value_as_f64(&v)?;
+        Quantity::from_f64(value).map(|q| Value::Integer(q.units())).ok_or_else(|| out_of_range(value))
+    };
+
+    rebuild_table(
+        tx,
+        "produtos",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         code TEXT NOT NULL UNIQUE,
+         name TEXT NOT NULL,
+         active INTEGER NOT NULL DEFAULT 1,
+         balance INTEGER NOT NULL DEFAULT 0,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL",
+        &[("balance", quantity)],
+    )?;
+
+    rebuild_table(
+        tx,
+        "vendas",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         tip INTEGER NOT NULL,
+         mod INTEGER NOT NULL,
+         serie_origin TEXT NOT NULL DEFAULT '0',
+         serie TEXT NOT NULL,
+         nr_nf_origin INTEGER NOT NULL DEFAULT 0,
+         nr_nf INTEGER NOT NULL,
+         cnpj TEXT NOT NULL,
+         doc_destinatario TEXT,
+         dh_emi TEXT NOT NULL,
+         dh_emi_canc TEXT,
+         total INTEGER NOT NULL DEFAULT 0,
+         addition INTEGER NOT NULL DEFAULT 0,
+         discount INTEGER NOT NULL DEFAULT 0,
+         chave TEXT NOT NULL,
+         chave_canc TEXT,
+         file_path TEXT,
+         cancel_file_path TEXT,
+         protocolo TEXT,
+         cancelled INTEGER NOT NULL DEFAULT 0,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL",
+        &[("total", money), ("addition", money), ("discount", money)],
+    )?;
+
+    rebuild_table(
+        tx,
+        "venda_itens",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         venda_id INTEGER NOT NULL,
+         produto_code TEXT NOT NULL,
+         produto_description TEXT NOT NULL,
+         produto_medida TEXT NOT NULL,
+         quantidade INTEGER NOT NULL,
+         preco_unitario INTEGER NOT NULL,
+         desconto INTEGER NOT NULL DEFAULT 0,
+         desconto_rat INTEGER NOT NULL DEFAULT 0,
+         acrescimo INTEGER NOT NULL DEFAULT 0,
+         acrescimo_rat INTEGER NOT NULL DEFAULT 0,
+         preco_total INTEGER NOT NULL,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL,
+         FOREIGN KEY (venda_id) REFERENCES vendas(id)",
+        &[
+            ("quantidade", quantity),
+            ("preco_unitario", money),
+            ("desconto", money),
+            ("desconto_rat", money),
+            ("acrescimo", money),
+            ("acrescimo_rat", money),
+            ("preco_total", money),
+        ],
+    )?;
+
+    rebuild_table(
+        tx,
+        "venda_pagamentos",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         venda_id INTEGER NOT NULL,
+         code TEXT NOT NULL,
+         name TEXT NOT NULL,
+         total_pagamento INTEGER NOT NULL,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL,
+         FOREIGN KEY (venda_id) REFERENCES vendas(id)",
+        &[("total_pagamento", money)],
+    )?;
+
+    rebuild_table(
+        tx,
+        "resumes",
+        "id TEXT PRIMARY KEY,
+         code TEXT NOT NULL,
+         amount_s INTEGER NOT NULL DEFAULT 0,
+         amount_n INTEGER NOT NULL DEFAULT 0,
+         updated_at INTEGER NOT NULL,
+         created_at INTEGER NOT NULL",
+        &[("amount_s", money), ("amount_n", money)],
+    )?;
+
+    tx.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_produtos_active ON produtos(active);
+         CREATE INDEX IF NOT EXISTS idx_vendas_dh_emi ON vendas(dh_emi);
+         CREATE INDEX IF NOT EXISTS idx_venda_itens_venda_id ON venda_itens(venda_id);
+         CREATE INDEX IF NOT EXISTS idx_venda_pagamentos_venda_id ON venda_pagamentos(venda_id);
+         CREATE INDEX IF NOT EXISTS idx_resumes_code_created_at ON resumes(code, created_at);",
+    ).map_err(|e| format!("Failed to recreate indexes: {}", e))
+}
+
+/// v4: grava todos os instantes como milissegundos UTC (INTEGER).
+///
+/// As colunas `created_at`/`updated_at` em texto RFC 3339 são convertidas,
+/// `config` passa de segundos para milissegundos (como `resumes`) e `vendas`
+/// ganha `dh_emi_ts`, o instante de `dh_emi` usado nas consultas por
+/// intervalo. Textos sem fuso são interpretados no fuso da loja, lido de
+/// `config.timezone` (a coluna é criada aqui quando ainda não existe).
+fn m0004_utc_millis_timestamps(tx: &Transaction) -> Result<(), String> {
+    let clock = SystemClock::new(store_timezone(tx)?);
+    let migrated_at = Utc::now().timestamp_millis();
+    // Só a ausência de data vira o instante da migração; um texto que não é
+    // data interrompe a migração, listando as linhas, para não perder o valor
+    let millis = move |v: Value| -> Result<Value, String> {
+        match &v {
+            Value::Integer(i) => Ok(Value::Integer(*i)),
+            Value::Text(text) if !text.trim().is_empty() => clock.parse_instant(text)
+                .map(|i| Value::Integer(i.timestamp_millis()))
+                .ok_or_else(|| format!("data '{}' não reconhecida", text)),
+            _ => Ok(Value::Integer(migrated_at)),
+        }
+    };
+    let timestamps: &[(&str, &Conversion)] = &[("created_at", &millis), ("updated_at", &millis)];
+
+    // Valores abaixo de 10^11 ainda estão em segundos (10^11 s ~ ano 5138)
+    tx.execute_batch(
+        "UPDATE config SET createdAt = createdAt * 1000 WHERE createdAt < 100000000000;
+         UPDATE config SET updatedAt = updatedAt * 1000 WHERE updatedAt < 100000000000;",
+    ).map_err(|e| format!("Failed to convert config timestamps: {}", e))?;
+
+    rebuild_table(
+        tx,
+        "history",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         action TEXT NOT NULL,
+         entity_type TEXT NOT NULL,
+         entity_id INTEGER,
+         details TEXT,
+         created_at INTEGER NOT NULL",
+        &timestamps[..1],
+    )?;
+
+    rebuild_table(
+        tx,
+        "produtos",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         code TEXT NOT NULL UNIQUE,
+         name TEXT NOT NULL,
+         active INTEGER NOT NULL DEFAULT 1,
+         balance INTEGER NOT NULL DEFAULT 0,
+         created_at INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL",
+        timestamps,
+    )?;
+
+    rebuild_table(
+        tx,
+        "vendas",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         tip INTEGER NOT NULL,
+         mod INTEGER NOT NULL,
+         serie_origin TEXT NOT NULL DEFAULT '0',
+         serie TEXT NOT NULL,
+         nr_nf_origin INTEGER NOT NULL DEFAULT 0,
+         nr_nf INTEGER NOT NULL,
+         cnpj TEXT NOT NULL,
+         doc_destinatario TEXT,
+         dh_emi TEXT NOT NULL,
+         dh_emi_canc TEXT,
+         total INTEGER NOT NULL DEFAULT 0,
+         addition INTEGER NOT NULL DEFAULT 0,
+         discount INTEGER NOT NULL DEFAULT 0,
+         chave TEXT NOT NULL,
+         chave_canc TEXT,
+         file_path TEXT,
+         cancel_file_path TEXT,
+         protocolo TEXT,
+         cancelled INTEGER NOT NULL DEFAULT 0,
+         created_at INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL",
+        timestamps,
+    )?;
+
+    tx.execute_batch("ALTER TABLE vendas ADD COLUMN dh_emi_ts INTEGER NOT NULL DEFAULT 0")
+        .map_err(|e| format!("Failed to add dh_emi_ts: {}", e))?;
+
+    let emissions = {
+        let mut stmt = tx.prepare("SELECT id, dh_emi, created_at FROM vendas")
+            .map_err(|e| format!("Failed to read vendas: {}", e))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?)))
+            .map_err(|e| format!("Failed to read vendas: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read vendas: {}", e))?
+    };
+    for (id, dh_emi, created_at) in emissions {
+        let emitted_at = clock.parse_instant(&dh_emi).map(|i| i.timestamp_millis()).unwrap_or(created_at);
+        tx.execute("UPDATE vendas SET dh_emi_ts = ?1 WHERE id = ?2", params![emitted_at, id])
+            .map_err(|e| format!("Failed to set dh_emi_ts: {}", e))?;
+    }
+
+    rebuild_table(
+        tx,
+        "venda_itens",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         venda_id INTEGER NOT NULL,
+         produto_code TEXT NOT NULL,
+         produto_description TEXT NOT NULL,
+         produto_medida TEXT NOT NULL,
+         quantidade INTEGER NOT NULL,
+         preco_unitario INTEGER NOT NULL,
+         desconto INTEGER NOT NULL DEFAULT 0,
+         desconto_rat INTEGER NOT NULL DEFAULT 0,
+         acrescimo INTEGER NOT NULL DEFAULT 0,
+         acrescimo_rat INTEGER NOT NULL DEFAULT 0,
+         preco_total INTEGER NOT NULL,
+         created_at INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL,
+         FOREIGN KEY (venda_id) REFERENCES vendas(id)",
+        timestamps,
+    )?;
+
+    rebuild_table(
+        tx,
+        "venda_pagamentos",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         venda_id INTEGER NOT NULL,
+         code TEXT NOT NULL,
+         name TEXT NOT NULL,
+         total_pagamento INTEGER NOT NULL,
+         created_at INTEGER NOT NULL,
+         updated_at INTEGER NOT NULL,
+         FOREIGN KEY (venda_id) REFERENCES vendas(id)",
+        timestamps,
+    )?;
+
+    tx.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_history_entity ON history(entity_type, entity_id);
+         CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at);
+         CREATE INDEX IF NOT EXISTS idx_produtos_active ON produtos(active);
+         CREATE INDEX IF NOT EXISTS idx_vendas_dh_emi_ts ON vendas(dh_emi_ts);
+         CREATE INDEX IF NOT EXISTS idx_venda_itens_venda_id ON venda_itens(venda_id);
+         CREATE INDEX IF NOT EXISTS idx_venda_pagamentos_venda_id ON venda_pagamentos(venda_id);",
+    ).map_err(|e| format!("Failed to recreate indexes: {}", e))
+}
+
+/// v5: prepara `history` para a trilha de auditoria.
+///
+/// `entity_id` passa a ser texto (configurações usam IDs como `"default"`) e a
+/// coluna `origin` indica se a alteração veio do Tauri, da API HTTP ou do sistema.
+fn m0005_history_audit(tx: &Transaction) -> Result<(), String> {
+    let text = |v: Value| -> Result<Value, String> {
+        match v {
+            Value::Integer(i) => Ok(Value::Text(i.to_string())),
+            v => Ok(v),
+        }
+    };
+
+    rebuild_table(
+        tx,
+        "history",
+        "id INTEGER PRIMARY KEY AUTOINCREMENT,
+         action TEXT NOT NULL,
+         entity_type TEXT NOT NULL,
+         entity_id TEXT,
+         details TEXT,
+         created_at INTEGER NOT NULL",
+        &[("entity_id", &text)],
+    )?;
+
+    tx.execute_batch(
+        "ALTER TABLE history ADD COLUMN origin TEXT NOT NULL DEFAULT 'system';
+         CREATE INDEX IF NOT EXISTS idx_history_entity ON history(entity_type, entity_id);
+         CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at);",
+    ).map_err(|e| format!("Failed to add history origin: {}", e))
+}
+
+/// v6: dados comerciais e fiscais do cadastro de produtos.
+///
+/// `produtos` ganha preço de venda (centavos), unidade comercial, GTIN, NCM,
+/// CEST, CFOP, origem da mercadoria e CST/CSOSN; `venda_itens` guarda uma cópia
+/// dos dados fiscais do produto no momento da venda.
+fn m0006_product_fiscal_fields(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "ALTER TABLE produtos ADD COLUMN price INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE produtos ADD COLUMN unit TEXT NOT NULL DEFAULT 'UN';
+         ALTER TABLE produtos ADD COLUMN gtin TEXT;
+         ALTER TABLE produtos ADD COLUMN ncm TEXT;
+         ALTER TABLE produtos ADD COLUMN cest TEXT;
+         ALTER TABLE produtos ADD COLUMN cfop TEXT;
+         ALTER TABLE produtos ADD COLUMN origem INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE produtos ADD COLUMN cst TEXT;
+
+         ALTER TABLE venda_itens ADD COLUMN gtin TEXT;
+         ALTER TABLE venda_itens ADD COLUMN ncm TEXT;
+         ALTER TABLE venda_itens ADD COLUMN cest TEXT;
+         ALTER TABLE venda_itens ADD COLUMN cfop TEXT;
+         ALTER TABLE venda_itens ADD COLUMN origem INTEGER;
+         ALTER TABLE venda_itens ADD COLUMN cst TEXT;",
+    ).map_err(|e| format!("Failed to add fiscal fields: {}", e))
+}
+
+/// v7: livro de movimentos de estoque.
+///
+/// Produtos com saldo recebem um movimento inicial de ajuste, para que o saldo
+/// gravado em `produtos` bata com a soma do livro desde o começo.
+fn m0007_stock_movements(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS stock_movements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            product_id INTEGER NOT NULL,
+            type TEXT NOT NULL,
+            quantity INTEGER NOT NULL,
+            balance_after INTEGER NOT NULL,
+            reference TEXT,
+            user_name TEXT,
+            origin TEXT NOT NULL DEFAULT 'system',
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (product_id) REFERENCES produtos(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_stock_movements_product ON stock_movements(product_id, created_at);
+
+        INSERT INTO stock_movements (product_id, type, quantity, balance_after, reference, origin, created_at)
+            SELECT id, 'adjustment', balance, balance, 'saldo inicial', 'system', updated_at
+            FROM produtos
+            WHERE balance <> 0;",
+    ).map_err(|e| format!("Failed to create stock movements: {}", e))
+}
+
+/// v8: política de venda com saldo negativo (`block`, `warn` ou `allow`),
+/// usada quando `controleEstoque` está ligado
+fn m0008_negative_stock_policy(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "ALTER TABLE config ADD COLUMN estoqueNegativo TEXT NOT NULL DEFAULT 'allow';",
+    ).map_err(|e| format!("Failed to add negative stock policy: {}", e))
+}
+
+/// v9: índice FTS5 de código e nome dos produtos, sem distinção de acentos e
+/// maiúsculas. É uma tabela de conteúdo externo mantida pelos triggers, então
+/// uma migração que recriar `produtos` precisa recriar os triggers também.
+fn m0009_product_search(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS produtos_fts USING fts5(
+            code, name,
+            content = 'produtos', content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2',
+            prefix = '2 3'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS produtos_fts_insert AFTER INSERT ON produtos BEGIN
+            INSERT INTO produtos_fts (rowid, code, name) VALUES (new.id, new.code, new.name);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS produtos_fts_delete AFTER DELETE ON produtos BEGIN
+            INSERT INTO produtos_fts (produtos_fts, rowid, code, name) VALUES ('delete', old.id, old.code, old.name);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS produtos_fts_update AFTER UPDATE OF code, name ON produtos BEGIN
+            INSERT INTO produtos_fts (produtos_fts, rowid, code, name) VALUES ('delete', old.id, old.code, old.name);
+            INSERT INTO produtos_fts (rowid, code, name) VALUES (new.id, new.code, new.name);
+        END;
+
+        INSERT INTO produtos_fts (produtos_fts) VALUES ('rebuild');",
+    ).map_err(|e| format!("Failed to create product search index: {}", e))
+}
+
+/// v10: leiaute das etiquetas de balança (padrão `2 CCCC 0 VVVVVV D`, preço) e
+/// índice de `produtos.gtin` para a leitura de códigos de barras
+fn m0010_scale_labels(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "ALTER TABLE config ADD COLUMN balancaDigitosCodigo INTEGER NOT NULL DEFAULT 4;
+         ALTER TABLE config ADD COLUMN balancaDigitosValor INTEGER NOT NULL DEFAULT 6;
+         ALTER TABLE config ADD COLUMN balancaValor TEXT NOT NULL DEFAULT 'price';
+
+         CREATE INDEX IF NOT EXISTS idx_produtos_gtin ON produtos(gtin);",
+    ).map_err(|e| format!("Failed to add scale label layout: {}", e))
+}
+
+/// v11: árvore de categorias de produtos (`categorias.parent_id`) e a
+/// categoria de cada produto
+fn m0011_categories(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS categorias (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            parent_id INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (parent_id) REFERENCES categorias(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_categorias_parent ON categorias(parent_id);
+
+        ALTER TABLE produtos ADD COLUMN category_id INTEGER REFERENCES categorias(id);
+        CREATE INDEX IF NOT EXISTS idx_produtos_category ON produtos(category_id);",
+    ).map_err(|e| format!("Failed to create categories: {}", e))
+}
+
+/// v12: promoções (regra em JSON) e a promoção aplicada em cada item vendido.
+/// `venda_itens.promotion_id` não tem chave estrangeira: a promoção pode ser
+/// removida depois e continua identificável pelo histórico.
+fn m0012_promotions(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS promocoes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            rule TEXT NOT NULL,
+            starts_at INTEGER,
+            ends_at INTEGER,
+            active INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_promocoes_active ON promocoes(active, starts_at, ends_at);
+
+        ALTER TABLE venda_itens ADD COLUMN promotion_id INTEGER;",
+    ).map_err(|e| format!("Failed to create promotions: {}", e))
+}
+
+/// v13: composição dos kits (componentes e quantidade por kit)
+fn m0013_compositions(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS produto_composicao (
+            kit_id INTEGER NOT NULL,
+            component_id INTEGER NOT NULL,
+            quantity INTEGER NOT NULL,
+            PRIMARY KEY (kit_id, component_id),
+            FOREIGN KEY (kit_id) REFERENCES produtos(id),
+            FOREIGN KEY (component_id) REFERENCES produtos(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_produto_composicao_component ON produto_composicao(component_id);",
+    ).map_err(|e| format!("Failed to create product compositions: {}", e))
+}
+
+/// v14: sessões de inventário e a contagem de cada produto. `expected` é o
+/// saldo congelado na abertura; `counted` fica nulo até o produto ser lido.
+fn m0014_inventory(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS inventarios (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open',
+            user_name TEXT,
+            opened_at INTEGER NOT NULL,
+            closed_at INTEGER
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_inventarios_status ON inventarios(status);
+
+        CREATE TABLE IF NOT EXISTS inventario_itens (
+            session_id INTEGER NOT NULL,
+            product_id INTEGER NOT NULL,
+            expected INTEGER NOT NULL,
+            counted INTEGER,
+            scans INTEGER NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (session_id, product_id),
+            FOREIGN KEY (session_id) REFERENCES inventarios(id),
+            FOREIGN KEY (product_id) REFERENCES produtos(id)
+        );",
+    ).map_err(|e| format!("Failed to create inventory sessions: {}", e))
+}
+
+/// v15: unidades de medida e a unidade de compra dos produtos. Além das
+/// unidades padrão, as que já aparecem nos produtos são cadastradas com 3
+/// casas decimais, para não recusar quantidades que já eram aceitas.
+fn m0015_units(tx: &Transaction) -> Result<(), String> {
+    let err = |e: rusqlite::Error| format!("Failed to create units: {}", e);
+
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS unidades (
+            code TEXT PRIMARY KEY,
+            description TEXT NOT NULL,
+            decimals INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+
+        ALTER TABLE produtos ADD COLUMN purchase_unit TEXT;
+        ALTER TABLE produtos ADD COLUMN purchase_factor INTEGER NOT NULL DEFAULT 1000;
+        UPDATE produtos SET unit = UPPER(TRIM(unit)) WHERE unit <> UPPER(TRIM(unit));",
+    ).map_err(err)?;
+
+    let now = Utc::now().timestamp_millis();
+    for (code, description, decimals) in DEFAULT_UNITS {
+        tx.execute(
+            "INSERT OR IGNORE INTO unidades (code, description, decimals, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![code, description, decimals, now],
+        ).map_err(err)?;
+    }
+    tx.execute(
+        "INSERT OR IGNORE INTO unidades (code, description, decimals, created_at, updated_at)
+         SELECT DISTINCT unit, unit, 3, ?1, ?1 FROM produtos WHERE unit <> ''",
+        params![now],
+    ).map_err(err)?;
+
+    Ok(())
+}
+
+/// v16: lotes com validade. `expires_at` é a data `YYYY-MM-DD` (ordena como
+/// texto); cada movimento de um produto com controle de lotes aponta o lote.
+fn m0016_lots(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS lotes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            product_id INTEGER NOT NULL,
+            code TEXT NOT NULL,
+            expires_at TEXT,
+            quantity INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            UNIQUE (product_id, code),
+            FOREIGN KEY (product_id) REFERENCES produtos(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_lotes_expires_at ON lotes(expires_at);
+
+        ALTER TABLE produtos ADD COLUMN track_lots INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE stock_movements ADD COLUMN lot_id INTEGER REFERENCES lotes(id);",
+    ).map_err(|e| format!("Failed to create lots: {}", e))
+}
+
+/// v17: estoque mínimo e máximo do produto (milésimos; NULL = sem limite)
+fn m0017_stock_levels(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "ALTER TABLE produtos ADD COLUMN min_stock INTEGER;
+         ALTER TABLE produtos ADD COLUMN max_stock INTEGER;",
+    ).map_err(|e| format!("Failed to add stock levels: {}", e))
+}
+
+/// v18: carrinhos do caixa, gravados a cada passo para recuperar a venda em
+/// andamento depois de uma queda do aplicativo
+fn m0018_carts(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS carrinhos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            status TEXT NOT NULL,
+            user_name TEXT,
+            doc_destinatario TEXT,
+            discount INTEGER NOT NULL DEFAULT 0,
+            addition INTEGER NOT NULL DEFAULT 0,
+            venda_id INTEGER,
+            opened_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            closed_at INTEGER,
+            FOREIGN KEY (venda_id) REFERENCES vendas(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_carrinhos_status ON carrinhos(status);
+
+        CREATE TABLE IF NOT EXISTS carrinho_itens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            cart_id INTEGER NOT NULL,
+            produto_code TEXT NOT NULL,
+            produto_description TEXT NOT NULL,
+            produto_medida TEXT NOT NULL,
+            quantidade INTEGER NOT NULL,
+            preco_unitario INTEGER NOT NULL,
+            desconto INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (cart_id) REFERENCES carrinhos(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_carrinho_itens_cart ON carrinho_itens(cart_id);
+
+        CREATE TABLE IF NOT EXISTS carrinho_pagamentos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            cart_id INTEGER NOT NULL,
+            code TEXT NOT NULL,
+            name TEXT NOT NULL,
+            total_pagamento INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (cart_id) REFERENCES carrinhos(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_carrinho_pagamentos_cart ON carrinho_pagamentos(cart_id);",
+    ).map_err(|e| format!("Failed to create carts: {}", e))
+}
+
+/// v19: tolerância de arredondamento na conferência dos totais da venda
+/// (centavos; padrão 1 centavo)
+fn m0019_total_tolerance(tx: &Transaction) -> Result<(), String> {
+    tx.execute_batch(
+        "ALTER TABLE config ADD COLUMN toleranciaTotal INTEGER NOT NULL DEFAULT 1;",
+    ).map_err(|e| format!("Failed to add total tolerance: {}", e))
+}
+
+/// v20: fuso horário da loja na configuração (nome IANA). Bancos migrados a
+/// partir de antes da v4 já ganharam a coluna em `m0004_utc_millis_timestamps`.
+fn m0020_store_timezone(tx: &Transaction) -> Result<(), String> {
+    ensure_timezone_column(tx)
+}
+
+/// Cria `config.timezone` se necessário. O valor inicial vem de
+/// `STORE_TIMEZONE` quando definido (instalações que já usavam a variável),
+/// senão do fuso padrão; um nome inválido aborta a migração.
+fn ensure_timezone_column(tx: &Transaction) -> Result<(), String> {
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('config') WHERE name = 'timezone'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(|e| format!("Failed to inspect config columns: {}", e))?;
+
+    if exists {
+        return Ok(());
+    }
+    add_timezone_column(tx, std::env::var("STORE_TIMEZONE").ok().as_deref())
+}
+
+fn add_timezone_column(tx: &Transaction, initial: Option<&str>) -> Result<(), String> {
+    let timezone = match initial {
+        Some(name) => parse_timezone(name).map_err(|e| format!("STORE_TIMEZONE: {}", e))?,
+        None => DEFAULT_TIMEZONE,
+    };
+
+    tx.execute_batch(&format!(
+        "ALTER TABLE config ADD COLUMN timezone TEXT NOT NULL DEFAULT '{}';",
+        DEFAULT_TIMEZONE.name()
+    )).map_err(|e| format!("Failed to add store timezone: {}", e))?;
+    tx.execute("UPDATE config SET timezone = ?1", params![timezone.name()])
+        .map_err(|e| format!("Failed to set store timezone: {}", e))?;
+    Ok(())
+}
+
+/// Fuso da loja gravado na configuração `default` (ou na primeira existente);
+/// sem configuração nenhuma usa o padrão. Um nome inválido é erro, não cai no padrão.
+fn store_timezone(tx: &Transaction) -> Result<Tz, String> {
+    ensure_timezone_column(tx)?;
+
+    let name: Option<String> = tx.query_row(
+        "SELECT timezone FROM config ORDER BY id = 'default' DESC, id LIMIT 1",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to read store timezone: {}", e))?;
+
+    match name {
+        Some(name) => parse_timezone(&name).map_err(|e| format!("config.timezone: {}", e)),
+        None => Ok(DEFAULT_TIMEZONE),
+    }
+}
+
+/// Converte o valor antigo de uma coluna para o novo formato; um erro aborta a migração
+type Conversion = dyn Fn(Value) -> Result<Value, String>;
+
+fn out_of_range(value: f64) -> String {
+    format!("valor {} fora do intervalo suportado", value)
+}
+
+/// Valor numérico antigo; vazio ou nulo é zero, e um texto que não é número é
+/// erro (a tabela, a coluna e a linha entram na mensagem de `rebuild_table`)
+fn value_as_f64(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Integer(i) => Ok(*i as f64),
+        Value::Real(f) => Ok(*f),
+        Value::Text(t) if t.trim().is_empty() => Ok(0.0),
+        Value::Text(t) => t.trim().parse().map_err(|_| format!("valor '{}' não é um número", t)),
+        Value::Null => Ok(0.0),
+        Value::Blob(_) => Err("valor binário não é um número".to_string()),
+    }
+}
+
+/// ID da linha como aparece na tabela (inteiro ou texto)
+fn row_id(id: &Value) -> String {
+    match id {
+        Value::Integer(i) => i.to_string(),
+        Value::Text(t) => format!("'{}'", t),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Recria `table` com a nova definição de colunas (mesmos nomes e ordem),
+/// convertendo os valores das colunas de `conversions`. Valores que não podem
+/// ser convertidos interrompem a migração com todas as linhas afetadas na
+/// mensagem. Os índices da tabela antiga são removidos junto com ela e precisam
+/// ser recriados por quem chama.
+fn rebuild_table(tx: &Transaction, table: &str, columns: &str, conversions: &[(&str, &Conversion)]) -> Result<(), String> {
+    let err = |e: rusqlite::Error| format!("Failed to rebuild table {}: {}", table, e);
+
+    tx.execute_batch(&format!(
+        "CREATE TABLE {table}_new ({columns});
+         INSERT INTO {table}_new SELECT * FROM {table};"
+    )).map_err(err)?;
+
+    let mut failures = Vec::new();
+    for (column, convert) in conversions {
+        let values = {
+            let mut stmt = tx.prepare(&format!("SELECT id, {column} FROM {table}")).map_err(err)?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, Value>(0)?, row.get::<_, Value>(1)?))
+            }).map_err(err)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(err)?
+        };
+
+        let mut update = tx.prepare(&format!("UPDATE {table}_new SET {column} = ?1 WHERE id = ?2")).map_err(err)?;
+        for (id, value) in values {
+            match convert(value) {
+                Ok(converted) => update.execute(params![converted, id]).map(|_| ()).map_err(err)?,
+                Err(e) => failures.push(format!("{} da linha {}: {}", column, row_id(&id), e)),
+            }
+        }
+    }
+    if !failures.is_empty() {
+        return Err(format!("Failed to rebuild table {}: {}", table, failures.join("; ")));
+    }
+
+    tx.execute_batch(&format!(
+        "DROP TABLE {table};
+         ALTER TABLE {table}_new RENAME TO {table};"
+    )).map_err(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_names(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        ).unwrap();
+        stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn test_fresh_database_reaches_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&mut conn).expect("Failed to migrate");
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert_eq!(
+            table_names(&conn),
+            vec![
+                "carrinho_itens", "carrinho_pagamentos", "carrinhos", "categorias", "config", "history", "inventario_itens", "inventarios", "lotes", "produto_composicao", "produtos", "produtos_fts", "produtos_fts_config",
+                "produtos_fts_data", "produtos_fts_docsize", "produtos_fts_idx", "promocoes", "resumes", "schema_version",
+                "stock_movements", "unidades", "venda_itens", "venda_pagamentos", "vendas",
+            ]
+        );
+
+        // Rodar novamente não deve aplicar nada
+        run_migrations(&mut conn).expect("Failed to re-run migrations");
+        let applied: i64 = conn.query_row("SELECT COUNT(*) FROM schema_version", [], |r| r.get(0)).unwrap();
+        assert_eq!(applied, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn test_upgrades_legacy_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE product (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, description TEXT,
+                price REAL NOT NULL, stock INTEGER NOT NULL, barcode TEXT,
+                active INTEGER NOT NULL DEFAULT 1, created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+            );
+            CREATE TABLE venda (id INTEGER PRIMARY KEY AUTOINCREMENT, total REAL NOT NULL);
+            CREATE TABLE resume (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL);
+            INSERT INTO product (name, price, stock, barcode, created_at, updated_at)
+                VALUES ('Arroz', 10.0, 5, '7891000100103', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');
+            INSERT INTO product (name, price, stock, barcode, created_at, updated_at)
+                VALUES ('Feijão', 8.0, 3, NULL, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');
+            INSERT INTO product (name, price, stock, barcode, created_at, updated_at)
+                VALUES ('Arroz 5kg', 25.0, 1, ' 7891000100103', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z');
+            INSERT INTO resume (title) VALUES ('antigo');",
+        ).unwrap();
+
+        run_migrations(&mut conn).expect("Failed to migrate legacy database");
+
+        let codes: Vec<String> = conn.prepare("SELECT code FROM produtos ORDER BY id").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        assert_eq!(codes, vec!["7891000100103".to_string(), "2".to_string()]);
+        let unit: String = conn.query_row("SELECT unit FROM produtos WHERE id = 1", [], |r| r.get(0)).unwrap();
+        assert_eq!(unit, "UN");
+
+        // O saldo importado vira o movimento inicial do livro
+        let opening: Vec<(i64, i64)> = conn.prepare("SELECT product_id, quantity FROM stock_movements ORDER BY product_id").unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        assert_eq!(opening, vec![(1, 5000), (2, 3000)]);
+
+        // Produtos que já existiam entram no índice de busca
+        let found: i64 = conn.query_row("SELECT rowid FROM produtos_fts WHERE produtos_fts MATCH '\"feijao\"'", [], |r| r.get(0)).unwrap();
+        assert_eq!(found, 2);
+
+        let tables = table_names(&conn);
+        assert!(!tables.contains(&"product".to_string()));
+        assert!(!tables.contains(&"venda".to_string()));
+        assert!(tables.contains(&"legacy_resume".to_string()));
+
+        // O produto com código repetido não foi importado e fica para conferência
+        assert!(tables.contains(&"legacy_product".to_string()));
+        let kept: i64 = conn.query_row("SELECT COUNT(*) FROM legacy_product", [], |r| r.get(0)).unwrap();
+        assert_eq!(kept, 3);
+    }
+
+    #[test]
+    fn test_converts_real_amounts_to_fixed_point() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        ensure_version_table(&tx).unwrap();
+        m0001_create_schema(&tx).unwrap();
+        tx.execute_batch(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, '', ''), (2, '', '');
+             INSERT INTO vendas (id, tip, mod, serie, nr_nf, cnpj, dh_emi, total, discount, chave, created_at, updated_at)
+                 VALUES (1, 1, 65, '1', 1, '', '2024-01-01', 0.3, 0.1, 'k', '', '');
+             INSERT INTO venda_itens (venda_id, produto_code, produto_description, produto_medida,
+                 quantidade, preco_unitario, preco_total, created_at, updated_at)
+                 VALUES (1, 'A', 'A', 'KG', 0.333, 1.2, 0.3996, '', '');
+             INSERT INTO resumes (id, code, amount_s, amount_n, updated_at, created_at)
+                 VALUES ('r', '01', 1.005, 2, 0, 0);",
+        ).unwrap();
+        tx.commit().unwrap();
+
+        run_migrations(&mut conn).unwrap();
+
+        let venda: (i64, i64) = conn.query_row("SELECT total, discount FROM vendas", [], |r| Ok((r.get(0)?, r.get(1)?))).unwrap();
+        assert_eq!(venda, (30, 10));
+        let item: (i64, i64, i64) = conn.query_row(
+            "SELECT quantidade, preco_unitario, preco_total FROM venda_itens", [], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))
+        ).unwrap();
+        assert_eq!(item, (333, 120, 40));
+        let resume: (i64, i64) = conn.query_row("SELECT amount_s, amount_n FROM resumes", [], |r| Ok((r.get(0)?, r.get(1)?))).unwrap();
+        assert_eq!(resume, (101, 200));
+
+        // Inteiros gravados continuam inteiros
+        let kind: String = conn.query_row("SELECT typeof(total) FROM vendas", [], |r| r.get(0)).unwrap();
+        assert_eq!(kind, "integer");
+        let fk_errors: i64 = conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |r| r.get(0)).unwrap();
+        assert_eq!(fk_errors, 0);
+    }
+
+    #[test]
+    fn test_unreadable_amounts_abort_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        ensure_version_table(&tx).unwrap();
+        m0001_create_schema(&tx).unwrap();
+        tx.execute_batch(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, '', ''), (2, '', '');
+             INSERT INTO produtos (id, code, name, balance, created_at, updated_at) VALUES
+                 (1, '001', 'Arroz', '', '', ''),
+                 (2, '002', 'Feijão', '1,5', '', '');",
+        ).unwrap();
+        tx.commit().unwrap();
+
+        let err = run_migrations(&mut conn).unwrap_err();
+        assert!(err.contains("Failed to rebuild table produtos: balance da linha 2: valor '1,5' não é um número"), "{}", err);
+        assert_eq!(current_version(&conn).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_converts_timestamps_to_utc_millis() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        ensure_version_table(&tx).unwrap();
+        m0001_create_schema(&tx).unwrap();
+        tx.execute_batch(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, '', ''), (2, '', ''), (3, '', '');
+             INSERT INTO vendas (id, tip, mod, serie, nr_nf, cnpj, dh_emi, chave, created_at, updated_at)
+                 VALUES (1, 1, 65, '1', 1, '', '2024-06-15T22:30:00-03:00', 'k',
+                         '2024-06-16T01:30:05+00:00', '2024-06-16T01:30:05+00:00');
+             INSERT INTO config (id, flowBaseUrl, createdAt, updatedAt) VALUES ('default', '', 1718501405, 1718501405);",
+        ).unwrap();
+        tx.commit().unwrap();
+
+        run_migrations(&mut conn).unwrap();
+
+        let venda: (i64, i64) = conn.query_row("SELECT dh_emi_ts, created_at FROM vendas", [], |r| Ok((r.get(0)?, r.get(1)?))).unwrap();
+        assert_eq!(venda, (1_718_501_400_000, 1_718_501_405_000));
+        let config: i64 = conn.query_row("SELECT createdAt FROM config", [], |r| r.get(0)).unwrap();
+        assert_eq!(config, 1_718_501_405_000);
+    }
+
+    #[test]
+    fn test_local_timestamps_use_configured_timezone() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        ensure_version_table(&tx).unwrap();
+        m0001_create_schema(&tx).unwrap();
+        tx.execute_batch(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, '', ''), (2, '', ''), (3, '', '');
+             INSERT INTO vendas (id, tip, mod, serie, nr_nf, cnpj, dh_emi, chave, created_at, updated_at)
+                 VALUES (1, 1, 65, '1', 1, '', '2024-06-15 22:30:00', 'k', '2024-06-15 22:30:05', '2024-06-15 22:30:05');
+             INSERT INTO config (id, flowBaseUrl, createdAt, updatedAt) VALUES ('default', '', 1718501405, 1718501405);",
+        ).unwrap();
+        add_timezone_column(&tx, Some("America/Manaus")).unwrap();
+        tx.commit().unwrap();
+
+        run_migrations(&mut conn).unwrap();
+
+        // 22:30 em Manaus (UTC-4) = 02:30 UTC do dia seguinte
+        let venda: (i64, i64) = conn.query_row("SELECT dh_emi_ts, created_at FROM vendas", [], |r| Ok((r.get(0)?, r.get(1)?))).unwrap();
+        assert_eq!(venda, (1_718_505_000_000, 1_718_505_005_000));
+        let timezone: String = conn.query_row("SELECT timezone FROM config", [], |r| r.get(0)).unwrap();
+        assert_eq!(timezone, "America/Manaus");
+    }
+
+    #[test]
+    fn test_unreadable_timestamps_abort_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        ensure_version_table(&tx).unwrap();
+        m0001_create_schema(&tx).unwrap();
+        tx.execute_batch(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, '', ''), (2, '', ''), (3, '', '');
+             INSERT INTO produtos (id, code, name, created_at, updated_at) VALUES
+                 (1, '001', 'Arroz', '', '2024-06-15 22:30:05'),
+                 (2, '002', 'Feijão', '15/06/2024', '2024-06-15 22:30:05'),
+                 (3, '003', 'Sal', 'ontem', '2024-06-15 22:30:05');",
+        ).unwrap();
+        tx.commit().unwrap();
+
+        // Datas vazias não têm o que perder; textos que não são data param a migração
+        let err = run_migrations(&mut conn).unwrap_err();
+        assert!(err.contains("created_at da linha 2: data '15/06/2024'") && err.contains("created_at da linha 3"), "{}", err);
+        assert!(!err.contains("linha 1"), "{}", err);
+        assert_eq!(current_version(&conn).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_invalid_store_timezone_aborts_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let tx = conn.transaction().unwrap();
+        ensure_version_table(&tx).unwrap();
+        m0001_create_schema(&tx).unwrap();
+        assert!(add_timezone_column(&tx, Some("Lua/Base")).unwrap_err().contains("Lua/Base"));
+        tx.execute_batch(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (1, '', ''), (2, '', ''), (3, '', '');
+             INSERT INTO config (id, flowBaseUrl, createdAt, updatedAt) VALUES ('default', '', 1718501405, 1718501405);",
+        ).unwrap();
+        add_timezone_column(&tx, None).unwrap();
+        tx.execute_batch("UPDATE config SET timezone = 'Lua/Base'").unwrap();
+        tx.commit().unwrap();
+
+        let err = run_migrations(&mut conn).unwrap_err();
+        assert!(err.contains("Migration 4") && err.contains("Lua/Base"), "{}", err);
+        assert_eq!(current_version(&conn).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'future', '')",
+            params![latest_version() + 1],
+        ).unwrap();
+
+        assert!(run_migrations(&mut conn).is_err());
+    }
+}
@@ -1,3 +1,10 @@
 pub mod sqlite_service;
+pub mod migrations;
+pub mod pool;
+pub mod backup_service;
+pub mod integrity_service;
 
 pub use sqlite_service::SqliteDbService;
+pub use pool::{DbPool, PoolConfig, PooledConnection};
+pub use backup_service::{BackupConfig, BackupInfo, BackupService};
+pub use integrity_service::{IntegrityReport, IntegrityService, RepairOptions, RepairReport};
@@ -0,0 +1,80 @@
+use r2d2_sqlite::SqliteConnectionManager;
+use std::time::Duration;
+
+use crate::error::{AppResult, ResultExt};
+
+/// Pool de conexões SQLite compartilhado pelo app Tauri e pelo servidor HTTP
+pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+/// Conexão emprestada do pool (devolvida automaticamente ao sair de escopo)
+pub type PooledConnection = r2d2::PooledConnection<SqliteConnectionManager>;
+
+/// Configuração do pool de conexões
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Número máximo de conexões abertas simultaneamente
+    pub max_size: u32,
+    /// Tempo que uma escrita espera por um lock antes de falhar com `database is locked`
+    pub busy_timeout: Duration,
+    /// Quantidade de prepared statements mantidos em cache por conexão
+    pub statement_cache_capacity: usize,
+    /// Tempo máximo de espera por uma conexão livre no pool
+    pub connection_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 8,
+            busy_timeout: Duration::from_secs(5),
+            statement_cache_capacity: 64,
+            connection_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Lê a configuração das variáveis de ambiente, usando os valores padrão
+    /// para as que não estiverem definidas:
+    /// `SQLITE_POOL_SIZE`, `SQLITE_BUSY_TIMEOUT_MS`, `SQLITE_STATEMENT_CACHE`
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            max_size: env_parse("SQLITE_POOL_SIZE").unwrap_or(default.max_size).max(1),
+            busy_timeout: env_parse("SQLITE_BUSY_TIMEOUT_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.busy_timeout),
+            statement_cache_capacity: env_parse("SQLITE_STATEMENT_CACHE")
+                .unwrap_or(default.statement_cache_capacity),
+            connection_timeout: default.connection_timeout,
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+/// Cria o pool aplicando em cada nova conexão: journaling WAL, busy timeout,
+/// chaves estrangeiras e o tamanho do cache de statements
+pub fn build_pool(manager: SqliteConnectionManager, config: &PoolConfig) -> AppResult<DbPool> {
+    let busy_timeout = config.busy_timeout;
+    let cache_capacity = config.statement_cache_capacity;
+
+    let manager = manager.with_init(move |conn| {
+        conn.busy_timeout(busy_timeout)?;
+        conn.set_prepared_statement_cache_capacity(cache_capacity);
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             PRAGMA foreign_keys = ON;",
+        )
+    });
+
+    r2d2::Pool::builder()
+        .max_size(config.max_size)
+        .connection_timeout(config.connection_timeout)
+        .build(manager)
+        .context("Failed to create connection pool")
+}
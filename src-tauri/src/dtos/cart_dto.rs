@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use crate::dtos::VendaCreatedDto;
+use crate::entities::{CartEntity, CartPaymentEntity, Money, Quantity, VendaItemEntity};
+
+/// Corpo de `POST /carts`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenCartDto {
+    #[serde(default)]
+    pub user: Option<String>,
+    /// CPF/CNPJ do cliente
+    #[serde(default)]
+    pub doc_destinatario: Option<String>,
+}
+
+/// Corpo de `POST /carts/:id/items`: um produto lido no caixa
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddCartItemDto {
+    /// Código, GTIN ou etiqueta de balança
+    pub barcode: String,
+    /// Ausente usa 1 (ou o peso da etiqueta de balança)
+    #[serde(default)]
+    pub quantidade: Option<Quantity>,
+    /// Unidade vendida (a de venda ou a de compra do produto); ausente usa a de venda
+    #[serde(default)]
+    pub produto_medida: Option<String>,
+    /// Desconto manual do item
+    #[serde(default)]
+    pub desconto: Option<Money>,
+}
+
+/// Corpo de `PUT /carts/:id/items/:item_id`; campos ausentes não são alterados
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateCartItemDto {
+    #[serde(default)]
+    pub quantidade: Option<Quantity>,
+    /// 0 remove o desconto manual (o item volta a receber promoções)
+    #[serde(default)]
+    pub desconto: Option<Money>,
+}
+
+/// Corpo de `PUT /carts/:id/discount`: desconto e acréscimo no total
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CartDiscountDto {
+    #[serde(default)]
+    pub discount: Option<Money>,
+    #[serde(default)]
+    pub addition: Option<Money>,
+}
+
+/// Corpo de `POST /carts/:id/payments`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddCartPaymentDto {
+    /// Código da forma de pagamento (`01` dinheiro, `03` crédito...)
+    pub code: String,
+    /// Ausente usa o nome da forma de pagamento
+    #[serde(default)]
+    pub name: Option<String>,
+    pub total_pagamento: Money,
+}
+
+/// Corpo de `POST /carts/:id/finalize`: dados do documento fiscal emitido
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FinalizeCartDto {
+    pub tip: i32,
+    pub mod_: i32,
+    pub serie: String,
+    pub nr_nf: i32,
+    pub cnpj: String,
+    pub chave: String,
+    /// Ausente usa o instante da finalização
+    #[serde(default)]
+    pub dh_emi: Option<String>,
+    #[serde(default)]
+    pub protocolo: Option<String>,
+    #[serde(default)]
+    pub file_path: Option<String>,
+}
+
+/// Carrinho com os itens precificados como ficarão na venda (promoções em
+/// vigor aplicadas; `id` é o do item no carrinho) e os totais
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartDto {
+    #[serde(flatten)]
+    pub cart: CartEntity,
+    pub items: Vec<VendaItemEntity>,
+    pub payments: Vec<CartPaymentEntity>,
+    /// Soma de `quantidade * preco_unitario` dos itens
+    pub subtotal: Money,
+    /// Soma dos descontos dos itens (manuais e de promoções)
+    pub items_discount: Money,
+    /// `subtotal - items_discount - discount + addition`
+    pub total: Money,
+    pub paid: Money,
+    /// Quanto falta pagar
+    pub remaining: Money,
+    /// Troco
+    pub change: Money,
+}
+
+/// Resultado de `POST /carts/:id/finalize`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartFinalizedDto {
+    pub cart: CartDto,
+    pub venda: VendaCreatedDto,
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use crate::entities::{CategoryEntity, Money, Quantity};
+
+/// Corpo de `POST /categories`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateCategoryDto {
+    pub name: String,
+    /// Categoria pai; ausente cria uma seção principal
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+}
+
+/// Corpo de `PUT /categories/:id`; campos ausentes não são alterados e
+/// `parent_id = 0` move a categoria para a raiz
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateCategoryDto {
+    pub name: Option<String>,
+    pub parent_id: Option<i64>,
+}
+
+/// Categoria com o caminho completo e as subcategorias
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryTreeDto {
+    #[serde(flatten)]
+    pub category: CategoryEntity,
+    /// Nomes desde a raiz, ex. `Bebidas > Refrigerantes`
+    pub path: String,
+    pub children: Vec<CategoryTreeDto>,
+}
+
+/// Corpo de `POST /categories/reassign`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReassignProductsDto {
+    pub product_ids: Vec<i64>,
+    /// Categoria de destino; ausente (ou 0) tira os produtos da categoria
+    #[serde(default)]
+    pub category_id: Option<i64>,
+}
+
+/// Parâmetros de `GET /categories/:id/products`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryProductsQuery {
+    /// Inclui os produtos das subcategorias (padrão `true`)
+    pub subcategories: Option<bool>,
+    /// `true` só ativos, `false` só inativos; ausente lista todos
+    pub active: Option<bool>,
+}
+
+/// Parâmetros de `DELETE /categories/:id`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteCategoryQuery {
+    /// Para onde vão os produtos e subcategorias; ausente, para a categoria pai
+    pub reassign_to: Option<i64>,
+}
+
+/// Vendas de uma categoria no período
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySalesDto {
+    /// `None` agrupa itens de produtos sem categoria ou sem cadastro
+    pub category_id: Option<i64>,
+    pub parent_id: Option<i64>,
+    pub name: String,
+    pub path: String,
+    /// Itens vendidos diretamente na categoria
+    pub items: i64,
+    pub quantidade: Quantity,
+    /// Total dos itens da categoria (`preco_total - desconto + acrescimo`)
+    pub total: Money,
+    /// `total` somado ao das subcategorias
+    pub total_with_children: Money,
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use crate::barcode::ScaleLayout;
+use crate::entities::{Money, NegativeStockPolicy};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateOrUpdateConfigDto {
@@ -36,6 +38,14 @@ pub struct CreateOrUpdateConfigDto {
     pub habilitar_contador_nao: Option<i32>,
     pub controle_estoque: Option<i32>,
     pub modelo: Option<i32>,
+    #[serde(default)]
+    pub estoque_negativo: Option<NegativeStockPolicy>,
+    #[serde(default)]
+    pub balanca: Option<ScaleLayout>,
+    #[serde(default)]
+    pub tolerancia_total: Option<Money>,
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Filtros da consulta ao histórico (`GET /history` e `get_history`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQueryDto {
+    /// `product`, `config` ou `venda`
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    /// Data inicial `YYYY-MM-DD` (dia local da loja, inclusivo)
+    pub dt_init: Option<String>,
+    /// Data final `YYYY-MM-DD` (dia local da loja, inclusivo)
+    pub dt_fim: Option<String>,
+    /// Máximo de entradas (padrão 200, máximo 1000)
+    pub limit: Option<u32>,
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use crate::entities::{InventorySessionEntity, InventoryStatus, Money, Quantity};
+
+/// Corpo de `POST /inventory`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenInventoryDto {
+    /// Ausente vira "Inventário <data>"
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// Filtros de `GET /inventory`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryListQuery {
+    pub status: Option<InventoryStatus>,
+}
+
+/// Corpo de `POST /inventory/:id/counts`: uma leitura do produto na contagem
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InventoryScanDto {
+    /// Código, GTIN ou etiqueta de balança, como no caixa
+    pub barcode: String,
+    /// Quantidade da leitura; ausente usa 1 (ou o peso da etiqueta de balança).
+    /// Negativa desfaz leituras.
+    #[serde(default)]
+    pub quantity: Option<Quantity>,
+    /// `true` substitui a contagem do produto em vez de somar
+    #[serde(default)]
+    pub replace: Option<bool>,
+}
+
+/// Parâmetros de `GET /inventory/:id/report`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryReportQuery {
+    /// `true` lista só os produtos contados com diferença
+    pub differences_only: Option<bool>,
+}
+
+/// Corpo de `POST /inventory/:id/close`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloseInventoryDto {
+    /// `true` zera o estoque dos produtos que não foram contados; por padrão
+    /// eles ficam como estão
+    #[serde(default)]
+    pub zero_uncounted: Option<bool>,
+    /// Usuário gravado nos movimentos de ajuste
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// Linha do relatório de divergências
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryLineDto {
+    pub product_id: i64,
+    pub code: String,
+    pub name: String,
+    pub unit: String,
+    pub price: Money,
+    /// Saldo congelado na abertura
+    pub expected: Quantity,
+    /// `None` enquanto o produto não foi contado
+    pub counted: Option<Quantity>,
+    pub scans: i64,
+    /// `counted - expected`
+    pub variance: Option<Quantity>,
+    /// Diferença valorizada pelo preço de venda
+    pub variance_value: Option<Money>,
+}
+
+/// Relatório de divergências da sessão
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryReportDto {
+    pub session: InventorySessionEntity,
+    pub lines: Vec<InventoryLineDto>,
+    /// Produtos no retrato da sessão
+    pub total_products: usize,
+    pub counted_products: usize,
+    /// Produtos contados com diferença
+    pub divergent_products: usize,
+    /// Soma das sobras valorizadas
+    pub surplus_value: Money,
+    /// Soma das faltas valorizadas (negativa)
+    pub shortage_value: Money,
+    /// `surplus_value + shortage_value`
+    pub variance_value: Money,
+}
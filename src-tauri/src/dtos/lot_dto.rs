@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use crate::entities::{LotEntity, Quantity};
+
+/// Corpo de `POST /lots`; o lote nasce sem quantidade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLotDto {
+    pub product_id: i64,
+    pub code: String,
+    #[serde(default)]
+    pub expires_at: Option<NaiveDate>,
+}
+
+/// Corpo de `PUT /lots/:id`; campos ausentes não são alterados
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateLotDto {
+    pub code: Option<String>,
+    pub expires_at: Option<NaiveDate>,
+}
+
+/// Parâmetros de `GET /lots/expiring`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiringLotsQuery {
+    /// Dias a partir de hoje (padrão 30); lotes já vencidos sempre entram
+    pub days: Option<u32>,
+}
+
+/// Lote do relatório de vencimentos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringLotDto {
+    #[serde(flatten)]
+    pub lot: LotEntity,
+    pub product_code: String,
+    pub product_name: String,
+    /// Dias até o vencimento; negativo para lotes vencidos
+    pub days_left: i64,
+}
+
+/// Resposta de `GET /products/:id/lots`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductLotsDto {
+    pub product_id: i64,
+    pub code: String,
+    pub track_lots: i32,
+    pub balance: Quantity,
+    /// Soma dos lotes; igual a `balance` com o controle de lotes ligado
+    pub lots_total: Quantity,
+    /// Lotes na ordem de saída (validade mais próxima primeiro)
+    pub lots: Vec<LotEntity>,
+}
@@ -1,3 +1,38 @@
 pub mod config_dto;
+pub mod venda_dto;
+pub mod history_dto;
+pub mod product_dto;
+pub mod stock_dto;
+pub mod category_dto;
+pub mod promotion_dto;
+pub mod inventory_dto;
+pub mod unit_dto;
+pub mod lot_dto;
+pub mod cart_dto;
 
 pub use config_dto::{CreateOrUpdateConfigDto, UpdatePercentConfigDto, CnpjResponseDto};
+pub use venda_dto::{StockWarningDto, VendaCreatedDto, VendaWithRelations, VendaResumo};
+pub use history_dto::HistoryQueryDto;
+pub use product_dto::{
+    BarcodeLookupDto, BarcodeMatch, BalanceChangeDto, ComponentDto, CreateProductDto, ExportFileDto, ImportAction, ImportRowDto,
+    KitComponentDto, KitDto, ProductExportQuery, ProductFiscalDto, ProductImportDto, ProductImportReportDto, ProductListQuery,
+    ProductPageDto, ProductSearchQuery, UpdateProductDto,
+};
+pub use stock_dto::{
+    KardexDto, KardexQueryDto, LowStockDto, ReorderQueryDto, ReorderReportDto, ReorderSuggestionDto, StockDiscrepancyDto,
+    StockMovementDto,
+};
+pub use category_dto::{
+    CategoryProductsQuery, CategorySalesDto, CategoryTreeDto, CreateCategoryDto, DeleteCategoryQuery, ReassignProductsDto,
+    UpdateCategoryDto,
+};
+pub use promotion_dto::{PricingDto, PricingPreviewDto, PromotionListQuery, SavePromotionDto};
+pub use inventory_dto::{
+    CloseInventoryDto, InventoryLineDto, InventoryListQuery, InventoryReportDto, InventoryReportQuery, InventoryScanDto,
+    OpenInventoryDto,
+};
+pub use unit_dto::{CreateUnitDto, UpdateUnitDto};
+pub use lot_dto::{CreateLotDto, ExpiringLotDto, ExpiringLotsQuery, ProductLotsDto, UpdateLotDto};
+pub use cart_dto::{
+    AddCartItemDto, AddCartPaymentDto, CartDiscountDto, CartDto, CartFinalizedDto, FinalizeCartDto, OpenCartDto, UpdateCartItemDto,
+};
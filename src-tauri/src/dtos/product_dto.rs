@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use crate::barcode::ScaleLabel;
+use crate::entities::{Money, MovementType, ProductEntity, Quantity, VendaItemEntity};
+use crate::error::FieldError;
+use crate::spreadsheet::SheetFormat;
+
+/// Dados comerciais e fiscais do produto.
+///
+/// Campos ausentes não são alterados; nos campos de texto opcionais (`gtin`,
+/// `ncm`, `cest`, `cfop`, `cst`, `purchase_unit`) uma string vazia limpa o
+/// valor, `category_id = 0` tira o produto da categoria e `min_stock` ou
+/// `max_stock` igual a 0 remove o limite.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductFiscalDto {
+    pub price: Option<Money>,
+    pub unit: Option<String>,
+    pub purchase_unit: Option<String>,
+    pub purchase_factor: Option<Quantity>,
+    pub gtin: Option<String>,
+    pub ncm: Option<String>,
+    pub cest: Option<String>,
+    pub cfop: Option<String>,
+    pub origem: Option<i32>,
+    pub cst: Option<String>,
+    pub category_id: Option<i64>,
+    /// 1 liga o controle de lotes; o saldo atual vira o lote `INICIAL`
+    pub track_lots: Option<i32>,
+    /// Estoque mínimo, na unidade de venda
+    pub min_stock: Option<Quantity>,
+    /// Estoque máximo, na unidade de venda
+    pub max_stock: Option<Quantity>,
+}
+
+/// Corpo de `POST /products`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateProductDto {
+    pub code: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub fiscal: ProductFiscalDto,
+}
+
+/// Corpo de `PUT /products/:id`; campos ausentes não são alterados
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProductDto {
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub active: Option<i32>,
+    pub balance: Option<Quantity>,
+    #[serde(flatten)]
+    pub fiscal: ProductFiscalDto,
+}
+
+/// Corpo de `PATCH /products/:id/increment` e `/decrement`.
+///
+/// `amount` é sempre positivo; o tipo do movimento é `adjustment` quando
+/// ausente e precisa combinar com a direção (entrada ou saída).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BalanceChangeDto {
+    pub amount: Quantity,
+    #[serde(default, rename = "type")]
+    pub kind: Option<MovementType>,
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Unidade de `amount` (a de venda ou a de compra); ausente usa a de venda
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Lote movimentado (produtos com controle de lotes); veja `StockMovementDto`
+    #[serde(default)]
+    pub lot: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<NaiveDate>,
+}
+
+/// Parâmetros de `GET /products/search` e `search_products`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductSearchQuery {
+    /// Texto buscado no código e no nome
+    #[serde(default)]
+    pub q: String,
+    /// Página, a partir de 1 (padrão 1)
+    pub page: Option<u32>,
+    /// Produtos por página (padrão 20, máximo 100)
+    pub per_page: Option<u32>,
+    /// `true` só ativos, `false` só inativos; ausente busca em todos
+    pub active: Option<bool>,
+}
+
+/// Página de resultados da busca de produtos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductPageDto {
+    pub items: Vec<ProductEntity>,
+    /// Total de produtos encontrados, somando todas as páginas
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+/// Como o código lido foi associado ao produto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BarcodeMatch {
+    /// Igual a `produtos.code`
+    Code,
+    /// Igual a `produtos.gtin`
+    Gtin,
+    /// Etiqueta de balança com o código do produto
+    Scale,
+}
+
+/// Resultado da leitura de um código de barras no caixa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarcodeLookupDto {
+    pub barcode: String,
+    pub matched_by: BarcodeMatch,
+    pub product: ProductEntity,
+    /// Etiqueta de balança decodificada, quando `matched_by = scale`
+    pub label: Option<ScaleLabel>,
+    /// Item pronto para `create_venda`, com a quantidade e o total da etiqueta
+    pub item: VendaItemEntity,
+}
+
+/// Filtros de `GET /products`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductListQuery {
+    /// `true` lista só os ativos, `false` só os inativos; ausente lista todos
+    pub active: Option<bool>,
+}
+
+/// Corpo de `POST /products/import` e `import_products`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductImportDto {
+    /// `csv` ou `xlsx`; ausente, é detectado pelo conteúdo
+    #[serde(default)]
+    pub format: Option<SheetFormat>,
+    /// Arquivo em base64
+    pub content: String,
+    /// Cabeçalho da coluna de cada campo (`{"code": "Código"}`); campos não
+    /// mapeados usam o próprio nome ou um dos nomes em português
+    #[serde(default)]
+    pub columns: BTreeMap<String, String>,
+    /// Só valida e monta o relatório, sem gravar nada
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// O que a importação fez (ou faria, em `dry_run`) com a linha
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Update,
+    /// Produto já cadastrado exatamente como na planilha
+    Unchanged,
+    Error,
+}
+
+/// Resultado de uma linha da planilha
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowDto {
+    /// Linha na planilha, contando o cabeçalho como linha 1
+    pub line: usize,
+    pub code: String,
+    pub action: ImportAction,
+    /// Produto criado ou atualizado (ou o existente, em `dry_run`)
+    pub product_id: Option<i64>,
+    /// Motivos da recusa, quando `action = error`
+    pub errors: Vec<FieldError>,
+}
+
+/// Relatório da importação
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductImportReportDto {
+    pub dry_run: bool,
+    /// Linhas com dados (as em branco são ignoradas)
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowDto>,
+}
+
+/// Parâmetros de `GET /products/export`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProductExportQuery {
+    /// `csv` (padrão) ou `xlsx`
+    pub format: Option<SheetFormat>,
+}
+
+/// Arquivo exportado, para o `export_products` do Tauri
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportFileDto {
+    pub filename: String,
+    pub content_type: String,
+    /// Conteúdo em base64
+    pub content: String,
+}
+
+/// Componente informado em `PUT /products/:id/components`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentDto {
+    pub product_id: i64,
+    /// Quantidade do componente em cada kit
+    pub quantity: Quantity,
+}
+
+/// Componente do kit com os dados do cadastro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KitComponentDto {
+    pub product_id: i64,
+    pub code: String,
+    pub name: String,
+    pub unit: String,
+    pub quantity: Quantity,
+    pub price: Money,
+    pub balance: Quantity,
+    /// Kits que o saldo deste componente permite montar
+    pub available: Quantity,
+}
+
+/// Composição do kit com custo e disponibilidade calculados
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KitDto {
+    pub kit_id: i64,
+    pub components: Vec<KitComponentDto>,
+    /// Soma dos componentes pelo preço de cadastro
+    pub cost: Money,
+    /// Kits completos que os saldos permitem montar (o menor entre os componentes)
+    pub available: Quantity,
+}
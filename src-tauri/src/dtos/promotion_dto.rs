@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use crate::entities::{Money, PromotionRule, VendaItemEntity};
+
+/// Corpo de `POST /promotions` e `PUT /promotions/:id` (o `PUT` substitui a
+/// promoção inteira). Datas sem fuso são do fuso da loja; `ends_at` é exclusivo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavePromotionDto {
+    pub name: String,
+    #[serde(flatten)]
+    pub rule: PromotionRule,
+    #[serde(default)]
+    pub starts_at: Option<String>,
+    #[serde(default)]
+    pub ends_at: Option<String>,
+    /// Padrão `true`
+    #[serde(default)]
+    pub active: Option<bool>,
+}
+
+/// Parâmetros de `GET /promotions`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromotionListQuery {
+    /// `true` lista só as promoções em vigor agora
+    pub current: Option<bool>,
+}
+
+/// Corpo de `POST /promotions/preview`: itens como seriam enviados na venda
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricingPreviewDto {
+    pub items: Vec<VendaItemEntity>,
+    /// Momento da venda; ausente usa o horário atual
+    #[serde(default)]
+    pub dh_emi: Option<String>,
+}
+
+/// Itens precificados com as promoções em vigor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingDto {
+    pub items: Vec<VendaItemEntity>,
+    /// Soma de `quantidade * preco_unitario` dos itens
+    pub subtotal: Money,
+    /// Soma dos descontos dos itens
+    pub desconto: Money,
+    /// `subtotal - desconto` mais os acréscimos dos itens
+    pub total: Money,
+}
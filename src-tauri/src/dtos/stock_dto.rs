@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use crate::entities::{MovementType, Quantity, StockMovementEntity};
+
+/// Corpo de `POST /stock/movements`.
+///
+/// `quantity` é informada sem sinal: o tipo define se é entrada ou saída. Só
+/// os ajustes (`adjustment`) aceitam quantidade negativa.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StockMovementDto {
+    pub product_id: i64,
+    #[serde(rename = "type")]
+    pub kind: MovementType,
+    pub quantity: Quantity,
+    pub reference: Option<String>,
+    pub user: Option<String>,
+    /// Unidade de `quantity` (ex. `CX` numa compra); ausente usa a de venda.
+    /// O movimento é gravado já convertido para a unidade de estoque.
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Lote, nos produtos com controle de lotes. Uma entrada cria o lote se
+    /// ainda não existe; uma saída sem lote baixa os lotes pela validade.
+    #[serde(default)]
+    pub lot: Option<String>,
+    /// Validade do lote criado pela entrada (`YYYY-MM-DD`)
+    #[serde(default)]
+    pub expires_at: Option<NaiveDate>,
+}
+
+/// Filtros do kardex (`GET /stock/kardex/:product_id` e `get_kardex`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KardexQueryDto {
+    /// Data inicial `YYYY-MM-DD` (dia local da loja, inclusivo)
+    pub dt_init: Option<String>,
+    /// Data final `YYYY-MM-DD` (dia local da loja, inclusivo)
+    pub dt_fim: Option<String>,
+}
+
+/// Kardex de um produto no período: saldo inicial, movimentos e saldo final
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KardexDto {
+    pub product_id: i64,
+    pub code: String,
+    pub name: String,
+    pub opening_balance: Quantity,
+    pub total_in: Quantity,
+    pub total_out: Quantity,
+    pub closing_balance: Quantity,
+    pub movements: Vec<StockMovementEntity>,
+}
+
+/// Produto cujo saldo gravado não bate com a soma do livro de movimentos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockDiscrepancyDto {
+    pub product_id: i64,
+    pub code: String,
+    pub balance: Quantity,
+    pub ledger_balance: Quantity,
+}
+
+/// Produto com saldo abaixo do estoque mínimo (`GET /stock/low` e o evento
+/// `low-stock` emitido quando uma operação baixa o saldo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LowStockDto {
+    pub product_id: i64,
+    pub code: String,
+    pub name: String,
+    pub unit: String,
+    pub balance: Quantity,
+    pub min_stock: Quantity,
+    pub max_stock: Option<Quantity>,
+}
+
+/// Parâmetros de `GET /stock/reorder` e `get_reorder_suggestions`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderQueryDto {
+    /// Início do período de vendas `YYYY-MM-DD`; padrão: 30 dias até `dtFim`
+    pub dt_init: Option<String>,
+    /// Fim do período de vendas `YYYY-MM-DD`; padrão: hoje
+    pub dt_fim: Option<String>,
+    /// Dias de venda que a compra deve cobrir; padrão 30
+    pub coverage_days: Option<u32>,
+}
+
+/// Sugestão de compra de um produto.
+///
+/// `target` é a média diária de vendas x `coverage_days`, nunca abaixo do
+/// estoque mínimo nem acima do máximo; `suggested` é o que falta do saldo até
+/// ele, arredondado para cima nas casas da unidade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderSuggestionDto {
+    pub product_id: i64,
+    pub code: String,
+    pub name: String,
+    pub unit: String,
+    pub balance: Quantity,
+    pub min_stock: Option<Quantity>,
+    pub max_stock: Option<Quantity>,
+    /// Vendido no período (vendas menos cancelamentos)
+    pub sold: Quantity,
+    pub daily_average: Quantity,
+    pub target: Quantity,
+    /// Quantidade sugerida na unidade de venda
+    pub suggested: Quantity,
+    pub purchase_unit: Option<String>,
+    /// `suggested` convertida para a unidade de compra, arredondada para cima
+    pub purchase_quantity: Option<Quantity>,
+}
+
+/// Relatório de sugestão de compra
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderReportDto {
+    pub dt_init: NaiveDate,
+    pub dt_fim: NaiveDate,
+    /// Dias do período de vendas
+    pub days: i64,
+    pub coverage_days: u32,
+    pub items: Vec<ReorderSuggestionDto>,
+}
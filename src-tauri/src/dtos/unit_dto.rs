@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// Corpo de `POST /units`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateUnitDto {
+    pub code: String,
+    pub description: String,
+    /// Casas decimais aceitas nas quantidades (0 a 3); padrão 0
+    #[serde(default)]
+    pub decimals: Option<u32>,
+}
+
+/// Corpo de `PUT /units/:code`; campos ausentes não são alterados
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateUnitDto {
+    pub description: Option<String>,
+    pub decimals: Option<u32>,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use crate::dtos::LowStockDto;
+use crate::entities::{Money, Quantity, VendaEntity, VendaItemEntity, VendaPagamentoEntity};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendaWithRelations {
+    #[serde(flatten)]
+    pub venda: VendaEntity,
+    pub itens: Vec<VendaItemEntity>,
+    pub pagamentos: Vec<VendaPagamentoEntity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendaResumo {
+    pub total_vendas: i64,
+    pub total_valor: Money,
+    pub total_desconto: Money,
+    pub total_acrescimo: Money,
+    pub total_canceladas: i64,
+}
+
+/// Produto que ficou com saldo negativo numa venda gravada com a política `warn`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockWarningDto {
+    pub product_id: i64,
+    pub code: String,
+    /// Saldo antes da venda
+    pub balance: Quantity,
+    /// Quantidade vendida
+    pub quantity: Quantity,
+    pub balance_after: Quantity,
+}
+
+/// Resultado de `create_venda`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendaCreatedDto {
+    pub id: i64,
+    /// Vazio, a não ser com controle de estoque e política `warn`
+    pub stock_warnings: Vec<StockWarningDto>,
+    /// Produtos que ficaram abaixo do estoque mínimo com esta venda (só com
+    /// controle de estoque); o app também os emite no evento `low-stock`
+    pub low_stock: Vec<LowStockDto>,
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use super::{Money, Quantity, VendaItemEntity, VendaPagamentoEntity};
+
+/// Etapa de um carrinho (venda em andamento no caixa).
+///
+/// ```text
+/// open ──checkout──> payment ──finalize──> finalized
+///   ^                   │
+///   └─────reopen────────┘      open/payment ──abort──> aborted
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CartStatus {
+    /// Recebendo itens e descontos
+    Open,
+    /// Itens fechados, recebendo pagamentos
+    Payment,
+    /// Gravado como venda (`venda_id`)
+    Finalized,
+    /// Descartado sem gerar venda
+    Aborted,
+}
+
+impl CartStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CartStatus::Open => "open",
+            CartStatus::Payment => "payment",
+            CartStatus::Finalized => "finalized",
+            CartStatus::Aborted => "aborted",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(CartStatus::Open),
+            "payment" => Some(CartStatus::Payment),
+            "finalized" => Some(CartStatus::Finalized),
+            "aborted" => Some(CartStatus::Aborted),
+            _ => None,
+        }
+    }
+
+    /// Ainda em andamento (pode ser recuperado depois de reiniciar o app)
+    pub fn is_active(&self) -> bool {
+        matches!(self, CartStatus::Open | CartStatus::Payment)
+    }
+
+    /// Transições permitidas pela máquina de estados
+    pub fn can_become(&self, next: CartStatus) -> bool {
+        matches!(
+            (self, next),
+            (CartStatus::Open, CartStatus::Payment)
+                | (CartStatus::Payment, CartStatus::Open)
+                | (CartStatus::Payment, CartStatus::Finalized)
+                | (CartStatus::Open | CartStatus::Payment, CartStatus::Aborted)
+        )
+    }
+}
+
+/// Venda em andamento, gravada a cada passo para sobreviver a uma queda do
+/// aplicativo. `discount` e `addition` valem para o carrinho todo e são
+/// rateados entre os itens ao gerar a venda.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartEntity {
+    pub id: Option<i64>,
+    pub status: CartStatus,
+    /// Operador que abriu o carrinho, quando informado
+    pub user: Option<String>,
+    /// CPF/CNPJ do cliente
+    pub doc_destinatario: Option<String>,
+    pub discount: Money,
+    pub addition: Money,
+    /// Venda gerada na finalização
+    pub venda_id: Option<i64>,
+    pub opened_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Quando foi finalizado ou abortado
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl CartEntity {
+    pub fn new(user: Option<String>, doc_destinatario: Option<String>, opened_at: DateTime<Utc>) -> Self {
+        Self {
+            id: None,
+            status: CartStatus::Open,
+            user,
+            doc_destinatario,
+            discount: Money::ZERO,
+            addition: Money::ZERO,
+            venda_id: None,
+            opened_at,
+            updated_at: opened_at,
+            closed_at: None,
+        }
+    }
+}
+
+/// Item do carrinho, com descrição, unidade e preço fixados ao ser lido
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartItemEntity {
+    pub id: Option<i64>,
+    pub cart_id: i64,
+    pub produto_code: String,
+    pub produto_description: String,
+    pub produto_medida: String,
+    pub quantidade: Quantity,
+    pub preco_unitario: Money,
+    /// Desconto manual do item; sem ele valem as promoções
+    pub desconto: Money,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CartItemEntity {
+    /// Item de venda equivalente; `id` continua sendo o do item no carrinho
+    pub fn to_venda_item(&self) -> VendaItemEntity {
+        VendaItemEntity {
+            id: self.id,
+            desconto: self.desconto,
+            ..VendaItemEntity::new(
+                0,
+                self.produto_code.clone(),
+                self.produto_description.clone(),
+                self.produto_medida.clone(),
+                self.quantidade,
+                self.preco_unitario,
+            )
+        }
+    }
+}
+
+/// Pagamento registrado no carrinho
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartPaymentEntity {
+    pub id: Option<i64>,
+    pub cart_id: i64,
+    /// Código da forma de pagamento (`PaymentTypes`)
+    pub code: String,
+    pub name: String,
+    pub total_pagamento: Money,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CartPaymentEntity {
+    /// Pagamento da venda equivalente
+    pub fn to_venda_payment(&self) -> VendaPagamentoEntity {
+        VendaPagamentoEntity::new(0, self.code.clone(), self.name.clone(), self.total_pagamento)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        use CartStatus::*;
+        let all = [Open, Payment, Finalized, Aborted];
+        let allowed: Vec<(CartStatus, CartStatus)> = all.iter()
+            .flat_map(|from| all.iter().map(move |to| (*from, *to)))
+            .filter(|(from, to)| from.can_become(*to))
+            .collect();
+        assert_eq!(allowed, vec![(Open, Payment), (Open, Aborted), (Payment, Open), (Payment, Finalized), (Payment, Aborted)]);
+        assert!(all.iter().all(|s| CartStatus::from_str(s.as_str()) == Some(*s)));
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppError, AppResult};
+
+/// Tamanho máximo do nome de uma categoria
+pub const CATEGORY_NAME_MAX: usize = 60;
+
+/// Categoria (seção) de produtos. Categorias formam uma árvore pelo `parent_id`;
+/// as sem pai são as seções principais (ex. Padaria > Pães > Pão francês).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryEntity {
+    pub id: Option<i64>,
+    pub name: String,
+    pub parent_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CategoryEntity {
+    pub fn new(name: String, parent_id: Option<i64>) -> Self {
+        let now = Utc::now();
+        Self { id: None, name, parent_id, created_at: now, updated_at: now }
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > CATEGORY_NAME_MAX {
+            return Err(AppError::validation("name", format!("Nome deve ter de 1 a {} caracteres", CATEGORY_NAME_MAX)));
+        }
+        if self.parent_id.is_some() && self.parent_id == self.id {
+            return Err(AppError::validation("parent_id", "Categoria não pode ser pai dela mesma"));
+        }
+        Ok(())
+    }
+}
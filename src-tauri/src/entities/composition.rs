@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use super::Quantity;
+
+/// Componente de um kit: `quantity` unidades de `component_id` em cada
+/// unidade de `kit_id`. Um produto com componentes é um kit e não tem estoque
+/// próprio; vendê-lo baixa os componentes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompositionItemEntity {
+    pub kit_id: i64,
+    pub component_id: i64,
+    pub quantity: Quantity,
+}
@@ -1,5 +1,44 @@
 use serde::{Deserialize, Serialize};
 
+use crate::barcode::ScaleLayout;
+use crate::clock::DEFAULT_TIMEZONE;
+use crate::entities::Money;
+use chrono::{DateTime, Utc};
+
+/// O que fazer quando uma venda deixaria o saldo de um produto negativo
+/// (só se aplica com `controle_estoque = 1`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NegativeStockPolicy {
+    /// Recusa a venda
+    Block,
+    /// Grava a venda e devolve avisos
+    Warn,
+    /// Grava a venda sem avisar
+    #[default]
+    Allow,
+}
+
+impl NegativeStockPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NegativeStockPolicy::Block => "block",
+            NegativeStockPolicy::Warn => "warn",
+            NegativeStockPolicy::Allow => "allow",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "block" => Some(NegativeStockPolicy::Block),
+            "warn" => Some(NegativeStockPolicy::Warn),
+            "allow" => Some(NegativeStockPolicy::Allow),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigEntity {
     pub id: String,
@@ -24,8 +63,11 @@ pub struct ConfigEntity {
     pub address_neiborhood: String,
     pub address_state: String,
     pub fone: Option<String>,
-    pub created_at: i64,
-    pub updated_at: i64,
+    /// Segundos desde a época no JSON
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub updated_at: DateTime<Utc>,
     pub percent_s: i32,
     pub only_money: i32, // 0 or 1
     pub error_as_success: i32, // 0 or 1
@@ -38,6 +80,27 @@ pub struct ConfigEntity {
     pub habilitar_contador_nao: i32,
     pub controle_estoque: i32,
     pub modelo: i32,
+    #[serde(default)]
+    pub estoque_negativo: NegativeStockPolicy,
+    /// Leiaute das etiquetas de balança (códigos EAN-13 iniciados em `2`)
+    #[serde(default)]
+    pub balanca: ScaleLayout,
+    /// Diferença aceita como arredondamento ao conferir os totais de uma venda
+    #[serde(default = "default_tolerancia_total")]
+    pub tolerancia_total: Money,
+    /// Fuso horário da loja (nome IANA); define o dia local das vendas e resumos.
+    /// É lido ao iniciar o app: uma mudança só vale depois de reiniciá-lo.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+}
+
+/// Um centavo
+fn default_tolerancia_total() -> Money {
+    Money::from_cents(1)
+}
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.name().to_string()
 }
 
 impl Default for ConfigEntity {
@@ -65,8 +128,8 @@ impl Default for ConfigEntity {
             address_neiborhood: "Consolação".to_string(),
             address_state: "SP".to_string(),
             fone: None,
-            created_at: 0,
-            updated_at: 0,
+            created_at: DateTime::UNIX_EPOCH,
+            updated_at: DateTime::UNIX_EPOCH,
             percent_s: 50,
             only_money: 0,
             error_as_success: 0,
@@ -79,6 +142,10 @@ impl Default for ConfigEntity {
             habilitar_contador_nao: 0,
             controle_estoque: 0,
             modelo: 59,
+            estoque_negativo: NegativeStockPolicy::default(),
+            balanca: ScaleLayout::default(),
+            tolerancia_total: default_tolerancia_total(),
+            timezone: default_timezone(),
         }
     }
 }
@@ -1,24 +1,63 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+/// Origem de uma alteração registrada no histórico
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    /// Comando Tauri (`invoke` do frontend)
+    Tauri,
+    /// API HTTP (porta 8088)
+    Http,
+    /// Processos internos e testes
+    #[default]
+    System,
+}
+
+impl Origin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Origin::Tauri => "tauri",
+            Origin::Http => "http",
+            Origin::System => "system",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "tauri" => Some(Origin::Tauri),
+            "http" => Some(Origin::Http),
+            "system" => Some(Origin::System),
+            _ => None,
+        }
+    }
+}
+
+/// Entrada do histórico de alterações.
+///
+/// `details` guarda `{ "before": ..., "after": ... }` com os estados da
+/// entidade antes e depois da alteração (`null` quando não existem).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntity {
     pub id: Option<i64>,
     pub action: String,
     pub entity_type: String,
-    pub entity_id: Option<i64>,
-    pub details: Option<String>,
+    pub entity_id: Option<String>,
+    pub details: Option<serde_json::Value>,
+    pub origin: Origin,
     pub created_at: DateTime<Utc>,
 }
 
 impl HistoryEntity {
-    pub fn new(action: String, entity_type: String, entity_id: Option<i64>, details: Option<String>) -> Self {
+    pub fn new(action: String, entity_type: String, entity_id: Option<String>, details: Option<serde_json::Value>) -> Self {
         Self {
             id: None,
             action,
             entity_type,
             entity_id,
             details,
+            origin: Origin::default(),
             created_at: Utc::now(),
         }
     }
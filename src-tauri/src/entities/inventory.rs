@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use super::Quantity;
+
+/// Tamanho máximo do nome de uma sessão de inventário
+pub const INVENTORY_NAME_MAX: usize = 80;
+
+/// Situação de uma sessão de inventário
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryStatus {
+    /// Recebendo contagens
+    Open,
+    /// Encerrada, com as diferenças lançadas no estoque
+    Closed,
+    /// Descartada sem mexer no estoque
+    Cancelled,
+}
+
+impl InventoryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InventoryStatus::Open => "open",
+            InventoryStatus::Closed => "closed",
+            InventoryStatus::Cancelled => "cancelled",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(InventoryStatus::Open),
+            "closed" => Some(InventoryStatus::Closed),
+            "cancelled" => Some(InventoryStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// Sessão de contagem física do estoque (balanço). Na abertura o saldo de cada
+/// produto é congelado em `inventario_itens.expected`; no encerramento a
+/// diferença entre o contado e o esperado vira um ajuste no livro de estoque.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventorySessionEntity {
+    pub id: Option<i64>,
+    pub name: String,
+    pub status: InventoryStatus,
+    /// Usuário que abriu a sessão, quando informado
+    pub user: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    /// Quando foi encerrada ou cancelada
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl InventorySessionEntity {
+    pub fn new(name: String, user: Option<String>, opened_at: DateTime<Utc>) -> Self {
+        Self { id: None, name, status: InventoryStatus::Open, user, opened_at, closed_at: None }
+    }
+}
+
+/// Contagem de um produto numa sessão de inventário
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryCountEntity {
+    pub session_id: i64,
+    pub product_id: i64,
+    /// Saldo na abertura da sessão (ou na primeira leitura, para produtos que
+    /// não estavam no retrato)
+    pub expected: Quantity,
+    /// Quantidade contada; `None` enquanto o produto não foi lido
+    pub counted: Option<Quantity>,
+    /// Número de leituras registradas
+    pub scans: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl InventoryCountEntity {
+    pub fn new(session_id: i64, product_id: i64, expected: Quantity, at: DateTime<Utc>) -> Self {
+        Self { session_id, product_id, expected, counted: None, scans: 0, updated_at: at }
+    }
+}
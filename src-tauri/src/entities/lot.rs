@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::{Quantity, StockMovementEntity};
+use crate::error::{AppError, AppResult};
+
+/// Tamanho máximo do código de um lote
+pub const LOT_CODE_MAX: usize = 30;
+
+/// Lote criado com o saldo que o produto tinha ao ligar o controle de lotes
+pub const OPENING_LOT: &str = "INICIAL";
+
+/// Lote de um produto com controle de lotes (`track_lots = 1`).
+///
+/// `quantity` só muda pelos movimentos de estoque que apontam para o lote
+/// (`lot_id`), então a soma dos lotes acompanha o saldo do produto.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LotEntity {
+    pub id: Option<i64>,
+    pub product_id: i64,
+    /// Número do lote impresso na embalagem; único por produto
+    pub code: String,
+    /// Data de validade; lotes sem validade saem por último
+    pub expires_at: Option<NaiveDate>,
+    pub quantity: Quantity,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LotEntity {
+    pub fn new(product_id: i64, code: String, expires_at: Option<NaiveDate>) -> Self {
+        let now = Utc::now();
+        Self { id: None, product_id, code, expires_at, quantity: Quantity::ZERO, created_at: now, updated_at: now }
+    }
+
+    /// Chave da ordem de saída (FEFO): validade mais próxima primeiro, lotes
+    /// sem validade por último e, na mesma validade, o mais antigo
+    pub fn fefo_key(&self) -> (bool, Option<NaiveDate>, Option<i64>) {
+        (self.expires_at.is_none(), self.expires_at, self.id)
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        let code = self.code.trim();
+        if code.is_empty() || code.chars().count() > LOT_CODE_MAX {
+            return Err(AppError::validation("code", format!("Lote deve ter de 1 a {} caracteres", LOT_CODE_MAX)));
+        }
+        Ok(())
+    }
+}
+
+/// Divide uma saída sem lote (`movement.quantity` negativa) entre os `lots` do
+/// produto, já na ordem FEFO: baixa primeiro o lote que vence antes e nenhum
+/// lote fica negativo. Se a soma dos lotes não cobre a saída, qualquer que
+/// seja a política de estoque negativo, o erro é `Conflict`.
+pub fn split_exit(lots: &[LotEntity], product_code: &str, movement: &StockMovementEntity) -> AppResult<Vec<StockMovementEntity>> {
+    if lots.is_empty() {
+        return Err(AppError::validation("lot", format!("Produto {} controla lotes e não tem lote cadastrado", product_code)));
+    }
+
+    let mut remaining = -movement.quantity;
+    let mut parts = Vec::new();
+    for lot in lots.iter().filter(|l| l.quantity > Quantity::ZERO) {
+        if remaining.is_zero() {
+            break;
+        }
+        let taken = remaining.min(lot.quantity);
+        parts.push(StockMovementEntity { lot_id: lot.id, quantity: -taken, ..movement.clone() });
+        remaining -= taken;
+    }
+    if !remaining.is_zero() {
+        let available: Quantity = lots.iter().map(|l| l.quantity).filter(|q| *q > Quantity::ZERO).sum();
+        return Err(AppError::Conflict(format!(
+            "Estoque insuficiente nos lotes: {} (lotes {}, saída {})", product_code, available, -movement.quantity,
+        )));
+    }
+    Ok(parts)
+}
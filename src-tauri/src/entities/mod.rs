@@ -6,12 +6,30 @@ pub mod venda;
 pub mod venda_item;
 pub mod venda_pagamento;
 pub mod e_pagamento;
+pub mod money;
+pub mod stock_movement;
+pub mod category;
+pub mod promotion;
+pub mod composition;
+pub mod inventory;
+pub mod unit;
+pub mod lot;
+pub mod cart;
 
-pub use config::ConfigEntity;
+pub use config::{ConfigEntity, NegativeStockPolicy};
 pub use resume::{ResumeEntity, PaymentTypes};
-pub use history::HistoryEntity;
+pub use history::{HistoryEntity, Origin};
 pub use product::ProductEntity;
 pub use venda::VendaEntity;
 pub use venda_item::VendaItemEntity;
 pub use venda_pagamento::VendaPagamentoEntity;
 pub use e_pagamento::EPagamento;
+pub use money::{Money, Percent, Quantity};
+pub use stock_movement::{MovementType, StockMovementEntity};
+pub use category::CategoryEntity;
+pub use promotion::{PromotionEntity, PromotionRule, PromotionTier};
+pub use composition::CompositionItemEntity;
+pub use inventory::{InventoryCountEntity, InventorySessionEntity, InventoryStatus};
+pub use unit::UnitEntity;
+pub use lot::LotEntity;
+pub use cart::{CartEntity, CartItemEntity, CartPaymentEntity, CartStatus};
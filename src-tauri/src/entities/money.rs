@@ -0,0 +1,338 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Define um decimal de ponto fixo armazenado como inteiro de unidades mínimas.
+///
+/// No JSON o valor continua sendo um número (`12.34`), e no SQLite é gravado
+/// como INTEGER (`1234`), sem erros de arredondamento de `f64`.
+macro_rules! fixed_decimal {
+    ($(#[$doc:meta])* $name:ident, $places:expr, $scale:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(i64);
+
+        impl $name {
+            pub const ZERO: Self = Self(0);
+            /// Casas decimais
+            pub const PLACES: u32 = $places;
+            const SCALE: i64 = $scale;
+
+            /// Cria a partir das unidades mínimas (centavos, milésimos)
+            pub const fn from_units(units: i64) -> Self {
+                Self(units)
+            }
+
+            /// Valor em unidades mínimas
+            pub const fn units(self) -> i64 {
+                self.0
+            }
+
+            /// Cria a partir de um valor inteiro (`from_int(3)` = 3,00)
+            pub const fn from_int(value: i64) -> Self {
+                Self(value * Self::SCALE)
+            }
+
+            /// Converte um `f64` usando sua representação decimal mais curta,
+            /// arredondando meio para longe do zero (`0.125` -> `0.13`).
+            /// `None` para NaN, infinito ou valor que não cabe no tipo.
+            pub fn from_f64(value: f64) -> Option<Self> {
+                if !value.is_finite() {
+                    return None;
+                }
+                // A representação de `{}` nunca usa notação científica
+                format!("{}", value).parse().ok()
+            }
+
+            pub fn to_f64(self) -> f64 {
+                self.0 as f64 / Self::SCALE as f64
+            }
+
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+
+            pub fn is_zero(self) -> bool {
+                self.0 == 0
+            }
+
+            pub fn is_negative(self) -> bool {
+                self.0 < 0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let sign = if self.0 < 0 { "-" } else { "" };
+                let abs = self.0.unsigned_abs();
+                let scale = Self::SCALE as u64;
+                write!(f, "{}{}.{:0width$}", sign, abs / scale, abs % scale, width = Self::PLACES as usize)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            /// Aceita `12`, `12.3`, `-0.125`; casas extras são arredondadas
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_fixed(s.trim(), Self::PLACES)
+                    .map(Self)
+                    .ok_or_else(|| format!("Invalid {} value: '{}'", stringify!($name), s))
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, Add::add)
+            }
+        }
+
+        impl<'a> Sum<&'a $name> for $name {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_f64(self.to_f64())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+
+                impl de::Visitor<'_> for Visitor {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a decimal number")
+                    }
+
+                    fn visit_i64<E: de::Error>(self, v: i64) -> Result<$name, E> {
+                        v.checked_mul($name::SCALE)
+                            .map($name)
+                            .ok_or_else(|| E::custom("value out of range"))
+                    }
+
+                    fn visit_u64<E: de::Error>(self, v: u64) -> Result<$name, E> {
+                        i64::try_from(v)
+                            .map_err(|_| E::custom("value out of range"))
+                            .and_then(|v| self.visit_i64(v))
+                    }
+
+                    fn visit_f64<E: de::Error>(self, v: f64) -> Result<$name, E> {
+                        if !v.is_finite() {
+                            return Err(E::custom("value must be finite"));
+                        }
+                        $name::from_f64(v).ok_or_else(|| E::custom("value out of range"))
+                    }
+
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<$name, E> {
+                        v.parse().map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_any(Visitor)
+            }
+        }
+
+        impl ToSql for $name {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                Ok(ToSqlOutput::from(self.0))
+            }
+        }
+
+        impl FromSql for $name {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                // Valores REAL só existem em bancos anteriores à migração v3
+                match value {
+                    ValueRef::Integer(i) => Ok(Self(i)),
+                    _ => Err(FromSqlError::InvalidType),
+                }
+            }
+        }
+    };
+}
+
+fixed_decimal!(
+    /// Valor monetário com 2 casas decimais, guardado em centavos
+    Money, 2, 100
+);
+
+fixed_decimal!(
+    /// Quantidade com 3 casas decimais, guardada em milésimos
+    Quantity, 3, 1000
+);
+
+fixed_decimal!(
+    /// Percentual com 2 casas decimais (`12.5` = 12,5%)
+    Percent, 2, 100
+);
+
+impl Money {
+    pub const fn from_cents(cents: i64) -> Self {
+        Self(cents)
+    }
+
+    pub const fn cents(self) -> i64 {
+        self.0
+    }
+
+    /// Percentual do valor, arredondado ao centavo (meio para longe do zero)
+    pub fn percent(self, rate: Percent) -> Money {
+        Money(div_round(self.0 as i128 * rate.0 as i128, 100 * Percent::SCALE as i128) as i64)
+    }
+}
+
+impl Mul<Quantity> for Money {
+    type Output = Money;
+
+    /// Preço unitário x quantidade, arredondado ao centavo (meio para longe do zero)
+    fn mul(self, rhs: Quantity) -> Money {
+        Money(div_round(self.0 as i128 * rhs.0 as i128, Quantity::SCALE as i128) as i64)
+    }
+}
+
+impl Mul<Money> for Quantity {
+    type Output = Money;
+
+    fn mul(self, rhs: Money) -> Money {
+        rhs * self
+    }
+}
+
+impl Mul<Quantity> for Quantity {
+    type Output = Quantity;
+
+    /// Quantidade x fator (ex. componentes por kit), arredondada ao milésimo
+    fn mul(self, rhs: Quantity) -> Quantity {
+        Quantity(div_round(self.0 as i128 * rhs.0 as i128, Quantity::SCALE as i128) as i64)
+    }
+}
+
+/// Divisão inteira arredondando meio para longe do zero
+fn div_round(value: i128, divisor: i128) -> i128 {
+    let half = divisor / 2;
+    if value >= 0 {
+        (value + half) / divisor
+    } else {
+        (value - half) / divisor
+    }
+}
+
+/// Converte texto decimal para unidades mínimas com `places` casas
+fn parse_fixed(s: &str, places: u32) -> Option<i64> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+    if int_part.is_empty() && frac_part.is_empty() {
+        return None;
+    }
+    if !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let scale = 10i128.pow(places);
+    let int_value: i128 = if int_part.is_empty() { 0 } else { int_part.parse().ok()? };
+
+    let mut frac_value: i128 = 0;
+    for (i, c) in frac_part.chars().take(places as usize).enumerate() {
+        frac_value += (c as i128 - '0' as i128) * 10i128.pow(places - 1 - i as u32);
+    }
+    // Arredonda pelo primeiro dígito descartado
+    let round_up = frac_part.chars().nth(places as usize).map(|c| c >= '5').unwrap_or(false);
+
+    let mut units = int_value.checked_mul(scale)? + frac_value + round_up as i128;
+    if negative {
+        units = -units;
+    }
+
+    i64::try_from(units).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_and_rounding() {
+        assert_eq!(Money::from_f64(0.1 + 0.2).unwrap(), Money::from_cents(30));
+        assert_eq!(Money::from_f64(1.005).unwrap(), Money::from_cents(101));
+        assert_eq!(Money::from_f64(-0.125).unwrap(), Money::from_cents(-13));
+        assert_eq!("12".parse::<Money>().unwrap(), Money::from_cents(1200));
+        assert_eq!(".5".parse::<Quantity>().unwrap(), Quantity::from_units(500));
+        assert!("1,5".parse::<Money>().is_err());
+        assert_eq!(Quantity::from_f64(1.2345).unwrap().to_string(), "1.235");
+        assert_eq!(Money::from_cents(-5).to_string(), "-0.05");
+
+        // Fora do intervalo ou não finito: erro, e não zero
+        assert_eq!(Money::from_f64(f64::NAN), None);
+        assert_eq!(Money::from_f64(f64::INFINITY), None);
+        assert_eq!(Money::from_f64(1e300), None);
+        assert!(serde_json::from_str::<Money>("1e300").unwrap_err().to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn test_arithmetic_is_exact() {
+        let total: Money = std::iter::repeat_n(Money::from_f64(0.1).unwrap(), 1000).sum();
+        assert_eq!(total, Money::from_int(100));
+
+        // 0,333 kg x R$ 9,99 = 3,32667 -> 3,33
+        assert_eq!(Money::from_f64(9.99).unwrap() * Quantity::from_f64(0.333).unwrap(), Money::from_cents(333));
+        assert_eq!(Quantity::from_f64(1.5).unwrap() * Money::from_f64(2.99).unwrap(), Money::from_cents(449));
+
+        // 12,5% de R$ 9,99 = 1,24875 -> 1,25
+        assert_eq!(Money::from_f64(9.99).unwrap().percent(Percent::from_f64(12.5).unwrap()), Money::from_cents(125));
+    }
+
+    #[test]
+    fn test_json_shape_is_numeric() {
+        let json = serde_json::to_string(&(Money::from_cents(1234), Quantity::from_units(1500))).unwrap();
+        assert_eq!(json, "[12.34,1.5]");
+
+        let (money, quantity): (Money, Quantity) = serde_json::from_str("[10, \"0.255\"]").unwrap();
+        assert_eq!(money, Money::from_cents(1000));
+        assert_eq!(quantity, Quantity::from_units(255));
+    }
+}
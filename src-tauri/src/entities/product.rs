@@ -1,17 +1,77 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+use crate::barcode::is_valid_gtin;
+use crate::error::{AppError, AppResult, FieldError};
+use super::{Money, Quantity};
+
+/// Unidade comercial padrão
+pub const DEFAULT_UNIT: &str = "UN";
+
+/// Valor aceito pela SEFAZ para produtos sem código de barras
+pub const SEM_GTIN: &str = "SEM GTIN";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductEntity {
     pub id: Option<i64>,
     pub code: String,
     pub name: String,
     pub active: i32,
-    pub balance: f64,
+    pub balance: Quantity,
+    /// Preço de venda
+    #[serde(default)]
+    pub price: Money,
+    /// Unidade de venda e de estoque (`UN`, `KG`, `CX`...); o saldo está nela
+    #[serde(default = "default_unit")]
+    pub unit: String,
+    /// Unidade de compra, quando diferente da de venda (ex. `CX`)
+    #[serde(default)]
+    pub purchase_unit: Option<String>,
+    /// Quantas unidades de venda há em uma unidade de compra (ex. 12)
+    #[serde(default = "one")]
+    pub purchase_factor: Quantity,
+    /// Código de barras (EAN/GTIN-8, 12, 13 ou 14) ou `"SEM GTIN"`
+    #[serde(default)]
+    pub gtin: Option<String>,
+    /// Nomenclatura Comum do Mercosul (8 dígitos)
+    #[serde(default)]
+    pub ncm: Option<String>,
+    /// Código Especificador da Substituição Tributária (7 dígitos)
+    #[serde(default)]
+    pub cest: Option<String>,
+    /// CFOP usado nas saídas (4 dígitos, iniciando em 5, 6 ou 7)
+    #[serde(default)]
+    pub cfop: Option<String>,
+    /// Origem da mercadoria (0 a 8, tabela do ICMS)
+    #[serde(default)]
+    pub origem: i32,
+    /// CST do ICMS (2 dígitos) ou CSOSN do Simples Nacional (3 dígitos)
+    #[serde(default)]
+    pub cst: Option<String>,
+    /// Categoria (seção) do produto
+    #[serde(default)]
+    pub category_id: Option<i64>,
+    /// 1 = saldo controlado por lotes com validade (o saldo é a soma dos lotes)
+    #[serde(default)]
+    pub track_lots: i32,
+    /// Estoque mínimo; abaixo dele o produto entra em `GET /stock/low`
+    #[serde(default)]
+    pub min_stock: Option<Quantity>,
+    /// Estoque máximo; a sugestão de compra repõe até ele
+    #[serde(default)]
+    pub max_stock: Option<Quantity>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+fn default_unit() -> String {
+    DEFAULT_UNIT.to_string()
+}
+
+fn one() -> Quantity {
+    Quantity::from_int(1)
+}
+
 impl ProductEntity {
     pub fn new(code: String, name: String) -> Self {
         let now = Utc::now();
@@ -20,9 +80,118 @@ impl ProductEntity {
             code,
             name,
             active: 1,
-            balance: 0.0,
+            balance: Quantity::ZERO,
+            price: Money::ZERO,
+            unit: default_unit(),
+            purchase_unit: None,
+            purchase_factor: one(),
+            gtin: None,
+            ncm: None,
+            cest: None,
+            cfop: None,
+            origem: 0,
+            cst: None,
+            category_id: None,
+            track_lots: 0,
+            min_stock: None,
+            max_stock: None,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Quantas unidades de estoque há em uma unidade `unit`, ou `None` se o
+    /// produto não é vendido nem comprado nessa unidade
+    pub fn unit_factor(&self, unit: &str) -> Option<Quantity> {
+        let unit = unit.trim();
+        if unit.eq_ignore_ascii_case(&self.unit) {
+            Some(one())
+        } else if self.purchase_unit.as_deref().is_some_and(|p| unit.eq_ignore_ascii_case(p)) {
+            Some(self.purchase_factor)
+        } else {
+            None
+        }
+    }
+
+    /// Valida o cadastro, reunindo todos os campos inválidos num único erro
+    pub fn validate(&self) -> AppResult<()> {
+        let mut errors = Vec::new();
+        let mut check = |field: &str, ok: bool, message: &str| {
+            if !ok {
+                errors.push(FieldError::new(field, message));
+            }
+        };
+
+        check("code", !self.code.trim().is_empty(), "Código obrigatório");
+        check("name", !self.name.trim().is_empty(), "Nome obrigatório");
+        check("price", !self.price.is_negative(), "Preço não pode ser negativo");
+        check("unit", !self.unit.trim().is_empty() && self.unit.chars().count() <= 6, "Unidade deve ter de 1 a 6 caracteres");
+        if let Some(purchase_unit) = &self.purchase_unit {
+            check("purchase_unit", !purchase_unit.eq_ignore_ascii_case(&self.unit), "Unidade de compra deve ser diferente da de venda");
+        }
+        check("purchase_factor", self.purchase_factor > Quantity::ZERO, "Fator de conversão deve ser maior que zero");
+        if let Some(gtin) = &self.gtin {
+            check("gtin", gtin == SEM_GTIN || is_valid_gtin(gtin), "GTIN inválido");
+        }
+        if let Some(ncm) = &self.ncm {
+            check("ncm", is_digits(ncm, &[8]), "NCM deve ter 8 dígitos");
+        }
+        if let Some(cest) = &self.cest {
+            check("cest", is_digits(cest, &[7]), "CEST deve ter 7 dígitos");
+        }
+        if let Some(cfop) = &self.cfop {
+            check("cfop", is_digits(cfop, &[4]) && matches!(&cfop[..1], "5" | "6" | "7"), "CFOP de saída deve ter 4 dígitos e iniciar em 5, 6 ou 7");
+        }
+        check("origem", (0..=8).contains(&self.origem), "Origem deve estar entre 0 e 8");
+        check("track_lots", (0..=1).contains(&self.track_lots), "Controle de lotes deve ser 0 ou 1");
+        check("min_stock", self.min_stock.is_none_or(|q| !q.is_negative()), "Estoque mínimo não pode ser negativo");
+        check("max_stock", self.max_stock.is_none_or(|q| !q.is_negative()), "Estoque máximo não pode ser negativo");
+        if let (Some(min), Some(max)) = (self.min_stock, self.max_stock) {
+            check("max_stock", max >= min, "Estoque máximo deve ser maior ou igual ao mínimo");
+        }
+        if let Some(cst) = &self.cst {
+            check("cst", is_digits(cst, &[2, 3]), "CST deve ter 2 dígitos ou CSOSN 3 dígitos");
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::invalid_fields(errors))
+        }
+    }
+}
+
+fn is_digits(value: &str, lengths: &[usize]) -> bool {
+    lengths.contains(&value.len()) && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_fiscal_fields() {
+        let mut product = ProductEntity::new("001".to_string(), "Arroz".to_string());
+        product.gtin = Some("7891000100103".to_string());
+        product.ncm = Some("10063021".to_string());
+        product.cfop = Some("5102".to_string());
+        product.cst = Some("102".to_string());
+        assert!(product.validate().is_ok());
+
+        product.gtin = Some("7891000100104".to_string());
+        product.ncm = Some("1006".to_string());
+        product.cfop = Some("1102".to_string());
+        product.origem = 9;
+        product.price = Money::from_int(-1);
+
+        let AppError::Validation { fields, .. } = product.validate().unwrap_err() else {
+            panic!("expected validation error");
+        };
+        let fields: Vec<&str> = fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, vec!["price", "gtin", "ncm", "cfop", "origem"]);
+
+        product = ProductEntity::new("002".to_string(), "Pão".to_string());
+        product.gtin = Some(SEM_GTIN.to_string());
+        assert!(product.validate().is_ok());
+    }
 }
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use super::{Money, Percent, Quantity};
+use crate::error::{AppError, AppResult, FieldError};
+
+/// Tamanho máximo do nome de uma promoção
+pub const PROMOTION_NAME_MAX: usize = 80;
+
+/// Faixa de um desconto progressivo: a partir de `min_quantity` unidades,
+/// `percent` de desconto sobre o total do produto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromotionTier {
+    pub min_quantity: Quantity,
+    pub percent: Percent,
+}
+
+/// Regra de uma promoção. No JSON (e no banco) o tipo vai em `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PromotionRule {
+    /// Preço promocional do produto
+    Price { product_id: i64, price: Money },
+    /// Leve `buy`, pague `pay` (ex. leve 3, pague 2)
+    BuyPay { product_id: i64, buy: i64, pay: i64 },
+    /// Desconto que cresce com a quantidade do produto na venda
+    Progressive { product_id: i64, tiers: Vec<PromotionTier> },
+    /// Percentual de desconto nos produtos da categoria e das subcategorias
+    CategoryPercent { category_id: i64, percent: Percent },
+}
+
+impl PromotionRule {
+    pub fn product_id(&self) -> Option<i64> {
+        match self {
+            PromotionRule::Price { product_id, .. }
+            | PromotionRule::BuyPay { product_id, .. }
+            | PromotionRule::Progressive { product_id, .. } => Some(*product_id),
+            PromotionRule::CategoryPercent { .. } => None,
+        }
+    }
+
+    /// Desconto para `quantity` unidades a `unit_price`, totalizando `total`.
+    /// Nunca passa do próprio total.
+    pub fn discount(&self, quantity: Quantity, unit_price: Money, total: Money) -> Money {
+        let discount = match self {
+            PromotionRule::Price { price, .. } if *price < unit_price => total - quantity * *price,
+            PromotionRule::Price { .. } => Money::ZERO,
+            PromotionRule::BuyPay { buy, pay, .. } => {
+                // Só unidades inteiras formam um conjunto
+                let sets = quantity.units() / Quantity::from_int(*buy).units();
+                Quantity::from_int(sets * (buy - pay)) * unit_price
+            }
+            PromotionRule::Progressive { tiers, .. } => tiers.iter()
+                .filter(|t| t.min_quantity <= quantity)
+                .max_by_key(|t| t.min_quantity)
+                .map(|t| total.percent(t.percent))
+                .unwrap_or(Money::ZERO),
+            PromotionRule::CategoryPercent { percent, .. } => total.percent(*percent),
+        };
+        discount.clamp(Money::ZERO, total.max(Money::ZERO))
+    }
+
+    fn validate(&self, errors: &mut Vec<FieldError>) {
+        let valid_percent = |p: Percent| p > Percent::ZERO && p <= Percent::from_int(100);
+        match self {
+            PromotionRule::Price { price, .. } => {
+                if price.is_negative() {
+                    errors.push(FieldError::new("price", "Preço não pode ser negativo"));
+                }
+            }
+            PromotionRule::BuyPay { buy, pay, .. } => {
+                if *pay < 1 || buy <= pay {
+                    errors.push(FieldError::new("buy", "Quantidade levada deve ser maior que a paga, e a paga ao menos 1"));
+                }
+            }
+            PromotionRule::Progressive { tiers, .. } => {
+                if tiers.is_empty() {
+                    errors.push(FieldError::new("tiers", "Informe ao menos uma faixa"));
+                }
+                for (i, tier) in tiers.iter().enumerate() {
+                    if tier.min_quantity <= Quantity::ZERO {
+                        errors.push(FieldError::new(&format!("tiers[{}].min_quantity", i), "Quantidade mínima deve ser maior que zero"));
+                    }
+                    if !valid_percent(tier.percent) {
+                        errors.push(FieldError::new(&format!("tiers[{}].percent", i), "Percentual deve ser maior que 0 e no máximo 100"));
+                    }
+                    if tiers[..i].iter().any(|t| t.min_quantity == tier.min_quantity) {
+                        errors.push(FieldError::new(&format!("tiers[{}].min_quantity", i), "Faixa repetida"));
+                    }
+                }
+            }
+            PromotionRule::CategoryPercent { percent, .. } => {
+                if !valid_percent(*percent) {
+                    errors.push(FieldError::new("percent", "Percentual deve ser maior que 0 e no máximo 100"));
+                }
+            }
+        }
+    }
+}
+
+/// Promoção aplicada automaticamente aos itens das vendas emitidas entre
+/// `starts_at` e `ends_at` (limites ausentes deixam o período aberto)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionEntity {
+    pub id: Option<i64>,
+    pub name: String,
+    #[serde(flatten)]
+    pub rule: PromotionRule,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// 1 = ativa, 0 = suspensa
+    pub active: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PromotionEntity {
+    pub fn new(name: String, rule: PromotionRule) -> Self {
+        let now = Utc::now();
+        Self { id: None, name, rule, starts_at: None, ends_at: None, active: 1, created_at: now, updated_at: now }
+    }
+
+    /// Ativa e dentro do período no instante `at`
+    pub fn is_valid_at(&self, at: DateTime<Utc>) -> bool {
+        self.active == 1 && self.starts_at.is_none_or(|s| s <= at) && self.ends_at.is_none_or(|e| at < e)
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        let mut errors = Vec::new();
+
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > PROMOTION_NAME_MAX {
+            errors.push(FieldError::new("name", format!("Nome deve ter de 1 a {} caracteres", PROMOTION_NAME_MAX)));
+        }
+        if let (Some(starts_at), Some(ends_at)) = (self.starts_at, self.ends_at) {
+            if ends_at <= starts_at {
+                errors.push(FieldError::new("ends_at", "Fim deve ser depois do início"));
+            }
+        }
+        self.rule.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::invalid_fields(errors))
+        }
+    }
+}
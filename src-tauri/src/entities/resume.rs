@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+use super::Money;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PaymentTypes {
     #[serde(rename = "01")]
@@ -50,6 +53,26 @@ impl PaymentTypes {
         }
     }
 
+    /// Nome da forma de pagamento
+    pub fn description(&self) -> &'static str {
+        match self {
+            PaymentTypes::Dinheiro => "Dinheiro",
+            PaymentTypes::Cheque => "Cheque",
+            PaymentTypes::CartaoDeCredito => "Cartão de Crédito",
+            PaymentTypes::CartaoDeDebito => "Cartão de Débito",
+            PaymentTypes::CreditoLoja => "Crédito Loja",
+            PaymentTypes::ValeAlimentacao => "Vale Alimentação",
+            PaymentTypes::ValeRefeicao => "Vale Refeição",
+            PaymentTypes::ValePresente => "Vale Presente",
+            PaymentTypes::ValeCombustivel => "Vale Combustível",
+            PaymentTypes::DuplicataMercantil => "Duplicata Mercantil",
+            PaymentTypes::BoletoBancario => "Boleto Bancário",
+            PaymentTypes::SemPagamento => "Sem Pagamento",
+            PaymentTypes::Outros => "Outros",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "01" => Some(PaymentTypes::Dinheiro),
@@ -74,20 +97,22 @@ impl PaymentTypes {
 pub struct ResumeEntity {
     pub id: String,
     pub code: PaymentTypes,
-    pub amount_s: f64,
-    pub amount_n: f64,
-    pub updated_at: i64,
-    pub created_at: i64,
+    pub amount_s: Money,
+    pub amount_n: Money,
+    /// Milissegundos desde a época no JSON
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
 }
 
 impl ResumeEntity {
-    pub fn new(code: PaymentTypes) -> Self {
-        let now = chrono::Utc::now().timestamp_millis();
+    pub fn new(code: PaymentTypes, now: DateTime<Utc>) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             code,
-            amount_s: 0.0,
-            amount_n: 0.0,
+            amount_s: Money::ZERO,
+            amount_n: Money::ZERO,
             updated_at: now,
             created_at: now,
         }
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use super::{Origin, Quantity};
+
+/// Tipo de movimento de estoque
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementType {
+    /// Saída por venda
+    Sale,
+    /// Entrada pelo cancelamento de uma venda
+    Cancellation,
+    /// Entrada de compra (nota de entrada)
+    Purchase,
+    /// Ajuste manual (inventário, correções); pode ser positivo ou negativo
+    Adjustment,
+    /// Saída por perda, quebra ou vencimento
+    Loss,
+    /// Entrada por devolução de cliente
+    Return,
+}
+
+impl MovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementType::Sale => "sale",
+            MovementType::Cancellation => "cancellation",
+            MovementType::Purchase => "purchase",
+            MovementType::Adjustment => "adjustment",
+            MovementType::Loss => "loss",
+            MovementType::Return => "return",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "sale" => Some(MovementType::Sale),
+            "cancellation" => Some(MovementType::Cancellation),
+            "purchase" => Some(MovementType::Purchase),
+            "adjustment" => Some(MovementType::Adjustment),
+            "loss" => Some(MovementType::Loss),
+            "return" => Some(MovementType::Return),
+            _ => None,
+        }
+    }
+
+    /// `Some(true)` para entradas, `Some(false)` para saídas e `None` para
+    /// ajustes, cujo sinal vem da própria quantidade
+    pub fn is_entry(&self) -> Option<bool> {
+        match self {
+            MovementType::Cancellation | MovementType::Purchase | MovementType::Return => Some(true),
+            MovementType::Sale | MovementType::Loss => Some(false),
+            MovementType::Adjustment => None,
+        }
+    }
+}
+
+/// Linha do livro de movimentos de estoque (kardex).
+///
+/// `quantity` é positiva nas entradas e negativa nas saídas; `balance_after` é
+/// o saldo do produto logo após o movimento.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockMovementEntity {
+    pub id: Option<i64>,
+    pub product_id: i64,
+    #[serde(rename = "type")]
+    pub kind: MovementType,
+    pub quantity: Quantity,
+    pub balance_after: Quantity,
+    /// Documento de origem (ex. `venda:12`, número da nota de compra)
+    pub reference: Option<String>,
+    /// Usuário que fez o movimento, quando informado
+    pub user: Option<String>,
+    /// Lote movimentado, nos produtos com controle de lotes
+    #[serde(default)]
+    pub lot_id: Option<i64>,
+    pub origin: Origin,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StockMovementEntity {
+    pub fn new(product_id: i64, kind: MovementType, quantity: Quantity, reference: Option<String>, user: Option<String>) -> Self {
+        Self {
+            id: None,
+            product_id,
+            kind,
+            quantity,
+            balance_after: Quantity::ZERO,
+            reference,
+            user,
+            lot_id: None,
+            origin: Origin::default(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Referência gravada nos movimentos de uma venda e do seu cancelamento
+    pub fn venda_reference(venda_id: i64) -> String {
+        format!("venda:{}", venda_id)
+    }
+
+    /// Referência gravada nos ajustes do encerramento de um inventário
+    pub fn inventory_reference(session_id: i64) -> String {
+        format!("inventario:{}", session_id)
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppError, AppResult, FieldError};
+use super::Quantity;
+
+/// Tamanho máximo do código de uma unidade (limite do `uCom` da NF-e)
+pub const UNIT_CODE_MAX: usize = 6;
+
+/// Tamanho máximo da descrição de uma unidade
+pub const UNIT_DESCRIPTION_MAX: usize = 60;
+
+/// Casas decimais que uma `Quantity` comporta
+pub const UNIT_DECIMALS_MAX: u32 = 3;
+
+/// Unidades cadastradas em bancos novos: código, descrição e casas decimais
+pub const DEFAULT_UNITS: &[(&str, &str, u32)] = &[
+    ("UN", "Unidade", 0),
+    ("KG", "Quilograma", 3),
+    ("G", "Grama", 0),
+    ("L", "Litro", 3),
+    ("ML", "Mililitro", 0),
+    ("M", "Metro", 3),
+    ("CX", "Caixa", 0),
+    ("PCT", "Pacote", 0),
+    ("FD", "Fardo", 0),
+    ("DZ", "Dúzia", 0),
+];
+
+/// Unidade de medida (`UN`, `KG`, `CX`...). `decimals` limita as casas
+/// decimais das quantidades nessa unidade: `UN` não aceita fração, `KG` aceita
+/// até gramas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitEntity {
+    pub code: String,
+    pub description: String,
+    pub decimals: u32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UnitEntity {
+    pub fn new(code: String, description: String, decimals: u32) -> Self {
+        let now = Utc::now();
+        Self { code, description, decimals, created_at: now, updated_at: now }
+    }
+
+    /// Unidades de `DEFAULT_UNITS`
+    pub fn defaults() -> Vec<Self> {
+        DEFAULT_UNITS.iter()
+            .map(|(code, description, decimals)| Self::new(code.to_string(), description.to_string(), *decimals))
+            .collect()
+    }
+
+    /// Código como gravado: sem espaços nas pontas e em maiúsculas
+    pub fn normalize_code(code: &str) -> String {
+        code.trim().to_uppercase()
+    }
+
+    /// Menor fração da unidade, em milésimos (`UN` = 1000, `KG` = 1)
+    fn step(&self) -> i64 {
+        10i64.pow(UNIT_DECIMALS_MAX - self.decimals.min(UNIT_DECIMALS_MAX))
+    }
+
+    /// A quantidade não tem mais casas decimais do que a unidade permite
+    pub fn accepts(&self, quantity: Quantity) -> bool {
+        quantity.units() % self.step() == 0
+    }
+
+    /// Arredonda para cima até as casas decimais da unidade (`2.1 UN` -> `3`)
+    pub fn round_up(&self, quantity: Quantity) -> Quantity {
+        let step = self.step();
+        let units = quantity.units();
+        let whole = units.div_euclid(step) + (units.rem_euclid(step) != 0) as i64;
+        Quantity::from_units(whole * step)
+    }
+
+    /// Mensagem para uma quantidade recusada por `accepts`
+    pub fn decimals_message(&self) -> String {
+        match self.decimals {
+            0 => format!("Unidade {} não aceita quantidade fracionada", self.code),
+            n => format!("Unidade {} aceita no máximo {} casas decimais", self.code, n),
+        }
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        let mut errors = Vec::new();
+
+        let code_len = self.code.chars().count();
+        if code_len == 0 || code_len > UNIT_CODE_MAX || !self.code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            errors.push(FieldError::new("code", format!("Código deve ter de 1 a {} letras ou dígitos", UNIT_CODE_MAX)));
+        }
+        let description = self.description.trim();
+        if description.is_empty() || description.chars().count() > UNIT_DESCRIPTION_MAX {
+            errors.push(FieldError::new("description", format!("Descrição deve ter de 1 a {} caracteres", UNIT_DESCRIPTION_MAX)));
+        }
+        if self.decimals > UNIT_DECIMALS_MAX {
+            errors.push(FieldError::new("decimals", format!("Casas decimais devem ser de 0 a {}", UNIT_DECIMALS_MAX)));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::invalid_fields(errors))
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+use super::Money;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VendaEntity {
     pub id: Option<i64>,
@@ -14,9 +16,9 @@ pub struct VendaEntity {
     pub doc_destinatario: Option<String>,
     pub dh_emi: String,
     pub dh_emi_canc: Option<String>,
-    pub total: f64,
-    pub addition: f64,
-    pub discount: f64,
+    pub total: Money,
+    pub addition: Money,
+    pub discount: Money,
     pub chave: String,
     pub chave_canc: Option<String>,
     pub file_path: Option<String>,
@@ -28,6 +30,7 @@ pub struct VendaEntity {
 }
 
 impl VendaEntity {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         tip: i32,
         mod_: i32,
@@ -35,7 +38,7 @@ impl VendaEntity {
         nr_nf: i32,
         cnpj: String,
         dh_emi: String,
-        total: f64,
+        total: Money,
         chave: String,
     ) -> Self {
         let now = Utc::now();
@@ -52,8 +55,8 @@ impl VendaEntity {
             dh_emi,
             dh_emi_canc: None,
             total,
-            addition: 0.0,
-            discount: 0.0,
+            addition: Money::ZERO,
+            discount: Money::ZERO,
             chave,
             chave_canc: None,
             file_path: None,
@@ -1,22 +1,39 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::{Money, ProductEntity, Quantity};
+
+/// Item de venda.
+///
+/// Na criação basta enviar `produto_code` e `quantidade`: descrição, preço e
+/// dados fiscais vêm do cadastro do produto (ver `inherit`), e a unidade
+/// ausente também.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VendaItemEntity {
     pub id: Option<i64>,
     pub venda_id: i64,
     pub produto_code: String,
     pub produto_description: String,
     pub produto_medida: String,
-    pub quantidade: f64,
-    pub preco_unitario: f64,
-    pub desconto: f64,
-    pub desconto_rat: f64,
-    pub acrescimo: f64,
-    pub acrescimo_rat: f64,
-    pub preco_total: f64,
+    pub quantidade: Quantity,
+    pub preco_unitario: Money,
+    pub desconto: Money,
+    pub desconto_rat: Money,
+    pub acrescimo: Money,
+    pub acrescimo_rat: Money,
+    /// Valor líquido do item: `quantidade * preco_unitario - desconto + acrescimo`
+    pub preco_total: Money,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub gtin: Option<String>,
+    pub ncm: Option<String>,
+    pub cest: Option<String>,
+    pub cfop: Option<String>,
+    pub origem: Option<i32>,
+    pub cst: Option<String>,
+    /// Promoção que gerou o `desconto` do item, quando aplicada pelo sistema
+    pub promotion_id: Option<i64>,
 }
 
 impl VendaItemEntity {
@@ -25,8 +42,8 @@ impl VendaItemEntity {
         produto_code: String,
         produto_description: String,
         produto_medida: String,
-        quantidade: f64,
-        preco_unitario: f64,
+        quantidade: Quantity,
+        preco_unitario: Money,
     ) -> Self {
         let now = Utc::now();
         let preco_total = quantidade * preco_unitario;
@@ -38,13 +55,58 @@ impl VendaItemEntity {
             produto_medida,
             quantidade,
             preco_unitario,
-            desconto: 0.0,
-            desconto_rat: 0.0,
-            acrescimo: 0.0,
-            acrescimo_rat: 0.0,
+            desconto: Money::ZERO,
+            desconto_rat: Money::ZERO,
+            acrescimo: Money::ZERO,
+            acrescimo_rat: Money::ZERO,
             preco_total,
             created_at: now,
             updated_at: now,
+            ..Self::default()
+        }
+    }
+
+    /// Valor bruto do item (`quantidade * preco_unitario`)
+    pub fn gross_total(&self) -> Money {
+        self.quantidade * self.preco_unitario
+    }
+
+    /// Valor líquido do item (`quantidade * preco_unitario - desconto + acrescimo`)
+    pub fn net_total(&self) -> Money {
+        self.gross_total() - self.desconto + self.acrescimo
+    }
+
+    /// Cria um item com descrição, unidade, preço e dados fiscais do produto
+    pub fn from_product(venda_id: i64, product: &ProductEntity, quantidade: Quantity) -> Self {
+        let mut item = Self::new(venda_id, product.code.clone(), String::new(), String::new(), quantidade, Money::ZERO);
+        item.inherit(product);
+        item
+    }
+
+    /// Copia do produto a descrição, os dados fiscais (GTIN, NCM, CEST, CFOP,
+    /// origem e CST) e o preço, descartando o que o item trouxer; só a unidade
+    /// vazia é preenchida com a do cadastro. Num item na unidade de compra (ex.
+    /// a caixa fechada) o preço é multiplicado pelo fator de conversão.
+    ///
+    /// Produtos com preço zero no cadastro são de preço aberto (ex. vendidos
+    /// por etiqueta de preço da balança): o `preco_unitario` do item é aceito
+    /// como veio, sem conferência com o cadastro.
+    pub fn inherit(&mut self, product: &ProductEntity) {
+        self.produto_description = product.name.clone();
+        if self.produto_medida.trim().is_empty() {
+            self.produto_medida = product.unit.clone();
+        }
+        if !product.price.is_zero() {
+            let factor = product.unit_factor(&self.produto_medida).unwrap_or(Quantity::from_int(1));
+            self.preco_unitario = product.price * factor;
         }
+        self.preco_total = self.net_total();
+
+        self.gtin = product.gtin.clone();
+        self.ncm = product.ncm.clone();
+        self.cest = product.cest.clone();
+        self.cfop = product.cfop.clone();
+        self.origem = Some(product.origem);
+        self.cst = product.cst.clone();
     }
 }
@@ -1,19 +1,21 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+use super::Money;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VendaPagamentoEntity {
     pub id: Option<i64>,
     pub venda_id: i64,
     pub code: String,
     pub name: String,
-    pub total_pagamento: f64,
+    pub total_pagamento: Money,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl VendaPagamentoEntity {
-    pub fn new(venda_id: i64, code: String, name: String, total_pagamento: f64) -> Self {
+    pub fn new(venda_id: i64, code: String, name: String, total_pagamento: Money) -> Self {
         let now = Utc::now();
         Self {
             id: None,
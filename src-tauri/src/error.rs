@@ -0,0 +1,218 @@
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Resultado padrão de repositórios, serviços, use cases e comandos
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Erro de um campo específico numa validação
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
+
+/// Erro da aplicação.
+///
+/// Serializa como `{ "code": "...", "message": "...", "fields": [...] }` tanto
+/// no `invoke` do Tauri quanto no corpo das respostas HTTP; `code` é estável e
+/// pode ser usado pelos clientes para decidir o que fazer.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// Registro inexistente
+    #[error("{0}")]
+    NotFound(String),
+
+    /// Dados de entrada inválidos
+    #[error("{message}")]
+    Validation { message: String, fields: Vec<FieldError> },
+
+    /// Conflito com o estado atual (ex. código duplicado)
+    #[error("{0}")]
+    Conflict(String),
+
+    /// Operação recusada para a origem da chamada
+    #[error("{0}")]
+    Forbidden(String),
+
+    /// Falha no banco de dados
+    #[error("{0}")]
+    Database(String),
+
+    /// Falha ao consultar um serviço externo
+    #[error("{0}")]
+    ExternalService(String),
+
+    /// Falha inesperada
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(message.into())
+    }
+
+    /// Erro de validação de um único campo
+    pub fn validation(field: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::Validation {
+            fields: vec![FieldError::new(field, message.clone())],
+            message,
+        }
+    }
+
+    /// Erro de validação com vários campos
+    pub fn invalid_fields(fields: Vec<FieldError>) -> Self {
+        let message = fields.iter()
+            .map(|f| format!("{}: {}", f.field, f.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Self::Validation { message, fields }
+    }
+
+    /// Código estável, legível por máquina
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::Validation { .. } => "VALIDATION_ERROR",
+            Self::Conflict(_) => "CONFLICT",
+            Self::Forbidden(_) => "FORBIDDEN",
+            Self::Database(_) => "DATABASE_ERROR",
+            Self::ExternalService(_) => "EXTERNAL_SERVICE_ERROR",
+            Self::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Validation { .. } => StatusCode::BAD_REQUEST,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::Database(_) | Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::ExternalService(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    /// Prefixa a mensagem com o contexto (`"Failed to save product: ..."`),
+    /// mantendo a variante
+    pub fn context(self, context: &str) -> Self {
+        let prefix = |message: String| format!("{}: {}", context, message);
+        match self {
+            Self::NotFound(m) => Self::NotFound(prefix(m)),
+            Self::Validation { message, fields } => Self::Validation { message: prefix(message), fields },
+            Self::Conflict(m) => Self::Conflict(prefix(m)),
+            Self::Forbidden(m) => Self::Forbidden(prefix(m)),
+            Self::Database(m) => Self::Database(prefix(m)),
+            Self::ExternalService(m) => Self::ExternalService(prefix(m)),
+            Self::Internal(m) => Self::Internal(prefix(m)),
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = match self {
+            Self::Validation { fields, .. } if !fields.is_empty() => Some(fields),
+            _ => None,
+        };
+
+        let mut state = serializer.serialize_struct("AppError", 2 + fields.is_some() as usize)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let Some(fields) = fields {
+            state.serialize_field("fields", fields)?;
+        }
+        state.end()
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        (self.status(), Json(self)).into_response()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::ConstraintViolation) => Self::Conflict(e.to_string()),
+            _ => Self::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(e: r2d2::Error) -> Self {
+        Self::Database(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::ExternalService(e.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+/// Converte o erro de origem em `AppError` acrescentando contexto
+pub trait ResultExt<T> {
+    fn context(self, context: &str) -> AppResult<T>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: &str) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_code_and_fields() {
+        let json = serde_json::to_value(AppError::validation("cnpj", "CNPJ inválido")).unwrap();
+        assert_eq!(json["code"], "VALIDATION_ERROR");
+        assert_eq!(json["message"], "CNPJ inválido");
+        assert_eq!(json["fields"][0]["field"], "cnpj");
+
+        let json = serde_json::to_value(AppError::not_found("Venda 1 não encontrada")).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "NOT_FOUND", "message": "Venda 1 não encontrada" }));
+    }
+
+    #[test]
+    fn test_constraint_violation_is_conflict() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (code TEXT UNIQUE); INSERT INTO t VALUES ('a');").unwrap();
+
+        let err = conn.execute("INSERT INTO t VALUES ('a')", []).context("Failed to insert").unwrap_err();
+        assert_eq!(err.code(), "CONFLICT");
+        assert_eq!(err.status(), StatusCode::CONFLICT);
+        assert!(err.to_string().starts_with("Failed to insert: UNIQUE constraint failed"));
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::Mutex;
+
+use crate::dtos::LowStockDto;
+
+/// Avisos que os serviços mandam ao frontend. No app viram eventos Tauri; o
+/// mesmo destino é compartilhado pelos comandos e pelo servidor HTTP.
+pub trait EventSink: Send + Sync {
+    /// Produtos que uma operação levou para baixo do estoque mínimo
+    fn low_stock(&self, products: &[LowStockDto]);
+}
+
+/// Descarta os avisos (estado sem frontend)
+#[derive(Debug, Default)]
+pub struct NoEvents;
+
+impl EventSink for NoEvents {
+    fn low_stock(&self, _products: &[LowStockDto]) {}
+}
+
+/// Guarda os avisos recebidos, para testes
+#[derive(Debug, Default)]
+pub struct RecordedEvents {
+    pub low_stock: Mutex<Vec<Vec<LowStockDto>>>,
+}
+
+impl RecordedEvents {
+    /// Códigos dos produtos avisados, na ordem dos avisos
+    pub fn low_stock_codes(&self) -> Vec<String> {
+        self.low_stock.lock().map(|events| {
+            events.iter().flatten().map(|p| p.code.clone()).collect()
+        }).unwrap_or_default()
+    }
+}
+
+impl EventSink for RecordedEvents {
+    fn low_stock(&self, products: &[LowStockDto]) {
+        if let Ok(mut events) = self.low_stock.lock() {
+            events.push(products.to_vec());
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    routing::{get, post},
+    Router,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::database::{BackupService, IntegrityService};
+use crate::error::{AppError, AppResult};
+
+/// Estado das rotas administrativas (específicas do SQLite)
+#[derive(Clone)]
+pub struct AdminState {
+    pub backups: BackupService,
+    pub integrity: IntegrityService,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreBackupBody {
+    pub file_name: String,
+    /// Repete `file_name`, confirmando que o banco em uso será substituído
+    #[serde(default)]
+    pub confirm: String,
+}
+
+/// Executa uma operação bloqueante de backup fora do runtime assíncrono
+async fn blocking<T, F>(f: F) -> AppResult<Json<T>>
+where
+    T: serde::Serialize + Send + 'static,
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+{
+    let value = tokio::task::spawn_blocking(f).await??;
+    Ok(Json(value))
+}
+
+/// GET /admin/backup
+async fn list_backups(State(state): State<AdminState>) -> impl IntoResponse {
+    blocking(move || state.backups.list_backups()).await
+}
+
+/// POST /admin/backup
+async fn create_backup(State(state): State<AdminState>) -> impl IntoResponse {
+    blocking(move || state.backups.create_backup()).await
+}
+
+/// POST /admin/backup/restore
+///
+/// Substitui o banco em uso, então só é aceita de integrações locais: o
+/// servidor escuta apenas em 127.0.0.1, as rotas `/admin` ficam fora do CORS e
+/// chamadas com `Origin` (enviado pelos navegadores) são recusadas com
+/// `Forbidden`. O corpo precisa repetir o nome do arquivo em `confirm`.
+async fn restore_backup(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Json(body): Json<RestoreBackupBody>,
+) -> AppResult<impl IntoResponse> {
+    if headers.contains_key(header::ORIGIN) {
+        return Err(AppError::Forbidden("Restauração de backup não é aceita de páginas web".to_string()));
+    }
+    if body.confirm != body.file_name {
+        return Err(AppError::validation("confirm", "Repita o nome do arquivo em confirm para restaurar"));
+    }
+    blocking(move || state.backups.restore_backup(&body.file_name)).await
+}
+
+/// GET /admin/integrity
+async fn check_integrity(State(state): State<AdminState>) -> impl IntoResponse {
+    blocking(move || state.integrity.check()).await
+}
+
+/// Cria as rotas administrativas. Reparar o banco fica só nos comandos Tauri;
+/// a restauração de backup tem as proteções de `restore_backup`.
+pub fn admin_routes() -> Router<AdminState> {
+    Router::new()
+        .route("/backup", get(list_backups).post(create_backup))
+        .route("/backup/restore", post(restore_backup))
+        .route("/integrity", get(check_integrity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{BackupConfig, PoolConfig, SqliteDbService};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    async fn restore(app: &Router, body: Value, origin: Option<&str>) -> (StatusCode, Value) {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/admin/backup/restore")
+            .header("content-type", "application/json");
+        if let Some(origin) = origin {
+            request = request.header("origin", origin);
+        }
+
+        let response = app.clone().oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_restore_route() {
+        let dir = std::env::temp_dir().join(format!("rabbit-admin-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = SqliteDbService::with_config(dir.join("db.sqlite"), &PoolConfig::default()).unwrap();
+        db.initialize().unwrap();
+        let backups = BackupService::new(db.clone(), BackupConfig { dir: dir.join("backups"), keep: 2, keep_pre_restore: 1, interval: None });
+        let file_name = backups.create_backup().unwrap().file_name;
+        let app = Router::new().nest("/admin", admin_routes().with_state(AdminState { integrity: IntegrityService::new(db), backups }));
+
+        let body = json!({ "fileName": file_name, "confirm": file_name });
+        let (status, error) = restore(&app, body.clone(), Some("http://example.com")).await;
+        assert_eq!((status, error["code"].as_str()), (StatusCode::FORBIDDEN, Some("FORBIDDEN")));
+
+        let (status, error) = restore(&app, json!({ "fileName": file_name }), None).await;
+        assert_eq!((status, error["fields"][0]["field"].as_str()), (StatusCode::BAD_REQUEST, Some("confirm")));
+
+        let (status, restored) = restore(&app, body, None).await;
+        assert_eq!((status, restored["file_name"].as_str()), (StatusCode::OK, Some(file_name.as_str())));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, Json, State},
+    routing::{delete, get, post, put},
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::dtos::{AddCartItemDto, AddCartPaymentDto, CartDiscountDto, FinalizeCartDto, OpenCartDto, UpdateCartItemDto};
+use crate::error::{AppError, AppResult};
+use crate::services::CartService;
+use crate::state::AppState;
+
+/// POST /carts
+async fn open_cart(
+    State(state): State<AppState>,
+    Json(body): Json<OpenCartDto>,
+) -> AppResult<impl IntoResponse> {
+    let cart = CartService::open(&state, body)?;
+    Ok((StatusCode::CREATED, Json(cart)))
+}
+
+/// GET /carts/current
+async fn current_cart(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::current(&state)?;
+    Ok(Json(cart))
+}
+
+/// GET /carts/:id
+async fn get_cart(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::find_by_id(&state, id)?
+        .ok_or_else(|| AppError::not_found(format!("Cart with id {} not found", id)))?;
+    Ok(Json(cart))
+}
+
+/// POST /carts/:id/items
+async fn add_item(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<AddCartItemDto>,
+) -> AppResult<impl IntoResponse> {
+    let cart = CartService::add_item(&state, id, body)?;
+    Ok(Json(cart))
+}
+
+/// PUT /carts/:id/items/:item_id
+async fn update_item(
+    State(state): State<AppState>,
+    Path((id, item_id)): Path<(i64, i64)>,
+    Json(body): Json<UpdateCartItemDto>,
+) -> AppResult<impl IntoResponse> {
+    let cart = CartService::update_item(&state, id, item_id, body)?;
+    Ok(Json(cart))
+}
+
+/// DELETE /carts/:id/items/:item_id
+async fn remove_item(State(state): State<AppState>, Path((id, item_id)): Path<(i64, i64)>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::remove_item(&state, id, item_id)?;
+    Ok(Json(cart))
+}
+
+/// PUT /carts/:id/discount
+async fn set_discount(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<CartDiscountDto>,
+) -> AppResult<impl IntoResponse> {
+    let cart = CartService::set_discount(&state, id, body)?;
+    Ok(Json(cart))
+}
+
+/// POST /carts/:id/checkout
+async fn checkout(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::checkout(&state, id)?;
+    Ok(Json(cart))
+}
+
+/// POST /carts/:id/reopen
+async fn reopen(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::reopen(&state, id)?;
+    Ok(Json(cart))
+}
+
+/// POST /carts/:id/payments
+async fn add_payment(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<AddCartPaymentDto>,
+) -> AppResult<impl IntoResponse> {
+    let cart = CartService::add_payment(&state, id, body)?;
+    Ok(Json(cart))
+}
+
+/// DELETE /carts/:id/payments/:payment_id
+async fn remove_payment(State(state): State<AppState>, Path((id, payment_id)): Path<(i64, i64)>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::remove_payment(&state, id, payment_id)?;
+    Ok(Json(cart))
+}
+
+/// POST /carts/:id/finalize
+async fn finalize(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<FinalizeCartDto>,
+) -> AppResult<impl IntoResponse> {
+    let finalized = CartService::finalize(&state, id, body)?;
+    Ok((StatusCode::CREATED, Json(finalized)))
+}
+
+/// POST /carts/:id/abort
+async fn abort(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let cart = CartService::abort(&state, id)?;
+    Ok(Json(cart))
+}
+
+/// Cria as rotas do controller de carrinhos
+pub fn cart_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(open_cart))
+        .route("/current", get(current_cart))
+        .route("/:id", get(get_cart))
+        .route("/:id/items", post(add_item))
+        .route("/:id/items/:item_id", put(update_item).delete(remove_item))
+        .route("/:id/discount", put(set_discount))
+        .route("/:id/checkout", post(checkout))
+        .route("/:id/reopen", post(reopen))
+        .route("/:id/payments", post(add_payment))
+        .route("/:id/payments/:payment_id", delete(remove_payment))
+        .route("/:id/finalize", post(finalize))
+        .route("/:id/abort", post(abort))
+}
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Path, Json, Query, State},
+    routing::{get, post},
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::dtos::{CategoryProductsQuery, CreateCategoryDto, DeleteCategoryQuery, ReassignProductsDto, UpdateCategoryDto};
+use crate::error::{AppError, AppResult};
+use crate::services::CategoryService;
+use crate::state::AppState;
+
+/// POST /categories
+async fn create_category(
+    State(state): State<AppState>,
+    Json(body): Json<CreateCategoryDto>,
+) -> AppResult<impl IntoResponse> {
+    let category = CategoryService::create(&state, body)?;
+    Ok((StatusCode::CREATED, Json(category)))
+}
+
+/// GET /categories
+async fn list_categories(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let categories = CategoryService::find_all(&state)?;
+    Ok(Json(categories))
+}
+
+/// GET /categories/tree
+async fn get_tree(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let tree = CategoryService::tree(&state)?;
+    Ok(Json(tree))
+}
+
+/// POST /categories/reassign
+async fn reassign_products(
+    State(state): State<AppState>,
+    Json(body): Json<ReassignProductsDto>,
+) -> AppResult<impl IntoResponse> {
+    let products = CategoryService::reassign(&state, body)?;
+    Ok(Json(products))
+}
+
+/// GET /categories/:id
+async fn get_category(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let category = CategoryService::find_by_id(&state, id)?
+        .ok_or_else(|| AppError::not_found(format!("Category with id {} not found", id)))?;
+    Ok(Json(category))
+}
+
+/// PUT /categories/:id
+async fn update_category(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<UpdateCategoryDto>,
+) -> AppResult<impl IntoResponse> {
+    let category = CategoryService::update(&state, id, body)?;
+    Ok(Json(category))
+}
+
+/// DELETE /categories/:id?reassignTo=2
+async fn delete_category(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<DeleteCategoryQuery>,
+) -> AppResult<impl IntoResponse> {
+    CategoryService::delete(&state, id, query.reassign_to)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /categories/:id/products?subcategories=false&active=true
+async fn get_category_products(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<CategoryProductsQuery>,
+) -> AppResult<impl IntoResponse> {
+    let products = CategoryService::products(&state, id, &query)?;
+    Ok(Json(products))
+}
+
+/// Cria as rotas do controller de categorias
+pub fn category_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_categories).post(create_category))
+        .route("/tree", get(get_tree))
+        .route("/reassign", post(reassign_products))
+        .route("/:id", get(get_category).put(update_category).delete(delete_category))
+        .route("/:id/products", get(get_category_products))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    async fn call(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_category_routes() {
+        let app = Router::new()
+            .nest("/categories", category_routes())
+            .nest("/products", crate::http::controllers::product_routes())
+            .with_state(AppState::in_memory());
+
+        let (status, bebidas) = call(&app, "POST", "/categories", Some(json!({ "name": "Bebidas" }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let bebidas = bebidas["id"].as_i64().unwrap();
+        let (_, refri) = call(&app, "POST", "/categories", Some(json!({ "name": "Refrigerantes", "parent_id": bebidas }))).await;
+        let refri = refri["id"].as_i64().unwrap();
+
+        let (status, _) = call(&app, "POST", "/categories", Some(json!({ "name": "refrigerantes", "parent_id": bebidas }))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, body) = call(&app, "PUT", &format!("/categories/{}", bebidas), Some(json!({ "parent_id": refri }))).await;
+        assert_eq!((status, body["fields"][0]["field"].as_str()), (StatusCode::BAD_REQUEST, Some("parent_id")));
+
+        let (_, product) = call(&app, "POST", "/products", Some(json!({ "code": "001", "name": "Guaraná", "category_id": refri }))).await;
+        assert_eq!(product["category_id"].as_i64(), Some(refri));
+        let (status, _) = call(&app, "POST", "/products", Some(json!({ "code": "002", "name": "Pão", "category_id": 99 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, tree) = call(&app, "GET", "/categories/tree", None).await;
+        assert_eq!(tree[0]["children"][0]["path"], "Bebidas > Refrigerantes");
+
+        let (_, products) = call(&app, "GET", &format!("/categories/{}/products", bebidas), None).await;
+        assert_eq!(products.as_array().unwrap().len(), 1);
+        let (_, products) = call(&app, "GET", &format!("/categories/{}/products?subcategories=false", bebidas), None).await;
+        assert_eq!(products.as_array().unwrap().len(), 0);
+
+        let (status, _) = call(&app, "DELETE", &format!("/categories/{}", refri), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (_, product) = call(&app, "GET", "/products/code/001", None).await;
+        assert_eq!(product["category_id"].as_i64(), Some(bebidas));
+
+        let (_, moved) = call(&app, "POST", "/categories/reassign", Some(json!({ "product_ids": [product["id"]] }))).await;
+        assert_eq!(moved[0]["category_id"], Value::Null);
+
+        let (status, body) = call(&app, "GET", "/categories/99", None).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::NOT_FOUND, Some("NOT_FOUND")));
+    }
+}
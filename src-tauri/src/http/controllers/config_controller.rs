@@ -1,13 +1,13 @@
 use axum::{
-    extract::{Path, Json},
+    extract::{Path, Json, State},
     routing::{get, post, patch},
     Router,
-    http::StatusCode,
     response::IntoResponse,
 };
-use serde_json::json;
 
 use crate::dtos::{CreateOrUpdateConfigDto, UpdatePercentConfigDto};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
 use crate::usecases::{
     CreateOrUpdateConfigUseCase,
     GetFirstConfigUseCase,
@@ -16,52 +16,38 @@ use crate::usecases::{
 };
 
 /// GET /config/cnpj/:cnpj
-async fn get_cnpj(Path(cnpj): Path<String>) -> impl IntoResponse {
-    match GetCnpjUseCase::execute(cnpj).await {
-        Ok(data) => (StatusCode::OK, Json(data)).into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+async fn get_cnpj(Path(cnpj): Path<String>) -> AppResult<impl IntoResponse> {
+    let data = GetCnpjUseCase::execute(cnpj).await?;
+    Ok(Json(data))
 }
 
 /// GET /config/
-async fn get_first_config() -> impl IntoResponse {
-    match GetFirstConfigUseCase::execute() {
-        Ok(Some(config)) => (StatusCode::OK, Json(config)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({ "error": "Configuração não encontrada" }))).into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+async fn get_first_config(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let config = GetFirstConfigUseCase::execute(&state)?
+        .ok_or_else(|| AppError::not_found("Configuração não encontrada"))?;
+    Ok(Json(config))
 }
 
 /// POST /config/
-async fn create_or_update_config(Json(body): Json<CreateOrUpdateConfigDto>) -> impl IntoResponse {
-    match CreateOrUpdateConfigUseCase::execute(body) {
-        Ok(config) => (StatusCode::OK, Json(config)).into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+async fn create_or_update_config(
+    State(state): State<AppState>,
+    Json(body): Json<CreateOrUpdateConfigDto>,
+) -> AppResult<impl IntoResponse> {
+    let config = CreateOrUpdateConfigUseCase::execute(&state, body)?;
+    Ok(Json(config))
 }
 
 /// PATCH /config/percent
-async fn update_percent(Json(body): Json<UpdatePercentConfigDto>) -> impl IntoResponse {
-    match UpdatePercentUseCase::execute(body) {
-        Ok(config) => (StatusCode::OK, Json(config)).into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+async fn update_percent(
+    State(state): State<AppState>,
+    Json(body): Json<UpdatePercentConfigDto>,
+) -> AppResult<impl IntoResponse> {
+    let config = UpdatePercentUseCase::execute(&state, body)?;
+    Ok(Json(config))
 }
 
 /// Cria as rotas do controller de configuração
-pub fn config_routes() -> Router {
+pub fn config_routes() -> Router<AppState> {
     Router::new()
         .route("/cnpj/:cnpj", get(get_cnpj))
         .route("/", get(get_first_config))
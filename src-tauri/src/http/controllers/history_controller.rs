@@ -0,0 +1,27 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Router,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::dtos::HistoryQueryDto;
+use crate::error::AppResult;
+use crate::services::HistoryService;
+use crate::state::AppState;
+
+/// GET /history?entityType=product&entityId=1&dtInit=2024-01-01&dtFim=2024-12-31&limit=200
+async fn get_history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQueryDto>,
+) -> AppResult<impl IntoResponse> {
+    let entries = HistoryService::find(&state, &query)?;
+    Ok(Json(entries))
+}
+
+/// Cria as rotas do histórico de alterações
+pub fn history_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_history))
+}
@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, Json, Query, State},
+    routing::{get, post},
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::dtos::{CloseInventoryDto, InventoryListQuery, InventoryReportQuery, InventoryScanDto, OpenInventoryDto};
+use crate::error::{AppError, AppResult};
+use crate::services::InventoryService;
+use crate::state::AppState;
+
+/// POST /inventory
+async fn open_inventory(
+    State(state): State<AppState>,
+    Json(body): Json<OpenInventoryDto>,
+) -> AppResult<impl IntoResponse> {
+    let session = InventoryService::open(&state, body)?;
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+/// GET /inventory?status=open
+async fn list_inventories(
+    State(state): State<AppState>,
+    Query(query): Query<InventoryListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let sessions = InventoryService::find_all(&state, &query)?;
+    Ok(Json(sessions))
+}
+
+/// GET /inventory/current
+async fn current_inventory(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let session = InventoryService::current(&state)?;
+    Ok(Json(session))
+}
+
+/// GET /inventory/:id
+async fn get_inventory(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let session = InventoryService::find_by_id(&state, id)?
+        .ok_or_else(|| AppError::not_found(format!("Inventory session with id {} not found", id)))?;
+    Ok(Json(session))
+}
+
+/// POST /inventory/:id/counts
+async fn scan(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<InventoryScanDto>,
+) -> AppResult<impl IntoResponse> {
+    let line = InventoryService::scan(&state, id, body)?;
+    Ok(Json(line))
+}
+
+/// GET /inventory/:id/report?differencesOnly=true
+async fn report(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<InventoryReportQuery>,
+) -> AppResult<impl IntoResponse> {
+    let report = InventoryService::report(&state, id, &query)?;
+    Ok(Json(report))
+}
+
+/// POST /inventory/:id/close
+async fn close_inventory(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<CloseInventoryDto>,
+) -> AppResult<impl IntoResponse> {
+    let report = InventoryService::close(&state, id, body)?;
+    Ok(Json(report))
+}
+
+/// POST /inventory/:id/cancel
+async fn cancel_inventory(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let session = InventoryService::cancel(&state, id)?;
+    Ok(Json(session))
+}
+
+/// Cria as rotas do controller de inventário
+pub fn inventory_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_inventories).post(open_inventory))
+        .route("/current", get(current_inventory))
+        .route("/:id", get(get_inventory))
+        .route("/:id/counts", post(scan))
+        .route("/:id/report", get(report))
+        .route("/:id/close", post(close_inventory))
+        .route("/:id/cancel", post(cancel_inventory))
+}
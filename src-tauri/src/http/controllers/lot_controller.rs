@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, Json, Query, State},
+    routing::{get, post},
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::dtos::{CreateLotDto, ExpiringLotsQuery, UpdateLotDto};
+use crate::error::{AppError, AppResult};
+use crate::services::LotService;
+use crate::state::AppState;
+
+/// POST /lots
+async fn create_lot(
+    State(state): State<AppState>,
+    Json(body): Json<CreateLotDto>,
+) -> AppResult<impl IntoResponse> {
+    let lot = LotService::create(&state, body)?;
+    Ok((StatusCode::CREATED, Json(lot)))
+}
+
+/// GET /lots/expiring?days=30
+async fn get_expiring(
+    State(state): State<AppState>,
+    Query(query): Query<ExpiringLotsQuery>,
+) -> AppResult<impl IntoResponse> {
+    let lots = LotService::expiring(&state, &query)?;
+    Ok(Json(lots))
+}
+
+/// GET /lots/:id
+async fn get_lot(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let lot = LotService::find_by_id(&state, id)?
+        .ok_or_else(|| AppError::not_found(format!("Lot with id {} not found", id)))?;
+    Ok(Json(lot))
+}
+
+/// PUT /lots/:id
+async fn update_lot(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<UpdateLotDto>,
+) -> AppResult<impl IntoResponse> {
+    let lot = LotService::update(&state, id, body)?;
+    Ok(Json(lot))
+}
+
+/// Cria as rotas do controller de lotes
+pub fn lot_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_lot))
+        .route("/expiring", get(get_expiring))
+        .route("/:id", get(get_lot).put(update_lot))
+}
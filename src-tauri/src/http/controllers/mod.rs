@@ -1,7 +1,27 @@
 pub mod config_controller;
 pub mod venda_controller;
 pub mod resume_controller;
+pub mod admin_controller;
+pub mod history_controller;
+pub mod product_controller;
+pub mod stock_controller;
+pub mod category_controller;
+pub mod promotion_controller;
+pub mod inventory_controller;
+pub mod unit_controller;
+pub mod lot_controller;
+pub mod cart_controller;
 
 pub use config_controller::config_routes;
 pub use venda_controller::venda_routes;
 pub use resume_controller::resume_routes;
+pub use admin_controller::{admin_routes, AdminState};
+pub use history_controller::history_routes;
+pub use product_controller::product_routes;
+pub use stock_controller::stock_routes;
+pub use category_controller::category_routes;
+pub use promotion_controller::promotion_routes;
+pub use inventory_controller::inventory_routes;
+pub use unit_controller::unit_routes;
+pub use lot_controller::lot_routes;
+pub use cart_controller::cart_routes;
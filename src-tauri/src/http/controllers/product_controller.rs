@@ -0,0 +1,287 @@
+use axum::{
+    extract::{Path, Json, Query, State},
+    routing::{get, patch, post},
+    Router,
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+
+use crate::dtos::{
+    BalanceChangeDto, ComponentDto, CreateProductDto, ProductExportQuery, ProductImportDto, ProductListQuery, ProductSearchQuery,
+    UpdateProductDto,
+};
+use crate::error::{AppError, AppResult};
+use crate::services::{CatalogService, LotService, ProductService};
+use crate::spreadsheet::SheetFormat;
+use crate::state::AppState;
+
+fn not_found(id: i64) -> AppError {
+    AppError::not_found(format!("Product with id {} not found", id))
+}
+
+/// POST /products
+async fn create_product(
+    State(state): State<AppState>,
+    Json(body): Json<CreateProductDto>,
+) -> AppResult<impl IntoResponse> {
+    let product = ProductService::create(&state, body)?;
+    Ok((StatusCode::CREATED, Json(product)))
+}
+
+/// GET /products?active=true
+async fn list_products(
+    State(state): State<AppState>,
+    Query(query): Query<ProductListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let products = ProductService::list(&state, query.active)?;
+    Ok(Json(products))
+}
+
+/// GET /products/search?q=arroz&page=1&perPage=20
+async fn search_products(
+    State(state): State<AppState>,
+    Query(query): Query<ProductSearchQuery>,
+) -> AppResult<impl IntoResponse> {
+    let page = ProductService::search(&state, &query)?;
+    Ok(Json(page))
+}
+
+/// GET /products/barcode/:barcode
+async fn lookup_barcode(State(state): State<AppState>, Path(barcode): Path<String>) -> AppResult<impl IntoResponse> {
+    let found = ProductService::lookup_barcode(&state, &barcode)?;
+    Ok(Json(found))
+}
+
+/// POST /products/import
+async fn import_products(
+    State(state): State<AppState>,
+    Json(body): Json<ProductImportDto>,
+) -> AppResult<impl IntoResponse> {
+    let report = CatalogService::import(&state, body)?;
+    Ok(Json(report))
+}
+
+/// GET /products/export?format=xlsx
+async fn export_products(
+    State(state): State<AppState>,
+    Query(query): Query<ProductExportQuery>,
+) -> AppResult<impl IntoResponse> {
+    let format = query.format.unwrap_or(SheetFormat::Csv);
+    let content = CatalogService::export(&state, format)?;
+    let disposition = format!("attachment; filename=\"{}\"", CatalogService::export_filename(format));
+    Ok(([(header::CONTENT_TYPE, format.content_type().to_string()), (header::CONTENT_DISPOSITION, disposition)], content))
+}
+
+/// GET /products/:id
+async fn get_product(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let product = ProductService::find_by_id(&state, id)?.ok_or_else(|| not_found(id))?;
+    Ok(Json(product))
+}
+
+/// GET /products/code/:code
+async fn get_product_by_code(State(state): State<AppState>, Path(code): Path<String>) -> AppResult<impl IntoResponse> {
+    let product = ProductService::find_by_code(&state, &code)?
+        .ok_or_else(|| AppError::not_found(format!("Product with code {} not found", code)))?;
+    Ok(Json(product))
+}
+
+/// PUT /products/:id
+async fn update_product(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<UpdateProductDto>,
+) -> AppResult<impl IntoResponse> {
+    let product = ProductService::update(&state, id, body)?;
+    Ok(Json(product))
+}
+
+/// DELETE /products/:id (soft delete)
+async fn delete_product(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    ProductService::delete(&state, id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /products/:id/increment
+async fn increment_balance(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<BalanceChangeDto>,
+) -> AppResult<impl IntoResponse> {
+    let product = ProductService::increment_balance(&state, id, body)?;
+    Ok(Json(product))
+}
+
+/// PATCH /products/:id/decrement
+async fn decrement_balance(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<BalanceChangeDto>,
+) -> AppResult<impl IntoResponse> {
+    let product = ProductService::decrement_balance(&state, id, body)?;
+    Ok(Json(product))
+}
+
+/// GET /products/:id/components
+async fn get_components(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let kit = ProductService::components(&state, id)?;
+    Ok(Json(kit))
+}
+
+/// PUT /products/:id/components
+async fn set_components(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<Vec<ComponentDto>>,
+) -> AppResult<impl IntoResponse> {
+    let kit = ProductService::set_components(&state, id, body)?;
+    Ok(Json(kit))
+}
+
+/// GET /products/:id/lots
+async fn get_lots(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let lots = LotService::find_by_product(&state, id)?;
+    Ok(Json(lots))
+}
+
+/// Cria as rotas do controller de produtos
+pub fn product_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_products).post(create_product))
+        .route("/search", get(search_products))
+        .route("/import", post(import_products))
+        .route("/export", get(export_products))
+        .route("/code/:code", get(get_product_by_code))
+        .route("/barcode/:barcode", get(lookup_barcode))
+        .route("/:id", get(get_product).put(update_product).delete(delete_product))
+        .route("/:id/increment", patch(increment_balance))
+        .route("/:id/decrement", patch(decrement_balance))
+        .route("/:id/components", get(get_components).put(set_components))
+        .route("/:id/lots", get(get_lots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use base64::Engine;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    async fn call(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .nest("/products", product_routes())
+            .with_state(AppState::in_memory())
+    }
+
+    /// Cadastra um produto e devolve o ID
+    async fn create(app: &Router, body: Value) -> i64 {
+        let (status, created) = call(app, "POST", "/products", Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        created["id"].as_i64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_product_routes() {
+        let app = app();
+
+        let (status, created) = call(&app, "POST", "/products", Some(json!({ "code": "001", "name": "Arroz", "unit": "KG" }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = created["id"].as_i64().unwrap();
+
+        let (status, body) = call(&app, "POST", "/products", Some(json!({ "code": "001", "name": "Outro" }))).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::CONFLICT, Some("CONFLICT")));
+
+        let (_, product) = call(&app, "PATCH", &format!("/products/{}/increment", id), Some(json!({ "amount": 2.5 }))).await;
+        assert_eq!(product["balance"], 2.5);
+        let (_, product) = call(&app, "PATCH", &format!("/products/{}/decrement", id), Some(json!({ "amount": "0.5" }))).await;
+        assert_eq!(product["balance"], 2.0);
+
+        let (status, product) = call(&app, "GET", "/products/code/001", None).await;
+        assert_eq!((status, product["id"].as_i64()), (StatusCode::OK, Some(id)));
+
+        let (_, product) = call(&app, "PUT", &format!("/products/{}", id), Some(json!({ "name": "Arroz 5kg" }))).await;
+        assert_eq!(product["name"], "Arroz 5kg");
+
+        let (status, _) = call(&app, "DELETE", &format!("/products/{}", id), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (_, active) = call(&app, "GET", "/products?active=true", None).await;
+        let (_, inactive) = call(&app, "GET", "/products?active=false", None).await;
+        assert_eq!((active.as_array().unwrap().len(), inactive.as_array().unwrap().len()), (0, 1));
+
+        let (status, body) = call(&app, "PUT", &format!("/products/{}", id), Some(json!({ "ncm": "123", "cfop": "5102" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["fields"][0]["field"], "ncm");
+
+        let (_, product) = call(&app, "PUT", &format!("/products/{}", id), Some(json!({ "price": "12.90", "ncm": "10063021" }))).await;
+        assert_eq!((product["price"].clone(), product["ncm"].clone(), product["unit"].clone()), (json!(12.9), json!("10063021"), json!("KG")));
+
+        let (status, body) = call(&app, "GET", "/products/999", None).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::NOT_FOUND, Some("NOT_FOUND")));
+    }
+
+    #[tokio::test]
+    async fn test_search_route() {
+        let app = app();
+        let id = create(&app, json!({ "code": "001", "name": "Arroz" })).await;
+        create(&app, json!({ "code": "002", "name": "Feijão" })).await;
+
+        let (status, page) = call(&app, "GET", "/products/search?q=arr&perPage=5", None).await;
+        assert_eq!((status, page["total"].as_i64(), page["items"][0]["id"].as_i64()), (StatusCode::OK, Some(1), Some(id)));
+        let (_, page) = call(&app, "GET", "/products/search?q=feijao", None).await;
+        assert_eq!(page["items"][0]["code"], "002");
+    }
+
+    #[tokio::test]
+    async fn test_barcode_route() {
+        let app = app();
+        create(&app, json!({ "code": "001", "name": "Arroz" })).await;
+
+        let (status, found) = call(&app, "GET", "/products/barcode/001", None).await;
+        assert_eq!((status, found["matched_by"].as_str(), found["item"]["quantidade"].as_f64()), (StatusCode::OK, Some("code"), Some(1.0)));
+
+        // Dígito verificador errado
+        let (status, body) = call(&app, "GET", "/products/barcode/7891000100104", None).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::BAD_REQUEST, Some("VALIDATION_ERROR")));
+        let (status, _) = call(&app, "GET", "/products/barcode/7891000100103", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_import_export_routes() {
+        let app = app();
+        create(&app, json!({ "code": "001", "name": "Arroz" })).await;
+
+        let content = base64::engine::general_purpose::STANDARD.encode("code;name\n002;Feijão\n");
+        let (status, report) = call(&app, "POST", "/products/import", Some(json!({ "content": content, "dryRun": true }))).await;
+        assert_eq!((status, report["created"].as_u64(), report["rows"][0]["line"].as_u64()), (StatusCode::OK, Some(1), Some(2)));
+        let (status, _) = call(&app, "GET", "/products/code/002", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, report) = call(&app, "POST", "/products/import", Some(json!({ "content": content }))).await;
+        assert_eq!(report["created"].as_u64(), Some(1));
+        let (status, _) = call(&app, "GET", "/products/code/002", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::builder().uri("/products/export?format=xlsx").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_DISPOSITION], "attachment; filename=\"produtos.xlsx\"");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
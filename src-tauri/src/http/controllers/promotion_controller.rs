@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Path, Json, Query, State},
+    routing::{get, post},
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::dtos::{PricingPreviewDto, PromotionListQuery, SavePromotionDto};
+use crate::error::{AppError, AppResult};
+use crate::services::PromotionService;
+use crate::state::AppState;
+
+/// POST /promotions
+async fn create_promotion(
+    State(state): State<AppState>,
+    Json(body): Json<SavePromotionDto>,
+) -> AppResult<impl IntoResponse> {
+    let promotion = PromotionService::create(&state, body)?;
+    Ok((StatusCode::CREATED, Json(promotion)))
+}
+
+/// GET /promotions?current=true
+async fn list_promotions(
+    State(state): State<AppState>,
+    Query(query): Query<PromotionListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let promotions = PromotionService::find_all(&state, &query)?;
+    Ok(Json(promotions))
+}
+
+/// POST /promotions/preview
+async fn preview(
+    State(state): State<AppState>,
+    Json(body): Json<PricingPreviewDto>,
+) -> AppResult<impl IntoResponse> {
+    let pricing = PromotionService::preview(&state, body)?;
+    Ok(Json(pricing))
+}
+
+/// GET /promotions/:id
+async fn get_promotion(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    let promotion = PromotionService::find_by_id(&state, id)?
+        .ok_or_else(|| AppError::not_found(format!("Promotion with id {} not found", id)))?;
+    Ok(Json(promotion))
+}
+
+/// PUT /promotions/:id
+async fn update_promotion(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<SavePromotionDto>,
+) -> AppResult<impl IntoResponse> {
+    let promotion = PromotionService::update(&state, id, body)?;
+    Ok(Json(promotion))
+}
+
+/// DELETE /promotions/:id
+async fn delete_promotion(State(state): State<AppState>, Path(id): Path<i64>) -> AppResult<impl IntoResponse> {
+    PromotionService::delete(&state, id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Cria as rotas do controller de promoções
+pub fn promotion_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_promotions).post(create_promotion))
+        .route("/preview", post(preview))
+        .route("/:id", get(get_promotion).put(update_promotion).delete(delete_promotion))
+}
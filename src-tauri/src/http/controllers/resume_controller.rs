@@ -1,27 +1,23 @@
 use axum::{
+    extract::State,
     routing::get,
     Router,
-    http::StatusCode,
     response::IntoResponse,
     Json,
 };
-use serde_json::json;
 
+use crate::error::AppResult;
 use crate::services::ResumeService;
+use crate::state::AppState;
 
 /// GET /resumes/
-async fn get_all_resumes_today() -> impl IntoResponse {
-    match ResumeService::get_all_today() {
-        Ok(resumes) => (StatusCode::OK, Json(resumes)).into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+async fn get_all_resumes_today(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let resumes = ResumeService::get_all_today(&state)?;
+    Ok(Json(resumes))
 }
 
 /// Cria as rotas do controller de resumos
-pub fn resume_routes() -> Router {
+pub fn resume_routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_all_resumes_today))
 }
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post},
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::dtos::{KardexQueryDto, ReorderQueryDto, StockMovementDto};
+use crate::error::AppResult;
+use crate::services::StockService;
+use crate::state::AppState;
+
+/// POST /stock/movements
+async fn register_movement(
+    State(state): State<AppState>,
+    Json(body): Json<StockMovementDto>,
+) -> AppResult<impl IntoResponse> {
+    let movement = StockService::register(&state, body)?;
+    Ok((StatusCode::CREATED, Json(movement)))
+}
+
+/// GET /stock/kardex/:product_id?dtInit=2024-01-01&dtFim=2024-12-31
+async fn get_kardex(
+    State(state): State<AppState>,
+    Path(product_id): Path<i64>,
+    Query(query): Query<KardexQueryDto>,
+) -> AppResult<impl IntoResponse> {
+    let kardex = StockService::kardex(&state, product_id, &query)?;
+    Ok(Json(kardex))
+}
+
+/// GET /stock/reconcile
+async fn check_balances(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let discrepancies = StockService::reconcile(&state, false)?;
+    Ok(Json(discrepancies))
+}
+
+/// POST /stock/reconcile
+async fn fix_balances(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let fixed = StockService::reconcile(&state, true)?;
+    Ok(Json(fixed))
+}
+
+/// GET /stock/low
+async fn get_low_stock(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let products = StockService::low_stock(&state)?;
+    Ok(Json(products))
+}
+
+/// GET /stock/reorder?dtInit=2024-06-01&dtFim=2024-06-30&coverageDays=15
+async fn get_reorder(
+    State(state): State<AppState>,
+    Query(query): Query<ReorderQueryDto>,
+) -> AppResult<impl IntoResponse> {
+    let report = StockService::reorder(&state, &query)?;
+    Ok(Json(report))
+}
+
+/// Cria as rotas do livro de movimentos de estoque
+pub fn stock_routes() -> Router<AppState> {
+    Router::new()
+        .route("/movements", post(register_movement))
+        .route("/kardex/:product_id", get(get_kardex))
+        .route("/reconcile", get(check_balances).post(fix_balances))
+        .route("/low", get(get_low_stock))
+        .route("/reorder", get(get_reorder))
+}
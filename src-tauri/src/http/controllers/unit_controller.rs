@@ -0,0 +1,57 @@
+use axum::{
+    extract::{Path, Json, State},
+    routing::get,
+    Router,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::dtos::{CreateUnitDto, UpdateUnitDto};
+use crate::error::{AppError, AppResult};
+use crate::services::UnitService;
+use crate::state::AppState;
+
+/// POST /units
+async fn create_unit(
+    State(state): State<AppState>,
+    Json(body): Json<CreateUnitDto>,
+) -> AppResult<impl IntoResponse> {
+    let unit = UnitService::create(&state, body)?;
+    Ok((StatusCode::CREATED, Json(unit)))
+}
+
+/// GET /units
+async fn list_units(State(state): State<AppState>) -> AppResult<impl IntoResponse> {
+    let units = UnitService::find_all(&state)?;
+    Ok(Json(units))
+}
+
+/// GET /units/:code
+async fn get_unit(State(state): State<AppState>, Path(code): Path<String>) -> AppResult<impl IntoResponse> {
+    let unit = UnitService::find_by_code(&state, &code)?
+        .ok_or_else(|| AppError::not_found(format!("Unit with code {} not found", code)))?;
+    Ok(Json(unit))
+}
+
+/// PUT /units/:code
+async fn update_unit(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Json(body): Json<UpdateUnitDto>,
+) -> AppResult<impl IntoResponse> {
+    let unit = UnitService::update(&state, &code, body)?;
+    Ok(Json(unit))
+}
+
+/// DELETE /units/:code
+async fn delete_unit(State(state): State<AppState>, Path(code): Path<String>) -> AppResult<impl IntoResponse> {
+    UnitService::delete(&state, &code)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Cria as rotas do controller de unidades de medida
+pub fn unit_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_units).post(create_unit))
+        .route("/:code", get(get_unit).put(update_unit).delete(delete_unit))
+}
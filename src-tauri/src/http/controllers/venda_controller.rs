@@ -1,5 +1,5 @@
 use axum::{
-    extract::Query,
+    extract::{Query, State},
     routing::get,
     Router,
     http::StatusCode,
@@ -9,7 +9,9 @@ use axum::{
 use serde::Deserialize;
 use serde_json::json;
 
+use crate::error::AppResult;
 use crate::services::VendaService;
+use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
 struct DateIntervalQuery {
@@ -29,62 +31,56 @@ async fn get_all() -> impl IntoResponse {
 
 /// GET /vendas/get-vendas-by-interval?dtInit=2024-01-01&dtFim=2024-12-31
 async fn get_vendas_by_interval(
+    State(state): State<AppState>,
     Query(params): Query<DateIntervalQuery>,
-) -> impl IntoResponse {
-    match VendaService::get_vendas_by_interval(&params.dt_init, &params.dt_end) {
-        Ok(vendas) => (StatusCode::OK, Json(vendas)).into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let vendas = VendaService::get_vendas_by_interval(&state, &params.dt_init, &params.dt_end)?;
+    Ok(Json(vendas))
 }
 
 /// GET /vendas/get-items-by-interval?dtInit=2024-01-01&dtFim=2024-12-31
 async fn get_items_by_interval(
+    State(state): State<AppState>,
     Query(params): Query<DateIntervalQuery>,
-) -> impl IntoResponse {
-    match VendaService::get_items_by_interval(&params.dt_init, &params.dt_end) {
-        Ok(items) => (StatusCode::OK, Json(items)).into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let items = VendaService::get_items_by_interval(&state, &params.dt_init, &params.dt_end)?;
+    Ok(Json(items))
 }
 
 /// GET /vendas/get-payments-by-interval?dtInit=2024-01-01&dtFim=2024-12-31
 async fn get_payments_by_interval(
+    State(state): State<AppState>,
     Query(params): Query<DateIntervalQuery>,
-) -> impl IntoResponse {
-    match VendaService::get_payments_by_interval(&params.dt_init, &params.dt_end) {
-        Ok(payments) => (StatusCode::OK, Json(payments)).into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let payments = VendaService::get_payments_by_interval(&state, &params.dt_init, &params.dt_end)?;
+    Ok(Json(payments))
 }
 
 /// GET /vendas/resumo-by-interval?dtInit=2024-01-01&dtFim=2024-12-31
 async fn get_resumo_by_interval(
+    State(state): State<AppState>,
     Query(params): Query<DateIntervalQuery>,
-) -> impl IntoResponse {
-    match VendaService::get_resumo_by_interval(&params.dt_init, &params.dt_end) {
-        Ok(resumo) => (StatusCode::OK, Json(resumo)).into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e }))
-        ).into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let resumo = VendaService::get_resumo_by_interval(&state, &params.dt_init, &params.dt_end)?;
+    Ok(Json(resumo))
+}
+
+/// GET /vendas/category-totals-by-interval?dtInit=2024-01-01&dtFim=2024-12-31
+async fn get_category_totals_by_interval(
+    State(state): State<AppState>,
+    Query(params): Query<DateIntervalQuery>,
+) -> AppResult<impl IntoResponse> {
+    let totals = VendaService::get_category_totals_by_interval(&state, &params.dt_init, &params.dt_end)?;
+    Ok(Json(totals))
 }
 
 /// Cria as rotas do controller de vendas
-pub fn venda_routes() -> Router {
+pub fn venda_routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_all))
         .route("/get-vendas-by-interval", get(get_vendas_by_interval))
         .route("/get-items-by-interval", get(get_items_by_interval))
         .route("/get-payments-by-interval", get(get_payments_by_interval))
         .route("/resumo-by-interval", get(get_resumo_by_interval))
+        .route("/category-totals-by-interval", get(get_category_totals_by_interval))
 }
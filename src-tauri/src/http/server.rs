@@ -2,9 +2,13 @@ use axum::{Router, http::Method};
 use tower_http::cors::{CorsLayer, Any};
 use std::net::SocketAddr;
 
-use crate::http::controllers::{config_routes, venda_routes, resume_routes};
+use crate::http::controllers::{
+    config_routes, venda_routes, resume_routes, history_routes, product_routes, stock_routes, category_routes,
+    promotion_routes, inventory_routes, unit_routes, lot_routes, cart_routes, admin_routes, AdminState,
+};
+use crate::state::AppState;
 
-pub async fn start_http_server() -> Result<(), Box<dyn std::error::Error>> {
+pub async fn start_http_server(state: AppState, admin: AdminState) -> Result<(), Box<dyn std::error::Error>> {
     // Configura CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -14,9 +18,21 @@ pub async fn start_http_server() -> Result<(), Box<dyn std::error::Error>> {
     // Configura as rotas
     let app = Router::new()
         .nest("/config", config_routes())
+        .nest("/products", product_routes())
+        .nest("/categories", category_routes())
+        .nest("/units", unit_routes())
+        .nest("/promotions", promotion_routes())
+        .nest("/stock", stock_routes())
+        .nest("/inventory", inventory_routes())
+        .nest("/lots", lot_routes())
+        .nest("/carts", cart_routes())
         .nest("/vendas", venda_routes())
         .nest("/resumes", resume_routes())
-        .layer(cors);
+        .nest("/history", history_routes())
+        .layer(cors)
+        // Depois do CORS: as rotas administrativas não respondem a páginas de outras origens
+        .nest("/admin", admin_routes().with_state(admin))
+        .with_state(state);
 
     // Inicia o servidor na porta 8088
     let addr = SocketAddr::from(([127, 0, 0, 1], 8088));
@@ -28,7 +44,17 @@ pub async fn start_http_server() -> Result<(), Box<dyn std::error::Error>> {
     println!("   - GET  http://localhost:8088/vendas/get-items-by-interval?dtInit=2024-01-01&dtFim=2024-12-31");
     println!("   - GET  http://localhost:8088/vendas/get-payments-by-interval?dtInit=2024-01-01&dtFim=2024-12-31");
     println!("   - GET  http://localhost:8088/vendas/resumo-by-interval?dtInit=2024-01-01&dtFim=2024-12-31");
+    println!("   - GET  http://localhost:8088/products?active=true");
+    println!("   - GET  http://localhost:8088/categories/tree");
+    println!("   - GET  http://localhost:8088/units");
+    println!("   - GET  http://localhost:8088/promotions?current=true");
+    println!("   - GET  http://localhost:8088/stock/kardex/1?dtInit=2024-01-01&dtFim=2024-12-31");
+    println!("   - GET  http://localhost:8088/inventory/current");
+    println!("   - GET  http://localhost:8088/lots/expiring?days=30");
+    println!("   - GET  http://localhost:8088/carts/current");
     println!("   - GET  http://localhost:8088/resumes/");
+    println!("   - GET  http://localhost:8088/history?entityType=product&entityId=1");
+    println!("   - POST http://localhost:8088/admin/backup");
     
     axum::serve(listener, app).await?;
     
@@ -5,18 +5,54 @@ pub mod services;
 pub mod dtos;
 pub mod usecases;
 pub mod http;
+pub mod repositories;
+pub mod state;
+pub mod clock;
+pub mod events;
+pub mod error;
+pub mod barcode;
+pub mod spreadsheet;
+#[cfg(test)]
+mod test_support;
 
-use database::SqliteDbService;
-use services::{ConfigService, ProductService, VendaService};
-use entities::{ConfigEntity, ProductEntity, VendaEntity, VendaItemEntity, VendaPagamentoEntity};
-use dtos::{CreateOrUpdateConfigDto, UpdatePercentConfigDto, CnpjResponseDto};
+use tauri::{AppHandle, Emitter, Manager, State};
+use chrono::NaiveDate;
+use std::sync::{Arc, OnceLock};
+use database::{
+    BackupConfig, BackupInfo, BackupService, IntegrityReport, IntegrityService,
+    RepairOptions, RepairReport, SqliteDbService,
+};
+use services::{
+    CartService, CatalogService, CategoryService, ConfigService, HistoryService, InventoryService, ProductService, PromotionService, StockService,
+    LotService, UnitService, VendaService,
+};
+use entities::{
+    CategoryEntity, ConfigEntity, HistoryEntity, InventorySessionEntity, PromotionEntity, MovementType, Origin, ProductEntity, Quantity, StockMovementEntity,
+    LotEntity, UnitEntity, VendaEntity, VendaItemEntity, VendaPagamentoEntity,
+};
+use dtos::{
+    CreateOrUpdateConfigDto, UpdatePercentConfigDto, CnpjResponseDto, HistoryQueryDto,
+    CreateProductDto, ProductFiscalDto, UpdateProductDto, BalanceChangeDto, ProductPageDto, ProductSearchQuery,
+    BarcodeLookupDto, ComponentDto, ExportFileDto, KitDto, ProductImportDto, ProductImportReportDto,
+    CategoryProductsQuery, CategoryTreeDto, CreateCategoryDto, ReassignProductsDto, UpdateCategoryDto,
+    PricingDto, PricingPreviewDto, PromotionListQuery, SavePromotionDto,
+    KardexDto, KardexQueryDto, LowStockDto, ReorderQueryDto, ReorderReportDto, StockDiscrepancyDto, StockMovementDto, VendaCreatedDto,
+    CloseInventoryDto, InventoryLineDto, InventoryListQuery, InventoryReportDto, InventoryReportQuery, InventoryScanDto,
+    OpenInventoryDto, CreateUnitDto, UpdateUnitDto, CreateLotDto, ExpiringLotDto, ExpiringLotsQuery, ProductLotsDto, UpdateLotDto,
+    AddCartItemDto, AddCartPaymentDto, CartDiscountDto, CartDto, CartFinalizedDto, FinalizeCartDto, OpenCartDto, UpdateCartItemDto,
+};
 use usecases::{
     CreateOrUpdateConfigUseCase, 
     GetFirstConfigUseCase, 
     UpdatePercentUseCase,
     GetCnpjUseCase,
 };
-use http::start_http_server;
+use http::{start_http_server, controllers::AdminState};
+use spreadsheet::SheetFormat;
+use state::AppState;
+use clock::SystemClock;
+use events::EventSink;
+use error::AppResult;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -26,160 +62,668 @@ fn greet(name: &str) -> String {
 
 // Comando para obter o caminho do banco de dados
 #[tauri::command]
-fn get_db_path() -> Result<String, String> {
-    let db = SqliteDbService::get_instance()?;
+fn get_db_path(db: State<'_, SqliteDbService>) -> AppResult<String> {
     Ok(db.get_db_path().to_string_lossy().to_string())
 }
 
+// Comandos de Backup
+
+/// POST /admin/backup - Cria uma cópia de segurança do banco
+#[tauri::command]
+async fn create_backup(backups: State<'_, BackupService>) -> AppResult<BackupInfo> {
+    let backups = backups.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || backups.create_backup())
+        .await?
+}
+
+/// GET /admin/backup - Lista as cópias de segurança
+#[tauri::command]
+fn list_backups(backups: State<'_, BackupService>) -> AppResult<Vec<BackupInfo>> {
+    backups.list_backups()
+}
+
+/// POST /admin/backup/restore - Restaura uma cópia de segurança
+#[tauri::command]
+async fn restore_backup(backups: State<'_, BackupService>, file_name: String) -> AppResult<BackupInfo> {
+    let backups = backups.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || backups.restore_backup(&file_name))
+        .await?
+}
+
+// Comandos de Integridade
+
+/// GET /admin/integrity - Diagnóstico de consistência do banco
+#[tauri::command]
+async fn check_integrity(integrity: State<'_, IntegrityService>) -> AppResult<IntegrityReport> {
+    let integrity = integrity.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || integrity.check())
+        .await?
+}
+
+/// Aplica os reparos selecionados, com backup prévio (só Tauri, sem rota HTTP)
+#[tauri::command]
+async fn repair_integrity(
+    integrity: State<'_, IntegrityService>,
+    backups: State<'_, BackupService>,
+    options: RepairOptions,
+) -> AppResult<RepairReport> {
+    let integrity = integrity.inner().clone();
+    let backups = backups.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || integrity.repair(Some(&backups), &options))
+        .await?
+}
+
 // Comandos de Configuração (seguindo o controller NestJS)
 
 /// GET /config/cnpj/:cnpj
 #[tauri::command]
-async fn get_cnpj(cnpj: String) -> Result<CnpjResponseDto, String> {
+async fn get_cnpj(cnpj: String) -> AppResult<CnpjResponseDto> {
     GetCnpjUseCase::execute(cnpj).await
 }
 
 /// GET /config/
 #[tauri::command]
-fn get_first_config() -> Result<Option<ConfigEntity>, String> {
-    GetFirstConfigUseCase::execute()
+fn get_first_config(state: State<'_, AppState>) -> AppResult<Option<ConfigEntity>> {
+    GetFirstConfigUseCase::execute(&state)
 }
 
 /// POST /config/
 #[tauri::command]
-fn create_or_update_config(body: CreateOrUpdateConfigDto) -> Result<ConfigEntity, String> {
-    CreateOrUpdateConfigUseCase::execute(body)
+fn create_or_update_config(state: State<'_, AppState>, body: CreateOrUpdateConfigDto) -> AppResult<ConfigEntity> {
+    CreateOrUpdateConfigUseCase::execute(&state, body)
 }
 
 /// PATCH /config/percent
 #[tauri::command]
-fn update_percent_config(body: UpdatePercentConfigDto) -> Result<ConfigEntity, String> {
-    UpdatePercentUseCase::execute(body)
+fn update_percent_config(state: State<'_, AppState>, body: UpdatePercentConfigDto) -> AppResult<ConfigEntity> {
+    UpdatePercentUseCase::execute(&state, body)
 }
 
 // Comandos legados (manter compatibilidade)
 #[tauri::command]
-fn get_config(id: String) -> Result<Option<ConfigEntity>, String> {
-    ConfigService::find_by_id(&id)
+fn get_config(state: State<'_, AppState>, id: String) -> AppResult<Option<ConfigEntity>> {
+    ConfigService::find_by_id(&state, &id)
 }
 
 #[tauri::command]
-fn save_config(config: ConfigEntity) -> Result<ConfigEntity, String> {
-    ConfigService::save(&config)
+fn save_config(state: State<'_, AppState>, config: ConfigEntity) -> AppResult<ConfigEntity> {
+    ConfigService::save(&state, &config)
 }
 
 #[tauri::command]
-fn list_configs() -> Result<Vec<ConfigEntity>, String> {
-    ConfigService::find_all()
+fn list_configs(state: State<'_, AppState>) -> AppResult<Vec<ConfigEntity>> {
+    ConfigService::find_all(&state)
 }
 
 // Comandos de Produto (correspondentes ao ProdutosController do NestJS)
 
 /// POST /products - Cria um novo produto
 #[tauri::command]
-fn create_product(code: String, name: String) -> Result<ProductEntity, String> {
-    ProductService::create(code, name)
+fn create_product(
+    state: State<'_, AppState>,
+    code: String,
+    name: String,
+    fiscal: Option<ProductFiscalDto>,
+) -> AppResult<ProductEntity> {
+    ProductService::create(&state, CreateProductDto { code, name, fiscal: fiscal.unwrap_or_default() })
+}
+
+/// GET /products/search - Busca produtos por código ou nome, paginada
+#[tauri::command]
+fn search_products(state: State<'_, AppState>, query: ProductSearchQuery) -> AppResult<ProductPageDto> {
+    ProductService::search(&state, &query)
+}
+
+/// GET /products/barcode/:barcode - Lê um código de barras (código, GTIN ou etiqueta de balança)
+#[tauri::command]
+fn lookup_barcode(state: State<'_, AppState>, barcode: String) -> AppResult<BarcodeLookupDto> {
+    ProductService::lookup_barcode(&state, &barcode)
+}
+
+/// POST /products/import - Importa produtos de CSV ou XLSX (ou só valida, com `dryRun`)
+#[tauri::command]
+fn import_products(state: State<'_, AppState>, dto: ProductImportDto) -> AppResult<ProductImportReportDto> {
+    CatalogService::import(&state, dto)
+}
+
+/// GET /products/export - Exporta o cadastro com saldos em CSV ou XLSX
+#[tauri::command]
+fn export_products(state: State<'_, AppState>, format: Option<SheetFormat>) -> AppResult<ExportFileDto> {
+    CatalogService::export_file(&state, format.unwrap_or(SheetFormat::Csv))
 }
 
 /// GET /products/:id - Busca produto por ID
 #[tauri::command]
-fn get_product(id: i64) -> Result<Option<ProductEntity>, String> {
-    ProductService::find_by_id(id)
+fn get_product(state: State<'_, AppState>, id: i64) -> AppResult<Option<ProductEntity>> {
+    ProductService::find_by_id(&state, id)
 }
 
 /// GET /products/code/:code - Busca produto por código
 #[tauri::command]
-fn get_product_by_code(code: String) -> Result<Option<ProductEntity>, String> {
-    ProductService::find_by_code(&code)
+fn get_product_by_code(state: State<'_, AppState>, code: String) -> AppResult<Option<ProductEntity>> {
+    ProductService::find_by_code(&state, &code)
 }
 
 /// GET /products - Lista todos os produtos
 #[tauri::command]
-fn get_all_products() -> Result<Vec<ProductEntity>, String> {
-    ProductService::find_all()
+fn get_all_products(state: State<'_, AppState>) -> AppResult<Vec<ProductEntity>> {
+    ProductService::find_all(&state)
 }
 
 /// Lista apenas produtos ativos (método auxiliar)
 #[tauri::command]
-fn list_active_products() -> Result<Vec<ProductEntity>, String> {
-    ProductService::find_all_active()
+fn list_active_products(state: State<'_, AppState>) -> AppResult<Vec<ProductEntity>> {
+    ProductService::find_all_active(&state)
 }
 
 /// PUT /products/:id - Atualiza um produto
 #[tauri::command]
 fn update_product(
+    state: State<'_, AppState>,
     id: i64,
     code: Option<String>,
     name: Option<String>,
     active: Option<i32>,
-    balance: Option<f64>
-) -> Result<ProductEntity, String> {
-    ProductService::update(id, code, name, active, balance)
+    balance: Option<Quantity>,
+    fiscal: Option<ProductFiscalDto>,
+) -> AppResult<ProductEntity> {
+    ProductService::update(&state, id, UpdateProductDto { code, name, active, balance, fiscal: fiscal.unwrap_or_default() })
 }
 
 /// DELETE /products/:id - Deleta um produto (soft delete)
 #[tauri::command]
-fn delete_product(id: i64) -> Result<(), String> {
-    ProductService::delete(id)
+fn delete_product(state: State<'_, AppState>, id: i64) -> AppResult<()> {
+    ProductService::delete(&state, id)
+}
+
+/// PATCH /products/:id/increment - Registra entrada no estoque
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn increment_product_balance(
+    state: State<'_, AppState>,
+    id: i64,
+    amount: Quantity,
+    kind: Option<MovementType>,
+    reference: Option<String>,
+    user: Option<String>,
+    unit: Option<String>,
+    lot: Option<String>,
+    expires_at: Option<NaiveDate>,
+) -> AppResult<ProductEntity> {
+    ProductService::increment_balance(&state, id, BalanceChangeDto { amount, kind, reference, user, unit, lot, expires_at })
+}
+
+/// PATCH /products/:id/decrement - Registra saída do estoque
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn decrement_product_balance(
+    state: State<'_, AppState>,
+    id: i64,
+    amount: Quantity,
+    kind: Option<MovementType>,
+    reference: Option<String>,
+    user: Option<String>,
+    unit: Option<String>,
+    lot: Option<String>,
+    expires_at: Option<NaiveDate>,
+) -> AppResult<ProductEntity> {
+    ProductService::decrement_balance(&state, id, BalanceChangeDto { amount, kind, reference, user, unit, lot, expires_at })
+}
+
+/// GET /products/:id/components - Composição do kit com custo e disponibilidade
+#[tauri::command]
+fn get_product_components(state: State<'_, AppState>, id: i64) -> AppResult<KitDto> {
+    ProductService::components(&state, id)
+}
+
+/// PUT /products/:id/components - Define os componentes do kit (vazio desfaz o kit)
+#[tauri::command]
+fn set_product_components(state: State<'_, AppState>, id: i64, components: Vec<ComponentDto>) -> AppResult<KitDto> {
+    ProductService::set_components(&state, id, components)
+}
+
+// Comandos de Categoria
+
+/// POST /categories - Cria uma categoria (`parent_id` vazio = seção principal)
+#[tauri::command]
+fn create_category(state: State<'_, AppState>, name: String, parent_id: Option<i64>) -> AppResult<CategoryEntity> {
+    CategoryService::create(&state, CreateCategoryDto { name, parent_id })
+}
+
+/// GET /categories - Lista todas as categorias
+#[tauri::command]
+fn get_categories(state: State<'_, AppState>) -> AppResult<Vec<CategoryEntity>> {
+    CategoryService::find_all(&state)
+}
+
+/// GET /categories/tree - Árvore de categorias
+#[tauri::command]
+fn get_category_tree(state: State<'_, AppState>) -> AppResult<Vec<CategoryTreeDto>> {
+    CategoryService::tree(&state)
+}
+
+/// GET /categories/:id - Busca categoria por ID
+#[tauri::command]
+fn get_category(state: State<'_, AppState>, id: i64) -> AppResult<Option<CategoryEntity>> {
+    CategoryService::find_by_id(&state, id)
+}
+
+/// PUT /categories/:id - Renomeia ou move uma categoria (`parent_id` 0 = raiz)
+#[tauri::command]
+fn update_category(
+    state: State<'_, AppState>,
+    id: i64,
+    name: Option<String>,
+    parent_id: Option<i64>,
+) -> AppResult<CategoryEntity> {
+    CategoryService::update(&state, id, UpdateCategoryDto { name, parent_id })
+}
+
+/// DELETE /categories/:id - Remove a categoria, movendo produtos e subcategorias
+#[tauri::command]
+fn delete_category(state: State<'_, AppState>, id: i64, reassign_to: Option<i64>) -> AppResult<()> {
+    CategoryService::delete(&state, id, reassign_to)
+}
+
+/// POST /categories/reassign - Move produtos para uma categoria
+#[tauri::command]
+fn reassign_products(
+    state: State<'_, AppState>,
+    product_ids: Vec<i64>,
+    category_id: Option<i64>,
+) -> AppResult<Vec<ProductEntity>> {
+    CategoryService::reassign(&state, ReassignProductsDto { product_ids, category_id })
+}
+
+/// GET /categories/:id/products - Produtos da categoria (e subcategorias)
+#[tauri::command]
+fn get_category_products(
+    state: State<'_, AppState>,
+    id: i64,
+    query: Option<CategoryProductsQuery>,
+) -> AppResult<Vec<ProductEntity>> {
+    CategoryService::products(&state, id, &query.unwrap_or_default())
+}
+
+// Comandos de Unidades
+
+/// POST /units - Cadastra uma unidade de medida
+#[tauri::command]
+fn create_unit(state: State<'_, AppState>, code: String, description: String, decimals: Option<u32>) -> AppResult<UnitEntity> {
+    UnitService::create(&state, CreateUnitDto { code, description, decimals })
+}
+
+/// GET /units - Lista as unidades de medida
+#[tauri::command]
+fn get_units(state: State<'_, AppState>) -> AppResult<Vec<UnitEntity>> {
+    UnitService::find_all(&state)
+}
+
+/// GET /units/:code - Busca uma unidade pelo código
+#[tauri::command]
+fn get_unit(state: State<'_, AppState>, code: String) -> AppResult<Option<UnitEntity>> {
+    UnitService::find_by_code(&state, &code)
+}
+
+/// PUT /units/:code - Atualiza a descrição ou as casas decimais
+#[tauri::command]
+fn update_unit(
+    state: State<'_, AppState>,
+    code: String,
+    description: Option<String>,
+    decimals: Option<u32>,
+) -> AppResult<UnitEntity> {
+    UnitService::update(&state, &code, UpdateUnitDto { description, decimals })
+}
+
+/// DELETE /units/:code - Remove uma unidade que nenhum produto usa
+#[tauri::command]
+fn delete_unit(state: State<'_, AppState>, code: String) -> AppResult<()> {
+    UnitService::delete(&state, &code)
+}
+
+// Comandos de Promoção
+
+/// POST /promotions - Cria uma promoção
+#[tauri::command]
+fn create_promotion(state: State<'_, AppState>, promotion: SavePromotionDto) -> AppResult<PromotionEntity> {
+    PromotionService::create(&state, promotion)
+}
+
+/// GET /promotions - Lista as promoções (`current` = só as em vigor)
+#[tauri::command]
+fn get_promotions(state: State<'_, AppState>, query: Option<PromotionListQuery>) -> AppResult<Vec<PromotionEntity>> {
+    PromotionService::find_all(&state, &query.unwrap_or_default())
+}
+
+/// GET /promotions/:id - Busca promoção por ID
+#[tauri::command]
+fn get_promotion(state: State<'_, AppState>, id: i64) -> AppResult<Option<PromotionEntity>> {
+    PromotionService::find_by_id(&state, id)
+}
+
+/// PUT /promotions/:id - Substitui uma promoção
+#[tauri::command]
+fn update_promotion(state: State<'_, AppState>, id: i64, promotion: SavePromotionDto) -> AppResult<PromotionEntity> {
+    PromotionService::update(&state, id, promotion)
+}
+
+/// DELETE /promotions/:id - Remove uma promoção
+#[tauri::command]
+fn delete_promotion(state: State<'_, AppState>, id: i64) -> AppResult<()> {
+    PromotionService::delete(&state, id)
+}
+
+/// POST /promotions/preview - Precifica os itens do caixa com as promoções em vigor
+#[tauri::command]
+fn preview_pricing(state: State<'_, AppState>, preview: PricingPreviewDto) -> AppResult<PricingDto> {
+    PromotionService::preview(&state, preview)
+}
+
+// Comandos de Estoque
+
+/// POST /stock/movements - Registra um movimento de estoque
+#[tauri::command]
+fn register_stock_movement(state: State<'_, AppState>, movement: StockMovementDto) -> AppResult<StockMovementEntity> {
+    StockService::register(&state, movement)
+}
+
+/// GET /stock/kardex/:product_id - Kardex do produto no período
+#[tauri::command]
+fn get_kardex(state: State<'_, AppState>, product_id: i64, query: Option<KardexQueryDto>) -> AppResult<KardexDto> {
+    StockService::kardex(&state, product_id, &query.unwrap_or_default())
+}
+
+/// GET/POST /stock/reconcile - Confere (e com `fix`, corrige) saldos contra o livro
+#[tauri::command]
+fn reconcile_stock(state: State<'_, AppState>, fix: Option<bool>) -> AppResult<Vec<StockDiscrepancyDto>> {
+    StockService::reconcile(&state, fix.unwrap_or(false))
+}
+
+/// GET /stock/low - Produtos com saldo abaixo do estoque mínimo
+#[tauri::command]
+fn get_low_stock(state: State<'_, AppState>) -> AppResult<Vec<LowStockDto>> {
+    StockService::low_stock(&state)
 }
 
-/// PATCH /products/:id/increment - Incrementa saldo
+/// GET /stock/reorder - Sugestão de compra pela média de vendas no período
 #[tauri::command]
-fn increment_product_balance(id: i64, amount: f64) -> Result<ProductEntity, String> {
-    ProductService::increment_balance(id, amount)
+fn get_reorder_suggestions(state: State<'_, AppState>, query: Option<ReorderQueryDto>) -> AppResult<ReorderReportDto> {
+    StockService::reorder(&state, &query.unwrap_or_default())
 }
 
-/// PATCH /products/:id/decrement - Decrementa saldo
+// Comandos de Lotes
+
+/// POST /lots - Cadastra um lote (sem quantidade) de um produto com controle de lotes
+#[tauri::command]
+fn create_lot(state: State<'_, AppState>, lot: CreateLotDto) -> AppResult<LotEntity> {
+    LotService::create(&state, lot)
+}
+
+/// GET /lots/:id - Busca lote por ID
+#[tauri::command]
+fn get_lot(state: State<'_, AppState>, id: i64) -> AppResult<Option<LotEntity>> {
+    LotService::find_by_id(&state, id)
+}
+
+/// PUT /lots/:id - Corrige o código ou a validade do lote
 #[tauri::command]
-fn decrement_product_balance(id: i64, amount: f64) -> Result<ProductEntity, String> {
-    ProductService::decrement_balance(id, amount)
+fn update_lot(state: State<'_, AppState>, id: i64, lot: UpdateLotDto) -> AppResult<LotEntity> {
+    LotService::update(&state, id, lot)
+}
+
+/// GET /products/:id/lots - Lotes do produto na ordem de saída
+#[tauri::command]
+fn get_product_lots(state: State<'_, AppState>, product_id: i64) -> AppResult<ProductLotsDto> {
+    LotService::find_by_product(&state, product_id)
+}
+
+/// GET /lots/expiring - Lotes com saldo que vencem nos próximos `days` dias
+#[tauri::command]
+fn get_expiring_lots(state: State<'_, AppState>, query: Option<ExpiringLotsQuery>) -> AppResult<Vec<ExpiringLotDto>> {
+    LotService::expiring(&state, &query.unwrap_or_default())
+}
+
+// Comandos de Inventário
+
+/// POST /inventory - Abre uma sessão de inventário
+#[tauri::command]
+fn open_inventory(state: State<'_, AppState>, inventory: Option<OpenInventoryDto>) -> AppResult<InventorySessionEntity> {
+    InventoryService::open(&state, inventory.unwrap_or_default())
+}
+
+/// GET /inventory - Lista as sessões de inventário (`status` filtra)
+#[tauri::command]
+fn get_inventories(state: State<'_, AppState>, query: Option<InventoryListQuery>) -> AppResult<Vec<InventorySessionEntity>> {
+    InventoryService::find_all(&state, &query.unwrap_or_default())
+}
+
+/// GET /inventory/current - Sessão de inventário aberta, se houver
+#[tauri::command]
+fn get_current_inventory(state: State<'_, AppState>) -> AppResult<Option<InventorySessionEntity>> {
+    InventoryService::current(&state)
+}
+
+/// GET /inventory/:id - Busca sessão de inventário por ID
+#[tauri::command]
+fn get_inventory(state: State<'_, AppState>, id: i64) -> AppResult<Option<InventorySessionEntity>> {
+    InventoryService::find_by_id(&state, id)
+}
+
+/// POST /inventory/:id/counts - Registra a leitura de um produto na contagem
+#[tauri::command]
+fn scan_inventory_count(state: State<'_, AppState>, id: i64, scan: InventoryScanDto) -> AppResult<InventoryLineDto> {
+    InventoryService::scan(&state, id, scan)
+}
+
+/// GET /inventory/:id/report - Relatório de divergências
+#[tauri::command]
+fn get_inventory_report(state: State<'_, AppState>, id: i64, query: Option<InventoryReportQuery>) -> AppResult<InventoryReportDto> {
+    InventoryService::report(&state, id, &query.unwrap_or_default())
+}
+
+/// POST /inventory/:id/close - Encerra a sessão lançando os ajustes de estoque
+#[tauri::command]
+fn close_inventory(state: State<'_, AppState>, id: i64, options: Option<CloseInventoryDto>) -> AppResult<InventoryReportDto> {
+    InventoryService::close(&state, id, options.unwrap_or_default())
+}
+
+/// POST /inventory/:id/cancel - Descarta a sessão sem mexer no estoque
+#[tauri::command]
+fn cancel_inventory(state: State<'_, AppState>, id: i64) -> AppResult<InventorySessionEntity> {
+    InventoryService::cancel(&state, id)
 }
 
 // Comandos de Venda
+
 #[tauri::command]
-fn get_venda(id: i64) -> Result<Option<VendaEntity>, String> {
-    VendaService::find_by_id(id)
+fn get_venda(state: State<'_, AppState>, id: i64) -> AppResult<Option<VendaEntity>> {
+    VendaService::find_by_id(&state, id)
 }
 
 #[tauri::command]
 fn create_venda(
+    state: State<'_, AppState>,
     venda: VendaEntity,
     items: Vec<VendaItemEntity>,
     payments: Vec<VendaPagamentoEntity>,
-) -> Result<i64, String> {
-    VendaService::create_venda(&venda, items, payments)
+) -> AppResult<VendaCreatedDto> {
+    VendaService::create_venda(&state, &venda, items, payments)
 }
 
 #[tauri::command]
-fn get_venda_items(venda_id: i64) -> Result<Vec<VendaItemEntity>, String> {
-    VendaService::find_items_by_venda_id(venda_id)
+fn get_venda_items(state: State<'_, AppState>, venda_id: i64) -> AppResult<Vec<VendaItemEntity>> {
+    VendaService::find_items_by_venda_id(&state, venda_id)
 }
 
 #[tauri::command]
-fn get_venda_payments(venda_id: i64) -> Result<Vec<VendaPagamentoEntity>, String> {
-    VendaService::find_payments_by_venda_id(venda_id)
+fn get_venda_payments(state: State<'_, AppState>, venda_id: i64) -> AppResult<Vec<VendaPagamentoEntity>> {
+    VendaService::find_payments_by_venda_id(&state, venda_id)
+}
+
+// Comandos de Carrinho
+
+/// POST /carts - Abre um carrinho
+#[tauri::command]
+fn open_cart(state: State<'_, AppState>, cart: Option<OpenCartDto>) -> AppResult<CartDto> {
+    CartService::open(&state, cart.unwrap_or_default())
+}
+
+/// GET /carts/current - Carrinho em andamento, se houver; usado para
+/// recuperar a venda ao reiniciar o aplicativo
+#[tauri::command]
+fn get_current_cart(state: State<'_, AppState>) -> AppResult<Option<CartDto>> {
+    CartService::current(&state)
+}
+
+/// GET /carts/:id - Busca carrinho por ID
+#[tauri::command]
+fn get_cart(state: State<'_, AppState>, id: i64) -> AppResult<Option<CartDto>> {
+    CartService::find_by_id(&state, id)
+}
+
+/// POST /carts/:id/items - Lê um produto no carrinho
+#[tauri::command]
+fn add_cart_item(state: State<'_, AppState>, id: i64, item: AddCartItemDto) -> AppResult<CartDto> {
+    CartService::add_item(&state, id, item)
+}
+
+/// PUT /carts/:id/items/:item_id - Altera quantidade ou desconto de um item
+#[tauri::command]
+fn update_cart_item(state: State<'_, AppState>, id: i64, item_id: i64, item: UpdateCartItemDto) -> AppResult<CartDto> {
+    CartService::update_item(&state, id, item_id, item)
+}
+
+/// DELETE /carts/:id/items/:item_id - Remove um item
+#[tauri::command]
+fn remove_cart_item(state: State<'_, AppState>, id: i64, item_id: i64) -> AppResult<CartDto> {
+    CartService::remove_item(&state, id, item_id)
+}
+
+/// PUT /carts/:id/discount - Desconto e acréscimo no total
+#[tauri::command]
+fn set_cart_discount(state: State<'_, AppState>, id: i64, discount: CartDiscountDto) -> AppResult<CartDto> {
+    CartService::set_discount(&state, id, discount)
+}
+
+/// POST /carts/:id/checkout - Passa o carrinho para pagamento
+#[tauri::command]
+fn checkout_cart(state: State<'_, AppState>, id: i64) -> AppResult<CartDto> {
+    CartService::checkout(&state, id)
+}
+
+/// POST /carts/:id/reopen - Volta o carrinho a aceitar itens
+#[tauri::command]
+fn reopen_cart(state: State<'_, AppState>, id: i64) -> AppResult<CartDto> {
+    CartService::reopen(&state, id)
+}
+
+/// POST /carts/:id/payments - Registra um pagamento
+#[tauri::command]
+fn add_cart_payment(state: State<'_, AppState>, id: i64, payment: AddCartPaymentDto) -> AppResult<CartDto> {
+    CartService::add_payment(&state, id, payment)
+}
+
+/// DELETE /carts/:id/payments/:payment_id - Remove um pagamento
+#[tauri::command]
+fn remove_cart_payment(state: State<'_, AppState>, id: i64, payment_id: i64) -> AppResult<CartDto> {
+    CartService::remove_payment(&state, id, payment_id)
+}
+
+/// POST /carts/:id/finalize - Grava o carrinho como venda
+#[tauri::command]
+fn finalize_cart(state: State<'_, AppState>, id: i64, fiscal: FinalizeCartDto) -> AppResult<CartFinalizedDto> {
+    CartService::finalize(&state, id, fiscal)
+}
+
+/// POST /carts/:id/abort - Descarta o carrinho
+#[tauri::command]
+fn abort_cart(state: State<'_, AppState>, id: i64) -> AppResult<CartDto> {
+    CartService::abort(&state, id)
+}
+
+// Comandos de Histórico
+
+/// GET /history - Consulta o histórico de alterações
+#[tauri::command]
+fn get_history(state: State<'_, AppState>, query: Option<HistoryQueryDto>) -> AppResult<Vec<HistoryEntity>> {
+    HistoryService::find(&state, &query.unwrap_or_default())
 }
 
 // Removidos: update_venda_status e list_vendas_by_status
 // A nova estrutura usa campos específicos de NF-e (cancelled, etc)
 
+/// Evento emitido ao frontend com os produtos que uma operação deixou abaixo do
+/// estoque mínimo (`Vec<LowStockDto>`)
+const LOW_STOCK_EVENT: &str = "low-stock";
+
+/// Avisos dos serviços (comandos e HTTP) como eventos Tauri. O `AppHandle` só
+/// existe depois que o app sobe; até lá os avisos são descartados.
+#[derive(Default)]
+struct TauriEvents {
+    app: OnceLock<AppHandle>,
+}
+
+impl EventSink for TauriEvents {
+    fn low_stock(&self, products: &[LowStockDto]) {
+        let Some(app) = self.app.get() else { return };
+        // Falhas só vão para o log: a operação já foi gravada
+        if let Err(e) = app.emit(LOW_STOCK_EVENT, products) {
+            eprintln!("Erro ao emitir evento {}: {}", LOW_STOCK_EVENT, e);
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Inicializa o banco de dados
-    if let Err(e) = SqliteDbService::get_instance() {
-        eprintln!("Failed to initialize database: {}", e);
-        std::process::exit(1);
-    }
+    let db = match SqliteDbService::get_instance() {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Repositórios compartilhados entre os comandos Tauri e o servidor HTTP
+    let events = Arc::new(TauriEvents::default());
+    let state = AppState::sqlite(db.clone())
+        .with_origin(Origin::Tauri)
+        .with_events(events.clone());
+    // O fuso da loja é lido uma vez: mudar `timezone` na configuração exige reiniciar o app
+    let timezone = match ConfigService::timezone(&state) {
+        Ok(timezone) => timezone,
+        Err(e) => {
+            eprintln!("Failed to read store timezone: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let state = state.with_clock(Arc::new(SystemClock::new(timezone)));
+
+    // Cópias de segurança agendadas
+    let backups = BackupService::new(db.clone(), BackupConfig::from_env(db.get_db_path()));
+    tauri::async_runtime::spawn(backups.clone().run_scheduler());
 
     // Inicia o servidor HTTP em background para integrações externas
-    tokio::spawn(async {
-        if let Err(e) = start_http_server().await {
+    let http_state = state.clone().with_origin(Origin::Http);
+    let integrity = IntegrityService::new(db.clone());
+    let admin_state = AdminState { backups: backups.clone(), integrity: integrity.clone() };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = start_http_server(http_state, admin_state).await {
             eprintln!("Erro ao iniciar servidor HTTP: {}", e);
         }
     });
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(move |app| {
+            let _ = events.app.set(app.handle().clone());
+            Ok(())
+        })
+        .manage(db)
+        .manage(state)
+        .manage(backups)
+        .manage(integrity)
         .invoke_handler(tauri::generate_handler![
             greet,
             get_db_path,
@@ -196,17 +740,89 @@ pub fn run() {
             create_product,
             get_product,
             get_product_by_code,
+            search_products,
+            lookup_barcode,
+            import_products,
+            export_products,
             get_all_products,
             update_product,
             delete_product,
             increment_product_balance,
             decrement_product_balance,
             list_active_products,
+            get_product_components,
+            set_product_components,
+            // Category commands
+            create_category,
+            get_categories,
+            get_category_tree,
+            get_category,
+            update_category,
+            delete_category,
+            reassign_products,
+            get_category_products,
+            // Unit commands
+            create_unit,
+            get_units,
+            get_unit,
+            update_unit,
+            delete_unit,
+            // Promotion commands
+            create_promotion,
+            get_promotions,
+            get_promotion,
+            update_promotion,
+            delete_promotion,
+            preview_pricing,
+            // Stock commands
+            register_stock_movement,
+            get_kardex,
+            reconcile_stock,
+            get_low_stock,
+            get_reorder_suggestions,
+            // Lot commands
+            create_lot,
+            get_lot,
+            update_lot,
+            get_product_lots,
+            get_expiring_lots,
+            // Inventory commands
+            open_inventory,
+            get_inventories,
+            get_current_inventory,
+            get_inventory,
+            scan_inventory_count,
+            get_inventory_report,
+            close_inventory,
+            cancel_inventory,
             // Venda commands
             get_venda,
             create_venda,
             get_venda_items,
             get_venda_payments,
+            // Cart commands
+            open_cart,
+            get_current_cart,
+            get_cart,
+            add_cart_item,
+            update_cart_item,
+            remove_cart_item,
+            set_cart_discount,
+            checkout_cart,
+            reopen_cart,
+            add_cart_payment,
+            remove_cart_payment,
+            finalize_cart,
+            abort_cart,
+            // History commands
+            get_history,
+            // Backup commands
+            create_backup,
+            list_backups,
+            restore_backup,
+            // Integrity commands
+            check_integrity,
+            repair_integrity,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,41 @@
+use crate::error::AppResult;
+use crate::entities::{CartEntity, CartItemEntity, CartPaymentEntity};
+use chrono::{DateTime, Utc};
+
+/// Carrinhos do caixa, com seus itens e pagamentos (tabelas `carrinhos`,
+/// `carrinho_itens` e `carrinho_pagamentos`)
+pub trait CartRepository: Send + Sync {
+    /// Cria o carrinho, retornando-o com `id`
+    fn create(&self, cart: &CartEntity) -> AppResult<CartEntity>;
+
+    /// Busca um carrinho por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<CartEntity>>;
+
+    /// Carrinho mais recente ainda em andamento (`open` ou `payment`)
+    fn find_active(&self) -> AppResult<Option<CartEntity>>;
+
+    /// Grava a situação, os descontos e os dados do carrinho
+    fn save(&self, cart: &CartEntity) -> AppResult<()>;
+
+    /// Encerra o carrinho em pagamento com a venda `venda_id`; `false` se ele
+    /// não está mais em pagamento (já finalizado ou abortado)
+    fn finalize(&self, id: i64, venda_id: i64, at: DateTime<Utc>) -> AppResult<bool>;
+
+    /// Itens do carrinho, na ordem em que foram lidos
+    fn find_items(&self, cart_id: i64) -> AppResult<Vec<CartItemEntity>>;
+
+    /// Insere (sem `id`) ou atualiza um item
+    fn save_item(&self, item: &CartItemEntity) -> AppResult<CartItemEntity>;
+
+    /// Remove o item; `false` se ele não pertence ao carrinho
+    fn delete_item(&self, cart_id: i64, item_id: i64) -> AppResult<bool>;
+
+    /// Pagamentos do carrinho, na ordem em que foram registrados
+    fn find_payments(&self, cart_id: i64) -> AppResult<Vec<CartPaymentEntity>>;
+
+    /// Registra um pagamento, retornando-o com `id`
+    fn add_payment(&self, payment: &CartPaymentEntity) -> AppResult<CartPaymentEntity>;
+
+    /// Remove o pagamento; `false` se ele não pertence ao carrinho
+    fn delete_payment(&self, cart_id: i64, payment_id: i64) -> AppResult<bool>;
+}
@@ -0,0 +1,17 @@
+use crate::error::AppResult;
+use crate::entities::CategoryEntity;
+
+/// Armazenamento de categorias de produtos (tabela `categorias`)
+pub trait CategoryRepository: Send + Sync {
+    /// Busca uma categoria por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<CategoryEntity>>;
+
+    /// Lista todas as categorias ordenadas por nome
+    fn find_all(&self) -> AppResult<Vec<CategoryEntity>>;
+
+    /// Insere (sem `id`) ou atualiza (com `id`) uma categoria
+    fn save(&self, category: &CategoryEntity) -> AppResult<CategoryEntity>;
+
+    /// Remove a categoria; subcategorias e produtos precisam ter sido movidos antes
+    fn delete(&self, id: i64) -> AppResult<()>;
+}
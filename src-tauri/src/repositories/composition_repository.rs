@@ -0,0 +1,17 @@
+use crate::error::AppResult;
+use crate::entities::CompositionItemEntity;
+
+/// Composição dos kits (tabela `produto_composicao`)
+pub trait CompositionRepository: Send + Sync {
+    /// Componentes do kit, na ordem em que foram cadastrados
+    fn find_by_kit(&self, kit_id: i64) -> AppResult<Vec<CompositionItemEntity>>;
+
+    /// Kits que usam o produto como componente
+    fn find_by_component(&self, component_id: i64) -> AppResult<Vec<CompositionItemEntity>>;
+
+    /// IDs dos produtos que são kits
+    fn kit_ids(&self) -> AppResult<Vec<i64>>;
+
+    /// Substitui todos os componentes do kit; lista vazia desfaz o kit
+    fn replace(&self, kit_id: i64, components: &[CompositionItemEntity]) -> AppResult<()>;
+}
@@ -0,0 +1,17 @@
+use crate::error::AppResult;
+use crate::entities::ConfigEntity;
+
+/// Armazenamento de configurações (tabela `config`)
+pub trait ConfigRepository: Send + Sync {
+    /// Busca uma configuração por ID
+    fn find_by_id(&self, id: &str) -> AppResult<Option<ConfigEntity>>;
+
+    /// Insere ou atualiza uma configuração
+    fn save(&self, config: &ConfigEntity) -> AppResult<ConfigEntity>;
+
+    /// Lista todas as configurações
+    fn find_all(&self) -> AppResult<Vec<ConfigEntity>>;
+
+    /// Remove uma configuração por ID
+    fn delete_by_id(&self, id: &str) -> AppResult<()>;
+}
@@ -0,0 +1,24 @@
+use crate::error::AppResult;
+use crate::entities::HistoryEntity;
+use chrono::{DateTime, Utc};
+
+/// Filtro das consultas ao histórico; campos `None` não restringem
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    /// Início do intervalo (inclusivo)
+    pub start: Option<DateTime<Utc>>,
+    /// Fim do intervalo (exclusivo)
+    pub end: Option<DateTime<Utc>>,
+    pub limit: u32,
+}
+
+/// Armazenamento do histórico de alterações (tabela `history`)
+pub trait HistoryRepository: Send + Sync {
+    /// Grava uma entrada e retorna o ID
+    fn insert(&self, entry: &HistoryEntity) -> AppResult<i64>;
+
+    /// Busca entradas pelo filtro (mais recentes primeiro)
+    fn find(&self, filter: &HistoryFilter) -> AppResult<Vec<HistoryEntity>>;
+}
@@ -0,0 +1,28 @@
+use crate::error::AppResult;
+use crate::entities::{InventoryCountEntity, InventorySessionEntity, InventoryStatus, StockMovementEntity};
+
+/// Sessões de inventário e suas contagens (tabelas `inventarios` e `inventario_itens`)
+pub trait InventoryRepository: Send + Sync {
+    /// Cria a sessão junto com o retrato dos saldos numa única transação.
+    /// O `session_id` de `counts` é ignorado.
+    fn open(&self, session: &InventorySessionEntity, counts: &[InventoryCountEntity]) -> AppResult<InventorySessionEntity>;
+
+    /// Busca uma sessão por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<InventorySessionEntity>>;
+
+    /// Lista as sessões, das mais recentes para as mais antigas; `status` filtra
+    fn find_all(&self, status: Option<InventoryStatus>) -> AppResult<Vec<InventorySessionEntity>>;
+
+    /// Contagens da sessão, por produto
+    fn find_counts(&self, session_id: i64) -> AppResult<Vec<InventoryCountEntity>>;
+
+    /// Contagem de um produto na sessão
+    fn find_count(&self, session_id: i64, product_id: i64) -> AppResult<Option<InventoryCountEntity>>;
+
+    /// Insere ou substitui a contagem do produto
+    fn save_count(&self, count: &InventoryCountEntity) -> AppResult<()>;
+
+    /// Grava a situação final da sessão e os ajustes de estoque numa única
+    /// transação; os movimentos recebem a referência do inventário
+    fn finish(&self, session: &InventorySessionEntity, movements: &[StockMovementEntity]) -> AppResult<()>;
+}
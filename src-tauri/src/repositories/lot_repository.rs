@@ -0,0 +1,26 @@
+use crate::error::AppResult;
+use crate::entities::LotEntity;
+use chrono::NaiveDate;
+
+/// Lotes dos produtos (tabela `lotes`). A quantidade dos lotes só muda pelo
+/// livro de movimentos (`StockMovementRepository::record`).
+pub trait LotRepository: Send + Sync {
+    /// Busca um lote por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<LotEntity>>;
+
+    /// Busca o lote do produto pelo código
+    fn find_by_code(&self, product_id: i64, code: &str) -> AppResult<Option<LotEntity>>;
+
+    /// Lotes do produto na ordem de saída (validade mais próxima primeiro)
+    fn find_by_product(&self, product_id: i64) -> AppResult<Vec<LotEntity>>;
+
+    /// Lotes com quantidade positiva que vencem até `until` (inclusive),
+    /// por validade
+    fn find_expiring(&self, until: NaiveDate) -> AppResult<Vec<LotEntity>>;
+
+    /// Insere o lote com a quantidade informada
+    fn create(&self, lot: &LotEntity) -> AppResult<LotEntity>;
+
+    /// Atualiza código e validade do lote (a quantidade não é alterada)
+    fn update(&self, lot: &LotEntity) -> AppResult<LotEntity>;
+}
@@ -0,0 +1,125 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::{CartEntity, CartItemEntity, CartPaymentEntity, CartStatus};
+use crate::repositories::CartRepository;
+use super::lock;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Store {
+    next_id: i64,
+    carts: BTreeMap<i64, CartEntity>,
+    items: BTreeMap<i64, CartItemEntity>,
+    payments: BTreeMap<i64, CartPaymentEntity>,
+}
+
+impl Store {
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// Repositório de carrinhos mantido em memória
+#[derive(Default)]
+pub struct InMemoryCartRepository {
+    store: Mutex<Store>,
+}
+
+impl InMemoryCartRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CartRepository for InMemoryCartRepository {
+    fn create(&self, cart: &CartEntity) -> AppResult<CartEntity> {
+        let mut store = lock(&self.store)?;
+
+        let id = store.next_id();
+        let saved = CartEntity { id: Some(id), ..cart.clone() };
+        store.carts.insert(id, saved.clone());
+        Ok(saved)
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<CartEntity>> {
+        Ok(lock(&self.store)?.carts.get(&id).cloned())
+    }
+
+    fn find_active(&self) -> AppResult<Option<CartEntity>> {
+        Ok(lock(&self.store)?.carts.values().rev().find(|c| c.status.is_active()).cloned())
+    }
+
+    fn save(&self, cart: &CartEntity) -> AppResult<()> {
+        let mut store = lock(&self.store)?;
+        let id = cart.id.unwrap_or_default();
+        if !store.carts.contains_key(&id) {
+            return Err(AppError::not_found(format!("Cart with id {} not found", id)));
+        }
+        store.carts.insert(id, cart.clone());
+        Ok(())
+    }
+
+    fn finalize(&self, id: i64, venda_id: i64, at: DateTime<Utc>) -> AppResult<bool> {
+        let mut store = lock(&self.store)?;
+        match store.carts.get_mut(&id) {
+            Some(cart) if cart.status == CartStatus::Payment => {
+                cart.status = CartStatus::Finalized;
+                cart.venda_id = Some(venda_id);
+                cart.updated_at = at;
+                cart.closed_at = Some(at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn find_items(&self, cart_id: i64) -> AppResult<Vec<CartItemEntity>> {
+        Ok(lock(&self.store)?.items.values().filter(|i| i.cart_id == cart_id).cloned().collect())
+    }
+
+    fn save_item(&self, item: &CartItemEntity) -> AppResult<CartItemEntity> {
+        let mut store = lock(&self.store)?;
+        let id = match item.id {
+            Some(id) if store.items.contains_key(&id) => id,
+            Some(id) => return Err(AppError::not_found(format!("Cart item with id {} not found", id))),
+            None => store.next_id(),
+        };
+
+        let saved = CartItemEntity { id: Some(id), ..item.clone() };
+        store.items.insert(id, saved.clone());
+        Ok(saved)
+    }
+
+    fn delete_item(&self, cart_id: i64, item_id: i64) -> AppResult<bool> {
+        let mut store = lock(&self.store)?;
+        let found = store.items.get(&item_id).is_some_and(|i| i.cart_id == cart_id);
+        if found {
+            store.items.remove(&item_id);
+        }
+        Ok(found)
+    }
+
+    fn find_payments(&self, cart_id: i64) -> AppResult<Vec<CartPaymentEntity>> {
+        Ok(lock(&self.store)?.payments.values().filter(|p| p.cart_id == cart_id).cloned().collect())
+    }
+
+    fn add_payment(&self, payment: &CartPaymentEntity) -> AppResult<CartPaymentEntity> {
+        let mut store = lock(&self.store)?;
+
+        let id = store.next_id();
+        let saved = CartPaymentEntity { id: Some(id), ..payment.clone() };
+        store.payments.insert(id, saved.clone());
+        Ok(saved)
+    }
+
+    fn delete_payment(&self, cart_id: i64, payment_id: i64) -> AppResult<bool> {
+        let mut store = lock(&self.store)?;
+        let found = store.payments.get(&payment_id).is_some_and(|p| p.cart_id == cart_id);
+        if found {
+            store.payments.remove(&payment_id);
+        }
+        Ok(found)
+    }
+}
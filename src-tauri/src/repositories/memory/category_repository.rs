@@ -0,0 +1,61 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::CategoryEntity;
+use crate::repositories::CategoryRepository;
+use super::lock;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Store {
+    next_id: i64,
+    categories: BTreeMap<i64, CategoryEntity>,
+}
+
+/// Repositório de categorias mantido em memória (testes e execuções sem banco)
+#[derive(Default)]
+pub struct InMemoryCategoryRepository {
+    store: Mutex<Store>,
+}
+
+impl InMemoryCategoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CategoryRepository for InMemoryCategoryRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<CategoryEntity>> {
+        Ok(lock(&self.store)?.categories.get(&id).cloned())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<CategoryEntity>> {
+        let mut list: Vec<CategoryEntity> = lock(&self.store)?.categories.values().cloned().collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    fn save(&self, category: &CategoryEntity) -> AppResult<CategoryEntity> {
+        let mut store = lock(&self.store)?;
+
+        match category.id {
+            Some(id) => {
+                let existing = store.categories.get_mut(&id)
+                    .ok_or_else(|| AppError::not_found(format!("Category with id {} not found", id)))?;
+                *existing = category.clone();
+                Ok(existing.clone())
+            }
+            None => {
+                store.next_id += 1;
+                let id = store.next_id;
+                let created = CategoryEntity { id: Some(id), ..category.clone() };
+                store.categories.insert(id, created.clone());
+                Ok(created)
+            }
+        }
+    }
+
+    fn delete(&self, id: i64) -> AppResult<()> {
+        lock(&self.store)?.categories.remove(&id);
+        Ok(())
+    }
+}
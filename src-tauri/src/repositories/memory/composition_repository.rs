@@ -0,0 +1,41 @@
+use crate::error::AppResult;
+use crate::entities::CompositionItemEntity;
+use crate::repositories::CompositionRepository;
+use super::lock;
+use std::sync::Mutex;
+
+/// Composição dos kits mantida em memória (testes e execuções sem banco)
+#[derive(Default)]
+pub struct InMemoryCompositionRepository {
+    items: Mutex<Vec<CompositionItemEntity>>,
+}
+
+impl InMemoryCompositionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CompositionRepository for InMemoryCompositionRepository {
+    fn find_by_kit(&self, kit_id: i64) -> AppResult<Vec<CompositionItemEntity>> {
+        Ok(lock(&self.items)?.iter().filter(|c| c.kit_id == kit_id).cloned().collect())
+    }
+
+    fn find_by_component(&self, component_id: i64) -> AppResult<Vec<CompositionItemEntity>> {
+        Ok(lock(&self.items)?.iter().filter(|c| c.component_id == component_id).cloned().collect())
+    }
+
+    fn kit_ids(&self) -> AppResult<Vec<i64>> {
+        let mut ids: Vec<i64> = lock(&self.items)?.iter().map(|c| c.kit_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    fn replace(&self, kit_id: i64, components: &[CompositionItemEntity]) -> AppResult<()> {
+        let mut items = lock(&self.items)?;
+        items.retain(|c| c.kit_id != kit_id);
+        items.extend(components.iter().map(|c| CompositionItemEntity { kit_id, ..c.clone() }));
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::error::AppResult;
+use crate::entities::ConfigEntity;
+use crate::repositories::ConfigRepository;
+use super::lock;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Repositório de configurações mantido em memória
+#[derive(Default)]
+pub struct InMemoryConfigRepository {
+    configs: Mutex<BTreeMap<String, ConfigEntity>>,
+}
+
+impl InMemoryConfigRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConfigRepository for InMemoryConfigRepository {
+    fn find_by_id(&self, id: &str) -> AppResult<Option<ConfigEntity>> {
+        Ok(lock(&self.configs)?.get(id).cloned())
+    }
+
+    fn save(&self, config: &ConfigEntity) -> AppResult<ConfigEntity> {
+        lock(&self.configs)?.insert(config.id.clone(), config.clone());
+        Ok(config.clone())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<ConfigEntity>> {
+        Ok(lock(&self.configs)?.values().cloned().collect())
+    }
+
+    fn delete_by_id(&self, id: &str) -> AppResult<()> {
+        lock(&self.configs)?.remove(id);
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use crate::error::AppResult;
+use crate::entities::HistoryEntity;
+use crate::repositories::{HistoryFilter, HistoryRepository};
+use super::lock;
+use std::sync::Mutex;
+
+/// Histórico de alterações mantido em memória
+#[derive(Default)]
+pub struct InMemoryHistoryRepository {
+    entries: Mutex<Vec<HistoryEntity>>,
+}
+
+impl InMemoryHistoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl HistoryRepository for InMemoryHistoryRepository {
+    fn insert(&self, entry: &HistoryEntity) -> AppResult<i64> {
+        let mut entries = lock(&self.entries)?;
+        let id = entries.len() as i64 + 1;
+        entries.push(HistoryEntity { id: Some(id), ..entry.clone() });
+        Ok(id)
+    }
+
+    fn find(&self, filter: &HistoryFilter) -> AppResult<Vec<HistoryEntity>> {
+        let entries = lock(&self.entries)?;
+        let mut found: Vec<HistoryEntity> = entries
+            .iter()
+            .filter(|e| filter.entity_type.as_ref().is_none_or(|t| &e.entity_type == t))
+            .filter(|e| filter.entity_id.is_none() || e.entity_id == filter.entity_id)
+            .filter(|e| filter.start.is_none_or(|start| e.created_at >= start))
+            .filter(|e| filter.end.is_none_or(|end| e.created_at < end))
+            .cloned()
+            .collect();
+
+        // Como `ORDER BY created_at DESC, id DESC`
+        found.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        found.truncate(filter.limit as usize);
+        Ok(found)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::{InventoryCountEntity, InventorySessionEntity, InventoryStatus, StockMovementEntity};
+use crate::repositories::{InventoryRepository, StockMovementRepository};
+use super::lock;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Store {
+    next_id: i64,
+    sessions: BTreeMap<i64, InventorySessionEntity>,
+    counts: BTreeMap<(i64, i64), InventoryCountEntity>,
+}
+
+/// Repositório de inventários mantido em memória; os ajustes vão para o livro
+/// de estoque compartilhado com o `AppState`
+pub struct InMemoryInventoryRepository {
+    stock: Arc<dyn StockMovementRepository>,
+    store: Mutex<Store>,
+}
+
+impl InMemoryInventoryRepository {
+    pub fn new(stock: Arc<dyn StockMovementRepository>) -> Self {
+        Self { stock, store: Mutex::default() }
+    }
+}
+
+impl InventoryRepository for InMemoryInventoryRepository {
+    fn open(&self, session: &InventorySessionEntity, counts: &[InventoryCountEntity]) -> AppResult<InventorySessionEntity> {
+        let mut store = lock(&self.store)?;
+
+        store.next_id += 1;
+        let id = store.next_id;
+        let saved = InventorySessionEntity { id: Some(id), ..session.clone() };
+        store.sessions.insert(id, saved.clone());
+        for count in counts {
+            store.counts.insert((id, count.product_id), InventoryCountEntity { session_id: id, ..count.clone() });
+        }
+
+        Ok(saved)
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<InventorySessionEntity>> {
+        Ok(lock(&self.store)?.sessions.get(&id).cloned())
+    }
+
+    fn find_all(&self, status: Option<InventoryStatus>) -> AppResult<Vec<InventorySessionEntity>> {
+        Ok(lock(&self.store)?.sessions
+            .values()
+            .rev()
+            .filter(|s| status.is_none_or(|status| s.status == status))
+            .cloned()
+            .collect())
+    }
+
+    fn find_counts(&self, session_id: i64) -> AppResult<Vec<InventoryCountEntity>> {
+        Ok(lock(&self.store)?.counts
+            .range((session_id, i64::MIN)..=(session_id, i64::MAX))
+            .map(|(_, c)| c.clone())
+            .collect())
+    }
+
+    fn find_count(&self, session_id: i64, product_id: i64) -> AppResult<Option<InventoryCountEntity>> {
+        Ok(lock(&self.store)?.counts.get(&(session_id, product_id)).cloned())
+    }
+
+    fn save_count(&self, count: &InventoryCountEntity) -> AppResult<()> {
+        lock(&self.store)?.counts.insert((count.session_id, count.product_id), count.clone());
+        Ok(())
+    }
+
+    fn finish(&self, session: &InventorySessionEntity, movements: &[StockMovementEntity]) -> AppResult<()> {
+        let mut store = lock(&self.store)?;
+        let id = session.id.unwrap_or_default();
+        if !store.sessions.contains_key(&id) {
+            return Err(AppError::not_found(format!("Inventory session with id {} not found", id)));
+        }
+
+        let reference = StockMovementEntity::inventory_reference(id);
+        let movements: Vec<StockMovementEntity> = movements.iter()
+            .map(|movement| StockMovementEntity { reference: Some(reference.clone()), ..movement.clone() })
+            .collect();
+        self.stock.record_all(&movements)?;
+        store.sessions.insert(id, session.clone());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::{LotEntity, Quantity};
+use crate::repositories::LotRepository;
+use super::lock;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::sync::Mutex;
+
+/// Lotes mantidos em memória (testes e execuções sem banco). A quantidade é
+/// alterada pelo livro de movimentos em memória via `add_quantity`.
+#[derive(Default)]
+pub struct InMemoryLotRepository {
+    lots: Mutex<Vec<LotEntity>>,
+}
+
+impl InMemoryLotRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Soma `delta` à quantidade do lote do produto, retornando a nova
+    /// quantidade ou `None` se o lote não existe
+    pub(crate) fn add_quantity(&self, id: i64, product_id: i64, delta: Quantity, at: DateTime<Utc>) -> AppResult<Option<Quantity>> {
+        Ok(lock(&self.lots)?
+            .iter_mut()
+            .find(|l| l.id == Some(id) && l.product_id == product_id)
+            .map(|lot| {
+                lot.quantity += delta;
+                lot.updated_at = at;
+                lot.quantity
+            }))
+    }
+}
+
+impl LotRepository for InMemoryLotRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<LotEntity>> {
+        Ok(lock(&self.lots)?.iter().find(|l| l.id == Some(id)).cloned())
+    }
+
+    fn find_by_code(&self, product_id: i64, code: &str) -> AppResult<Option<LotEntity>> {
+        Ok(lock(&self.lots)?.iter().find(|l| l.product_id == product_id && l.code == code).cloned())
+    }
+
+    fn find_by_product(&self, product_id: i64) -> AppResult<Vec<LotEntity>> {
+        let mut lots: Vec<LotEntity> = lock(&self.lots)?.iter().filter(|l| l.product_id == product_id).cloned().collect();
+        lots.sort_by_key(LotEntity::fefo_key);
+        Ok(lots)
+    }
+
+    fn find_expiring(&self, until: NaiveDate) -> AppResult<Vec<LotEntity>> {
+        let mut lots: Vec<LotEntity> = lock(&self.lots)?
+            .iter()
+            .filter(|l| l.quantity > Quantity::ZERO && l.expires_at.is_some_and(|e| e <= until))
+            .cloned()
+            .collect();
+        lots.sort_by_key(LotEntity::fefo_key);
+        Ok(lots)
+    }
+
+    fn create(&self, lot: &LotEntity) -> AppResult<LotEntity> {
+        let mut lots = lock(&self.lots)?;
+        let saved = LotEntity { id: Some(lots.len() as i64 + 1), ..lot.clone() };
+        lots.push(saved.clone());
+        Ok(saved)
+    }
+
+    fn update(&self, lot: &LotEntity) -> AppResult<LotEntity> {
+        let mut lots = lock(&self.lots)?;
+        let existing = lots.iter_mut()
+            .find(|l| l.id.is_some() && l.id == lot.id)
+            .ok_or_else(|| AppError::not_found(format!("Lot with id {} not found", lot.id.unwrap_or_default())))?;
+
+        existing.code = lot.code.clone();
+        existing.expires_at = lot.expires_at;
+        existing.updated_at = lot.updated_at;
+        Ok(existing.clone())
+    }
+}
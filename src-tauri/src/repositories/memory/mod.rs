@@ -0,0 +1,35 @@
+pub mod product_repository;
+pub mod venda_repository;
+pub mod config_repository;
+pub mod resume_repository;
+pub mod history_repository;
+pub mod stock_movement_repository;
+pub mod category_repository;
+pub mod promotion_repository;
+pub mod composition_repository;
+pub mod inventory_repository;
+pub mod unit_repository;
+pub mod lot_repository;
+pub mod cart_repository;
+
+pub use product_repository::InMemoryProductRepository;
+pub use venda_repository::InMemoryVendaRepository;
+pub use config_repository::InMemoryConfigRepository;
+pub use resume_repository::InMemoryResumeRepository;
+pub use history_repository::InMemoryHistoryRepository;
+pub use stock_movement_repository::InMemoryStockMovementRepository;
+pub use category_repository::InMemoryCategoryRepository;
+pub use promotion_repository::InMemoryPromotionRepository;
+pub use composition_repository::InMemoryCompositionRepository;
+pub use inventory_repository::InMemoryInventoryRepository;
+pub use unit_repository::InMemoryUnitRepository;
+pub use lot_repository::InMemoryLotRepository;
+pub use cart_repository::InMemoryCartRepository;
+
+use crate::error::{AppError, AppResult};
+use std::sync::{Mutex, MutexGuard};
+
+/// Trava o estado de um repositório em memória convertendo envenenamento em erro
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> AppResult<MutexGuard<'_, T>> {
+    mutex.lock().map_err(|e| AppError::Internal(format!("Failed to lock in-memory store: {}", e)))
+}
@@ -0,0 +1,143 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::{ProductEntity, Quantity};
+use crate::repositories::{search_terms, ProductRepository, ProductSearch};
+use super::lock;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Store {
+    next_id: i64,
+    products: BTreeMap<i64, ProductEntity>,
+}
+
+/// Repositório de produtos mantido em memória (testes e execuções sem banco)
+#[derive(Default)]
+pub struct InMemoryProductRepository {
+    store: Mutex<Store>,
+}
+
+impl InMemoryProductRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Soma `delta` ao saldo e retorna o novo saldo (`None` se o produto não
+    /// existe); usado pelo livro de movimentos em memória
+    pub(crate) fn add_balance(&self, id: i64, delta: Quantity, at: DateTime<Utc>) -> AppResult<Option<Quantity>> {
+        Ok(lock(&self.store)?.products.get_mut(&id).map(|product| {
+            product.balance += delta;
+            product.updated_at = at;
+            product.balance
+        }))
+    }
+
+    fn sorted_by_name<'a>(products: impl Iterator<Item = &'a ProductEntity>) -> Vec<ProductEntity> {
+        let mut list: Vec<ProductEntity> = products.cloned().collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+}
+
+impl ProductRepository for InMemoryProductRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<ProductEntity>> {
+        Ok(lock(&self.store)?.products.get(&id).cloned())
+    }
+
+    fn find_by_code(&self, code: &str) -> AppResult<Option<ProductEntity>> {
+        Ok(lock(&self.store)?.products.values().find(|p| p.code == code).cloned())
+    }
+
+    fn find_by_gtin(&self, gtin: &str) -> AppResult<Option<ProductEntity>> {
+        Ok(lock(&self.store)?.products.values().find(|p| p.gtin.as_deref() == Some(gtin)).cloned())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<ProductEntity>> {
+        Ok(Self::sorted_by_name(lock(&self.store)?.products.values()))
+    }
+
+    fn find_all_active(&self) -> AppResult<Vec<ProductEntity>> {
+        Ok(Self::sorted_by_name(lock(&self.store)?.products.values().filter(|p| p.active == 1)))
+    }
+
+    fn find_by_categories(&self, category_ids: &[i64]) -> AppResult<Vec<ProductEntity>> {
+        let store = lock(&self.store)?;
+        Ok(Self::sorted_by_name(store.products.values().filter(|p| p.category_id.is_some_and(|id| category_ids.contains(&id)))))
+    }
+
+    fn search(&self, search: &ProductSearch) -> AppResult<(Vec<ProductEntity>, i64)> {
+        let Some(first) = search.terms.first() else {
+            return Ok((Vec::new(), 0));
+        };
+
+        // Mesma regra do FTS: cada termo é prefixo de alguma palavra do código ou do nome
+        let mut found: Vec<(bool, bool, ProductEntity)> = lock(&self.store)?
+            .products
+            .values()
+            .filter(|p| search.active.is_none_or(|active| (p.active == 1) == active))
+            .filter_map(|p| {
+                let code = search_terms(&p.code);
+                let name = search_terms(&p.name);
+                let words: Vec<&String> = code.iter().chain(&name).collect();
+                search.terms.iter()
+                    .all(|t| words.iter().any(|w| w.starts_with(t.as_str())))
+                    .then(|| {
+                        let leading = [code.first(), name.first()].into_iter().flatten().any(|w| w.starts_with(first.as_str()));
+                        (p.code == search.query, leading, p.clone())
+                    })
+            })
+            .collect();
+
+        found.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)).then_with(|| a.2.name.cmp(&b.2.name)));
+
+        let total = found.len() as i64;
+        let page = found.into_iter()
+            .skip(search.offset as usize)
+            .take(search.limit as usize)
+            .map(|(_, _, p)| p)
+            .collect();
+        Ok((page, total))
+    }
+
+    fn save(&self, product: &ProductEntity) -> AppResult<ProductEntity> {
+        let mut store = lock(&self.store)?;
+
+        // Mesma restrição UNIQUE da coluna produtos.code
+        if store.products.values().any(|p| p.code == product.code && p.id != product.id) {
+            return Err(AppError::Conflict("Failed to save product: UNIQUE constraint failed: produtos.code".to_string()));
+        }
+
+        match product.id {
+            Some(id) => {
+                let existing = store.products.get_mut(&id)
+                    .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id)))?;
+                *existing = ProductEntity { balance: existing.balance, ..product.clone() };
+                Ok(existing.clone())
+            }
+            None => {
+                store.next_id += 1;
+                let id = store.next_id;
+                let created = ProductEntity { id: Some(id), ..product.clone() };
+                store.products.insert(id, created.clone());
+                Ok(created)
+            }
+        }
+    }
+
+    fn set_balance(&self, id: i64, balance: Quantity, at: DateTime<Utc>) -> AppResult<()> {
+        if let Some(product) = lock(&self.store)?.products.get_mut(&id) {
+            product.balance = balance;
+            product.updated_at = at;
+        }
+        Ok(())
+    }
+
+    fn deactivate(&self, id: i64, at: DateTime<Utc>) -> AppResult<()> {
+        if let Some(product) = lock(&self.store)?.products.get_mut(&id) {
+            product.active = 0;
+            product.updated_at = at;
+        }
+        Ok(())
+    }
+}
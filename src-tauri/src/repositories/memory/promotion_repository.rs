@@ -0,0 +1,64 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::PromotionEntity;
+use crate::repositories::PromotionRepository;
+use super::lock;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Store {
+    next_id: i64,
+    promotions: BTreeMap<i64, PromotionEntity>,
+}
+
+/// Repositório de promoções mantido em memória (testes e execuções sem banco)
+#[derive(Default)]
+pub struct InMemoryPromotionRepository {
+    store: Mutex<Store>,
+}
+
+impl InMemoryPromotionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PromotionRepository for InMemoryPromotionRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<PromotionEntity>> {
+        Ok(lock(&self.store)?.promotions.get(&id).cloned())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<PromotionEntity>> {
+        Ok(lock(&self.store)?.promotions.values().rev().cloned().collect())
+    }
+
+    fn find_valid_at(&self, at: DateTime<Utc>) -> AppResult<Vec<PromotionEntity>> {
+        Ok(lock(&self.store)?.promotions.values().filter(|p| p.is_valid_at(at)).cloned().collect())
+    }
+
+    fn save(&self, promotion: &PromotionEntity) -> AppResult<PromotionEntity> {
+        let mut store = lock(&self.store)?;
+
+        match promotion.id {
+            Some(id) => {
+                let existing = store.promotions.get_mut(&id)
+                    .ok_or_else(|| AppError::not_found(format!("Promotion with id {} not found", id)))?;
+                *existing = promotion.clone();
+                Ok(existing.clone())
+            }
+            None => {
+                store.next_id += 1;
+                let id = store.next_id;
+                let created = PromotionEntity { id: Some(id), ..promotion.clone() };
+                store.promotions.insert(id, created.clone());
+                Ok(created)
+            }
+        }
+    }
+
+    fn delete(&self, id: i64) -> AppResult<()> {
+        lock(&self.store)?.promotions.remove(&id);
+        Ok(())
+    }
+}
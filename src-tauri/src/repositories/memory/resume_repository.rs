@@ -0,0 +1,72 @@
+use crate::error::AppResult;
+use crate::entities::{Money, PaymentTypes, ResumeEntity};
+use crate::repositories::ResumeRepository;
+use super::lock;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Repositório de resumos mantido em memória
+#[derive(Default)]
+pub struct InMemoryResumeRepository {
+    resumes: Mutex<BTreeMap<String, ResumeEntity>>,
+}
+
+impl InMemoryResumeRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ResumeRepository for InMemoryResumeRepository {
+    fn find_since(&self, since: DateTime<Utc>) -> AppResult<Vec<ResumeEntity>> {
+        let mut list: Vec<ResumeEntity> = lock(&self.resumes)?
+            .values()
+            .filter(|r| r.created_at >= since)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.code.as_str().cmp(b.code.as_str()));
+        Ok(list)
+    }
+
+    fn find_by_id(&self, id: &str) -> AppResult<Option<ResumeEntity>> {
+        Ok(lock(&self.resumes)?.get(id).cloned())
+    }
+
+    fn find_by_code_since(&self, code: &PaymentTypes, since: DateTime<Utc>) -> AppResult<Option<ResumeEntity>> {
+        Ok(lock(&self.resumes)?
+            .values()
+            .find(|r| &r.code == code && r.created_at >= since)
+            .cloned())
+    }
+
+    fn save(&self, resume: &ResumeEntity) -> AppResult<()> {
+        lock(&self.resumes)?.insert(resume.id.clone(), resume.clone());
+        Ok(())
+    }
+
+    fn update_amounts(&self, id: &str, amount_s: Money, amount_n: Money, updated_at: DateTime<Utc>) -> AppResult<()> {
+        if let Some(resume) = lock(&self.resumes)?.get_mut(id) {
+            resume.amount_s = amount_s;
+            resume.amount_n = amount_n;
+            resume.updated_at = updated_at;
+        }
+        Ok(())
+    }
+
+    fn increment_amounts(&self, id: &str, amount_s_inc: Money, amount_n_inc: Money, updated_at: DateTime<Utc>) -> AppResult<()> {
+        if let Some(resume) = lock(&self.resumes)?.get_mut(id) {
+            resume.amount_s += amount_s_inc;
+            resume.amount_n += amount_n_inc;
+            resume.updated_at = updated_at;
+        }
+        Ok(())
+    }
+
+    fn delete_before(&self, before: DateTime<Utc>) -> AppResult<usize> {
+        let mut resumes = lock(&self.resumes)?;
+        let count = resumes.len();
+        resumes.retain(|_, r| r.created_at >= before);
+        Ok(count - resumes.len())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::{AppError, AppResult};
+use crate::entities::lot::split_exit;
+use crate::entities::{Quantity, StockMovementEntity};
+use crate::repositories::{LotRepository, ProductRepository, StockMovementFilter, StockMovementRepository};
+use super::{lock, InMemoryLotRepository, InMemoryProductRepository};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Livro de movimentos mantido em memória; aplica os saldos nos repositórios
+/// de produtos e de lotes em memória compartilhados com o `AppState`
+pub struct InMemoryStockMovementRepository {
+    products: Arc<InMemoryProductRepository>,
+    lots: Arc<InMemoryLotRepository>,
+    movements: Mutex<Vec<StockMovementEntity>>,
+}
+
+impl InMemoryStockMovementRepository {
+    pub fn new(products: Arc<InMemoryProductRepository>, lots: Arc<InMemoryLotRepository>) -> Self {
+        Self { products, lots, movements: Mutex::default() }
+    }
+}
+
+impl InMemoryStockMovementRepository {
+    /// Aplica o movimento com o livro já travado
+    fn record_locked(&self, movements: &mut Vec<StockMovementEntity>, movement: &StockMovementEntity) -> AppResult<StockMovementEntity> {
+        if let Some(lot_id) = movement.lot_id {
+            self.lots
+                .add_quantity(lot_id, movement.product_id, movement.quantity, movement.created_at)?
+                .ok_or_else(|| AppError::not_found(format!("Lot with id {} not found", lot_id)))?;
+        }
+        let balance_after = self.products
+            .add_balance(movement.product_id, movement.quantity, movement.created_at)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", movement.product_id)))?;
+
+        let saved = StockMovementEntity {
+            id: Some(movements.len() as i64 + 1),
+            balance_after,
+            ..movement.clone()
+        };
+        movements.push(saved.clone());
+        Ok(saved)
+    }
+
+    /// Confere todas as partes antes de aplicar a primeira, já que em memória
+    /// não há desfazer: produto e lote precisam existir, nenhuma saída deixa o
+    /// lote negativo e, com `block_negative`, nem o saldo do produto (como os
+    /// `UPDATE` condicionados do SQLite); nesses casos o erro é `Conflict`.
+    fn record_checked(
+        &self,
+        ledger: &mut Vec<StockMovementEntity>,
+        movements: &[StockMovementEntity],
+        block_negative: bool,
+    ) -> AppResult<Vec<StockMovementEntity>> {
+        let mut balances: HashMap<i64, Quantity> = HashMap::new();
+        let mut lots: HashMap<i64, Quantity> = HashMap::new();
+        for movement in movements {
+            let exit = movement.quantity.is_negative();
+            if let Some(lot_id) = movement.lot_id {
+                let lot = self.lots.find_by_id(lot_id)?
+                    .filter(|lot| lot.product_id == movement.product_id)
+                    .ok_or_else(|| AppError::not_found(format!("Lot with id {} not found", lot_id)))?;
+
+                let quantity = lots.entry(lot_id).or_insert(lot.quantity);
+                if exit && (*quantity + movement.quantity).is_negative() {
+                    return Err(AppError::Conflict(format!("Estoque insuficiente no lote {} (saldo {}, saída {})", lot.code, quantity, -movement.quantity)));
+                }
+                *quantity += movement.quantity;
+            }
+            let product = self.products.find_by_id(movement.product_id)?
+                .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", movement.product_id)))?;
+
+            let balance = balances.entry(movement.product_id).or_insert(product.balance);
+            if block_negative && exit && (*balance + movement.quantity).is_negative() {
+                return Err(AppError::Conflict(format!("Estoque insuficiente: {} (saldo {}, vendido {})", product.code, balance, -movement.quantity)));
+            }
+            *balance += movement.quantity;
+        }
+
+        movements.iter().map(|movement| self.record_locked(ledger, movement)).collect()
+    }
+
+    /// Grava as baixas de uma venda com o livro travado: saídas sem lote de
+    /// produtos com controle de lotes são divididas entre os lotes (FEFO) com
+    /// as quantidades do momento, e `block_negative` recusa a venda que
+    /// deixaria um produto negativo
+    pub(crate) fn record_sale(&self, movements: &[StockMovementEntity], block_negative: bool) -> AppResult<Vec<StockMovementEntity>> {
+        let mut ledger = lock(&self.movements)?;
+        let mut parts = Vec::new();
+        for movement in movements {
+            match self.products.find_by_id(movement.product_id)? {
+                Some(product) if product.track_lots == 1 && movement.lot_id.is_none() && movement.quantity.is_negative() => {
+                    let lots = self.lots.find_by_product(movement.product_id)?;
+                    parts.extend(split_exit(&lots, &product.code, movement)?);
+                }
+                _ => parts.push(movement.clone()),
+            }
+        }
+        self.record_checked(&mut ledger, &parts, block_negative)
+    }
+}
+
+impl StockMovementRepository for InMemoryStockMovementRepository {
+    fn record(&self, movement: &StockMovementEntity) -> AppResult<StockMovementEntity> {
+        let mut ledger = lock(&self.movements)?;
+        Ok(self.record_checked(&mut ledger, std::slice::from_ref(movement), false)?.remove(0))
+    }
+
+    fn record_all(&self, movements: &[StockMovementEntity]) -> AppResult<Vec<StockMovementEntity>> {
+        let mut ledger = lock(&self.movements)?;
+        self.record_checked(&mut ledger, movements, false)
+    }
+
+    fn find(&self, filter: &StockMovementFilter) -> AppResult<Vec<StockMovementEntity>> {
+        let mut found: Vec<StockMovementEntity> = lock(&self.movements)?
+            .iter()
+            .filter(|m| filter.product_id.is_none_or(|id| m.product_id == id))
+            .filter(|m| filter.start.is_none_or(|start| m.created_at >= start))
+            .filter(|m| filter.end.is_none_or(|end| m.created_at < end))
+            .filter(|m| filter.reference.is_none() || m.reference == filter.reference)
+            .cloned()
+            .collect();
+
+        // Como `ORDER BY created_at, id`
+        found.sort_by_key(|m| (m.created_at, m.id));
+        Ok(found)
+    }
+
+    fn sum_quantity(&self, product_id: i64, before: Option<DateTime<Utc>>) -> AppResult<Quantity> {
+        Ok(lock(&self.movements)?
+            .iter()
+            .filter(|m| m.product_id == product_id && before.is_none_or(|b| m.created_at < b))
+            .fold(Quantity::ZERO, |sum, m| sum + m.quantity))
+    }
+
+    fn ledger_balances(&self) -> AppResult<Vec<(i64, Quantity)>> {
+        let mut balances: BTreeMap<i64, Quantity> = BTreeMap::new();
+        for m in lock(&self.movements)?.iter() {
+            *balances.entry(m.product_id).or_default() += m.quantity;
+        }
+        Ok(balances.into_iter().collect())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::AppResult;
+use crate::entities::UnitEntity;
+use crate::repositories::UnitRepository;
+use super::lock;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Unidades de medida mantidas em memória (testes e execuções sem banco).
+/// Começa com as unidades padrão, como um banco novo.
+pub struct InMemoryUnitRepository {
+    units: Mutex<BTreeMap<String, UnitEntity>>,
+}
+
+impl InMemoryUnitRepository {
+    pub fn new() -> Self {
+        let units = UnitEntity::defaults().into_iter().map(|u| (u.code.clone(), u)).collect();
+        Self { units: Mutex::new(units) }
+    }
+}
+
+impl Default for InMemoryUnitRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnitRepository for InMemoryUnitRepository {
+    fn find_by_code(&self, code: &str) -> AppResult<Option<UnitEntity>> {
+        Ok(lock(&self.units)?.get(code).cloned())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<UnitEntity>> {
+        Ok(lock(&self.units)?.values().cloned().collect())
+    }
+
+    fn save(&self, unit: &UnitEntity) -> AppResult<UnitEntity> {
+        lock(&self.units)?.insert(unit.code.clone(), unit.clone());
+        Ok(unit.clone())
+    }
+
+    fn delete(&self, code: &str) -> AppResult<()> {
+        lock(&self.units)?.remove(code);
+        Ok(())
+    }
+}
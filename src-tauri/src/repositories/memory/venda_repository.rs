@@ -0,0 +1,179 @@
+use crate::error::{AppError, AppResult};
+use crate::dtos::VendaResumo;
+use crate::entities::{CartStatus, StockMovementEntity, VendaEntity, VendaItemEntity, VendaPagamentoEntity};
+use crate::repositories::{CartRepository, VendaRepository};
+use super::{lock, InMemoryStockMovementRepository};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Store {
+    next_venda_id: i64,
+    next_item_id: i64,
+    next_payment_id: i64,
+    vendas: BTreeMap<i64, VendaEntity>,
+    emitted_at: BTreeMap<i64, DateTime<Utc>>,
+    items: Vec<VendaItemEntity>,
+    payments: Vec<VendaPagamentoEntity>,
+}
+
+impl Store {
+    /// Vendas emitidas em `[start, end)`, mais recentes primeiro (como `ORDER BY dh_emi_ts DESC`)
+    fn vendas_in_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&VendaEntity> {
+        let mut vendas: Vec<(DateTime<Utc>, &VendaEntity)> = self.vendas
+            .iter()
+            .filter_map(|(id, v)| self.emitted_at.get(id).map(|at| (*at, v)))
+            .filter(|(at, _)| *at >= start && *at < end)
+            .collect();
+        vendas.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+        vendas.into_iter().map(|(_, v)| v).collect()
+    }
+}
+
+/// Repositório de vendas mantido em memória; os movimentos de estoque e o
+/// encerramento do carrinho vão para os repositórios compartilhados com o `AppState`
+pub struct InMemoryVendaRepository {
+    stock: Arc<InMemoryStockMovementRepository>,
+    carts: Arc<dyn CartRepository>,
+    store: Mutex<Store>,
+}
+
+impl InMemoryVendaRepository {
+    pub fn new(stock: Arc<InMemoryStockMovementRepository>, carts: Arc<dyn CartRepository>) -> Self {
+        Self { stock, carts, store: Mutex::default() }
+    }
+
+    fn record_movements(&self, venda_id: i64, movements: &[StockMovementEntity], block_negative: bool) -> AppResult<()> {
+        let reference = StockMovementEntity::venda_reference(venda_id);
+        let movements: Vec<StockMovementEntity> = movements.iter()
+            .map(|movement| StockMovementEntity { reference: Some(reference.clone()), ..movement.clone() })
+            .collect();
+        self.stock.record_sale(&movements, block_negative).map(|_| ())
+    }
+}
+
+impl VendaRepository for InMemoryVendaRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<VendaEntity>> {
+        Ok(lock(&self.store)?.vendas.get(&id).cloned())
+    }
+
+    fn create_venda(
+        &self,
+        venda: &VendaEntity,
+        emitted_at: DateTime<Utc>,
+        items: &[VendaItemEntity],
+        payments: &[VendaPagamentoEntity],
+        movements: &[StockMovementEntity],
+        block_negative: bool,
+        cart_id: Option<i64>,
+    ) -> AppResult<i64> {
+        let mut store = lock(&self.store)?;
+        let venda_id = store.next_venda_id + 1;
+
+        // O carrinho é conferido antes de tudo e, com a trava das vendas,
+        // duas finalizações não passam juntas
+        if let Some(cart_id) = cart_id {
+            let cart = self.carts.find_by_id(cart_id)?;
+            if cart.is_none_or(|c| c.status != CartStatus::Payment) {
+                return Err(AppError::Conflict(format!("Carrinho {} não está mais em pagamento", cart_id)));
+            }
+        }
+
+        // O estoque é baixado antes de gravar a venda, para não deixar uma
+        // venda sem movimentos se um produto não existir ou faltar saldo
+        self.record_movements(venda_id, movements, block_negative)?;
+        if let Some(cart_id) = cart_id {
+            self.carts.finalize(cart_id, venda_id, venda.created_at)?;
+        }
+
+        store.next_venda_id = venda_id;
+        store.vendas.insert(venda_id, VendaEntity { id: Some(venda_id), ..venda.clone() });
+        store.emitted_at.insert(venda_id, emitted_at);
+
+        for item in items {
+            store.next_item_id += 1;
+            let item = VendaItemEntity { id: Some(store.next_item_id), venda_id, ..item.clone() };
+            store.items.push(item);
+        }
+
+        for payment in payments {
+            store.next_payment_id += 1;
+            let payment = VendaPagamentoEntity { id: Some(store.next_payment_id), venda_id, ..payment.clone() };
+            store.payments.push(payment);
+        }
+
+        Ok(venda_id)
+    }
+
+    fn find_items_by_venda_id(&self, venda_id: i64) -> AppResult<Vec<VendaItemEntity>> {
+        Ok(lock(&self.store)?.items.iter().filter(|i| i.venda_id == venda_id).cloned().collect())
+    }
+
+    fn find_payments_by_venda_id(&self, venda_id: i64) -> AppResult<Vec<VendaPagamentoEntity>> {
+        Ok(lock(&self.store)?.payments.iter().filter(|p| p.venda_id == venda_id).cloned().collect())
+    }
+
+    fn find_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaEntity>> {
+        let store = lock(&self.store)?;
+        Ok(store.vendas_in_interval(start, end).into_iter().cloned().collect())
+    }
+
+    fn find_items_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaItemEntity>> {
+        let store = lock(&self.store)?;
+        let items = store.vendas_in_interval(start, end)
+            .into_iter()
+            .flat_map(|v| store.items.iter().filter(move |i| Some(i.venda_id) == v.id))
+            .cloned()
+            .collect();
+        Ok(items)
+    }
+
+    fn find_payments_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaPagamentoEntity>> {
+        let store = lock(&self.store)?;
+        let payments = store.vendas_in_interval(start, end)
+            .into_iter()
+            .flat_map(|v| store.payments.iter().filter(move |p| Some(p.venda_id) == v.id))
+            .cloned()
+            .collect();
+        Ok(payments)
+    }
+
+    fn resumo_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<VendaResumo> {
+        let store = lock(&self.store)?;
+        let vendas = store.vendas_in_interval(start, end);
+
+        Ok(VendaResumo {
+            total_vendas: vendas.len() as i64,
+            total_valor: vendas.iter().map(|v| v.total).sum(),
+            total_desconto: vendas.iter().map(|v| v.discount).sum(),
+            total_acrescimo: vendas.iter().map(|v| v.addition).sum(),
+            total_canceladas: vendas.iter().filter(|v| v.cancelled == 1).count() as i64,
+        })
+    }
+
+    fn cancel(
+        &self,
+        venda_id: i64,
+        chave_canc: &str,
+        dh_emi_canc: &str,
+        cancel_file_path: Option<&str>,
+        at: DateTime<Utc>,
+        movements: &[StockMovementEntity],
+    ) -> AppResult<bool> {
+        let mut store = lock(&self.store)?;
+        let venda = store.vendas.get_mut(&venda_id)
+            .ok_or_else(|| AppError::not_found(format!("Venda with id {} not found", venda_id)))?;
+        if venda.cancelled != 0 {
+            return Ok(false);
+        }
+
+        self.record_movements(venda_id, movements, false)?;
+        venda.cancelled = 1;
+        venda.chave_canc = Some(chave_canc.to_string());
+        venda.dh_emi_canc = Some(dh_emi_canc.to_string());
+        venda.cancel_file_path = cancel_file_path.map(|s| s.to_string());
+        venda.updated_at = at;
+        Ok(true)
+    }
+}
@@ -0,0 +1,29 @@
+pub mod product_repository;
+pub mod venda_repository;
+pub mod config_repository;
+pub mod resume_repository;
+pub mod history_repository;
+pub mod stock_movement_repository;
+pub mod category_repository;
+pub mod promotion_repository;
+pub mod composition_repository;
+pub mod inventory_repository;
+pub mod unit_repository;
+pub mod lot_repository;
+pub mod cart_repository;
+pub mod sqlite;
+pub mod memory;
+
+pub use product_repository::{search_terms, ProductRepository, ProductSearch};
+pub use venda_repository::VendaRepository;
+pub use config_repository::ConfigRepository;
+pub use resume_repository::ResumeRepository;
+pub use history_repository::{HistoryRepository, HistoryFilter};
+pub use stock_movement_repository::{StockMovementRepository, StockMovementFilter};
+pub use category_repository::CategoryRepository;
+pub use promotion_repository::PromotionRepository;
+pub use composition_repository::CompositionRepository;
+pub use inventory_repository::InventoryRepository;
+pub use unit_repository::UnitRepository;
+pub use lot_repository::LotRepository;
+pub use cart_repository::CartRepository;
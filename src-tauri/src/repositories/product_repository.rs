@@ -0,0 +1,77 @@
+use crate::error::AppResult;
+use crate::entities::{ProductEntity, Quantity};
+use chrono::{DateTime, Utc};
+
+/// Busca textual de produtos por código e nome
+#[derive(Debug, Clone, Default)]
+pub struct ProductSearch {
+    /// Texto como digitado, comparado com o código exato
+    pub query: String,
+    /// Termos já normalizados (ver `search_terms`); todos precisam casar com o
+    /// início de alguma palavra do código ou do nome
+    pub terms: Vec<String>,
+    /// `Some(true)` só ativos, `Some(false)` só inativos
+    pub active: Option<bool>,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// Quebra o texto de busca em termos sem acentos e em minúsculas, separando
+/// por tudo que não é letra ou dígito (como o tokenizer `unicode61` do FTS5)
+pub fn search_terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.chars().flat_map(char::to_lowercase).map(fold_accent).collect())
+        .collect()
+}
+
+/// Remove o acento das letras latinas usadas em português e espanhol
+fn fold_accent(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        'ñ' => 'n',
+        _ => c,
+    }
+}
+
+/// Armazenamento de produtos (tabela `produtos`)
+pub trait ProductRepository: Send + Sync {
+    /// Busca um produto por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<ProductEntity>>;
+
+    /// Busca um produto por código
+    fn find_by_code(&self, code: &str) -> AppResult<Option<ProductEntity>>;
+
+    /// Busca o primeiro produto com o GTIN informado
+    fn find_by_gtin(&self, gtin: &str) -> AppResult<Option<ProductEntity>>;
+
+    /// Lista todos os produtos ordenados por nome
+    fn find_all(&self) -> AppResult<Vec<ProductEntity>>;
+
+    /// Lista os produtos ativos ordenados por nome
+    fn find_all_active(&self) -> AppResult<Vec<ProductEntity>>;
+
+    /// Lista os produtos das categorias informadas, ordenados por nome
+    fn find_by_categories(&self, category_ids: &[i64]) -> AppResult<Vec<ProductEntity>>;
+
+    /// Busca produtos pelos termos e retorna a página pedida junto com o total
+    /// encontrado. Ordem: código idêntico à busca, depois código ou nome que
+    /// começam pelo primeiro termo, depois relevância e nome.
+    fn search(&self, search: &ProductSearch) -> AppResult<(Vec<ProductEntity>, i64)>;
+
+    /// Insere (sem `id`) ou atualiza (com `id`) um produto, gravando os
+    /// instantes da entidade como estão. O saldo só é gravado na inserção;
+    /// depois disso muda apenas pelo livro de movimentos (`StockMovementRepository`).
+    fn save(&self, product: &ProductEntity) -> AppResult<ProductEntity>;
+
+    /// Grava o saldo recalculado a partir do livro de movimentos (reconciliação)
+    fn set_balance(&self, id: i64, balance: Quantity, at: DateTime<Utc>) -> AppResult<()>;
+
+    /// Marca o produto como inativo (soft delete)
+    fn deactivate(&self, id: i64, at: DateTime<Utc>) -> AppResult<()>;
+}
@@ -0,0 +1,21 @@
+use crate::error::AppResult;
+use crate::entities::PromotionEntity;
+use chrono::{DateTime, Utc};
+
+/// Armazenamento de promoções (tabela `promocoes`)
+pub trait PromotionRepository: Send + Sync {
+    /// Busca uma promoção por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<PromotionEntity>>;
+
+    /// Lista todas as promoções, das mais recentes para as mais antigas
+    fn find_all(&self) -> AppResult<Vec<PromotionEntity>>;
+
+    /// Promoções ativas cujo período contém `at`, por ID
+    fn find_valid_at(&self, at: DateTime<Utc>) -> AppResult<Vec<PromotionEntity>>;
+
+    /// Insere (sem `id`) ou atualiza (com `id`) uma promoção
+    fn save(&self, promotion: &PromotionEntity) -> AppResult<PromotionEntity>;
+
+    /// Remove a promoção
+    fn delete(&self, id: i64) -> AppResult<()>;
+}
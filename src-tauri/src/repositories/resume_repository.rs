@@ -0,0 +1,29 @@
+use crate::error::AppResult;
+use crate::entities::{Money, PaymentTypes, ResumeEntity};
+use chrono::{DateTime, Utc};
+
+/// Armazenamento dos resumos por forma de pagamento (tabela `resumes`).
+///
+/// Os instantes são gravados em milissegundos UTC.
+pub trait ResumeRepository: Send + Sync {
+    /// Lista os resumos criados a partir de `since`, ordenados por código
+    fn find_since(&self, since: DateTime<Utc>) -> AppResult<Vec<ResumeEntity>>;
+
+    /// Busca um resumo por ID
+    fn find_by_id(&self, id: &str) -> AppResult<Option<ResumeEntity>>;
+
+    /// Busca o resumo de um código criado a partir de `since`
+    fn find_by_code_since(&self, code: &PaymentTypes, since: DateTime<Utc>) -> AppResult<Option<ResumeEntity>>;
+
+    /// Insere ou substitui um resumo
+    fn save(&self, resume: &ResumeEntity) -> AppResult<()>;
+
+    /// Define os valores de um resumo
+    fn update_amounts(&self, id: &str, amount_s: Money, amount_n: Money, updated_at: DateTime<Utc>) -> AppResult<()>;
+
+    /// Soma aos valores de um resumo de forma atômica
+    fn increment_amounts(&self, id: &str, amount_s_inc: Money, amount_n_inc: Money, updated_at: DateTime<Utc>) -> AppResult<()>;
+
+    /// Remove os resumos criados antes de `before` e retorna quantos foram removidos
+    fn delete_before(&self, before: DateTime<Utc>) -> AppResult<usize>;
+}
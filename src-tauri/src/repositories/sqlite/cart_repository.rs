@@ -0,0 +1,284 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{CartEntity, CartItemEntity, CartPaymentEntity, CartStatus};
+use crate::repositories::CartRepository;
+use rusqlite::{params, Row, Transaction};
+use chrono::{DateTime, Utc};
+
+const CART_COLUMNS: &str = "id, status, user_name, doc_destinatario, discount, addition, venda_id, opened_at, updated_at, closed_at";
+const ITEM_COLUMNS: &str = "id, cart_id, produto_code, produto_description, produto_medida, quantidade, preco_unitario, desconto,
+             created_at, updated_at";
+const PAYMENT_COLUMNS: &str = "id, cart_id, code, name, total_pagamento, created_at";
+
+pub struct SqliteCartRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteCartRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_cart(row: &Row) -> rusqlite::Result<CartEntity> {
+        let status: String = row.get(1)?;
+        let closed_at: Option<i64> = row.get(9)?;
+
+        Ok(CartEntity {
+            id: row.get(0)?,
+            status: CartStatus::from_str(&status).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    1,
+                    rusqlite::types::Type::Text,
+                    format!("Invalid cart status '{}'", status).into(),
+                )
+            })?,
+            user: row.get(2)?,
+            doc_destinatario: row.get(3)?,
+            discount: row.get(4)?,
+            addition: row.get(5)?,
+            venda_id: row.get(6)?,
+            opened_at: from_millis(row.get(7)?),
+            updated_at: from_millis(row.get(8)?),
+            closed_at: closed_at.map(from_millis),
+        })
+    }
+
+    fn map_item(row: &Row) -> rusqlite::Result<CartItemEntity> {
+        Ok(CartItemEntity {
+            id: row.get(0)?,
+            cart_id: row.get(1)?,
+            produto_code: row.get(2)?,
+            produto_description: row.get(3)?,
+            produto_medida: row.get(4)?,
+            quantidade: row.get(5)?,
+            preco_unitario: row.get(6)?,
+            desconto: row.get(7)?,
+            created_at: from_millis(row.get(8)?),
+            updated_at: from_millis(row.get(9)?),
+        })
+    }
+
+    fn map_payment(row: &Row) -> rusqlite::Result<CartPaymentEntity> {
+        Ok(CartPaymentEntity {
+            id: row.get(0)?,
+            cart_id: row.get(1)?,
+            code: row.get(2)?,
+            name: row.get(3)?,
+            total_pagamento: row.get(4)?,
+            created_at: from_millis(row.get(5)?),
+        })
+    }
+
+    /// Encerra o carrinho dentro de uma transação, só se ele ainda está em pagamento
+    pub(crate) fn finalize_in_transaction(tx: &Transaction, id: i64, venda_id: i64, at: DateTime<Utc>) -> AppResult<bool> {
+        let updated = tx.execute(
+            "UPDATE carrinhos SET status = ?1, venda_id = ?2, updated_at = ?3, closed_at = ?3
+             WHERE id = ?4 AND status = ?5",
+            params![
+                CartStatus::Finalized.as_str(),
+                venda_id,
+                at.timestamp_millis(),
+                id,
+                CartStatus::Payment.as_str()
+            ],
+        ).context("Failed to finalize cart")?;
+
+        Ok(updated == 1)
+    }
+
+    fn find_cart(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> AppResult<Option<CartEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM carrinhos {} ORDER BY id DESC LIMIT 1", CART_COLUMNS, filter)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row(params, Self::map_cart) {
+            Ok(cart) => Ok(Some(cart)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query cart")),
+        }
+    }
+}
+
+impl CartRepository for SqliteCartRepository {
+    fn create(&self, cart: &CartEntity) -> AppResult<CartEntity> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT INTO carrinhos (status, user_name, doc_destinatario, discount, addition, venda_id, opened_at, updated_at, closed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                cart.status.as_str(),
+                cart.user,
+                cart.doc_destinatario,
+                cart.discount,
+                cart.addition,
+                cart.venda_id,
+                cart.opened_at.timestamp_millis(),
+                cart.updated_at.timestamp_millis(),
+                cart.closed_at.map(|d| d.timestamp_millis())
+            ],
+        ).context("Failed to insert cart")?;
+
+        Ok(CartEntity { id: Some(conn.last_insert_rowid()), ..cart.clone() })
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<CartEntity>> {
+        self.find_cart("WHERE id = ?1", &[&id])
+    }
+
+    fn find_active(&self) -> AppResult<Option<CartEntity>> {
+        self.find_cart("WHERE status IN ('open', 'payment')", &[])
+    }
+
+    fn save(&self, cart: &CartEntity) -> AppResult<()> {
+        let id = cart.id.unwrap_or_default();
+        let conn = self.db.get_connection()?;
+
+        let updated = conn.execute(
+            "UPDATE carrinhos SET status = ?1, user_name = ?2, doc_destinatario = ?3, discount = ?4, addition = ?5,
+             venda_id = ?6, updated_at = ?7, closed_at = ?8
+             WHERE id = ?9",
+            params![
+                cart.status.as_str(),
+                cart.user,
+                cart.doc_destinatario,
+                cart.discount,
+                cart.addition,
+                cart.venda_id,
+                cart.updated_at.timestamp_millis(),
+                cart.closed_at.map(|d| d.timestamp_millis()),
+                id
+            ],
+        ).context("Failed to update cart")?;
+
+        if updated == 0 {
+            return Err(AppError::not_found(format!("Cart with id {} not found", id)));
+        }
+        Ok(())
+    }
+
+    fn finalize(&self, id: i64, venda_id: i64, at: DateTime<Utc>) -> AppResult<bool> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+        let finalized = Self::finalize_in_transaction(&tx, id, venda_id, at)?;
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(finalized)
+    }
+
+    fn find_items(&self, cart_id: i64) -> AppResult<Vec<CartItemEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM carrinho_itens WHERE cart_id = ?1 ORDER BY id", ITEM_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let items = stmt.query_map([cart_id], Self::map_item)
+            .context("Failed to query cart items")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect cart items")?;
+
+        Ok(items)
+    }
+
+    fn save_item(&self, item: &CartItemEntity) -> AppResult<CartItemEntity> {
+        let conn = self.db.get_connection()?;
+
+        if let Some(id) = item.id {
+            let updated = conn.execute(
+                "UPDATE carrinho_itens SET produto_code = ?1, produto_description = ?2, produto_medida = ?3, quantidade = ?4,
+                 preco_unitario = ?5, desconto = ?6, updated_at = ?7
+                 WHERE id = ?8 AND cart_id = ?9",
+                params![
+                    item.produto_code,
+                    item.produto_description,
+                    item.produto_medida,
+                    item.quantidade,
+                    item.preco_unitario,
+                    item.desconto,
+                    item.updated_at.timestamp_millis(),
+                    id,
+                    item.cart_id
+                ],
+            ).context("Failed to update cart item")?;
+
+            if updated == 0 {
+                return Err(AppError::not_found(format!("Cart item with id {} not found", id)));
+            }
+            Ok(item.clone())
+        } else {
+            conn.execute(
+                "INSERT INTO carrinho_itens (cart_id, produto_code, produto_description, produto_medida, quantidade,
+                 preco_unitario, desconto, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    item.cart_id,
+                    item.produto_code,
+                    item.produto_description,
+                    item.produto_medida,
+                    item.quantidade,
+                    item.preco_unitario,
+                    item.desconto,
+                    item.created_at.timestamp_millis(),
+                    item.updated_at.timestamp_millis()
+                ],
+            ).context("Failed to insert cart item")?;
+
+            Ok(CartItemEntity { id: Some(conn.last_insert_rowid()), ..item.clone() })
+        }
+    }
+
+    fn delete_item(&self, cart_id: i64, item_id: i64) -> AppResult<bool> {
+        let conn = self.db.get_connection()?;
+
+        let deleted = conn.execute(
+            "DELETE FROM carrinho_itens WHERE id = ?1 AND cart_id = ?2",
+            params![item_id, cart_id],
+        ).context("Failed to delete cart item")?;
+
+        Ok(deleted > 0)
+    }
+
+    fn find_payments(&self, cart_id: i64) -> AppResult<Vec<CartPaymentEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM carrinho_pagamentos WHERE cart_id = ?1 ORDER BY id", PAYMENT_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let payments = stmt.query_map([cart_id], Self::map_payment)
+            .context("Failed to query cart payments")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect cart payments")?;
+
+        Ok(payments)
+    }
+
+    fn add_payment(&self, payment: &CartPaymentEntity) -> AppResult<CartPaymentEntity> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT INTO carrinho_pagamentos (cart_id, code, name, total_pagamento, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![payment.cart_id, payment.code, payment.name, payment.total_pagamento, payment.created_at.timestamp_millis()],
+        ).context("Failed to insert cart payment")?;
+
+        Ok(CartPaymentEntity { id: Some(conn.last_insert_rowid()), ..payment.clone() })
+    }
+
+    fn delete_payment(&self, cart_id: i64, payment_id: i64) -> AppResult<bool> {
+        let conn = self.db.get_connection()?;
+
+        let deleted = conn.execute(
+            "DELETE FROM carrinho_pagamentos WHERE id = ?1 AND cart_id = ?2",
+            params![payment_id, cart_id],
+        ).context("Failed to delete cart payment")?;
+
+        Ok(deleted > 0)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::CategoryEntity;
+use crate::repositories::CategoryRepository;
+use rusqlite::{params, Row};
+
+const CATEGORY_COLUMNS: &str = "id, name, parent_id, created_at, updated_at";
+
+pub struct SqliteCategoryRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteCategoryRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<CategoryEntity> {
+        Ok(CategoryEntity {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            parent_id: row.get(2)?,
+            created_at: from_millis(row.get(3)?),
+            updated_at: from_millis(row.get(4)?),
+        })
+    }
+}
+
+impl CategoryRepository for SqliteCategoryRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<CategoryEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM categorias WHERE id = ?1", CATEGORY_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row([id], Self::map_row) {
+            Ok(c) => Ok(Some(c)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query category")),
+        }
+    }
+
+    fn find_all(&self) -> AppResult<Vec<CategoryEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM categorias ORDER BY name", CATEGORY_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let categories = stmt.query_map([], Self::map_row)
+            .context("Failed to query categories")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect categories")?;
+
+        Ok(categories)
+    }
+
+    fn save(&self, category: &CategoryEntity) -> AppResult<CategoryEntity> {
+        let conn = self.db.get_connection()?;
+
+        if let Some(id) = category.id {
+            conn.execute(
+                "UPDATE categorias SET name = ?1, parent_id = ?2, updated_at = ?3 WHERE id = ?4",
+                params![category.name, category.parent_id, category.updated_at.timestamp_millis(), id],
+            ).context("Failed to update category")?;
+
+            self.find_by_id(id)?
+                .ok_or_else(|| AppError::not_found(format!("Category with id {} not found", id)))
+        } else {
+            conn.execute(
+                "INSERT INTO categorias (name, parent_id, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    category.name,
+                    category.parent_id,
+                    category.created_at.timestamp_millis(),
+                    category.updated_at.timestamp_millis()
+                ],
+            ).context("Failed to insert category")?;
+
+            let id = conn.last_insert_rowid();
+            Ok(CategoryEntity { id: Some(id), ..category.clone() })
+        }
+    }
+
+    fn delete(&self, id: i64) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute("DELETE FROM categorias WHERE id = ?1", [id])
+            .context("Failed to delete category")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::error::{AppResult, ResultExt};
+use crate::database::SqliteDbService;
+use crate::entities::CompositionItemEntity;
+use crate::repositories::CompositionRepository;
+use rusqlite::{params, Row};
+
+const COMPOSITION_COLUMNS: &str = "kit_id, component_id, quantity";
+
+pub struct SqliteCompositionRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteCompositionRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<CompositionItemEntity> {
+        Ok(CompositionItemEntity {
+            kit_id: row.get(0)?,
+            component_id: row.get(1)?,
+            quantity: row.get(2)?,
+        })
+    }
+
+    fn find_where(&self, column: &str, id: i64) -> AppResult<Vec<CompositionItemEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM produto_composicao WHERE {} = ?1 ORDER BY rowid", COMPOSITION_COLUMNS, column)
+        ).context("Failed to prepare statement")?;
+
+        let items = stmt.query_map([id], Self::map_row)
+            .context("Failed to query product composition")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect product composition")?;
+
+        Ok(items)
+    }
+}
+
+impl CompositionRepository for SqliteCompositionRepository {
+    fn find_by_kit(&self, kit_id: i64) -> AppResult<Vec<CompositionItemEntity>> {
+        self.find_where("kit_id", kit_id)
+    }
+
+    fn find_by_component(&self, component_id: i64) -> AppResult<Vec<CompositionItemEntity>> {
+        self.find_where("component_id", component_id)
+    }
+
+    fn kit_ids(&self) -> AppResult<Vec<i64>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached("SELECT DISTINCT kit_id FROM produto_composicao ORDER BY kit_id")
+            .context("Failed to prepare statement")?;
+
+        let ids = stmt.query_map([], |row| row.get(0))
+            .context("Failed to query kits")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect kits")?;
+
+        Ok(ids)
+    }
+
+    fn replace(&self, kit_id: i64, components: &[CompositionItemEntity]) -> AppResult<()> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+        tx.execute("DELETE FROM produto_composicao WHERE kit_id = ?1", [kit_id])
+            .context("Failed to clear product composition")?;
+        for component in components {
+            tx.execute(
+                "INSERT INTO produto_composicao (kit_id, component_id, quantity) VALUES (?1, ?2, ?3)",
+                params![kit_id, component.component_id, component.quantity],
+            ).context("Failed to insert product component")?;
+        }
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::barcode::{ScaleLayout, ScaleValueKind};
+use crate::entities::{ConfigEntity, NegativeStockPolicy};
+use crate::repositories::ConfigRepository;
+use rusqlite::{params, Row};
+
+const CONFIG_COLUMNS: &str = "id, flowBaseUrl, codeUf, nserieSAT, nserieSATNao, nrNfSim, nrNfNao, 
+                    signAC, regimeTributario, cnpj, name, shortName, zipcode, addressName, 
+                    addressNumber, addressCity, addressCityCode, tipoAmbiente, addressCpl, 
+                    addressNeiborhood, addressState, fone, createdAt, updatedAt, percentS, 
+                    onlyMoney, errorAsSuccess, ie, pagamentos, ignoreCpf, numeroCaixa, 
+                    emitirL, habilitarContador, habilitarContadorNao, controleEstoque, modelo,
+                    estoqueNegativo, balancaDigitosCodigo, balancaDigitosValor, balancaValor,
+                    toleranciaTotal, timezone";
+
+pub struct SqliteConfigRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteConfigRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<ConfigEntity> {
+        let estoque_negativo: String = row.get(36)?;
+        let balanca_valor: String = row.get(39)?;
+
+        Ok(ConfigEntity {
+            id: row.get(0)?,
+            flow_base_url: row.get(1)?,
+            code_uf: row.get(2)?,
+            nserie_sat: row.get(3)?,
+            nserie_sat_nao: row.get(4)?,
+            nr_nf_sim: row.get(5)?,
+            nr_nf_nao: row.get(6)?,
+            sign_ac: row.get(7)?,
+            regime_tributario: row.get(8)?,
+            cnpj: row.get(9)?,
+            name: row.get(10)?,
+            short_name: row.get(11)?,
+            zipcode: row.get(12)?,
+            address_name: row.get(13)?,
+            address_number: row.get(14)?,
+            address_city: row.get(15)?,
+            address_city_code: row.get(16)?,
+            tipo_ambiente: row.get(17)?,
+            address_cpl: row.get(18)?,
+            address_neiborhood: row.get(19)?,
+            address_state: row.get(20)?,
+            fone: row.get(21)?,
+            created_at: from_millis(row.get(22)?),
+            updated_at: from_millis(row.get(23)?),
+            percent_s: row.get(24)?,
+            only_money: row.get(25)?,
+            error_as_success: row.get(26)?,
+            ie: row.get(27)?,
+            pagamentos: row.get(28)?,
+            ignore_cpf: row.get(29)?,
+            numero_caixa: row.get(30)?,
+            emitir_l: row.get(31)?,
+            habilitar_contador: row.get(32)?,
+            habilitar_contador_nao: row.get(33)?,
+            controle_estoque: row.get(34)?,
+            modelo: row.get(35)?,
+            estoque_negativo: NegativeStockPolicy::from_str(&estoque_negativo).unwrap_or_default(),
+            balanca: ScaleLayout {
+                code_digits: row.get(37)?,
+                value_digits: row.get(38)?,
+                kind: ScaleValueKind::from_str(&balanca_valor).unwrap_or_default(),
+            },
+            tolerancia_total: row.get(40)?,
+            timezone: row.get(41)?,
+        })
+    }
+}
+
+impl ConfigRepository for SqliteConfigRepository {
+    fn find_by_id(&self, id: &str) -> AppResult<Option<ConfigEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM config WHERE id = ?1", CONFIG_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row(params![id], Self::map_row) {
+            Ok(c) => Ok(Some(c)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query config")),
+        }
+    }
+
+    fn save(&self, config: &ConfigEntity) -> AppResult<ConfigEntity> {
+        let conn = self.db.get_connection()?;
+
+        // Verifica se já existe
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) FROM config WHERE id = ?1",
+            params![config.id],
+            |row| {
+                let count: i32 = row.get(0)?;
+                Ok(count > 0)
+            }
+        ).context("Failed to check config existence")?;
+
+        if exists {
+            // Update
+            conn.execute(
+                "UPDATE config SET flowBaseUrl = ?1, codeUf = ?2, nserieSAT = ?3, nserieSATNao = ?4, 
+                        nrNfSim = ?5, nrNfNao = ?6, signAC = ?7, regimeTributario = ?8, cnpj = ?9, 
+                        name = ?10, shortName = ?11, zipcode = ?12, addressName = ?13, addressNumber = ?14, 
+                        addressCity = ?15, addressCityCode = ?16, tipoAmbiente = ?17, addressCpl = ?18, 
+                        addressNeiborhood = ?19, addressState = ?20, fone = ?21, updatedAt = ?22, 
+                        percentS = ?23, onlyMoney = ?24, errorAsSuccess = ?25, ie = ?26, pagamentos = ?27, 
+                        ignoreCpf = ?28, numeroCaixa = ?29, emitirL = ?30, habilitarContador = ?31, 
+                        habilitarContadorNao = ?32, controleEstoque = ?33, modelo = ?34, estoqueNegativo = ?35,
+                        balancaDigitosCodigo = ?36, balancaDigitosValor = ?37, balancaValor = ?38,
+                        toleranciaTotal = ?39, timezone = ?40
+                 WHERE id = ?41",
+                params![
+                    config.flow_base_url, config.code_uf, config.nserie_sat, config.nserie_sat_nao,
+                    config.nr_nf_sim, config.nr_nf_nao, config.sign_ac, config.regime_tributario,
+                    config.cnpj, config.name, config.short_name, config.zipcode, config.address_name,
+                    config.address_number, config.address_city, config.address_city_code, config.tipo_ambiente,
+                    config.address_cpl, config.address_neiborhood, config.address_state, config.fone,
+                    config.updated_at.timestamp_millis(), config.percent_s, config.only_money, config.error_as_success,
+                    config.ie, config.pagamentos, config.ignore_cpf, config.numero_caixa, config.emitir_l,
+                    config.habilitar_contador, config.habilitar_contador_nao, config.controle_estoque,
+                    config.modelo, config.estoque_negativo.as_str(), config.balanca.code_digits,
+                    config.balanca.value_digits, config.balanca.kind.as_str(), config.tolerancia_total, config.timezone, config.id
+                ],
+            ).context("Failed to update config")?;
+        } else {
+            // Insert
+            conn.execute(
+                "INSERT INTO config (id, flowBaseUrl, codeUf, nserieSAT, nserieSATNao, nrNfSim, nrNfNao, 
+                        signAC, regimeTributario, cnpj, name, shortName, zipcode, addressName, addressNumber, 
+                        addressCity, addressCityCode, tipoAmbiente, addressCpl, addressNeiborhood, addressState, 
+                        fone, createdAt, updatedAt, percentS, onlyMoney, errorAsSuccess, ie, pagamentos, 
+                        ignoreCpf, numeroCaixa, emitirL, habilitarContador, habilitarContadorNao, 
+                        controleEstoque, modelo, estoqueNegativo, balancaDigitosCodigo, balancaDigitosValor,
+                        balancaValor, toleranciaTotal, timezone)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, 
+                         ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37,
+                         ?38, ?39, ?40, ?41, ?42)",
+                params![
+                    config.id, config.flow_base_url, config.code_uf, config.nserie_sat, config.nserie_sat_nao,
+                    config.nr_nf_sim, config.nr_nf_nao, config.sign_ac, config.regime_tributario,
+                    config.cnpj, config.name, config.short_name, config.zipcode, config.address_name,
+                    config.address_number, config.address_city, config.address_city_code, config.tipo_ambiente,
+                    config.address_cpl, config.address_neiborhood, config.address_state, config.fone,
+                    config.created_at.timestamp_millis(), config.updated_at.timestamp_millis(), config.percent_s, config.only_money,
+                    config.error_as_success, config.ie, config.pagamentos, config.ignore_cpf,
+                    config.numero_caixa, config.emitir_l, config.habilitar_contador,
+                    config.habilitar_contador_nao, config.controle_estoque, config.modelo,
+                    config.estoque_negativo.as_str(), config.balanca.code_digits, config.balanca.value_digits,
+                    config.balanca.kind.as_str(), config.tolerancia_total, config.timezone
+                ],
+            ).context("Failed to insert config")?;
+        }
+
+        Ok(config.clone())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<ConfigEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM config", CONFIG_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let configs = stmt.query_map([], Self::map_row)
+            .context("Failed to query configs")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect configs")?;
+
+        Ok(configs)
+    }
+
+    fn delete_by_id(&self, id: &str) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute("DELETE FROM config WHERE id = ?1", params![id])
+            .context("Failed to delete config")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use crate::error::{AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{HistoryEntity, Origin};
+use crate::repositories::{HistoryFilter, HistoryRepository};
+use rusqlite::{params, Row};
+
+const HISTORY_COLUMNS: &str = "id, action, entity_type, entity_id, details, origin, created_at";
+
+pub struct SqliteHistoryRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteHistoryRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<HistoryEntity> {
+        let details: Option<String> = row.get(4)?;
+        let origin: String = row.get(5)?;
+
+        Ok(HistoryEntity {
+            id: row.get(0)?,
+            action: row.get(1)?,
+            entity_type: row.get(2)?,
+            entity_id: row.get(3)?,
+            // Textos que não sejam JSON são devolvidos como string
+            details: details.map(|d| serde_json::from_str(&d).unwrap_or(serde_json::Value::String(d))),
+            origin: Origin::from_str(&origin).unwrap_or_default(),
+            created_at: from_millis(row.get(6)?),
+        })
+    }
+}
+
+impl HistoryRepository for SqliteHistoryRepository {
+    fn insert(&self, entry: &HistoryEntity) -> AppResult<i64> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT INTO history (action, entity_type, entity_id, details, origin, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                entry.action,
+                entry.entity_type,
+                entry.entity_id,
+                entry.details.as_ref().map(|d| d.to_string()),
+                entry.origin.as_str(),
+                entry.created_at.timestamp_millis()
+            ],
+        ).context("Failed to insert history")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn find(&self, filter: &HistoryFilter) -> AppResult<Vec<HistoryEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {} FROM history
+                 WHERE (?1 IS NULL OR entity_type = ?1)
+                   AND (?2 IS NULL OR entity_id = ?2)
+                   AND (?3 IS NULL OR created_at >= ?3)
+                   AND (?4 IS NULL OR created_at < ?4)
+                 ORDER BY created_at DESC, id DESC
+                 LIMIT ?5",
+                HISTORY_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let entries = stmt.query_map(
+            params![
+                filter.entity_type,
+                filter.entity_id,
+                filter.start.map(|s| s.timestamp_millis()),
+                filter.end.map(|e| e.timestamp_millis()),
+                filter.limit
+            ],
+            Self::map_row,
+        )
+            .context("Failed to query history")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect history")?;
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,196 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{InventoryCountEntity, InventorySessionEntity, InventoryStatus, StockMovementEntity};
+use crate::repositories::InventoryRepository;
+use super::SqliteStockMovementRepository;
+use rusqlite::{params, Row};
+
+const SESSION_COLUMNS: &str = "id, name, status, user_name, opened_at, closed_at";
+const COUNT_COLUMNS: &str = "session_id, product_id, expected, counted, scans, updated_at";
+
+pub struct SqliteInventoryRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteInventoryRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_session(row: &Row) -> rusqlite::Result<InventorySessionEntity> {
+        let status: String = row.get(2)?;
+        let closed_at: Option<i64> = row.get(5)?;
+
+        Ok(InventorySessionEntity {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            status: InventoryStatus::from_str(&status).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    format!("Invalid inventory status '{}'", status).into(),
+                )
+            })?,
+            user: row.get(3)?,
+            opened_at: from_millis(row.get(4)?),
+            closed_at: closed_at.map(from_millis),
+        })
+    }
+
+    fn map_count(row: &Row) -> rusqlite::Result<InventoryCountEntity> {
+        Ok(InventoryCountEntity {
+            session_id: row.get(0)?,
+            product_id: row.get(1)?,
+            expected: row.get(2)?,
+            counted: row.get(3)?,
+            scans: row.get(4)?,
+            updated_at: from_millis(row.get(5)?),
+        })
+    }
+}
+
+impl InventoryRepository for SqliteInventoryRepository {
+    fn open(&self, session: &InventorySessionEntity, counts: &[InventoryCountEntity]) -> AppResult<InventorySessionEntity> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+
+        tx.execute(
+            "INSERT INTO inventarios (name, status, user_name, opened_at, closed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session.name,
+                session.status.as_str(),
+                session.user,
+                session.opened_at.timestamp_millis(),
+                session.closed_at.map(|d| d.timestamp_millis())
+            ],
+        ).context("Failed to insert inventory session")?;
+        let id = tx.last_insert_rowid();
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO inventario_itens (session_id, product_id, expected, counted, scans, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+            ).context("Failed to prepare statement")?;
+            for count in counts {
+                stmt.execute(params![id, count.product_id, count.expected, count.counted, count.scans, count.updated_at.timestamp_millis()])
+                    .context("Failed to insert inventory count")?;
+            }
+        }
+
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(InventorySessionEntity { id: Some(id), ..session.clone() })
+    }
+
+    fn find_by_id(&self, id: i64) -> AppResult<Option<InventorySessionEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM inventarios WHERE id = ?1", SESSION_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row([id], Self::map_session) {
+            Ok(s) => Ok(Some(s)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query inventory session")),
+        }
+    }
+
+    fn find_all(&self, status: Option<InventoryStatus>) -> AppResult<Vec<InventorySessionEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM inventarios WHERE (?1 IS NULL OR status = ?1) ORDER BY id DESC", SESSION_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let sessions = stmt.query_map([status.map(|s| s.as_str())], Self::map_session)
+            .context("Failed to query inventory sessions")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect inventory sessions")?;
+
+        Ok(sessions)
+    }
+
+    fn find_counts(&self, session_id: i64) -> AppResult<Vec<InventoryCountEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM inventario_itens WHERE session_id = ?1 ORDER BY product_id", COUNT_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let counts = stmt.query_map([session_id], Self::map_count)
+            .context("Failed to query inventory counts")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect inventory counts")?;
+
+        Ok(counts)
+    }
+
+    fn find_count(&self, session_id: i64, product_id: i64) -> AppResult<Option<InventoryCountEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM inventario_itens WHERE session_id = ?1 AND product_id = ?2", COUNT_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row([session_id, product_id], Self::map_count) {
+            Ok(c) => Ok(Some(c)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query inventory count")),
+        }
+    }
+
+    fn save_count(&self, count: &InventoryCountEntity) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT INTO inventario_itens (session_id, product_id, expected, counted, scans, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (session_id, product_id) DO UPDATE SET
+                expected = excluded.expected, counted = excluded.counted,
+                scans = excluded.scans, updated_at = excluded.updated_at",
+            params![
+                count.session_id,
+                count.product_id,
+                count.expected,
+                count.counted,
+                count.scans,
+                count.updated_at.timestamp_millis()
+            ],
+        ).context("Failed to save inventory count")?;
+
+        Ok(())
+    }
+
+    fn finish(&self, session: &InventorySessionEntity, movements: &[StockMovementEntity]) -> AppResult<()> {
+        let id = session.id.unwrap_or_default();
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+
+        let updated = tx.execute(
+            "UPDATE inventarios SET name = ?1, status = ?2, user_name = ?3, closed_at = ?4 WHERE id = ?5",
+            params![session.name, session.status.as_str(), session.user, session.closed_at.map(|d| d.timestamp_millis()), id],
+        ).context("Failed to update inventory session")?;
+
+        if updated == 0 {
+            return Err(AppError::not_found(format!("Inventory session with id {} not found", id)));
+        }
+
+        let reference = StockMovementEntity::inventory_reference(id);
+        for movement in movements {
+            let movement = StockMovementEntity { reference: Some(reference.clone()), ..movement.clone() };
+            SqliteStockMovementRepository::record_in_transaction(&tx, &movement, false)?;
+        }
+
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::lot::split_exit;
+use crate::entities::{LotEntity, StockMovementEntity};
+use crate::repositories::LotRepository;
+use chrono::NaiveDate;
+use rusqlite::{params, OptionalExtension, Row, Transaction};
+
+const LOT_COLUMNS: &str = "id, product_id, code, expires_at, quantity, created_at, updated_at";
+
+/// Ordem de saída: validade mais próxima primeiro, sem validade por último
+const FEFO_ORDER: &str = "ORDER BY expires_at IS NULL, expires_at, id";
+
+pub struct SqliteLotRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteLotRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<LotEntity> {
+        let expires_at: Option<String> = row.get(3)?;
+
+        Ok(LotEntity {
+            id: row.get(0)?,
+            product_id: row.get(1)?,
+            code: row.get(2)?,
+            expires_at: expires_at
+                .map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+                }))
+                .transpose()?,
+            quantity: row.get(4)?,
+            created_at: from_millis(row.get(5)?),
+            updated_at: from_millis(row.get(6)?),
+        })
+    }
+
+    /// Divide entre os lotes (FEFO) uma saída sem lote de um produto com
+    /// controle de lotes, com as quantidades lidas na transação já aberta (usado
+    /// pela gravação de vendas). Os demais movimentos voltam como vieram.
+    pub(crate) fn allocate_in_transaction(tx: &Transaction, movement: &StockMovementEntity) -> AppResult<Vec<StockMovementEntity>> {
+        if movement.lot_id.is_some() || !movement.quantity.is_negative() {
+            return Ok(vec![movement.clone()]);
+        }
+        let product: Option<(String, i32)> = tx.query_row(
+            "SELECT code, track_lots FROM produtos WHERE id = ?1",
+            params![movement.product_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional().context("Failed to query product")?;
+        let Some((code, 1)) = product else {
+            return Ok(vec![movement.clone()]);
+        };
+
+        let mut stmt = tx.prepare_cached(
+            &format!("SELECT {} FROM lotes WHERE product_id = ?1 {}", LOT_COLUMNS, FEFO_ORDER)
+        ).context("Failed to prepare statement")?;
+        let lots = stmt.query_map(params![movement.product_id], Self::map_row)
+            .context("Failed to query lots")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect lots")?;
+
+        split_exit(&lots, &code, movement)
+    }
+
+    fn find_one(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> AppResult<Option<LotEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM lotes {}", LOT_COLUMNS, filter)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row(params, Self::map_row) {
+            Ok(lot) => Ok(Some(lot)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query lot")),
+        }
+    }
+
+    fn find_many(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> AppResult<Vec<LotEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM lotes {}", LOT_COLUMNS, filter)
+        ).context("Failed to prepare statement")?;
+
+        let lots = stmt.query_map(params, Self::map_row)
+            .context("Failed to query lots")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect lots")?;
+
+        Ok(lots)
+    }
+}
+
+impl LotRepository for SqliteLotRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<LotEntity>> {
+        self.find_one("WHERE id = ?1", &[&id])
+    }
+
+    fn find_by_code(&self, product_id: i64, code: &str) -> AppResult<Option<LotEntity>> {
+        self.find_one("WHERE product_id = ?1 AND code = ?2", &[&product_id, &code])
+    }
+
+    fn find_by_product(&self, product_id: i64) -> AppResult<Vec<LotEntity>> {
+        self.find_many(&format!("WHERE product_id = ?1 {}", FEFO_ORDER), &[&product_id])
+    }
+
+    fn find_expiring(&self, until: NaiveDate) -> AppResult<Vec<LotEntity>> {
+        self.find_many(
+            "WHERE quantity > 0 AND expires_at IS NOT NULL AND expires_at <= ?1 ORDER BY expires_at, id",
+            &[&until.to_string()],
+        )
+    }
+
+    fn create(&self, lot: &LotEntity) -> AppResult<LotEntity> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT INTO lotes (product_id, code, expires_at, quantity, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                lot.product_id,
+                lot.code,
+                lot.expires_at.map(|d| d.to_string()),
+                lot.quantity,
+                lot.created_at.timestamp_millis(),
+                lot.updated_at.timestamp_millis()
+            ],
+        ).context("Failed to insert lot")?;
+
+        Ok(LotEntity { id: Some(conn.last_insert_rowid()), ..lot.clone() })
+    }
+
+    fn update(&self, lot: &LotEntity) -> AppResult<LotEntity> {
+        let id = lot.id.ok_or_else(|| AppError::validation("id", "Lote sem ID"))?;
+        let conn = self.db.get_connection()?;
+
+        let updated = conn.execute(
+            "UPDATE lotes SET code = ?1, expires_at = ?2, updated_at = ?3 WHERE id = ?4",
+            params![lot.code, lot.expires_at.map(|d| d.to_string()), lot.updated_at.timestamp_millis(), id],
+        ).context("Failed to update lot")?;
+
+        if updated == 0 {
+            return Err(AppError::not_found(format!("Lot with id {} not found", id)));
+        }
+        self.find_by_id(id)?
+            .ok_or_else(|| AppError::not_found(format!("Lot with id {} not found", id)))
+    }
+}
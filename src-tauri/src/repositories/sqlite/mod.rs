@@ -0,0 +1,27 @@
+pub mod product_repository;
+pub mod venda_repository;
+pub mod config_repository;
+pub mod resume_repository;
+pub mod history_repository;
+pub mod stock_movement_repository;
+pub mod category_repository;
+pub mod promotion_repository;
+pub mod composition_repository;
+pub mod inventory_repository;
+pub mod unit_repository;
+pub mod lot_repository;
+pub mod cart_repository;
+
+pub use product_repository::SqliteProductRepository;
+pub use venda_repository::SqliteVendaRepository;
+pub use config_repository::SqliteConfigRepository;
+pub use resume_repository::SqliteResumeRepository;
+pub use history_repository::SqliteHistoryRepository;
+pub use stock_movement_repository::SqliteStockMovementRepository;
+pub use category_repository::SqliteCategoryRepository;
+pub use promotion_repository::SqlitePromotionRepository;
+pub use composition_repository::SqliteCompositionRepository;
+pub use inventory_repository::SqliteInventoryRepository;
+pub use unit_repository::SqliteUnitRepository;
+pub use lot_repository::SqliteLotRepository;
+pub use cart_repository::SqliteCartRepository;
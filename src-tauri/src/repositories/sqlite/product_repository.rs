@@ -0,0 +1,254 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{ProductEntity, Quantity};
+use crate::repositories::{ProductRepository, ProductSearch};
+use rusqlite::{params, Row};
+use chrono::{DateTime, Utc};
+
+const PRODUCT_COLUMNS: &str = "id, code, name, active, balance, created_at, updated_at,
+             price, unit, gtin, ncm, cest, cfop, origem, cst, category_id, purchase_unit, purchase_factor, track_lots,
+             min_stock, max_stock";
+
+pub struct SqliteProductRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteProductRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<ProductEntity> {
+        Ok(ProductEntity {
+            id: row.get(0)?,
+            code: row.get(1)?,
+            name: row.get(2)?,
+            active: row.get(3)?,
+            balance: row.get(4)?,
+            created_at: from_millis(row.get(5)?),
+            updated_at: from_millis(row.get(6)?),
+            price: row.get(7)?,
+            unit: row.get(8)?,
+            gtin: row.get(9)?,
+            ncm: row.get(10)?,
+            cest: row.get(11)?,
+            cfop: row.get(12)?,
+            origem: row.get(13)?,
+            cst: row.get(14)?,
+            category_id: row.get(15)?,
+            purchase_unit: row.get(16)?,
+            purchase_factor: row.get(17)?,
+            track_lots: row.get(18)?,
+            min_stock: row.get(19)?,
+            max_stock: row.get(20)?,
+        })
+    }
+
+    fn find_one(&self, filter: &str, param: &dyn rusqlite::ToSql) -> AppResult<Option<ProductEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM produtos WHERE {} = ?1 ORDER BY id LIMIT 1", PRODUCT_COLUMNS, filter)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row([param], Self::map_row) {
+            Ok(p) => Ok(Some(p)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query product")),
+        }
+    }
+
+    fn find_many(&self, filter: &str) -> AppResult<Vec<ProductEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM produtos {} ORDER BY name", PRODUCT_COLUMNS, filter)
+        ).context("Failed to prepare statement")?;
+
+        let products = stmt.query_map([], Self::map_row)
+            .context("Failed to query products")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect products")?;
+
+        Ok(products)
+    }
+}
+
+impl ProductRepository for SqliteProductRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<ProductEntity>> {
+        self.find_one("id", &id)
+    }
+
+    fn find_by_code(&self, code: &str) -> AppResult<Option<ProductEntity>> {
+        self.find_one("code", &code)
+    }
+
+    fn find_by_gtin(&self, gtin: &str) -> AppResult<Option<ProductEntity>> {
+        self.find_one("gtin", &gtin)
+    }
+
+    fn find_all(&self) -> AppResult<Vec<ProductEntity>> {
+        self.find_many("")
+    }
+
+    fn find_all_active(&self) -> AppResult<Vec<ProductEntity>> {
+        self.find_many("WHERE active = 1")
+    }
+
+    fn find_by_categories(&self, category_ids: &[i64]) -> AppResult<Vec<ProductEntity>> {
+        if category_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids = category_ids.iter().map(i64::to_string).collect::<Vec<_>>().join(", ");
+        self.find_many(&format!("WHERE category_id IN ({})", ids))
+    }
+
+    fn search(&self, search: &ProductSearch) -> AppResult<(Vec<ProductEntity>, i64)> {
+        let Some(first) = search.terms.first() else {
+            return Ok((Vec::new(), 0));
+        };
+        let conn = self.db.get_connection()?;
+
+        // Cada termo vira uma busca por prefixo; os termos só têm letras e dígitos
+        let matches = search.terms.iter()
+            .map(|t| format!("\"{}\"*", t))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let leading = format!("^\"{}\"*", first);
+        let active = search.active.map(i32::from);
+
+        let total: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM produtos_fts
+             JOIN produtos ON produtos.id = produtos_fts.rowid
+             WHERE produtos_fts MATCH ?1 AND (?2 IS NULL OR produtos.active = ?2)",
+            params![matches, active],
+            |row| row.get(0),
+        ).context("Failed to count products")?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "WITH hits AS (
+                     SELECT rowid AS product_id, bm25(produtos_fts, 4.0, 1.0) AS score
+                     FROM produtos_fts WHERE produtos_fts MATCH ?1
+                 ),
+                 leading AS (
+                     SELECT rowid AS product_id FROM produtos_fts WHERE produtos_fts MATCH ?3
+                 )
+                 SELECT {} FROM produtos
+                 JOIN hits ON hits.product_id = produtos.id
+                 WHERE (?2 IS NULL OR active = ?2)
+                 ORDER BY code = ?4 DESC,
+                          id IN (SELECT product_id FROM leading) DESC,
+                          hits.score,
+                          name
+                 LIMIT ?5 OFFSET ?6",
+                PRODUCT_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let products = stmt.query_map(
+            params![matches, active, leading, search.query, search.limit, search.offset],
+            Self::map_row,
+        )
+            .context("Failed to search products")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect products")?;
+
+        Ok((products, total))
+    }
+
+    fn save(&self, product: &ProductEntity) -> AppResult<ProductEntity> {
+        let conn = self.db.get_connection()?;
+
+        if let Some(id) = product.id {
+            // Update
+            conn.execute(
+                "UPDATE produtos SET code = ?1, name = ?2, active = ?3, updated_at = ?4,
+                 price = ?5, unit = ?6, gtin = ?7, ncm = ?8, cest = ?9, cfop = ?10, origem = ?11, cst = ?12,
+                 category_id = ?13, purchase_unit = ?14, purchase_factor = ?15, track_lots = ?16,
+                 min_stock = ?17, max_stock = ?18
+                 WHERE id = ?19",
+                params![
+                    product.code,
+                    product.name,
+                    product.active,
+                    product.updated_at.timestamp_millis(),
+                    product.price,
+                    product.unit,
+                    product.gtin,
+                    product.ncm,
+                    product.cest,
+                    product.cfop,
+                    product.origem,
+                    product.cst,
+                    product.category_id,
+                    product.purchase_unit,
+                    product.purchase_factor,
+                    product.track_lots,
+                    product.min_stock,
+                    product.max_stock,
+                    id
+                ],
+            ).context("Failed to update product")?;
+
+            self.find_by_id(id)?
+                .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id)))
+        } else {
+            // Insert
+            conn.execute(
+                "INSERT INTO produtos (code, name, active, balance, created_at, updated_at,
+                 price, unit, gtin, ncm, cest, cfop, origem, cst, category_id, purchase_unit, purchase_factor, track_lots,
+                 min_stock, max_stock)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+                params![
+                    product.code,
+                    product.name,
+                    product.active,
+                    product.balance,
+                    product.created_at.timestamp_millis(),
+                    product.updated_at.timestamp_millis(),
+                    product.price,
+                    product.unit,
+                    product.gtin,
+                    product.ncm,
+                    product.cest,
+                    product.cfop,
+                    product.origem,
+                    product.cst,
+                    product.category_id,
+                    product.purchase_unit,
+                    product.purchase_factor,
+                    product.track_lots,
+                    product.min_stock,
+                    product.max_stock
+                ],
+            ).context("Failed to insert product")?;
+
+            let id = conn.last_insert_rowid();
+            Ok(ProductEntity { id: Some(id), ..product.clone() })
+        }
+    }
+
+    fn set_balance(&self, id: i64, balance: Quantity, at: DateTime<Utc>) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "UPDATE produtos SET balance = ?1, updated_at = ?2 WHERE id = ?3",
+            params![balance, at.timestamp_millis(), id],
+        ).context("Failed to update balance")?;
+
+        Ok(())
+    }
+
+    fn deactivate(&self, id: i64, at: DateTime<Utc>) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "UPDATE produtos SET active = 0, updated_at = ?1 WHERE id = ?2",
+            params![at.timestamp_millis(), id],
+        ).context("Failed to delete product")?;
+
+        Ok(())
+    }
+}
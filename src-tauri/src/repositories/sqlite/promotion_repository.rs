@@ -0,0 +1,119 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{PromotionEntity, PromotionRule};
+use crate::repositories::PromotionRepository;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Row};
+
+const PROMOTION_COLUMNS: &str = "id, name, rule, starts_at, ends_at, active, created_at, updated_at";
+
+pub struct SqlitePromotionRepository {
+    db: SqliteDbService,
+}
+
+impl SqlitePromotionRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<PromotionEntity> {
+        let rule: String = row.get(2)?;
+        let starts_at: Option<i64> = row.get(3)?;
+        let ends_at: Option<i64> = row.get(4)?;
+
+        Ok(PromotionEntity {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            rule: serde_json::from_str::<PromotionRule>(&rule).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    format!("Invalid promotion rule '{}': {}", rule, e).into(),
+                )
+            })?,
+            starts_at: starts_at.map(from_millis),
+            ends_at: ends_at.map(from_millis),
+            active: row.get(5)?,
+            created_at: from_millis(row.get(6)?),
+            updated_at: from_millis(row.get(7)?),
+        })
+    }
+
+    fn query(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> AppResult<Vec<PromotionEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM promocoes {}", PROMOTION_COLUMNS, filter)
+        ).context("Failed to prepare statement")?;
+
+        let promotions = stmt.query_map(params, Self::map_row)
+            .context("Failed to query promotions")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect promotions")?;
+
+        Ok(promotions)
+    }
+}
+
+impl PromotionRepository for SqlitePromotionRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<PromotionEntity>> {
+        Ok(self.query("WHERE id = ?1", &[&id])?.into_iter().next())
+    }
+
+    fn find_all(&self) -> AppResult<Vec<PromotionEntity>> {
+        self.query("ORDER BY id DESC", &[])
+    }
+
+    fn find_valid_at(&self, at: DateTime<Utc>) -> AppResult<Vec<PromotionEntity>> {
+        self.query(
+            "WHERE active = 1 AND (starts_at IS NULL OR starts_at <= ?1) AND (ends_at IS NULL OR ?1 < ends_at) ORDER BY id",
+            &[&at.timestamp_millis()],
+        )
+    }
+
+    fn save(&self, promotion: &PromotionEntity) -> AppResult<PromotionEntity> {
+        let conn = self.db.get_connection()?;
+        let rule = serde_json::to_string(&promotion.rule)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize promotion rule: {}", e)))?;
+        let starts_at = promotion.starts_at.map(|d| d.timestamp_millis());
+        let ends_at = promotion.ends_at.map(|d| d.timestamp_millis());
+
+        if let Some(id) = promotion.id {
+            conn.execute(
+                "UPDATE promocoes SET name = ?1, rule = ?2, starts_at = ?3, ends_at = ?4, active = ?5, updated_at = ?6
+                 WHERE id = ?7",
+                params![promotion.name, rule, starts_at, ends_at, promotion.active, promotion.updated_at.timestamp_millis(), id],
+            ).context("Failed to update promotion")?;
+
+            self.find_by_id(id)?
+                .ok_or_else(|| AppError::not_found(format!("Promotion with id {} not found", id)))
+        } else {
+            conn.execute(
+                "INSERT INTO promocoes (name, rule, starts_at, ends_at, active, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    promotion.name,
+                    rule,
+                    starts_at,
+                    ends_at,
+                    promotion.active,
+                    promotion.created_at.timestamp_millis(),
+                    promotion.updated_at.timestamp_millis()
+                ],
+            ).context("Failed to insert promotion")?;
+
+            let id = conn.last_insert_rowid();
+            Ok(PromotionEntity { id: Some(id), ..promotion.clone() })
+        }
+    }
+
+    fn delete(&self, id: i64) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute("DELETE FROM promocoes WHERE id = ?1", [id])
+            .context("Failed to delete promotion")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{Money, PaymentTypes, ResumeEntity};
+use chrono::{DateTime, Utc};
+use crate::repositories::ResumeRepository;
+use rusqlite::{params, Row};
+
+const RESUME_COLUMNS: &str = "id, code, amount_s, amount_n, updated_at, created_at";
+
+pub struct SqliteResumeRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteResumeRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<ResumeEntity> {
+        let code_str: String = row.get(1)?;
+        let code = PaymentTypes::from_str(&code_str).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                1,
+                rusqlite::types::Type::Text,
+                format!("Unknown payment code '{}' (see /admin/integrity)", code_str).into(),
+            )
+        })?;
+
+        Ok(ResumeEntity {
+            id: row.get(0)?,
+            code,
+            amount_s: row.get(2)?,
+            amount_n: row.get(3)?,
+            updated_at: from_millis(row.get(4)?),
+            created_at: from_millis(row.get(5)?),
+        })
+    }
+}
+
+impl ResumeRepository for SqliteResumeRepository {
+    fn find_since(&self, since: DateTime<Utc>) -> AppResult<Vec<ResumeEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {} FROM resumes
+                 WHERE created_at >= ?1
+                 ORDER BY code",
+                RESUME_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let resumes = stmt.query_map(params![since.timestamp_millis()], Self::map_row)
+            .context("Failed to query resumes")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect resumes")?;
+
+        Ok(resumes)
+    }
+
+    fn find_by_id(&self, id: &str) -> AppResult<Option<ResumeEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM resumes WHERE id = ?1", RESUME_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row(params![id], Self::map_row) {
+            Ok(r) => Ok(Some(r)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query resume")),
+        }
+    }
+
+    fn find_by_code_since(&self, code: &PaymentTypes, since: DateTime<Utc>) -> AppResult<Option<ResumeEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {} FROM resumes
+                 WHERE code = ?1 AND created_at >= ?2
+                 LIMIT 1",
+                RESUME_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row(params![code.as_str(), since.timestamp_millis()], Self::map_row) {
+            Ok(r) => Ok(Some(r)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query resume")),
+        }
+    }
+
+    fn save(&self, resume: &ResumeEntity) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO resumes (id, code, amount_s, amount_n, updated_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                resume.id,
+                resume.code.as_str(),
+                resume.amount_s,
+                resume.amount_n,
+                resume.updated_at.timestamp_millis(),
+                resume.created_at.timestamp_millis()
+            ],
+        ).context("Failed to save resume")?;
+
+        Ok(())
+    }
+
+    fn update_amounts(&self, id: &str, amount_s: Money, amount_n: Money, updated_at: DateTime<Utc>) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "UPDATE resumes SET amount_s = ?1, amount_n = ?2, updated_at = ?3 WHERE id = ?4",
+            params![amount_s, amount_n, updated_at.timestamp_millis(), id],
+        ).context("Failed to update resume")?;
+
+        Ok(())
+    }
+
+    fn increment_amounts(&self, id: &str, amount_s_inc: Money, amount_n_inc: Money, updated_at: DateTime<Utc>) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "UPDATE resumes
+             SET amount_s = amount_s + ?1,
+                 amount_n = amount_n + ?2,
+                 updated_at = ?3
+             WHERE id = ?4",
+            params![amount_s_inc, amount_n_inc, updated_at.timestamp_millis(), id],
+        ).context("Failed to increment resume amounts")?;
+
+        Ok(())
+    }
+
+    fn delete_before(&self, before: DateTime<Utc>) -> AppResult<usize> {
+        let conn = self.db.get_connection()?;
+
+        let deleted = conn.execute(
+            "DELETE FROM resumes WHERE created_at < ?1",
+            params![before.timestamp_millis()],
+        ).context("Failed to delete old resumes")?;
+
+        Ok(deleted)
+    }
+}
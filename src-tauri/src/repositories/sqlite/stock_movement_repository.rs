@@ -0,0 +1,200 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::{MovementType, Origin, Quantity, StockMovementEntity};
+use crate::repositories::{StockMovementFilter, StockMovementRepository};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension, Row, Transaction};
+
+const MOVEMENT_COLUMNS: &str = "id, product_id, type, quantity, balance_after, reference, user_name, origin, created_at, lot_id";
+
+pub struct SqliteStockMovementRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteStockMovementRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<StockMovementEntity> {
+        let kind: String = row.get(2)?;
+        let origin: String = row.get(7)?;
+
+        Ok(StockMovementEntity {
+            id: row.get(0)?,
+            product_id: row.get(1)?,
+            kind: MovementType::from_str(&kind).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    format!("Unknown stock movement type '{}'", kind).into(),
+                )
+            })?,
+            quantity: row.get(3)?,
+            balance_after: row.get(4)?,
+            reference: row.get(5)?,
+            user: row.get(6)?,
+            origin: Origin::from_str(&origin).unwrap_or_default(),
+            created_at: from_millis(row.get(8)?),
+            lot_id: row.get(9)?,
+        })
+    }
+
+    /// Aplica o movimento ao saldo do produto (e do lote, quando informado) e
+    /// grava a linha no livro dentro de uma transação já aberta (usado também
+    /// pela gravação de vendas). Uma saída nunca deixa o lote negativo e, com
+    /// `block_negative`, nem o saldo do produto: nesses casos nada é alterado e
+    /// o erro é `Conflict`. A conferência é feita no próprio `UPDATE`, então
+    /// vale também contra outra venda gravada depois da leitura do saldo.
+    pub(crate) fn record_in_transaction(tx: &Transaction, movement: &StockMovementEntity, block_negative: bool) -> AppResult<StockMovementEntity> {
+        if let Some(lot_id) = movement.lot_id {
+            let updated = tx.execute(
+                "UPDATE lotes SET quantity = quantity + ?1, updated_at = ?2
+                 WHERE id = ?3 AND product_id = ?4 AND (?1 >= 0 OR quantity + ?1 >= 0)",
+                params![movement.quantity, movement.created_at.timestamp_millis(), lot_id, movement.product_id],
+            ).context("Failed to update lot quantity")?;
+
+            if updated == 0 {
+                let lot: Option<(String, Quantity)> = tx.query_row(
+                    "SELECT code, quantity FROM lotes WHERE id = ?1 AND product_id = ?2",
+                    params![lot_id, movement.product_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                ).optional().context("Failed to read lot quantity")?;
+
+                return Err(match lot {
+                    Some((code, quantity)) => AppError::Conflict(format!("Estoque insuficiente no lote {} (saldo {}, saída {})", code, quantity, -movement.quantity)),
+                    None => AppError::not_found(format!("Lot with id {} not found", lot_id)),
+                });
+            }
+        }
+
+        let updated = tx.execute(
+            "UPDATE produtos SET balance = balance + ?1, updated_at = ?2
+             WHERE id = ?3 AND (?4 = 0 OR ?1 >= 0 OR balance + ?1 >= 0)",
+            params![movement.quantity, movement.created_at.timestamp_millis(), movement.product_id, block_negative],
+        ).context("Failed to update balance")?;
+
+        if updated == 0 {
+            let product: Option<(String, Quantity)> = tx.query_row(
+                "SELECT code, balance FROM produtos WHERE id = ?1",
+                params![movement.product_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            ).optional().context("Failed to read balance")?;
+
+            return Err(match product {
+                Some((code, balance)) => AppError::Conflict(format!("Estoque insuficiente: {} (saldo {}, vendido {})", code, balance, -movement.quantity)),
+                None => AppError::not_found(format!("Product with id {} not found", movement.product_id)),
+            });
+        }
+
+        let balance_after: Quantity = tx.query_row(
+            "SELECT balance FROM produtos WHERE id = ?1",
+            params![movement.product_id],
+            |row| row.get(0),
+        ).context("Failed to read balance")?;
+
+        tx.execute(
+            "INSERT INTO stock_movements (product_id, type, quantity, balance_after, reference, user_name, origin, created_at, lot_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                movement.product_id,
+                movement.kind.as_str(),
+                movement.quantity,
+                balance_after,
+                movement.reference,
+                movement.user,
+                movement.origin.as_str(),
+                movement.created_at.timestamp_millis(),
+                movement.lot_id
+            ],
+        ).context("Failed to insert stock movement")?;
+
+        Ok(StockMovementEntity { id: Some(tx.last_insert_rowid()), balance_after, ..movement.clone() })
+    }
+}
+
+impl StockMovementRepository for SqliteStockMovementRepository {
+    fn record(&self, movement: &StockMovementEntity) -> AppResult<StockMovementEntity> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+        let saved = Self::record_in_transaction(&tx, movement, false)?;
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(saved)
+    }
+
+    fn record_all(&self, movements: &[StockMovementEntity]) -> AppResult<Vec<StockMovementEntity>> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+        let saved = movements.iter()
+            .map(|movement| Self::record_in_transaction(&tx, movement, false))
+            .collect::<AppResult<Vec<_>>>()?;
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(saved)
+    }
+
+    fn find(&self, filter: &StockMovementFilter) -> AppResult<Vec<StockMovementEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {} FROM stock_movements
+                 WHERE (?1 IS NULL OR product_id = ?1)
+                   AND (?2 IS NULL OR created_at >= ?2)
+                   AND (?3 IS NULL OR created_at < ?3)
+                   AND (?4 IS NULL OR reference = ?4)
+                 ORDER BY created_at, id",
+                MOVEMENT_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let movements = stmt.query_map(
+            params![
+                filter.product_id,
+                filter.start.map(|s| s.timestamp_millis()),
+                filter.end.map(|e| e.timestamp_millis()),
+                filter.reference
+            ],
+            Self::map_row,
+        )
+            .context("Failed to query stock movements")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect stock movements")?;
+
+        Ok(movements)
+    }
+
+    fn sum_quantity(&self, product_id: i64, before: Option<DateTime<Utc>>) -> AppResult<Quantity> {
+        let conn = self.db.get_connection()?;
+
+        conn.query_row(
+            "SELECT COALESCE(SUM(quantity), 0) FROM stock_movements
+             WHERE product_id = ?1 AND (?2 IS NULL OR created_at < ?2)",
+            params![product_id, before.map(|b| b.timestamp_millis())],
+            |row| row.get(0),
+        ).context("Failed to sum stock movements")
+    }
+
+    fn ledger_balances(&self) -> AppResult<Vec<(i64, Quantity)>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT product_id, SUM(quantity) FROM stock_movements GROUP BY product_id ORDER BY product_id"
+        ).context("Failed to prepare statement")?;
+
+        let balances = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context("Failed to query ledger balances")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect ledger balances")?;
+
+        Ok(balances)
+    }
+}
@@ -0,0 +1,87 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::entities::UnitEntity;
+use crate::repositories::UnitRepository;
+use rusqlite::{params, Row};
+
+const UNIT_COLUMNS: &str = "code, description, decimals, created_at, updated_at";
+
+pub struct SqliteUnitRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteUnitRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_row(row: &Row) -> rusqlite::Result<UnitEntity> {
+        Ok(UnitEntity {
+            code: row.get(0)?,
+            description: row.get(1)?,
+            decimals: row.get(2)?,
+            created_at: from_millis(row.get(3)?),
+            updated_at: from_millis(row.get(4)?),
+        })
+    }
+}
+
+impl UnitRepository for SqliteUnitRepository {
+    fn find_by_code(&self, code: &str) -> AppResult<Option<UnitEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM unidades WHERE code = ?1", UNIT_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row([code], Self::map_row) {
+            Ok(u) => Ok(Some(u)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query unit")),
+        }
+    }
+
+    fn find_all(&self) -> AppResult<Vec<UnitEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM unidades ORDER BY code", UNIT_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let units = stmt.query_map([], Self::map_row)
+            .context("Failed to query units")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect units")?;
+
+        Ok(units)
+    }
+
+    fn save(&self, unit: &UnitEntity) -> AppResult<UnitEntity> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute(
+            "INSERT INTO unidades (code, description, decimals, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (code) DO UPDATE SET
+                description = excluded.description, decimals = excluded.decimals, updated_at = excluded.updated_at",
+            params![
+                unit.code,
+                unit.description,
+                unit.decimals,
+                unit.created_at.timestamp_millis(),
+                unit.updated_at.timestamp_millis()
+            ],
+        ).context("Failed to save unit")?;
+
+        Ok(unit.clone())
+    }
+
+    fn delete(&self, code: &str) -> AppResult<()> {
+        let conn = self.db.get_connection()?;
+
+        conn.execute("DELETE FROM unidades WHERE code = ?1", [code])
+            .context("Failed to delete unit")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,425 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::from_millis;
+use crate::database::SqliteDbService;
+use crate::dtos::VendaResumo;
+use crate::entities::{StockMovementEntity, VendaEntity, VendaItemEntity, VendaPagamentoEntity};
+use crate::repositories::VendaRepository;
+use super::{SqliteCartRepository, SqliteLotRepository, SqliteStockMovementRepository};
+use rusqlite::{params, Row, Transaction};
+use chrono::{DateTime, Utc};
+
+const VENDA_COLUMNS: &str = "id, tip, mod, serie_origin, serie, nr_nf_origin, nr_nf, cnpj, doc_destinatario,
+             dh_emi, dh_emi_canc, total, addition, discount, chave, chave_canc, file_path,
+             cancel_file_path, protocolo, cancelled, created_at, updated_at";
+
+const ITEM_COLUMNS: &str = "vi.id, vi.venda_id, vi.produto_code, vi.produto_description, vi.produto_medida,
+             vi.quantidade, vi.preco_unitario, vi.desconto, vi.desconto_rat, vi.acrescimo,
+             vi.acrescimo_rat, vi.preco_total, vi.created_at, vi.updated_at,
+             vi.gtin, vi.ncm, vi.cest, vi.cfop, vi.origem, vi.cst, vi.promotion_id";
+
+const PAYMENT_COLUMNS: &str = "vp.id, vp.venda_id, vp.code, vp.name, vp.total_pagamento, vp.created_at, vp.updated_at";
+
+pub struct SqliteVendaRepository {
+    db: SqliteDbService,
+}
+
+impl SqliteVendaRepository {
+    pub fn new(db: SqliteDbService) -> Self {
+        Self { db }
+    }
+
+    fn map_venda(row: &Row) -> rusqlite::Result<VendaEntity> {
+        Ok(VendaEntity {
+            id: row.get(0)?,
+            tip: row.get(1)?,
+            mod_: row.get(2)?,
+            serie_origin: row.get(3)?,
+            serie: row.get(4)?,
+            nr_nf_origin: row.get(5)?,
+            nr_nf: row.get(6)?,
+            cnpj: row.get(7)?,
+            doc_destinatario: row.get(8)?,
+            dh_emi: row.get(9)?,
+            dh_emi_canc: row.get(10)?,
+            total: row.get(11)?,
+            addition: row.get(12)?,
+            discount: row.get(13)?,
+            chave: row.get(14)?,
+            chave_canc: row.get(15)?,
+            file_path: row.get(16)?,
+            cancel_file_path: row.get(17)?,
+            protocolo: row.get(18)?,
+            cancelled: row.get(19)?,
+            created_at: from_millis(row.get(20)?),
+            updated_at: from_millis(row.get(21)?),
+        })
+    }
+
+    fn map_item(row: &Row) -> rusqlite::Result<VendaItemEntity> {
+        Ok(VendaItemEntity {
+            id: row.get(0)?,
+            venda_id: row.get(1)?,
+            produto_code: row.get(2)?,
+            produto_description: row.get(3)?,
+            produto_medida: row.get(4)?,
+            quantidade: row.get(5)?,
+            preco_unitario: row.get(6)?,
+            desconto: row.get(7)?,
+            desconto_rat: row.get(8)?,
+            acrescimo: row.get(9)?,
+            acrescimo_rat: row.get(10)?,
+            preco_total: row.get(11)?,
+            created_at: from_millis(row.get(12)?),
+            updated_at: from_millis(row.get(13)?),
+            gtin: row.get(14)?,
+            ncm: row.get(15)?,
+            cest: row.get(16)?,
+            cfop: row.get(17)?,
+            origem: row.get(18)?,
+            cst: row.get(19)?,
+            promotion_id: row.get(20)?,
+        })
+    }
+
+    fn map_payment(row: &Row) -> rusqlite::Result<VendaPagamentoEntity> {
+        Ok(VendaPagamentoEntity {
+            id: row.get(0)?,
+            venda_id: row.get(1)?,
+            code: row.get(2)?,
+            name: row.get(3)?,
+            total_pagamento: row.get(4)?,
+            created_at: from_millis(row.get(5)?),
+            updated_at: from_millis(row.get(6)?),
+        })
+    }
+
+    /// Insere um item de venda dentro de uma transação
+    fn insert_item_in_transaction(
+        tx: &Transaction,
+        venda_id: i64,
+        item: &VendaItemEntity,
+    ) -> AppResult<()> {
+        tx.execute(
+            "INSERT INTO venda_itens (venda_id, produto_code, produto_description, produto_medida,
+             quantidade, preco_unitario, desconto, desconto_rat, acrescimo, acrescimo_rat,
+             preco_total, created_at, updated_at, gtin, ncm, cest, cfop, origem, cst, promotion_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+            params![
+                venda_id,
+                item.produto_code,
+                item.produto_description,
+                item.produto_medida,
+                item.quantidade,
+                item.preco_unitario,
+                item.desconto,
+                item.desconto_rat,
+                item.acrescimo,
+                item.acrescimo_rat,
+                item.preco_total,
+                item.created_at.timestamp_millis(),
+                item.updated_at.timestamp_millis(),
+                item.gtin,
+                item.ncm,
+                item.cest,
+                item.cfop,
+                item.origem,
+                item.cst,
+                item.promotion_id
+            ],
+        ).context("Failed to insert venda_item")?;
+
+        Ok(())
+    }
+
+    /// Insere um pagamento dentro de uma transação
+    fn insert_payment_in_transaction(
+        tx: &Transaction,
+        venda_id: i64,
+        payment: &VendaPagamentoEntity,
+    ) -> AppResult<()> {
+        tx.execute(
+            "INSERT INTO venda_pagamentos (venda_id, code, name, total_pagamento, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                venda_id,
+                payment.code,
+                payment.name,
+                payment.total_pagamento,
+                payment.created_at.timestamp_millis(),
+                payment.updated_at.timestamp_millis()
+            ],
+        ).context("Failed to insert venda_pagamento")?;
+
+        Ok(())
+    }
+}
+
+impl VendaRepository for SqliteVendaRepository {
+    fn find_by_id(&self, id: i64) -> AppResult<Option<VendaEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM vendas WHERE id = ?1", VENDA_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        match stmt.query_row(params![id], Self::map_venda) {
+            Ok(v) => Ok(Some(v)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::from(e).context("Failed to query venda")),
+        }
+    }
+
+    fn create_venda(
+        &self,
+        venda: &VendaEntity,
+        emitted_at: DateTime<Utc>,
+        items: &[VendaItemEntity],
+        payments: &[VendaPagamentoEntity],
+        movements: &[StockMovementEntity],
+        block_negative: bool,
+        cart_id: Option<i64>,
+    ) -> AppResult<i64> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+
+        // Insere a venda
+        tx.execute(
+            "INSERT INTO vendas (tip, mod, serie_origin, serie, nr_nf_origin, nr_nf, cnpj,
+             doc_destinatario, dh_emi, dh_emi_canc, total, addition, discount, chave, chave_canc,
+             file_path, cancel_file_path, protocolo, cancelled, created_at, updated_at, dh_emi_ts)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+            params![
+                venda.tip,
+                venda.mod_,
+                venda.serie_origin,
+                venda.serie,
+                venda.nr_nf_origin,
+                venda.nr_nf,
+                venda.cnpj,
+                venda.doc_destinatario,
+                venda.dh_emi,
+                venda.dh_emi_canc,
+                venda.total,
+                venda.addition,
+                venda.discount,
+                venda.chave,
+                venda.chave_canc,
+                venda.file_path,
+                venda.cancel_file_path,
+                venda.protocolo,
+                venda.cancelled,
+                venda.created_at.timestamp_millis(),
+                venda.updated_at.timestamp_millis(),
+                emitted_at.timestamp_millis()
+            ],
+        ).context("Failed to insert venda")?;
+
+        let venda_id = tx.last_insert_rowid();
+
+        // Insere os itens
+        for item in items {
+            Self::insert_item_in_transaction(&tx, venda_id, item)?;
+        }
+
+        // Insere os pagamentos
+        for payment in payments {
+            Self::insert_payment_in_transaction(&tx, venda_id, payment)?;
+        }
+
+        // Baixa o estoque, dividindo entre os lotes com as quantidades desta
+        // transação; com `block_negative` o saldo é conferido no próprio UPDATE
+        let reference = StockMovementEntity::venda_reference(venda_id);
+        for movement in movements {
+            let movement = StockMovementEntity { reference: Some(reference.clone()), ..movement.clone() };
+            for part in SqliteLotRepository::allocate_in_transaction(&tx, &movement)? {
+                SqliteStockMovementRepository::record_in_transaction(&tx, &part, block_negative)?;
+            }
+        }
+
+        // Encerra o carrinho; se outra finalização chegou antes, a venda é desfeita
+        if let Some(cart_id) = cart_id {
+            if !SqliteCartRepository::finalize_in_transaction(&tx, cart_id, venda_id, venda.created_at)? {
+                return Err(AppError::Conflict(format!("Carrinho {} não está mais em pagamento", cart_id)));
+            }
+        }
+
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(venda_id)
+    }
+
+    fn find_items_by_venda_id(&self, venda_id: i64) -> AppResult<Vec<VendaItemEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM venda_itens vi WHERE vi.venda_id = ?1 ORDER BY vi.id", ITEM_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let items = stmt.query_map(params![venda_id], Self::map_item)
+            .context("Failed to query venda_items")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect venda_items")?;
+
+        Ok(items)
+    }
+
+    fn find_payments_by_venda_id(&self, venda_id: i64) -> AppResult<Vec<VendaPagamentoEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!("SELECT {} FROM venda_pagamentos vp WHERE vp.venda_id = ?1 ORDER BY vp.id", PAYMENT_COLUMNS)
+        ).context("Failed to prepare statement")?;
+
+        let payments = stmt.query_map(params![venda_id], Self::map_payment)
+            .context("Failed to query venda_pagamentos")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect venda_pagamentos")?;
+
+        Ok(payments)
+    }
+
+    fn find_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {} FROM vendas
+                 WHERE dh_emi_ts >= ?1 AND dh_emi_ts < ?2
+                 ORDER BY dh_emi_ts DESC",
+                VENDA_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let vendas = stmt.query_map(params![start.timestamp_millis(), end.timestamp_millis()], Self::map_venda)
+            .context("Failed to query vendas")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect vendas")?;
+
+        Ok(vendas)
+    }
+
+    fn find_items_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaItemEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {}
+                 FROM venda_itens vi
+                 INNER JOIN vendas v ON vi.venda_id = v.id
+                 WHERE v.dh_emi_ts >= ?1 AND v.dh_emi_ts < ?2
+                 ORDER BY v.dh_emi_ts DESC, vi.id",
+                ITEM_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let items = stmt.query_map(params![start.timestamp_millis(), end.timestamp_millis()], Self::map_item)
+            .context("Failed to query items")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect items")?;
+
+        Ok(items)
+    }
+
+    fn find_payments_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaPagamentoEntity>> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            &format!(
+                "SELECT {}
+                 FROM venda_pagamentos vp
+                 INNER JOIN vendas v ON vp.venda_id = v.id
+                 WHERE v.dh_emi_ts >= ?1 AND v.dh_emi_ts < ?2
+                 ORDER BY v.dh_emi_ts DESC, vp.id",
+                PAYMENT_COLUMNS
+            )
+        ).context("Failed to prepare statement")?;
+
+        let payments = stmt.query_map(params![start.timestamp_millis(), end.timestamp_millis()], Self::map_payment)
+            .context("Failed to query payments")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to collect payments")?;
+
+        Ok(payments)
+    }
+
+    fn resumo_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<VendaResumo> {
+        let conn = self.db.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT
+                COUNT(*) as total_vendas,
+                COALESCE(SUM(total), 0) as total_valor,
+                COALESCE(SUM(discount), 0) as total_desconto,
+                COALESCE(SUM(addition), 0) as total_acrescimo,
+                COALESCE(SUM(CASE WHEN cancelled = 1 THEN 1 ELSE 0 END), 0) as total_canceladas
+             FROM vendas
+             WHERE dh_emi_ts >= ?1 AND dh_emi_ts < ?2"
+        ).context("Failed to prepare statement")?;
+
+        let resumo = stmt.query_row(params![start.timestamp_millis(), end.timestamp_millis()], |row| {
+            Ok(VendaResumo {
+                total_vendas: row.get(0)?,
+                total_valor: row.get(1)?,
+                total_desconto: row.get(2)?,
+                total_acrescimo: row.get(3)?,
+                total_canceladas: row.get(4)?,
+            })
+        })
+        .context("Failed to query resumo")?;
+
+        Ok(resumo)
+    }
+
+    fn cancel(
+        &self,
+        venda_id: i64,
+        chave_canc: &str,
+        dh_emi_canc: &str,
+        cancel_file_path: Option<&str>,
+        at: DateTime<Utc>,
+        movements: &[StockMovementEntity],
+    ) -> AppResult<bool> {
+        let mut conn = self.db.get_connection()?;
+
+        let tx = conn.transaction()
+            .context("Failed to start transaction")?;
+
+        // Só a primeira chamada cancela; as concorrentes não alteram nenhuma linha
+        let updated = tx.execute(
+            "UPDATE vendas SET cancelled = 1, chave_canc = ?1, dh_emi_canc = ?2, cancel_file_path = ?3, updated_at = ?4
+             WHERE id = ?5 AND cancelled = 0",
+            params![chave_canc, dh_emi_canc, cancel_file_path, at.timestamp_millis(), venda_id],
+        ).context("Failed to cancel venda")?;
+
+        if updated == 0 {
+            let exists: bool = tx.query_row(
+                "SELECT COUNT(*) FROM vendas WHERE id = ?1",
+                params![venda_id],
+                |row| {
+                    let count: i32 = row.get(0)?;
+                    Ok(count > 0)
+                }
+            ).context("Failed to check venda existence")?;
+
+            if !exists {
+                return Err(AppError::not_found(format!("Venda with id {} not found", venda_id)));
+            }
+            return Ok(false);
+        }
+
+        // Devolve o estoque
+        let reference = StockMovementEntity::venda_reference(venda_id);
+        for movement in movements {
+            let movement = StockMovementEntity { reference: Some(reference.clone()), ..movement.clone() };
+            SqliteStockMovementRepository::record_in_transaction(&tx, &movement, false)?;
+        }
+
+        tx.commit()
+            .context("Failed to commit transaction")?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::error::AppResult;
+use crate::entities::{Quantity, StockMovementEntity};
+use chrono::{DateTime, Utc};
+
+/// Filtro das consultas ao livro de movimentos; campos `None` não restringem
+#[derive(Debug, Clone, Default)]
+pub struct StockMovementFilter {
+    pub product_id: Option<i64>,
+    /// Início do intervalo (inclusivo)
+    pub start: Option<DateTime<Utc>>,
+    /// Fim do intervalo (exclusivo)
+    pub end: Option<DateTime<Utc>>,
+    /// Documento de origem exato (ex. `venda:12`)
+    pub reference: Option<String>,
+}
+
+/// Livro de movimentos de estoque (tabela `stock_movements`)
+pub trait StockMovementRepository: Send + Sync {
+    /// Grava o movimento e soma `quantity` ao saldo do produto numa única
+    /// transação. Retorna o movimento com `id` e `balance_after` preenchidos.
+    fn record(&self, movement: &StockMovementEntity) -> AppResult<StockMovementEntity>;
+
+    /// Grava as partes de um mesmo movimento (ex. uma saída dividida entre
+    /// lotes) numa única transação: ou todas entram, ou nenhuma
+    fn record_all(&self, movements: &[StockMovementEntity]) -> AppResult<Vec<StockMovementEntity>>;
+
+    /// Busca movimentos pelo filtro, em ordem cronológica
+    fn find(&self, filter: &StockMovementFilter) -> AppResult<Vec<StockMovementEntity>>;
+
+    /// Soma das quantidades do produto antes de `before` (todas quando `None`)
+    fn sum_quantity(&self, product_id: i64, before: Option<DateTime<Utc>>) -> AppResult<Quantity>;
+
+    /// Saldo do livro de cada produto que tem movimentos
+    fn ledger_balances(&self) -> AppResult<Vec<(i64, Quantity)>>;
+}
@@ -0,0 +1,17 @@
+use crate::error::AppResult;
+use crate::entities::UnitEntity;
+
+/// Unidades de medida (tabela `unidades`)
+pub trait UnitRepository: Send + Sync {
+    /// Busca uma unidade pelo código (já normalizado)
+    fn find_by_code(&self, code: &str) -> AppResult<Option<UnitEntity>>;
+
+    /// Lista as unidades por código
+    fn find_all(&self) -> AppResult<Vec<UnitEntity>>;
+
+    /// Insere ou atualiza a unidade pelo código
+    fn save(&self, unit: &UnitEntity) -> AppResult<UnitEntity>;
+
+    /// Remove a unidade
+    fn delete(&self, code: &str) -> AppResult<()>;
+}
@@ -0,0 +1,68 @@
+use crate::error::AppResult;
+use crate::dtos::VendaResumo;
+use crate::entities::{StockMovementEntity, VendaEntity, VendaItemEntity, VendaPagamentoEntity};
+use chrono::{DateTime, Utc};
+
+/// Armazenamento de vendas, itens e pagamentos
+/// (tabelas `vendas`, `venda_itens` e `venda_pagamentos`).
+///
+/// Os intervalos são semiabertos (`[start, end)`) e comparados com o instante de
+/// emissão (`dh_emi_ts`), calculado a partir de `dh_emi` ao gravar a venda.
+pub trait VendaRepository: Send + Sync {
+    /// Busca uma venda por ID
+    fn find_by_id(&self, id: i64) -> AppResult<Option<VendaEntity>>;
+
+    /// Grava a venda com seus itens e pagamentos de forma atômica e retorna o ID.
+    /// `emitted_at` é o instante de `dh_emi`, usado nas consultas por intervalo.
+    /// Os `movements` de estoque entram na mesma transação, com `reference`
+    /// preenchida com `venda:{id}`. Saídas sem lote de produtos com controle de
+    /// lotes são divididas entre os lotes (FEFO) com as quantidades lidas na
+    /// transação, e lote nenhum fica negativo; com `block_negative`, uma baixa
+    /// que deixaria o saldo de um produto negativo desfaz tudo. Em ambos os
+    /// casos o erro é `Conflict`. Com
+    /// `cart_id`, o carrinho que gerou a venda é encerrado na mesma transação
+    /// (em `venda.created_at`), desde que ainda esteja em pagamento; senão nada
+    /// é gravado e o erro é `Conflict`.
+    #[allow(clippy::too_many_arguments)]
+    fn create_venda(
+        &self,
+        venda: &VendaEntity,
+        emitted_at: DateTime<Utc>,
+        items: &[VendaItemEntity],
+        payments: &[VendaPagamentoEntity],
+        movements: &[StockMovementEntity],
+        block_negative: bool,
+        cart_id: Option<i64>,
+    ) -> AppResult<i64>;
+
+    /// Busca os itens de uma venda
+    fn find_items_by_venda_id(&self, venda_id: i64) -> AppResult<Vec<VendaItemEntity>>;
+
+    /// Busca os pagamentos de uma venda
+    fn find_payments_by_venda_id(&self, venda_id: i64) -> AppResult<Vec<VendaPagamentoEntity>>;
+
+    /// Busca vendas emitidas no intervalo (mais recentes primeiro)
+    fn find_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaEntity>>;
+
+    /// Busca itens de vendas por intervalo de datas
+    fn find_items_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaItemEntity>>;
+
+    /// Busca pagamentos de vendas por intervalo de datas
+    fn find_payments_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<VendaPagamentoEntity>>;
+
+    /// Calcula os totais das vendas do intervalo
+    fn resumo_by_interval(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<VendaResumo>;
+
+    /// Marca a venda como cancelada, gravando na mesma transação os `movements`
+    /// de estoque que devolvem os itens. Retorna `false`, sem gravar nada, se a
+    /// venda já estava cancelada, e `NotFound` se ela não existe.
+    fn cancel(
+        &self,
+        venda_id: i64,
+        chave_canc: &str,
+        dh_emi_canc: &str,
+        cancel_file_path: Option<&str>,
+        at: DateTime<Utc>,
+        movements: &[StockMovementEntity],
+    ) -> AppResult<bool>;
+}
@@ -0,0 +1,477 @@
+use crate::dtos::{
+    AddCartItemDto, AddCartPaymentDto, CartDiscountDto, CartDto, CartFinalizedDto, FinalizeCartDto, OpenCartDto,
+    UpdateCartItemDto,
+};
+use crate::error::{AppError, AppResult};
+use crate::entities::{
+    CartEntity, CartItemEntity, CartPaymentEntity, CartStatus, Money, PaymentTypes, UnitEntity, VendaEntity,
+    VendaItemEntity,
+};
+use crate::services::history_service::{HistoryService, ENTITY_CART};
+use crate::services::{ProductService, UnitService, VendaService};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+
+pub struct CartService;
+
+impl CartService {
+    /// POST /carts - Abre um carrinho. Só pode haver um carrinho em andamento.
+    pub fn open(state: &AppState, dto: OpenCartDto) -> AppResult<CartDto> {
+        if let Some(active) = state.carts.find_active()? {
+            return Err(AppError::Conflict(format!("Já existe um carrinho em andamento ({})", active.id.unwrap_or_default())));
+        }
+
+        let doc = dto.doc_destinatario.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+        let cart = state.carts.create(&CartEntity::new(dto.user, doc, state.clock.now()))?;
+        Self::record(state, "open", None, Some(&cart));
+        Self::view(state, cart)
+    }
+
+    /// GET /carts/:id - Busca um carrinho por ID, com itens e totais
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<CartDto>> {
+        state.carts.find_by_id(id)?.map(|cart| Self::view(state, cart)).transpose()
+    }
+
+    /// GET /carts/current - Carrinho em andamento, se houver (inclusive de antes
+    /// de reiniciar o aplicativo)
+    pub fn current(state: &AppState) -> AppResult<Option<CartDto>> {
+        state.carts.find_active()?.map(|cart| Self::view(state, cart)).transpose()
+    }
+
+    /// POST /carts/:id/items - Lê um produto pelo código, GTIN ou etiqueta de
+    /// balança. Descrição e unidade ficam fixadas no item; o preço vem do cadastro.
+    pub fn add_item(state: &AppState, id: i64, dto: AddCartItemDto) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Open)?;
+
+        let lookup = ProductService::lookup_barcode(state, &dto.barcode)?;
+        let product = lookup.product;
+        let medida = dto.produto_medida
+            .map(|m| UnitEntity::normalize_code(&m))
+            .filter(|m| !m.is_empty())
+            .unwrap_or(lookup.item.produto_medida.clone());
+        let preco_unitario = if medida == lookup.item.produto_medida {
+            lookup.item.preco_unitario
+        } else {
+            let factor = product.unit_factor(&medida)
+                .ok_or_else(|| AppError::validation("produto_medida", format!("Produto {} não usa a unidade {}", product.code, medida)))?;
+            product.price * factor
+        };
+
+        let now = state.clock.now();
+        let item = CartItemEntity {
+            id: None,
+            cart_id: id,
+            produto_code: product.code.clone(),
+            produto_description: lookup.item.produto_description,
+            produto_medida: medida,
+            quantidade: dto.quantidade.unwrap_or(lookup.item.quantidade),
+            preco_unitario,
+            desconto: dto.desconto.unwrap_or(Money::ZERO),
+            created_at: now,
+            updated_at: now,
+        };
+        Self::check_item(state, &item)?;
+        state.carts.save_item(&item)?;
+
+        Self::touch(state, cart)
+    }
+
+    /// PUT /carts/:id/items/:item_id - Altera a quantidade ou o desconto manual de um item
+    pub fn update_item(state: &AppState, id: i64, item_id: i64, dto: UpdateCartItemDto) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Open)?;
+        let mut item = state.carts.find_items(id)?
+            .into_iter()
+            .find(|i| i.id == Some(item_id))
+            .ok_or_else(|| AppError::not_found(format!("Cart item with id {} not found", item_id)))?;
+
+        if let Some(quantidade) = dto.quantidade {
+            item.quantidade = quantidade;
+        }
+        if let Some(desconto) = dto.desconto {
+            item.desconto = desconto;
+        }
+        item.updated_at = state.clock.now();
+        Self::check_item(state, &item)?;
+        state.carts.save_item(&item)?;
+
+        Self::touch(state, cart)
+    }
+
+    /// DELETE /carts/:id/items/:item_id - Remove um item
+    pub fn remove_item(state: &AppState, id: i64, item_id: i64) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Open)?;
+        if !state.carts.delete_item(id, item_id)? {
+            return Err(AppError::not_found(format!("Cart item with id {} not found", item_id)));
+        }
+        Self::touch(state, cart)
+    }
+
+    /// PUT /carts/:id/discount - Define o desconto e o acréscimo no total do carrinho
+    pub fn set_discount(state: &AppState, id: i64, dto: CartDiscountDto) -> AppResult<CartDto> {
+        let mut cart = Self::require(state, id, CartStatus::Open)?;
+
+        let discount = dto.discount.unwrap_or(cart.discount);
+        let addition = dto.addition.unwrap_or(cart.addition);
+        if discount.is_negative() {
+            return Err(AppError::validation("discount", "Desconto não pode ser negativo"));
+        }
+        if addition.is_negative() {
+            return Err(AppError::validation("addition", "Acréscimo não pode ser negativo"));
+        }
+
+        let view = Self::view(state, cart.clone())?;
+        if discount > view.subtotal - view.items_discount + addition {
+            return Err(AppError::validation("discount", "Desconto maior que o total do carrinho"));
+        }
+
+        cart.discount = discount;
+        cart.addition = addition;
+        Self::touch(state, cart)
+    }
+
+    /// POST /carts/:id/checkout - Fecha os itens e passa a receber pagamentos
+    pub fn checkout(state: &AppState, id: i64) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Open)?;
+
+        let view = Self::view(state, cart.clone())?;
+        if view.items.is_empty() {
+            return Err(AppError::validation("items", "Carrinho sem itens"));
+        }
+        if view.total.is_negative() {
+            return Err(AppError::validation("discount", "Desconto maior que o total do carrinho"));
+        }
+
+        Self::transition(state, cart, CartStatus::Payment)
+    }
+
+    /// POST /carts/:id/reopen - Volta a aceitar itens; os pagamentos precisam
+    /// ser removidos antes
+    pub fn reopen(state: &AppState, id: i64) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Payment)?;
+        if !state.carts.find_payments(id)?.is_empty() {
+            return Err(AppError::Conflict(format!("Carrinho {} já tem pagamentos; remova-os antes de reabrir", id)));
+        }
+        Self::transition(state, cart, CartStatus::Open)
+    }
+
+    /// POST /carts/:id/payments - Registra um pagamento. Só dinheiro pode
+    /// passar do que falta pagar (a diferença vira troco).
+    pub fn add_payment(state: &AppState, id: i64, dto: AddCartPaymentDto) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Payment)?;
+
+        let kind = PaymentTypes::from_str(dto.code.trim())
+            .ok_or_else(|| AppError::validation("code", format!("Forma de pagamento inválida: '{}'", dto.code)))?;
+        if dto.total_pagamento.is_negative() || dto.total_pagamento.is_zero() {
+            return Err(AppError::validation("total_pagamento", "Valor deve ser maior que zero"));
+        }
+        let view = Self::view(state, cart.clone())?;
+        if kind != PaymentTypes::Dinheiro && dto.total_pagamento > view.remaining {
+            return Err(AppError::validation("total_pagamento", format!("Valor maior que o restante ({})", view.remaining)));
+        }
+
+        let name = dto.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        state.carts.add_payment(&CartPaymentEntity {
+            id: None,
+            cart_id: id,
+            code: kind.as_str().to_string(),
+            name: name.unwrap_or_else(|| kind.description().to_string()),
+            total_pagamento: dto.total_pagamento,
+            created_at: state.clock.now(),
+        })?;
+
+        Self::touch(state, cart)
+    }
+
+    /// DELETE /carts/:id/payments/:payment_id - Remove um pagamento
+    pub fn remove_payment(state: &AppState, id: i64, payment_id: i64) -> AppResult<CartDto> {
+        let cart = Self::require(state, id, CartStatus::Payment)?;
+        if !state.carts.delete_payment(id, payment_id)? {
+            return Err(AppError::not_found(format!("Cart payment with id {} not found", payment_id)));
+        }
+        Self::touch(state, cart)
+    }
+
+    /// POST /carts/:id/finalize - Grava o carrinho como venda (o desconto e o
+    /// acréscimo do carrinho são rateados entre os itens) e o encerra
+    pub fn finalize(state: &AppState, id: i64, dto: FinalizeCartDto) -> AppResult<CartFinalizedDto> {
+        let cart = Self::require(state, id, CartStatus::Payment)?;
+
+        let now = state.clock.now();
+        let dh_emi = dto.dh_emi
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| now.with_timezone(&state.clock.timezone()).to_rfc3339());
+        let emitted_at = state.clock
+            .parse_instant(&dh_emi)
+            .ok_or_else(|| AppError::validation("dh_emi", format!("Data/hora inválida: '{}'", dh_emi)))?;
+
+        // Preços e promoções como ficarão na venda, emitida em `dh_emi`
+        let view = Self::view_at(state, cart.clone(), emitted_at)?;
+        if !view.remaining.is_zero() {
+            return Err(AppError::validation("payments", format!("Faltam {} para completar o pagamento", view.remaining)));
+        }
+
+        let items = view.items.iter().map(|i| VendaItemEntity { id: None, ..i.clone() }).collect();
+        let payments = view.payments.iter().map(CartPaymentEntity::to_venda_payment).collect();
+
+        let mut venda = VendaEntity::new(dto.tip, dto.mod_, dto.serie, dto.nr_nf, dto.cnpj, dh_emi, view.total, dto.chave);
+        venda.discount = cart.discount;
+        venda.addition = cart.addition;
+        venda.doc_destinatario = cart.doc_destinatario.clone();
+        venda.protocolo = dto.protocolo;
+        venda.file_path = dto.file_path;
+        venda.created_at = now;
+        venda.updated_at = now;
+
+        // A venda e o encerramento do carrinho vão na mesma transação
+        let created = VendaService::create_from_cart(state, id, &venda, items, payments)?;
+
+        // A venda já foi gravada: o carrinho encerrado é relido, e uma falha na
+        // leitura só troca a resposta pelo carrinho como ficou na transação
+        let finalized = state.carts.find_by_id(id).ok().flatten().unwrap_or_else(|| CartEntity {
+            status: CartStatus::Finalized,
+            venda_id: Some(created.id),
+            updated_at: now,
+            closed_at: Some(now),
+            ..cart.clone()
+        });
+        Self::record(state, "finalize", Some(&cart), Some(&finalized));
+
+        Ok(CartFinalizedDto { cart: CartDto { cart: finalized, ..view }, venda: created })
+    }
+
+    /// POST /carts/:id/abort - Descarta o carrinho sem gerar venda
+    pub fn abort(state: &AppState, id: i64) -> AppResult<CartDto> {
+        let cart = state.carts.find_by_id(id)?
+            .ok_or_else(|| AppError::not_found(format!("Cart with id {} not found", id)))?;
+
+        let before = cart.clone();
+        let aborted = Self::transition(state, cart, CartStatus::Aborted)?;
+        Self::record(state, "abort", Some(&before), Some(&aborted.cart));
+        Ok(aborted)
+    }
+
+    /// Carrinho `id`, que precisa estar na etapa `status`
+    fn require(state: &AppState, id: i64, status: CartStatus) -> AppResult<CartEntity> {
+        let cart = state.carts.find_by_id(id)?
+            .ok_or_else(|| AppError::not_found(format!("Cart with id {} not found", id)))?;
+        if cart.status != status {
+            return Err(AppError::Conflict(format!(
+                "Carrinho {} está em '{}', esperado '{}'",
+                id,
+                cart.status.as_str(),
+                status.as_str()
+            )));
+        }
+        Ok(cart)
+    }
+
+    /// Passa o carrinho para `next`, se a máquina de estados permitir
+    fn transition(state: &AppState, mut cart: CartEntity, next: CartStatus) -> AppResult<CartDto> {
+        if !cart.status.can_become(next) {
+            return Err(AppError::Conflict(format!(
+                "Carrinho {} não pode passar de '{}' para '{}'",
+                cart.id.unwrap_or_default(),
+                cart.status.as_str(),
+                next.as_str()
+            )));
+        }
+
+        let now = state.clock.now();
+        cart.status = next;
+        if !next.is_active() {
+            cart.closed_at = Some(now);
+        }
+        cart.updated_at = now;
+        state.carts.save(&cart)?;
+        Self::view(state, cart)
+    }
+
+    /// Grava o carrinho com `updated_at` atual e devolve a visão completa
+    fn touch(state: &AppState, mut cart: CartEntity) -> AppResult<CartDto> {
+        cart.updated_at = state.clock.now();
+        state.carts.save(&cart)?;
+        Self::view(state, cart)
+    }
+
+    /// Confere a unidade, as casas decimais e o desconto manual do item
+    fn check_item(state: &AppState, item: &CartItemEntity) -> AppResult<()> {
+        if item.quantidade.is_negative() || item.quantidade.is_zero() {
+            return Err(AppError::validation("quantidade", "Quantidade deve ser maior que zero"));
+        }
+        UnitService::check_quantity(state, &item.produto_medida, item.quantidade, "quantidade")?;
+        if item.desconto.is_negative() {
+            return Err(AppError::validation("desconto", "Desconto não pode ser negativo"));
+        }
+        if item.desconto > item.quantidade * item.preco_unitario {
+            return Err(AppError::validation("desconto", "Desconto maior que o valor do item"));
+        }
+        Ok(())
+    }
+
+    fn view(state: &AppState, cart: CartEntity) -> AppResult<CartDto> {
+        let at = state.clock.now();
+        Self::view_at(state, cart, at)
+    }
+
+    /// Carrinho com os itens precificados em `at` e os totais
+    fn view_at(state: &AppState, cart: CartEntity, at: DateTime<Utc>) -> AppResult<CartDto> {
+        let id = cart.id.unwrap_or_default();
+        let items = state.carts.find_items(id)?.iter().map(CartItemEntity::to_venda_item).collect();
+        let items = VendaService::price_items(state, items, at)?;
+        let payments = state.carts.find_payments(id)?;
+
+        let subtotal: Money = items.iter().map(VendaItemEntity::gross_total).sum();
+        let items_discount: Money = items.iter().map(|i| i.desconto).sum();
+        let total = subtotal - items_discount - cart.discount + cart.addition;
+        let paid: Money = payments.iter().map(|p| p.total_pagamento).sum();
+        let balance = total - paid;
+
+        Ok(CartDto {
+            cart,
+            items,
+            payments,
+            subtotal,
+            items_discount,
+            total,
+            paid,
+            remaining: if balance.is_negative() { Money::ZERO } else { balance },
+            change: if balance.is_negative() { -balance } else { Money::ZERO },
+        })
+    }
+
+    fn record(state: &AppState, action: &str, before: Option<&CartEntity>, after: Option<&CartEntity>) {
+        let id = after.or(before).and_then(|c| c.id).map(|id| id.to_string());
+        HistoryService::record(state, action, ENTITY_CART, id, before, after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::entities::Quantity;
+    use crate::test_support::{on_both_backends, save_product};
+    use std::sync::Arc;
+
+    fn add(state: &AppState, id: i64, barcode: &str, quantidade: Option<i64>) -> AppResult<CartDto> {
+        let dto = AddCartItemDto {
+            barcode: barcode.to_string(),
+            quantidade: quantidade.map(Quantity::from_int),
+            ..Default::default()
+        };
+        CartService::add_item(state, id, dto)
+    }
+
+    fn pay(state: &AppState, id: i64, code: &str, cents: i64) -> AppResult<CartDto> {
+        let dto = AddCartPaymentDto { code: code.to_string(), name: None, total_pagamento: Money::from_cents(cents) };
+        CartService::add_payment(state, id, dto)
+    }
+
+    fn finalize_dto() -> FinalizeCartDto {
+        FinalizeCartDto {
+            tip: 1,
+            mod_: 65,
+            serie: "1".to_string(),
+            nr_nf: 42,
+            cnpj: "28095955000199".to_string(),
+            chave: "CFe42".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn cart_flow(state: &AppState) {
+        save_product(state, "001", Money::from_cents(1000), Quantity::ZERO);
+        save_product(state, "002", Money::from_cents(350), Quantity::ZERO);
+
+        let cart = CartService::open(state, OpenCartDto { user: Some("ana".to_string()), doc_destinatario: None }).unwrap();
+        let id = cart.cart.id.unwrap();
+        assert_eq!(CartService::open(state, OpenCartDto::default()).unwrap_err().code(), "CONFLICT");
+
+        add(state, id, "001", Some(2)).unwrap();
+        let view = add(state, id, "002", None).unwrap();
+        assert_eq!((view.subtotal, view.total), (Money::from_cents(2350), Money::from_cents(2350)));
+        assert_eq!(add(state, id, "999", None).unwrap_err().code(), "NOT_FOUND");
+        assert_eq!(add(state, id, "001", Some(0)).unwrap_err().code(), "VALIDATION_ERROR");
+
+        // Recuperação depois de reiniciar: o carrinho em andamento continua lá
+        let current = CartService::current(state).unwrap().unwrap();
+        assert_eq!((current.cart.id, current.items.len()), (Some(id), 2));
+
+        let arroz = current.items[0].id.unwrap();
+        let update = UpdateCartItemDto { quantidade: Some(Quantity::from_int(3)), desconto: Some(Money::from_cents(100)) };
+        let view = CartService::update_item(state, id, arroz, update).unwrap();
+        assert_eq!((view.items_discount, view.total), (Money::from_cents(100), Money::from_cents(3250)));
+        let too_much = UpdateCartItemDto { desconto: Some(Money::from_int(31)), ..Default::default() };
+        assert_eq!(CartService::update_item(state, id, arroz, too_much).unwrap_err().code(), "VALIDATION_ERROR");
+
+        let discount = CartDiscountDto { discount: Some(Money::from_cents(250)), addition: None };
+        assert_eq!(CartService::set_discount(state, id, discount).unwrap().total, Money::from_int(30));
+
+        // Pagamentos só depois do checkout; itens só antes
+        assert_eq!(pay(state, id, "01", 1000).unwrap_err().code(), "CONFLICT");
+        assert_eq!(CartService::checkout(state, id).unwrap().cart.status, CartStatus::Payment);
+        assert_eq!(add(state, id, "002", None).unwrap_err().code(), "CONFLICT");
+        assert_eq!(CartService::checkout(state, id).unwrap_err().code(), "CONFLICT");
+        assert_eq!(CartService::reopen(state, id).unwrap().cart.status, CartStatus::Open);
+        CartService::checkout(state, id).unwrap();
+
+        assert_eq!(pay(state, id, "77", 1000).unwrap_err().code(), "VALIDATION_ERROR");
+        assert_eq!(pay(state, id, "03", 3001).unwrap_err().code(), "VALIDATION_ERROR");
+        let view = pay(state, id, "03", 1000).unwrap();
+        assert_eq!((view.paid, view.remaining), (Money::from_int(10), Money::from_int(20)));
+        assert_eq!(view.payments[0].name, "Cartão de Crédito");
+        assert_eq!(CartService::reopen(state, id).unwrap_err().code(), "CONFLICT");
+        assert_eq!(CartService::finalize(state, id, finalize_dto()).unwrap_err().code(), "VALIDATION_ERROR");
+
+        let view = pay(state, id, "01", 2500).unwrap();
+        assert_eq!((view.remaining, view.change), (Money::ZERO, Money::from_int(5)));
+
+        let finalized = CartService::finalize(state, id, finalize_dto()).unwrap();
+        assert_eq!(finalized.cart.cart.status, CartStatus::Finalized);
+        assert_eq!(finalized.cart.cart.venda_id, Some(finalized.venda.id));
+        let saved = state.carts.find_by_id(id).unwrap().unwrap();
+        assert_eq!((saved.status, saved.closed_at), (CartStatus::Finalized, finalized.cart.cart.closed_at));
+        assert!(CartService::current(state).unwrap().is_none());
+        assert_eq!(CartService::abort(state, id).unwrap_err().code(), "CONFLICT");
+
+        let venda = VendaService::find_with_relations(state, finalized.venda.id).unwrap().unwrap();
+        assert_eq!((venda.venda.total, venda.venda.discount), (Money::from_int(30), Money::from_cents(250)));
+        let rateio: Money = venda.itens.iter().map(|i| i.desconto_rat).sum();
+        assert_eq!(rateio, Money::from_cents(250));
+        assert_eq!(venda.pagamentos.len(), 2);
+
+        // Uma finalização concorrente que passou da checagem não grava outra venda
+        let again = VendaService::create_from_cart(state, id, &venda.venda, venda.itens.clone(), venda.pagamentos.clone());
+        assert_eq!(again.unwrap_err().code(), "CONFLICT");
+        assert!(VendaService::find_by_id(state, finalized.venda.id + 1).unwrap().is_none());
+
+        // Um carrinho abortado não gera venda e libera a abertura de outro
+        let second = CartService::open(state, OpenCartDto::default()).unwrap().cart.id.unwrap();
+        add(state, second, "002", None).unwrap();
+        assert_eq!(CartService::abort(state, second).unwrap().cart.status, CartStatus::Aborted);
+        assert!(CartService::open(state, OpenCartDto::default()).is_ok());
+    }
+
+    #[test]
+    fn test_cart_flow() {
+        on_both_backends(cart_flow);
+    }
+
+    #[test]
+    fn test_finalize_defaults_emission_to_now() {
+        let state = AppState::in_memory();
+        let now = DateTime::parse_from_rfc3339("2024-06-15T22:30:00-03:00").unwrap().with_timezone(&Utc);
+        let state = state.clone().with_clock(Arc::new(FixedClock { now, timezone: state.clock.timezone() }));
+        save_product(&state, "001", Money::from_int(5), Quantity::ZERO);
+
+        let id = CartService::open(&state, OpenCartDto::default()).unwrap().cart.id.unwrap();
+        add(&state, id, "001", None).unwrap();
+        CartService::checkout(&state, id).unwrap();
+        pay(&state, id, "04", 500).unwrap();
+        let finalized = CartService::finalize(&state, id, finalize_dto()).unwrap();
+
+        let venda = VendaService::find_by_id(&state, finalized.venda.id).unwrap().unwrap();
+        assert_eq!(state.clock.parse_instant(&venda.dh_emi), Some(now));
+    }
+}
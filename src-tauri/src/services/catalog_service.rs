@@ -0,0 +1,406 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use crate::dtos::{
+    CreateProductDto, ExportFileDto, ImportAction, ImportRowDto, ProductImportDto, ProductImportReportDto, UpdateProductDto,
+};
+use crate::entities::{Money, ProductEntity, Quantity};
+use crate::error::{AppError, AppResult, FieldError};
+use crate::repositories::search_terms;
+use crate::services::ProductService;
+use crate::spreadsheet::{read_rows, write_rows, Cell, SheetFormat};
+use crate::state::AppState;
+
+/// Campos da planilha, na ordem da exportação, com os outros nomes de
+/// cabeçalho aceitos na importação
+const FIELDS: &[(&str, &[&str])] = &[
+    ("code", &["codigo", "cod"]),
+    ("name", &["nome", "descricao", "produto"]),
+    ("active", &["ativo"]),
+    ("balance", &["saldo", "estoque"]),
+    ("price", &["preco", "preco venda"]),
+    ("unit", &["unidade", "un"]),
+    ("purchase_unit", &["unidade compra", "un compra"]),
+    ("purchase_factor", &["fator", "fator compra"]),
+    ("gtin", &["ean", "codigo de barras"]),
+    ("ncm", &[]),
+    ("cest", &[]),
+    ("cfop", &[]),
+    ("origem", &[]),
+    ("cst", &["csosn"]),
+    ("min_stock", &["estoque minimo", "minimo"]),
+    ("max_stock", &["estoque maximo", "maximo"]),
+];
+
+/// Cabeçalho comparável: sem acentos, maiúsculas e pontuação
+fn normalize(header: &str) -> String {
+    search_terms(header).join(" ")
+}
+
+/// Número no formato brasileiro (`1.234,56`) ou com ponto decimal (`1234.56`,
+/// como vem do XLSX). Com vírgula, os pontos são separadores de milhar.
+fn parse_decimal<T: FromStr>(text: &str) -> Option<T> {
+    let text = text.trim().trim_start_matches("R$").trim();
+    let normalized = if text.contains(',') {
+        text.replace('.', "").replace(',', ".")
+    } else {
+        text.to_string()
+    };
+    normalized.parse().ok()
+}
+
+fn parse_active(text: &str) -> Option<i32> {
+    match normalize(text).as_str() {
+        "1" | "s" | "sim" | "true" | "ativo" => Some(1),
+        "0" | "n" | "nao" | "false" | "inativo" => Some(0),
+        _ => None,
+    }
+}
+
+/// Posição de cada campo na planilha, a partir do cabeçalho e do mapeamento
+fn resolve_columns(header: &[String], mapping: &BTreeMap<String, String>) -> AppResult<Vec<(&'static str, usize)>> {
+    let headers: Vec<String> = header.iter().map(|h| normalize(h)).collect();
+    let position = |name: &str| headers.iter().position(|h| *h == normalize(name));
+
+    let mut columns = Vec::new();
+    for &(field, aliases) in FIELDS {
+        let index = match mapping.get(field) {
+            Some(name) => Some(position(name).ok_or_else(|| {
+                AppError::validation("columns", format!("Coluna '{}' ({}) não encontrada no cabeçalho", name, field))
+            })?),
+            None => std::iter::once(field).chain(aliases.iter().copied()).find_map(position),
+        };
+        if let Some(index) = index {
+            columns.push((field, index));
+        }
+    }
+
+    if !columns.iter().any(|(field, _)| *field == "code") {
+        return Err(AppError::validation("columns", "Coluna do código (code) não encontrada no cabeçalho"));
+    }
+    Ok(columns)
+}
+
+/// Converte uma linha em alteração de produto; células vazias não alteram o campo
+fn parse_row(cells: &[String], columns: &[(&'static str, usize)]) -> (String, UpdateProductDto, Vec<FieldError>) {
+    let mut code = String::new();
+    let mut dto = UpdateProductDto::default();
+    let mut errors = Vec::new();
+
+    for &(field, index) in columns {
+        let Some(value) = cells.get(index).map(|v| v.trim()).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        let text = Some(value.to_string());
+        let mut invalid = |message: &str| errors.push(FieldError::new(field, format!("{}: '{}'", message, value)));
+
+        match field {
+            "code" => code = value.to_string(),
+            "name" => dto.name = text,
+            "active" => match parse_active(value) {
+                Some(active) => dto.active = Some(active),
+                None => invalid("Use 1 ou 0, sim ou não"),
+            },
+            "balance" => match parse_decimal::<Quantity>(value) {
+                Some(balance) => dto.balance = Some(balance),
+                None => invalid("Saldo inválido"),
+            },
+            "price" => match parse_decimal::<Money>(value) {
+                Some(price) => dto.fiscal.price = Some(price),
+                None => invalid("Preço inválido"),
+            },
+            "purchase_factor" => match parse_decimal::<Quantity>(value) {
+                Some(factor) => dto.fiscal.purchase_factor = Some(factor),
+                None => invalid("Fator inválido"),
+            },
+            "min_stock" => match parse_decimal::<Quantity>(value) {
+                Some(min_stock) => dto.fiscal.min_stock = Some(min_stock),
+                None => invalid("Estoque mínimo inválido"),
+            },
+            "max_stock" => match parse_decimal::<Quantity>(value) {
+                Some(max_stock) => dto.fiscal.max_stock = Some(max_stock),
+                None => invalid("Estoque máximo inválido"),
+            },
+            "origem" => match value.parse() {
+                Ok(origem) => dto.fiscal.origem = Some(origem),
+                Err(_) => invalid("Origem inválida"),
+            },
+            "unit" => dto.fiscal.unit = text,
+            "purchase_unit" => dto.fiscal.purchase_unit = text,
+            "gtin" => dto.fiscal.gtin = text,
+            "ncm" => dto.fiscal.ncm = text,
+            "cest" => dto.fiscal.cest = text,
+            "cfop" => dto.fiscal.cfop = text,
+            "cst" => dto.fiscal.cst = text,
+            _ => {}
+        }
+    }
+
+    (code, dto, errors)
+}
+
+/// Campos comparados para saber se a linha muda o cadastro
+#[allow(clippy::type_complexity)]
+fn snapshot(p: &ProductEntity) -> (&str, i32, Quantity, Money, &str, [&Option<String>; 6], Quantity, i32, [Option<Quantity>; 2]) {
+    let texts = [&p.purchase_unit, &p.gtin, &p.ncm, &p.cest, &p.cfop, &p.cst];
+    (&p.name, p.active, p.balance, p.price, &p.unit, texts, p.purchase_factor, p.origem, [p.min_stock, p.max_stock])
+}
+
+pub struct CatalogService;
+
+impl CatalogService {
+    /// POST /products/import - Importa (ou, com `dry_run`, só valida) uma
+    /// planilha de produtos, criando ou atualizando pelo código.
+    ///
+    /// As linhas são independentes: as válidas são gravadas mesmo que outras
+    /// sejam recusadas, e o relatório traz o motivo de cada recusa.
+    pub fn import(state: &AppState, dto: ProductImportDto) -> AppResult<ProductImportReportDto> {
+        let content = BASE64
+            .decode(dto.content.trim())
+            .map_err(|_| AppError::validation("content", "Arquivo deve ser enviado em base64"))?;
+        let format = dto.format.unwrap_or_else(|| SheetFormat::detect(&content));
+        let rows = read_rows(&content, format).map_err(|e| AppError::validation("content", e))?;
+
+        let known: Vec<&str> = FIELDS.iter().map(|(field, _)| *field).collect();
+        if let Some(field) = dto.columns.keys().find(|f| !known.contains(&f.as_str())) {
+            return Err(AppError::validation("columns", format!("Campo desconhecido: {}", field)));
+        }
+        let Some(header) = rows.first() else {
+            return Err(AppError::validation("content", "Planilha vazia"));
+        };
+        let columns = resolve_columns(header, &dto.columns)?;
+
+        let mut report = ProductImportReportDto {
+            dry_run: dto.dry_run,
+            total: 0,
+            created: 0,
+            updated: 0,
+            unchanged: 0,
+            failed: 0,
+            rows: Vec::new(),
+        };
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for (index, cells) in rows.iter().enumerate().skip(1) {
+            if cells.iter().all(|c| c.trim().is_empty()) {
+                continue;
+            }
+            let line = index + 1;
+            let row = Self::import_row(state, line, cells, &columns, &mut seen, dto.dry_run)?;
+
+            report.total += 1;
+            match row.action {
+                ImportAction::Create => report.created += 1,
+                ImportAction::Update => report.updated += 1,
+                ImportAction::Unchanged => report.unchanged += 1,
+                ImportAction::Error => report.failed += 1,
+            }
+            report.rows.push(row);
+        }
+
+        Ok(report)
+    }
+
+    /// GET /products/export - Cadastro completo, com saldos, nas mesmas
+    /// colunas aceitas pela importação
+    pub fn export(state: &AppState, format: SheetFormat) -> AppResult<Vec<u8>> {
+        let text = |value: &Option<String>| Cell::Text(value.clone().unwrap_or_default());
+        let number = |value: Option<Quantity>| value.map(|q| Cell::Number(q.to_string())).unwrap_or(Cell::Text(String::new()));
+
+        let mut rows = vec![FIELDS.iter().map(|(field, _)| Cell::Text(field.to_string())).collect::<Vec<_>>()];
+        for p in ProductService::find_all(state)? {
+            rows.push(vec![
+                Cell::Text(p.code.clone()),
+                Cell::Text(p.name.clone()),
+                Cell::Number(p.active.to_string()),
+                Cell::Number(p.balance.to_string()),
+                Cell::Number(p.price.to_string()),
+                Cell::Text(p.unit.clone()),
+                text(&p.purchase_unit),
+                Cell::Number(p.purchase_factor.to_string()),
+                text(&p.gtin),
+                text(&p.ncm),
+                text(&p.cest),
+                text(&p.cfop),
+                Cell::Number(p.origem.to_string()),
+                text(&p.cst),
+                number(p.min_stock),
+                number(p.max_stock),
+            ]);
+        }
+
+        write_rows(&rows, format).map_err(|e| AppError::Internal(format!("Failed to export products: {}", e)))
+    }
+
+    /// Nome do arquivo exportado
+    pub fn export_filename(format: SheetFormat) -> String {
+        format!("produtos.{}", format.extension())
+    }
+
+    /// Exportação com o conteúdo em base64, para o `invoke` do Tauri
+    pub fn export_file(state: &AppState, format: SheetFormat) -> AppResult<ExportFileDto> {
+        let content = Self::export(state, format)?;
+        Ok(ExportFileDto {
+            filename: Self::export_filename(format),
+            content_type: format.content_type().to_string(),
+            content: BASE64.encode(content),
+        })
+    }
+
+    fn import_row(
+        state: &AppState,
+        line: usize,
+        cells: &[String],
+        columns: &[(&'static str, usize)],
+        seen: &mut HashMap<String, usize>,
+        dry_run: bool,
+    ) -> AppResult<ImportRowDto> {
+        let (code, dto, mut errors) = parse_row(cells, columns);
+        let mut row = ImportRowDto { line, code: code.clone(), action: ImportAction::Error, product_id: None, errors: Vec::new() };
+
+        if code.is_empty() {
+            row.errors = vec![FieldError::new("code", "Código obrigatório")];
+            return Ok(row);
+        }
+        if let Some(first) = seen.get(&code) {
+            row.errors = vec![FieldError::new("code", format!("Código repetido (já aparece na linha {})", first))];
+            return Ok(row);
+        }
+        seen.insert(code.clone(), line);
+
+        // Monta o produto como ficaria, para validar antes de gravar
+        let existing = state.products.find_by_code(&code)?;
+        let mut preview = existing.clone().unwrap_or_else(|| ProductEntity::new(code.clone(), String::new()));
+        preview.name = dto.name.clone().unwrap_or(preview.name);
+        preview.active = dto.active.unwrap_or(preview.active);
+        preview.balance = dto.balance.unwrap_or(preview.balance);
+        ProductService::apply_fiscal(&mut preview, dto.fiscal.clone());
+        for result in [preview.validate(), ProductService::check_units(state, &preview)] {
+            match result {
+                Err(AppError::Validation { fields, .. }) => {
+                    // Campos que já falharam na conversão não são repetidos
+                    let failed: Vec<String> = errors.iter().map(|e| e.field.clone()).collect();
+                    errors.extend(fields.into_iter().filter(|f| !failed.contains(&f.field)));
+                }
+                Err(e) => return Err(e),
+                Ok(()) => {}
+            }
+        }
+
+        row.product_id = existing.as_ref().and_then(|p| p.id);
+        if !errors.is_empty() {
+            row.errors = errors;
+            return Ok(row);
+        }
+        row.action = match &existing {
+            None => ImportAction::Create,
+            Some(existing) if snapshot(existing) == snapshot(&preview) => ImportAction::Unchanged,
+            Some(_) => ImportAction::Update,
+        };
+        if dry_run || row.action == ImportAction::Unchanged {
+            return Ok(row);
+        }
+
+        let saved = match existing.and_then(|p| p.id) {
+            Some(id) => ProductService::update(state, id, dto),
+            None => {
+                let create = CreateProductDto { code, name: preview.name, fiscal: dto.fiscal };
+                ProductService::create(state, create).and_then(|created| {
+                    let id = created.id.unwrap_or_default();
+                    if dto.active.is_none() && dto.balance.is_none() {
+                        return Ok(created);
+                    }
+                    let rest = UpdateProductDto { active: dto.active, balance: dto.balance, ..Default::default() };
+                    ProductService::update(state, id, rest)
+                })
+            }
+        };
+
+        match saved {
+            Ok(product) => row.product_id = product.id,
+            Err(AppError::Validation { fields, .. }) => {
+                row.action = ImportAction::Error;
+                row.errors = fields;
+            }
+            Err(AppError::Conflict(message)) => {
+                row.action = ImportAction::Error;
+                row.errors = vec![FieldError::new("code", message)];
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::StockMovementFilter;
+    use crate::test_support::on_both_backends;
+
+    fn import(state: &AppState, csv: &str, columns: &[(&str, &str)], dry_run: bool) -> AppResult<ProductImportReportDto> {
+        let dto = ProductImportDto {
+            format: None,
+            content: BASE64.encode(csv),
+            columns: columns.iter().map(|(f, h)| (f.to_string(), h.to_string())).collect(),
+            dry_run,
+        };
+        CatalogService::import(state, dto)
+    }
+
+    fn import_flow(state: &AppState) {
+        ProductService::create(state, CreateProductDto { code: "001".to_string(), name: "Arroz".to_string(), fiscal: Default::default() }).unwrap();
+
+        let csv = "Código;Descrição;Preço;Saldo;NCM;Situação;Estoque mínimo\n\
+                   001;Arroz 5kg;\"1.234,50\";10;10063021;sim;2\n\
+                   002;Feijão;8,9;;;\n\
+                   \n\
+                   003;;abc;;123;talvez\n\
+                   002;Feijão de novo;;;;\n";
+        let columns = [("active", "situacao")];
+
+        let report = import(state, csv, &columns, true).unwrap();
+        let summary = |r: &ProductImportReportDto| (r.total, r.created, r.updated, r.unchanged, r.failed);
+        assert_eq!(summary(&report), (4, 1, 1, 0, 2));
+        assert_eq!(ProductService::find_all(state).unwrap().len(), 1);
+
+        let failed = &report.rows[2];
+        assert_eq!((failed.line, failed.code.as_str(), failed.action), (5, "003", ImportAction::Error));
+        let fields: Vec<&str> = failed.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["active", "price", "name", "ncm"]);
+        assert_eq!(report.rows[3].errors[0].message, "Código repetido (já aparece na linha 3)");
+
+        let report = import(state, csv, &columns, false).unwrap();
+        assert_eq!(summary(&report), (4, 1, 1, 0, 2));
+        let arroz = ProductService::find_by_code(state, "001").unwrap().unwrap();
+        assert_eq!((arroz.name.as_str(), arroz.price, arroz.balance), ("Arroz 5kg", Money::from_cents(123450), Quantity::from_int(10)));
+        assert_eq!(arroz.min_stock, Some(Quantity::from_int(2)));
+        let feijao = ProductService::find_by_code(state, "002").unwrap().unwrap();
+        assert_eq!((feijao.price, feijao.balance, feijao.active), (Money::from_cents(890), Quantity::ZERO, 1));
+        assert_eq!(report.rows[1].product_id, feijao.id);
+
+        // O saldo importado entra no livro como ajuste
+        let filter = StockMovementFilter { product_id: arroz.id, ..Default::default() };
+        assert_eq!(state.stock.find(&filter).unwrap().len(), 1);
+
+        // A exportação volta na importação sem alterar nada, nos dois formatos
+        for format in [SheetFormat::Csv, SheetFormat::Xlsx] {
+            let content = CatalogService::export(state, format).unwrap();
+            let dto = ProductImportDto { content: BASE64.encode(&content), ..Default::default() };
+            let report = CatalogService::import(state, dto).unwrap();
+            assert_eq!(summary(&report), (2, 0, 0, 2, 0), "{:?}", format);
+        }
+
+        let err = import(state, "nome;preco\nArroz;1\n", &[], true).unwrap_err();
+        assert_eq!(err.code(), "VALIDATION_ERROR");
+        let err = import(state, csv, &[("preço", "Preço")], true).unwrap_err();
+        assert_eq!(err.to_string(), "Campo desconhecido: preço");
+    }
+
+    #[test]
+    fn test_import_export() {
+        on_both_backends(import_flow);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::dtos::{CategoryProductsQuery, CategoryTreeDto, CreateCategoryDto, ReassignProductsDto, UpdateCategoryDto};
+use crate::error::{AppError, AppResult};
+use crate::entities::{CategoryEntity, ProductEntity};
+use crate::services::history_service::{HistoryService, ENTITY_CATEGORY};
+use crate::services::ProductService;
+use crate::state::AppState;
+
+/// Separador dos nomes no caminho de uma categoria
+const PATH_SEPARATOR: &str = " > ";
+
+/// Todas as categorias, carregadas de uma vez para navegar pela árvore
+pub(crate) struct CategoryTree {
+    categories: Vec<CategoryEntity>,
+}
+
+impl CategoryTree {
+    pub(crate) fn load(state: &AppState) -> AppResult<Self> {
+        Ok(Self { categories: state.categories.find_all()? })
+    }
+
+    pub(crate) fn get(&self, id: i64) -> Option<&CategoryEntity> {
+        self.categories.iter().find(|c| c.id == Some(id))
+    }
+
+    /// Filhas diretas de `parent` (`None` são as seções principais), por nome
+    pub(crate) fn children(&self, parent: Option<i64>) -> impl Iterator<Item = &CategoryEntity> {
+        self.categories.iter().filter(move |c| c.parent_id == parent)
+    }
+
+    /// A própria categoria e todas as descendentes
+    pub(crate) fn subtree(&self, id: i64) -> Vec<i64> {
+        let mut ids = vec![id];
+        let mut i = 0;
+        while i < ids.len() {
+            let children: Vec<i64> = self.children(Some(ids[i])).filter_map(|c| c.id).filter(|c| !ids.contains(c)).collect();
+            ids.extend(children);
+            i += 1;
+        }
+        ids
+    }
+
+    /// Todas as categorias em pré-ordem: cada uma seguida das subcategorias
+    pub(crate) fn preorder(&self) -> Vec<&CategoryEntity> {
+        let mut ordered: Vec<&CategoryEntity> = Vec::new();
+        let mut stack: Vec<&CategoryEntity> = self.children(None).collect();
+        stack.reverse();
+        while let Some(category) = stack.pop() {
+            if ordered.iter().any(|c| c.id == category.id) {
+                continue;
+            }
+            ordered.push(category);
+            let start = stack.len();
+            stack.extend(self.children(category.id));
+            stack[start..].reverse();
+        }
+        ordered
+    }
+
+    /// Nomes desde a raiz até a categoria
+    pub(crate) fn path(&self, id: i64) -> String {
+        let mut names = Vec::new();
+        let mut current = self.get(id);
+        while let Some(category) = current {
+            names.push(category.name.as_str());
+            // Limite defensivo contra ciclos gravados fora do serviço
+            if names.len() > self.categories.len() {
+                break;
+            }
+            current = category.parent_id.and_then(|p| self.get(p));
+        }
+        names.reverse();
+        names.join(PATH_SEPARATOR)
+    }
+
+    fn node(&self, category: &CategoryEntity) -> CategoryTreeDto {
+        let id = category.id.unwrap_or_default();
+        CategoryTreeDto {
+            category: category.clone(),
+            path: self.path(id),
+            children: self.children(Some(id)).filter(|c| c.id != Some(id)).map(|c| self.node(c)).collect(),
+        }
+    }
+}
+
+pub struct CategoryService;
+
+impl CategoryService {
+    /// POST /categories - Cria uma categoria
+    pub fn create(state: &AppState, dto: CreateCategoryDto) -> AppResult<CategoryEntity> {
+        let now = state.clock.now();
+        let category = CategoryEntity {
+            created_at: now,
+            updated_at: now,
+            ..CategoryEntity::new(dto.name.trim().to_string(), dto.parent_id.filter(|p| *p != 0))
+        };
+        Self::check(state, &category)?;
+
+        let saved = state.categories.save(&category)?;
+        Self::record(state, "create", None, Some(&saved));
+        Ok(saved)
+    }
+
+    /// GET /categories/:id - Busca uma categoria por ID
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<CategoryEntity>> {
+        state.categories.find_by_id(id)
+    }
+
+    /// GET /categories - Lista todas as categorias, por nome
+    pub fn find_all(state: &AppState) -> AppResult<Vec<CategoryEntity>> {
+        state.categories.find_all()
+    }
+
+    /// GET /categories/tree - Árvore de categorias a partir das seções principais
+    pub fn tree(state: &AppState) -> AppResult<Vec<CategoryTreeDto>> {
+        let tree = CategoryTree::load(state)?;
+        Ok(tree.children(None).map(|c| tree.node(c)).collect())
+    }
+
+    /// PUT /categories/:id - Renomeia ou move uma categoria
+    pub fn update(state: &AppState, id: i64, dto: UpdateCategoryDto) -> AppResult<CategoryEntity> {
+        let existing = Self::find_by_id(state, id)?.ok_or_else(|| not_found(id))?;
+
+        let updated = CategoryEntity {
+            name: dto.name.map(|n| n.trim().to_string()).unwrap_or_else(|| existing.name.clone()),
+            parent_id: match dto.parent_id {
+                Some(0) => None,
+                Some(parent) => Some(parent),
+                None => existing.parent_id,
+            },
+            updated_at: state.clock.now(),
+            ..existing.clone()
+        };
+        Self::check(state, &updated)?;
+
+        let saved = state.categories.save(&updated)?;
+        Self::record(state, "update", Some(&existing), Some(&saved));
+        Ok(saved)
+    }
+
+    /// DELETE /categories/:id - Remove a categoria, movendo os produtos e as
+    /// subcategorias para `reassign_to` ou, se ausente, para a categoria pai
+    /// (produtos de uma seção principal ficam sem categoria)
+    pub fn delete(state: &AppState, id: i64, reassign_to: Option<i64>) -> AppResult<()> {
+        let existing = Self::find_by_id(state, id)?.ok_or_else(|| not_found(id))?;
+        let tree = CategoryTree::load(state)?;
+
+        let target = match reassign_to.filter(|t| *t != 0) {
+            Some(target) if tree.subtree(id).contains(&target) => {
+                return Err(AppError::validation("reassignTo", "Destino não pode ser a própria categoria nem uma subcategoria dela"));
+            }
+            Some(target) if tree.get(target).is_none() => {
+                return Err(AppError::validation("reassignTo", format!("Categoria {} não encontrada", target)));
+            }
+            Some(target) => Some(target),
+            None => existing.parent_id,
+        };
+
+        let now = state.clock.now();
+        for child in tree.children(Some(id)) {
+            let moved = CategoryEntity { parent_id: target, updated_at: now, ..child.clone() };
+            let saved = state.categories.save(&moved)?;
+            Self::record(state, "update", Some(child), Some(&saved));
+        }
+        for product in state.products.find_by_categories(&[id])? {
+            Self::move_product(state, product, target)?;
+        }
+
+        state.categories.delete(id)?;
+        Self::record(state, "delete", Some(&existing), None);
+        Ok(())
+    }
+
+    /// GET /categories/:id/products - Produtos da categoria e, por padrão, das
+    /// subcategorias
+    pub fn products(state: &AppState, id: i64, query: &CategoryProductsQuery) -> AppResult<Vec<ProductEntity>> {
+        let tree = CategoryTree::load(state)?;
+        if tree.get(id).is_none() {
+            return Err(not_found(id));
+        }
+
+        let ids = if query.subcategories.unwrap_or(true) { tree.subtree(id) } else { vec![id] };
+        Ok(state.products.find_by_categories(&ids)?
+            .into_iter()
+            .filter(|p| query.active.is_none_or(|active| (p.active == 1) == active))
+            .collect())
+    }
+
+    /// POST /categories/reassign - Move vários produtos para uma categoria (ou
+    /// tira da categoria). Nada é alterado se algum produto não existir.
+    pub fn reassign(state: &AppState, dto: ReassignProductsDto) -> AppResult<Vec<ProductEntity>> {
+        let target = dto.category_id.filter(|c| *c != 0);
+        if let Some(target) = target {
+            if Self::find_by_id(state, target)?.is_none() {
+                return Err(AppError::validation("category_id", format!("Categoria {} não encontrada", target)));
+            }
+        }
+
+        let products = dto.product_ids.iter()
+            .map(|&id| state.products.find_by_id(id)?
+                .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id))))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        products.into_iter().map(|p| Self::move_product(state, p, target)).collect()
+    }
+
+    /// Valida nome, pai (existente e fora da própria subárvore) e nome único
+    /// entre as irmãs
+    fn check(state: &AppState, category: &CategoryEntity) -> AppResult<()> {
+        category.validate()?;
+        let tree = CategoryTree::load(state)?;
+
+        if let Some(parent) = category.parent_id {
+            if tree.get(parent).is_none() {
+                return Err(AppError::validation("parent_id", format!("Categoria {} não encontrada", parent)));
+            }
+            if category.id.is_some_and(|id| tree.subtree(id).contains(&parent)) {
+                return Err(AppError::validation("parent_id", "Categoria não pode ficar dentro de uma subcategoria dela"));
+            }
+        }
+
+        let name = category.name.to_lowercase();
+        if tree.children(category.parent_id).any(|c| c.id != category.id && c.name.to_lowercase() == name) {
+            return Err(AppError::Conflict(format!("Categoria '{}' já existe neste nível", category.name)));
+        }
+        Ok(())
+    }
+
+    fn move_product(state: &AppState, product: ProductEntity, category_id: Option<i64>) -> AppResult<ProductEntity> {
+        if product.category_id == category_id {
+            return Ok(product);
+        }
+        let moved = ProductEntity { category_id, updated_at: state.clock.now(), ..product.clone() };
+        let saved = state.products.save(&moved)?;
+        ProductService::record(state, "update", Some(&product), Some(&saved));
+        Ok(saved)
+    }
+
+    fn record(state: &AppState, action: &str, before: Option<&CategoryEntity>, after: Option<&CategoryEntity>) {
+        let id = after.or(before).and_then(|c| c.id).map(|id| id.to_string());
+        HistoryService::record(state, action, ENTITY_CATEGORY, id, before, after);
+    }
+}
+
+fn not_found(id: i64) -> AppError {
+    AppError::not_found(format!("Category with id {} not found", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::on_both_backends;
+
+    fn category_flow(state: &AppState) {
+        let create = |name: &str, parent_id: Option<i64>| {
+            CategoryService::create(state, CreateCategoryDto { name: name.to_string(), parent_id }).unwrap().id.unwrap()
+        };
+        let mercearia = create("Mercearia", None);
+        let graos = create("Grãos", Some(mercearia));
+        let arroz = create("Arroz", Some(graos));
+        let limpeza = create("Limpeza", None);
+
+        let product = state.products.save(&ProductEntity {
+            category_id: Some(arroz),
+            ..ProductEntity::new("001".to_string(), "Arroz 5kg".to_string())
+        }).unwrap();
+
+        let cycle = UpdateCategoryDto { name: None, parent_id: Some(arroz) };
+        assert_eq!(CategoryService::update(state, mercearia, cycle).unwrap_err().code(), "VALIDATION_ERROR");
+        let duplicate = CreateCategoryDto { name: "GRÃOS".to_string(), parent_id: Some(mercearia) };
+        assert_eq!(CategoryService::create(state, duplicate).unwrap_err().code(), "CONFLICT");
+
+        let tree = CategoryService::tree(state).unwrap();
+        assert_eq!(tree.iter().map(|n| n.category.name.as_str()).collect::<Vec<_>>(), vec!["Limpeza", "Mercearia"]);
+        assert_eq!(tree[1].children[0].children[0].path, "Mercearia > Grãos > Arroz");
+
+        let all = CategoryProductsQuery::default();
+        assert_eq!(CategoryService::products(state, mercearia, &all).unwrap().len(), 1);
+
+        // Destino dentro da própria subárvore é recusado
+        assert!(CategoryService::delete(state, graos, Some(arroz)).is_err());
+        CategoryService::delete(state, graos, None).unwrap();
+        assert_eq!(CategoryService::find_by_id(state, arroz).unwrap().unwrap().parent_id, Some(mercearia));
+
+        CategoryService::delete(state, arroz, Some(limpeza)).unwrap();
+        assert_eq!(state.products.find_by_id(product.id.unwrap()).unwrap().unwrap().category_id, Some(limpeza));
+
+        let moved = CategoryService::reassign(state, ReassignProductsDto { product_ids: vec![product.id.unwrap()], category_id: Some(0) }).unwrap();
+        assert_eq!(moved[0].category_id, None);
+        assert!(CategoryService::reassign(state, ReassignProductsDto { product_ids: vec![99], category_id: None }).is_err());
+    }
+
+    #[test]
+    fn test_category_flow() {
+        on_both_backends(category_flow);
+    }
+}
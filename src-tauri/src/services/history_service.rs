@@ -0,0 +1,131 @@
+use crate::clock::parse_date;
+use crate::dtos::HistoryQueryDto;
+use crate::error::{AppError, AppResult};
+use crate::entities::HistoryEntity;
+use crate::repositories::HistoryFilter;
+use crate::state::AppState;
+use serde::Serialize;
+use serde_json::json;
+
+/// Tipos de entidade registrados no histórico
+pub const ENTITY_PRODUCT: &str = "product";
+pub const ENTITY_CONFIG: &str = "config";
+pub const ENTITY_VENDA: &str = "venda";
+pub const ENTITY_CATEGORY: &str = "category";
+pub const ENTITY_PROMOTION: &str = "promotion";
+pub const ENTITY_INVENTORY: &str = "inventory";
+pub const ENTITY_UNIT: &str = "unit";
+pub const ENTITY_LOT: &str = "lot";
+pub const ENTITY_CART: &str = "cart";
+
+const DEFAULT_LIMIT: u32 = 200;
+const MAX_LIMIT: u32 = 1000;
+
+pub struct HistoryService;
+
+impl HistoryService {
+    /// Registra uma alteração com os estados antes e depois.
+    ///
+    /// A alteração já foi gravada quando isto é chamado, então uma falha ao
+    /// registrar o histórico é apenas logada: não desfaz a operação nem volta
+    /// como erro, o que levaria o cliente a repeti-la (ex. uma venda duplicada).
+    pub fn record<T: Serialize>(
+        state: &AppState,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<String>,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) {
+        let entry = HistoryEntity {
+            origin: state.origin,
+            created_at: state.clock.now(),
+            ..HistoryEntity::new(
+                action.to_string(),
+                entity_type.to_string(),
+                entity_id,
+                Some(json!({ "before": before, "after": after })),
+            )
+        };
+
+        if let Err(e) = state.history.insert(&entry) {
+            eprintln!("Erro ao registrar histórico ({} {}): {}", action, entity_type, e);
+        }
+    }
+
+    /// GET /history - Busca o histórico (mais recente primeiro)
+    pub fn find(state: &AppState, query: &HistoryQueryDto) -> AppResult<Vec<HistoryEntity>> {
+        let start = query.dt_init.as_deref()
+            .map(|d| parse_date(d).map_err(|e| AppError::validation("dtInit", e)))
+            .transpose()?;
+        let end = query.dt_fim.as_deref()
+            .map(|d| parse_date(d).map_err(|e| AppError::validation("dtFim", e)))
+            .transpose()?;
+
+        let filter = HistoryFilter {
+            entity_type: query.entity_type.clone(),
+            entity_id: query.entity_id.clone(),
+            start: start.map(|d| state.clock.start_of_day(d)),
+            end: end.map(|d| state.clock.day_range(d, d).1),
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        };
+
+        state.history.find(&filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{Origin, Quantity};
+    use crate::dtos::{BalanceChangeDto, CreateProductDto, UpdateProductDto};
+    use crate::services::{ConfigService, ProductService};
+    use crate::test_support::backends;
+
+    fn audit_flow(state: &AppState) {
+        let product = ProductService::create(state, CreateProductDto {
+            code: "001".to_string(),
+            name: "Arroz".to_string(),
+            fiscal: Default::default(),
+        }).unwrap();
+        let id = product.id.unwrap();
+        ProductService::increment_balance(state, id, BalanceChangeDto { amount: Quantity::from_int(5), ..Default::default() }).unwrap();
+        ProductService::update(state, id, UpdateProductDto { name: Some("Arroz 5kg".to_string()), ..Default::default() }).unwrap();
+        ProductService::delete(state, id).unwrap();
+
+        let query = HistoryQueryDto {
+            entity_type: Some(ENTITY_PRODUCT.to_string()),
+            entity_id: Some(id.to_string()),
+            ..Default::default()
+        };
+        let entries = HistoryService::find(state, &query).unwrap();
+        let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["delete", "update", "increment_balance", "create"]);
+        assert!(entries.iter().all(|e| e.origin == Origin::Http));
+
+        let update = &entries[1].details.as_ref().unwrap();
+        assert_eq!(update["before"]["name"], "Arroz");
+        assert_eq!(update["after"]["name"], "Arroz 5kg");
+        assert!(entries[3].details.as_ref().unwrap()["before"].is_null());
+
+        let config = ConfigService::find_by_id(state, "default").unwrap().unwrap_or_default();
+        ConfigService::save(state, &crate::entities::ConfigEntity { id: "default".to_string(), ..config }).unwrap();
+        let configs = HistoryService::find(state, &HistoryQueryDto {
+            entity_type: Some(ENTITY_CONFIG.to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(configs[0].entity_id.as_deref(), Some("default"));
+
+        let today = state.clock.today().format("%Y-%m-%d").to_string();
+        let query = HistoryQueryDto { dt_init: Some(today.clone()), dt_fim: Some(today), limit: Some(2), ..Default::default() };
+        assert_eq!(HistoryService::find(state, &query).unwrap().len(), 2);
+        assert!(HistoryService::find(state, &HistoryQueryDto { dt_init: Some("ontem".to_string()), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_audit_trail() {
+        for state in backends() {
+            audit_flow(&state.with_origin(Origin::Http));
+        }
+    }
+}
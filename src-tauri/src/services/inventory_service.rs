@@ -0,0 +1,321 @@
+use crate::dtos::{
+    CloseInventoryDto, InventoryLineDto, InventoryListQuery, InventoryReportDto, InventoryReportQuery, InventoryScanDto,
+    OpenInventoryDto,
+};
+use crate::error::{AppError, AppResult};
+use crate::entities::{
+    InventoryCountEntity, InventorySessionEntity, InventoryStatus, Money, MovementType, ProductEntity, Quantity,
+    StockMovementEntity,
+};
+use crate::entities::inventory::INVENTORY_NAME_MAX;
+use crate::services::history_service::{HistoryService, ENTITY_INVENTORY};
+use crate::services::{LotService, ProductService, StockService};
+use crate::state::AppState;
+use std::collections::{HashMap, HashSet};
+
+pub struct InventoryService;
+
+impl InventoryService {
+    /// POST /inventory - Abre uma sessão de inventário congelando o saldo dos
+    /// produtos ativos (kits ficam de fora). Só pode haver uma sessão aberta.
+    pub fn open(state: &AppState, dto: OpenInventoryDto) -> AppResult<InventorySessionEntity> {
+        if let Some(open) = Self::current(state)? {
+            return Err(AppError::Conflict(format!("Já existe um inventário aberto ({})", open.id.unwrap_or_default())));
+        }
+
+        let now = state.clock.now();
+        let name = dto.name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("Inventário {}", state.clock.local_date(now).format("%d/%m/%Y")));
+        if name.chars().count() > INVENTORY_NAME_MAX {
+            return Err(AppError::validation("name", format!("Nome deve ter no máximo {} caracteres", INVENTORY_NAME_MAX)));
+        }
+
+        let kits: HashSet<i64> = state.compositions.kit_ids()?.into_iter().collect();
+        let counts: Vec<InventoryCountEntity> = state.products.find_all_active()?
+            .into_iter()
+            .filter_map(|p| p.id.filter(|id| !kits.contains(id)).map(|id| InventoryCountEntity::new(0, id, p.balance, now)))
+            .collect();
+
+        let session = state.inventories.open(&InventorySessionEntity::new(name, dto.user, now), &counts)?;
+        Self::record(state, "open", None, Some(&session));
+        Ok(session)
+    }
+
+    /// GET /inventory/:id - Busca uma sessão por ID
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<InventorySessionEntity>> {
+        state.inventories.find_by_id(id)
+    }
+
+    /// GET /inventory - Lista as sessões, das mais recentes para as mais antigas
+    pub fn find_all(state: &AppState, query: &InventoryListQuery) -> AppResult<Vec<InventorySessionEntity>> {
+        state.inventories.find_all(query.status)
+    }
+
+    /// GET /inventory/current - Sessão aberta, se houver (inclusive de antes de
+    /// reiniciar o aplicativo)
+    pub fn current(state: &AppState) -> AppResult<Option<InventorySessionEntity>> {
+        Ok(state.inventories.find_all(Some(InventoryStatus::Open))?.into_iter().next())
+    }
+
+    /// POST /inventory/:id/counts - Registra a leitura de um produto. Cada
+    /// leitura soma à contagem, então o mesmo produto pode ser passado várias
+    /// vezes. Produtos que não estavam no retrato entram com o saldo atual
+    /// como esperado.
+    pub fn scan(state: &AppState, id: i64, dto: InventoryScanDto) -> AppResult<InventoryLineDto> {
+        Self::find_open(state, id)?;
+
+        let lookup = ProductService::lookup_barcode(state, &dto.barcode)?;
+        let product = lookup.product;
+        let product_id = product.id.unwrap_or_default();
+        if !state.compositions.find_by_kit(product_id)?.is_empty() {
+            return Err(AppError::validation("barcode", "Kit não tem estoque próprio; conte os componentes"));
+        }
+
+        let replace = dto.replace.unwrap_or(false);
+        let quantity = dto.quantity.unwrap_or(lookup.item.quantidade);
+        if quantity.is_zero() && !replace {
+            return Err(AppError::validation("quantity", "Quantidade deve ser diferente de zero"));
+        }
+
+        let now = state.clock.now();
+        let mut count = state.inventories.find_count(id, product_id)?
+            .unwrap_or_else(|| InventoryCountEntity::new(id, product_id, product.balance, now));
+        let counted = if replace { quantity } else { count.counted.unwrap_or(Quantity::ZERO) + quantity };
+        if counted.is_negative() {
+            return Err(AppError::validation("quantity", "Contagem não pode ficar negativa"));
+        }
+
+        count.counted = Some(counted);
+        count.scans += 1;
+        count.updated_at = now;
+        state.inventories.save_count(&count)?;
+
+        Ok(line(&product, &count))
+    }
+
+    /// GET /inventory/:id/report - Relatório de divergências entre o saldo
+    /// congelado na abertura e o contado, por nome do produto
+    pub fn report(state: &AppState, id: i64, query: &InventoryReportQuery) -> AppResult<InventoryReportDto> {
+        let session = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Inventory session with id {} not found", id)))?;
+        let counts = state.inventories.find_counts(id)?;
+        let products: HashMap<i64, ProductEntity> = state.products.find_all()?
+            .into_iter()
+            .filter_map(|p| p.id.map(|id| (id, p)))
+            .collect();
+
+        let mut lines: Vec<InventoryLineDto> = counts.iter()
+            .filter_map(|c| products.get(&c.product_id).map(|p| line(p, c)))
+            .collect();
+        lines.sort_by(|a, b| a.name.cmp(&b.name).then(a.product_id.cmp(&b.product_id)));
+
+        let values: Vec<Money> = lines.iter().filter_map(|l| l.variance_value).collect();
+        let surplus_value: Money = values.iter().filter(|v| !v.is_negative()).copied().sum();
+        let shortage_value: Money = values.iter().filter(|v| v.is_negative()).copied().sum();
+        let total_products = lines.len();
+        let counted_products = lines.iter().filter(|l| l.counted.is_some()).count();
+        let divergent_products = lines.iter().filter(|l| l.variance.is_some_and(|v| !v.is_zero())).count();
+
+        if query.differences_only.unwrap_or(false) {
+            lines.retain(|l| l.variance.is_some_and(|v| !v.is_zero()));
+        }
+
+        Ok(InventoryReportDto {
+            session,
+            lines,
+            total_products,
+            counted_products,
+            divergent_products,
+            surplus_value,
+            shortage_value,
+            variance_value: surplus_value + shortage_value,
+        })
+    }
+
+    /// POST /inventory/:id/close - Encerra a sessão lançando, para cada
+    /// produto contado, um ajuste de `contado - esperado` no livro de estoque
+    /// (referência `inventario:<id>`). Movimentos feitos depois da abertura
+    /// continuam valendo, então a contagem deve refletir o estoque da abertura.
+    pub fn close(state: &AppState, id: i64, dto: CloseInventoryDto) -> AppResult<InventoryReportDto> {
+        let session = Self::find_open(state, id)?;
+        let now = state.clock.now();
+        let kits: HashSet<i64> = state.compositions.kit_ids()?.into_iter().collect();
+
+        let mut movements = Vec::new();
+        let mut lowered = Vec::new();
+        for mut count in state.inventories.find_counts(id)? {
+            if kits.contains(&count.product_id) {
+                continue;
+            }
+            if count.counted.is_none() && dto.zero_uncounted.unwrap_or(false) {
+                count.counted = Some(Quantity::ZERO);
+                count.updated_at = now;
+                state.inventories.save_count(&count)?;
+            }
+            let Some(counted) = count.counted else { continue };
+
+            let adjustment = counted - count.expected;
+            if adjustment.is_negative() {
+                lowered.extend(state.products.find_by_id(count.product_id)?);
+            }
+            if !adjustment.is_zero() {
+                let movement = StockMovementEntity::new(count.product_id, MovementType::Adjustment, adjustment, None, dto.user.clone());
+                movements.extend(LotService::allocate(state, StockMovementEntity { origin: state.origin, created_at: now, ..movement })?);
+            }
+        }
+
+        let closed = InventorySessionEntity { status: InventoryStatus::Closed, closed_at: Some(now), ..session.clone() };
+        state.inventories.finish(&closed, &movements)?;
+        Self::record(state, "close", Some(&session), Some(&closed));
+
+        let mut low_stock = Vec::new();
+        for before in lowered {
+            if let Some(after) = state.products.find_by_id(before.id.unwrap_or(0))? {
+                low_stock.extend(StockService::crossed_min(&before, after.balance));
+            }
+        }
+        StockService::notify_low_stock(state, &low_stock);
+
+        Self::report(state, id, &InventoryReportQuery::default())
+    }
+
+    /// POST /inventory/:id/cancel - Descarta a sessão sem mexer no estoque
+    pub fn cancel(state: &AppState, id: i64) -> AppResult<InventorySessionEntity> {
+        let session = Self::find_open(state, id)?;
+
+        let cancelled = InventorySessionEntity {
+            status: InventoryStatus::Cancelled,
+            closed_at: Some(state.clock.now()),
+            ..session.clone()
+        };
+        state.inventories.finish(&cancelled, &[])?;
+        Self::record(state, "cancel", Some(&session), Some(&cancelled));
+        Ok(cancelled)
+    }
+
+    /// Sessão `id`, que precisa estar aberta
+    fn find_open(state: &AppState, id: i64) -> AppResult<InventorySessionEntity> {
+        let session = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Inventory session with id {} not found", id)))?;
+        if session.status != InventoryStatus::Open {
+            return Err(AppError::Conflict(format!("Inventário {} já foi encerrado", id)));
+        }
+        Ok(session)
+    }
+
+    fn record(state: &AppState, action: &str, before: Option<&InventorySessionEntity>, after: Option<&InventorySessionEntity>) {
+        let id = after.or(before).and_then(|s| s.id).map(|id| id.to_string());
+        HistoryService::record(state, action, ENTITY_INVENTORY, id, before, after);
+    }
+}
+
+/// Linha do relatório para a contagem do produto
+fn line(product: &ProductEntity, count: &InventoryCountEntity) -> InventoryLineDto {
+    let variance = count.counted.map(|c| c - count.expected);
+    InventoryLineDto {
+        product_id: count.product_id,
+        code: product.code.clone(),
+        name: product.name.clone(),
+        unit: product.unit.clone(),
+        price: product.price,
+        expected: count.expected,
+        counted: count.counted,
+        scans: count.scans,
+        variance,
+        variance_value: variance.map(|v| product.price * v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dtos::ComponentDto;
+    use crate::test_support::{on_both_backends, save_product};
+
+    fn scan(state: &AppState, id: i64, barcode: &str, quantity: Option<i64>, replace: bool) -> AppResult<InventoryLineDto> {
+        let dto = InventoryScanDto {
+            barcode: barcode.to_string(),
+            quantity: quantity.map(Quantity::from_int),
+            replace: Some(replace),
+        };
+        InventoryService::scan(state, id, dto)
+    }
+
+    fn inventory_flow(state: &AppState) {
+        let save = |code: &str, price: i64, balance: i64| save_product(state, code, Money::from_int(price), Quantity::from_int(balance));
+        let arroz = save("001", 10, 5);
+        let feijao = save("002", 8, 3);
+        let sabao = save("003", 2, 4);
+        let cesta = save("004", 30, 0);
+        ProductService::set_components(state, cesta, vec![ComponentDto { product_id: arroz, quantity: Quantity::from_int(1) }]).unwrap();
+
+        let session = InventoryService::open(state, OpenInventoryDto { name: None, user: Some("ana".to_string()) }).unwrap();
+        let id = session.id.unwrap();
+        assert!(session.name.starts_with("Inventário "));
+        let again = InventoryService::open(state, OpenInventoryDto::default());
+        assert_eq!(again.unwrap_err().code(), "CONFLICT");
+        assert_eq!(InventoryService::current(state).unwrap().unwrap().id, Some(id));
+
+        // Arroz passado três vezes e corrigido; feijão conferido; kit não é contado
+        for _ in 0..3 {
+            scan(state, id, "001", None, false).unwrap();
+        }
+        let arroz_line = scan(state, id, "001", Some(-1), false).unwrap();
+        assert_eq!((arroz_line.counted, arroz_line.scans), (Some(Quantity::from_int(2)), 4));
+        scan(state, id, "002", Some(3), true).unwrap();
+        assert_eq!(scan(state, id, "004", None, false).unwrap_err().code(), "VALIDATION_ERROR");
+        assert_eq!(scan(state, id, "002", Some(-5), false).unwrap_err().code(), "VALIDATION_ERROR");
+
+        // Produto cadastrado durante a contagem entra com o saldo atual
+        let novo = save("005", 1, 2);
+        scan(state, id, "005", Some(3), false).unwrap();
+
+        let report = InventoryService::report(state, id, &InventoryReportQuery::default()).unwrap();
+        assert_eq!((report.total_products, report.counted_products, report.divergent_products), (4, 3, 2));
+        assert_eq!(report.surplus_value, Money::from_int(1));
+        assert_eq!(report.shortage_value, Money::from_int(-30));
+        assert_eq!(report.variance_value, Money::from_int(-29));
+        let differences = InventoryService::report(state, id, &InventoryReportQuery { differences_only: Some(true) }).unwrap();
+        let codes: Vec<&str> = differences.lines.iter().map(|l| l.code.as_str()).collect();
+        assert_eq!(codes, vec!["001", "005"]);
+
+        let report = InventoryService::close(state, id, CloseInventoryDto { zero_uncounted: None, user: Some("ana".to_string()) }).unwrap();
+        assert_eq!(report.session.status, InventoryStatus::Closed);
+        let balance = |id: i64| state.products.find_by_id(id).unwrap().unwrap().balance;
+        assert_eq!(balance(arroz), Quantity::from_int(2));
+        assert_eq!(balance(feijao), Quantity::from_int(3));
+        assert_eq!(balance(sabao), Quantity::from_int(4));
+        assert_eq!(balance(novo), Quantity::from_int(3));
+
+        let reference = StockMovementEntity::inventory_reference(id);
+        let filter = crate::repositories::StockMovementFilter { reference: Some(reference), ..Default::default() };
+        let adjustments: Vec<(i64, Quantity)> = state.stock.find(&filter).unwrap().iter().map(|m| (m.product_id, m.quantity)).collect();
+        assert_eq!(adjustments, vec![(arroz, Quantity::from_int(-3)), (novo, Quantity::from_int(1))]);
+        assert_eq!(scan(state, id, "001", None, false).unwrap_err().code(), "CONFLICT");
+
+        // Um novo inventário zerando o que não foi contado; outro cancelado
+        let second = InventoryService::open(state, OpenInventoryDto { name: Some("Seção limpeza".to_string()), user: None }).unwrap();
+        let second_id = second.id.unwrap();
+        scan(state, second_id, "001", Some(2), false).unwrap();
+        scan(state, second_id, "002", Some(3), false).unwrap();
+        InventoryService::close(state, second_id, CloseInventoryDto { zero_uncounted: Some(true), user: None }).unwrap();
+        assert_eq!(balance(arroz), Quantity::from_int(2));
+        assert_eq!(balance(sabao), Quantity::ZERO);
+
+        let third = InventoryService::open(state, OpenInventoryDto::default()).unwrap().id.unwrap();
+        scan(state, third, "002", Some(10), false).unwrap();
+        assert_eq!(InventoryService::cancel(state, third).unwrap().status, InventoryStatus::Cancelled);
+        assert_eq!(balance(feijao), Quantity::from_int(3));
+
+        let closed = InventoryService::find_all(state, &InventoryListQuery { status: Some(InventoryStatus::Closed) }).unwrap();
+        assert_eq!(closed.iter().map(|s| s.id.unwrap()).collect::<Vec<_>>(), vec![second_id, id]);
+        assert!(InventoryService::current(state).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_inventory_flow() {
+        on_both_backends(inventory_flow);
+    }
+}
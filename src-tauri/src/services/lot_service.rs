@@ -0,0 +1,300 @@
+use crate::dtos::{CreateLotDto, ExpiringLotDto, ExpiringLotsQuery, ProductLotsDto, UpdateLotDto};
+use crate::error::{AppError, AppResult};
+use crate::entities::{LotEntity, MovementType, ProductEntity, Quantity, StockMovementEntity};
+use crate::entities::lot::{split_exit, OPENING_LOT};
+use crate::services::history_service::{HistoryService, ENTITY_LOT};
+use crate::state::AppState;
+use chrono::{Days, NaiveDate};
+use std::collections::HashMap;
+
+/// Prazo padrão do relatório de vencimentos
+const DEFAULT_EXPIRING_DAYS: u32 = 30;
+
+pub struct LotService;
+
+impl LotService {
+    /// POST /lots - Cadastra um lote, ainda sem quantidade, de um produto com
+    /// controle de lotes
+    pub fn create(state: &AppState, dto: CreateLotDto) -> AppResult<LotEntity> {
+        let product = Self::tracked_product(state, dto.product_id)?;
+        let code = dto.code.trim().to_string();
+        if state.lots.find_by_code(dto.product_id, &code)?.is_some() {
+            return Err(AppError::Conflict(format!("Lote {} já cadastrado para o produto {}", code, product.code)));
+        }
+
+        Self::insert(state, LotEntity::new(dto.product_id, code, dto.expires_at))
+    }
+
+    /// GET /lots/:id - Busca um lote por ID
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<LotEntity>> {
+        state.lots.find_by_id(id)
+    }
+
+    /// PUT /lots/:id - Corrige o código ou a validade de um lote
+    pub fn update(state: &AppState, id: i64, dto: UpdateLotDto) -> AppResult<LotEntity> {
+        let existing = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Lot with id {} not found", id)))?;
+
+        let updated = LotEntity {
+            code: dto.code.map(|c| c.trim().to_string()).unwrap_or_else(|| existing.code.clone()),
+            expires_at: dto.expires_at.or(existing.expires_at),
+            updated_at: state.clock.now(),
+            ..existing.clone()
+        };
+        updated.validate()?;
+        if updated.code != existing.code && state.lots.find_by_code(updated.product_id, &updated.code)?.is_some() {
+            return Err(AppError::Conflict(format!("Lote {} já cadastrado para este produto", updated.code)));
+        }
+
+        let saved = state.lots.update(&updated)?;
+        HistoryService::record(state, "update", ENTITY_LOT, Some(id.to_string()), Some(&existing), Some(&saved));
+        Ok(saved)
+    }
+
+    /// GET /products/:id/lots - Lotes do produto na ordem de saída, com o
+    /// saldo do produto e a soma dos lotes
+    pub fn find_by_product(state: &AppState, product_id: i64) -> AppResult<ProductLotsDto> {
+        let product = state.products.find_by_id(product_id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", product_id)))?;
+        let lots = state.lots.find_by_product(product_id)?;
+
+        Ok(ProductLotsDto {
+            product_id,
+            code: product.code,
+            track_lots: product.track_lots,
+            balance: product.balance,
+            lots_total: lots.iter().map(|l| l.quantity).sum(),
+            lots,
+        })
+    }
+
+    /// GET /lots/expiring?days=30 - Lotes com saldo que vencem nos próximos
+    /// `days` dias, incluindo os já vencidos, por validade
+    pub fn expiring(state: &AppState, query: &ExpiringLotsQuery) -> AppResult<Vec<ExpiringLotDto>> {
+        let today = state.clock.today();
+        let days = query.days.unwrap_or(DEFAULT_EXPIRING_DAYS);
+        let until = today.checked_add_days(Days::new(days.into())).unwrap_or(NaiveDate::MAX);
+
+        let mut products: HashMap<i64, Option<ProductEntity>> = HashMap::new();
+        let mut expiring = Vec::new();
+        for lot in state.lots.find_expiring(until)? {
+            let product = match products.get(&lot.product_id) {
+                Some(product) => product.clone(),
+                None => {
+                    let product = state.products.find_by_id(lot.product_id)?;
+                    products.insert(lot.product_id, product.clone());
+                    product
+                }
+            };
+            // Lotes de produtos que deixaram de controlar lotes não valem mais
+            let Some(product) = product.filter(|p| p.track_lots == 1) else { continue };
+            let Some(expires_at) = lot.expires_at else { continue };
+
+            expiring.push(ExpiringLotDto {
+                days_left: (expires_at - today).num_days(),
+                product_code: product.code,
+                product_name: product.name,
+                lot,
+            });
+        }
+        Ok(expiring)
+    }
+
+    /// Lote informado (`lot`, `expires_at`) numa entrada ou saída do produto.
+    /// Entradas criam o lote que ainda não existe; saídas exigem um lote
+    /// cadastrado. Sem `lot`, retorna `None` e a divisão fica com `allocate`.
+    pub(crate) fn resolve(
+        state: &AppState,
+        product: &ProductEntity,
+        code: Option<&str>,
+        expires_at: Option<NaiveDate>,
+        entry: bool,
+    ) -> AppResult<Option<i64>> {
+        let Some(code) = code.map(str::trim).filter(|c| !c.is_empty()) else {
+            return match expires_at {
+                Some(_) => Err(AppError::validation("lot", "Informe o lote da validade")),
+                None => Ok(None),
+            };
+        };
+        if product.track_lots != 1 {
+            return Err(AppError::validation("lot", format!("Produto {} não controla lotes", product.code)));
+        }
+        let product_id = product.id.unwrap_or_default();
+
+        match state.lots.find_by_code(product_id, code)? {
+            Some(lot) if expires_at.is_some() && expires_at != lot.expires_at => Err(AppError::validation(
+                "expires_at",
+                format!("Lote {} já cadastrado com validade {}", lot.code, lot.expires_at.map(|d| d.to_string()).unwrap_or_else(|| "em branco".to_string())),
+            )),
+            Some(lot) => Ok(lot.id),
+            None if entry => Ok(Self::insert(state, LotEntity::new(product_id, code.to_string(), expires_at))?.id),
+            None => Err(AppError::validation("lot", format!("Lote {} não encontrado para o produto {}", code, product.code))),
+        }
+    }
+
+    /// Divide o movimento de um produto com controle de lotes entre os lotes.
+    ///
+    /// Saídas sem lote baixam primeiro o lote que vence antes (FEFO, ver
+    /// `split_exit`). Entradas sem lote, exceto compras, voltam para o lote de
+    /// validade mais distante. Movimentos com lote ou de produtos sem controle
+    /// passam direto. As vendas não passam por aqui: a divisão delas é feita na
+    /// transação que grava a venda, com os lotes lidos nela.
+    pub(crate) fn allocate(state: &AppState, movement: StockMovementEntity) -> AppResult<Vec<StockMovementEntity>> {
+        if movement.lot_id.is_some() {
+            return Ok(vec![movement]);
+        }
+        let product = match state.products.find_by_id(movement.product_id)? {
+            Some(product) if product.track_lots == 1 => product,
+            _ => return Ok(vec![movement]),
+        };
+
+        if movement.kind == MovementType::Purchase {
+            return Err(AppError::validation("lot", format!("Informe o lote da compra do produto {}", product.code)));
+        }
+        let lots = state.lots.find_by_product(movement.product_id)?;
+        if movement.quantity.is_negative() {
+            return split_exit(&lots, &product.code, &movement);
+        }
+
+        let Some(last) = lots.last().and_then(|l| l.id) else {
+            return Err(AppError::validation("lot", format!("Produto {} controla lotes e não tem lote cadastrado", product.code)));
+        };
+        Ok(vec![StockMovementEntity { lot_id: Some(last), ..movement }])
+    }
+
+    /// Lote a criar quando o produto liga o controle de lotes: sem lotes, o
+    /// saldo atual vira o lote `INICIAL`. Lotes antigos precisam somar o saldo.
+    pub(crate) fn opening_lot(state: &AppState, product: &ProductEntity) -> AppResult<Option<LotEntity>> {
+        let product_id = product.id.unwrap_or_default();
+        let lots = state.lots.find_by_product(product_id)?;
+        let lots_total: Quantity = lots.iter().map(|l| l.quantity).sum();
+
+        if lots.is_empty() && !product.balance.is_zero() {
+            let lot = LotEntity::new(product_id, OPENING_LOT.to_string(), None);
+            return Ok(Some(LotEntity { quantity: product.balance, ..lot }));
+        }
+        if lots_total != product.balance {
+            return Err(AppError::validation(
+                "track_lots",
+                format!("Saldo {} difere da soma dos lotes {}; ajuste o estoque antes de ligar o controle de lotes", product.balance, lots_total),
+            ));
+        }
+        Ok(None)
+    }
+
+    /// Grava um lote novo e registra no histórico
+    pub(crate) fn insert(state: &AppState, lot: LotEntity) -> AppResult<LotEntity> {
+        let now = state.clock.now();
+        let lot = LotEntity { created_at: now, updated_at: now, ..lot };
+        lot.validate()?;
+
+        let saved = state.lots.create(&lot)?;
+        HistoryService::record(state, "create", ENTITY_LOT, saved.id.map(|id| id.to_string()), None, Some(&saved));
+        Ok(saved)
+    }
+
+    fn tracked_product(state: &AppState, product_id: i64) -> AppResult<ProductEntity> {
+        let product = state.products.find_by_id(product_id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", product_id)))?;
+        if product.track_lots != 1 {
+            return Err(AppError::validation("product_id", format!("Produto {} não controla lotes", product.code)));
+        }
+        Ok(product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::dtos::{BalanceChangeDto, CreateProductDto, ProductFiscalDto, StockMovementDto, UpdateProductDto};
+    use crate::entities::{ConfigEntity, Money, NegativeStockPolicy};
+    use crate::services::{ConfigService, ProductService, StockService, VendaService};
+    use crate::test_support::{cash, item, new_venda, on_both_backends};
+    use chrono::{TimeZone, Utc};
+    use std::sync::Arc;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    fn lot_quantities(state: &AppState, product_id: i64) -> Vec<(String, Quantity)> {
+        let lots = LotService::find_by_product(state, product_id).unwrap();
+        assert_eq!(lots.lots_total, lots.balance);
+        lots.lots.into_iter().map(|l| (l.code, l.quantity)).collect()
+    }
+
+    fn lots_flow(state: &AppState) {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 15, 0, 0).unwrap();
+        let state = &state.clone().with_clock(Arc::new(FixedClock { now, timezone: state.clock.timezone() }));
+        let config = ConfigEntity { id: "default".to_string(), controle_estoque: 1, estoque_negativo: NegativeStockPolicy::Allow, ..Default::default() };
+        ConfigService::save(state, &config).unwrap();
+
+        let fiscal = ProductFiscalDto { price: Some(Money::from_int(5)), ..Default::default() };
+        let product = ProductService::create(state, CreateProductDto { code: "001".to_string(), name: "Dipirona".to_string(), fiscal }).unwrap();
+        let id = product.id.unwrap();
+        let untracked_lot = BalanceChangeDto { amount: Quantity::from_int(1), lot: Some("L1".to_string()), ..Default::default() };
+        assert_eq!(ProductService::increment_balance(state, id, untracked_lot).unwrap_err().code(), "VALIDATION_ERROR");
+
+        // O saldo de antes do controle vira o lote inicial
+        ProductService::update(state, id, UpdateProductDto { balance: Some(Quantity::from_int(5)), ..Default::default() }).unwrap();
+        let track = ProductFiscalDto { track_lots: Some(1), ..Default::default() };
+        ProductService::update(state, id, UpdateProductDto { fiscal: track, ..Default::default() }).unwrap();
+        assert_eq!(lot_quantities(state, id), vec![("INICIAL".to_string(), Quantity::from_int(5))]);
+
+        let purchase = |lot: Option<&str>, expires_at: Option<NaiveDate>, quantity: i64| StockMovementDto {
+            product_id: id,
+            kind: MovementType::Purchase,
+            quantity: Quantity::from_int(quantity),
+            reference: None,
+            user: None,
+            unit: None,
+            lot: lot.map(str::to_string),
+            expires_at,
+        };
+        assert_eq!(StockService::register(state, purchase(None, None, 10)).unwrap_err().code(), "VALIDATION_ERROR");
+        StockService::register(state, purchase(Some("L1"), Some(date(25)), 10)).unwrap();
+        let other_expiry = StockService::register(state, purchase(Some("L1"), Some(date(20)), 1));
+        assert_eq!(other_expiry.unwrap_err().code(), "VALIDATION_ERROR");
+        let l2 = BalanceChangeDto { amount: Quantity::from_int(4), kind: Some(MovementType::Purchase), lot: Some("L2".to_string()), expires_at: Some(date(18)), ..Default::default() };
+        ProductService::increment_balance(state, id, l2).unwrap();
+
+        // A venda baixa primeiro o lote que vence antes
+        let venda = new_venda("2024-06-15T10:30:00-03:00", Money::from_int(30));
+        let created = VendaService::create_venda(state, &venda, vec![item("001", Quantity::from_int(6))], cash(Money::from_int(30))).unwrap();
+        let q = Quantity::from_int;
+        assert_eq!(lot_quantities(state, id), vec![("L2".to_string(), q(0)), ("L1".to_string(), q(8)), ("INICIAL".to_string(), q(5))]);
+
+        // Os lotes são lidos na transação da venda: uma baixa maior que eles é recusada
+        // mesmo com estoque negativo permitido, e nenhum lote fica negativo
+        let stale = StockMovementEntity::new(id, MovementType::Sale, q(-14), None, None);
+        let err = state.vendas.create_venda(&venda, now, &[], &[], &[stale], false, None).unwrap_err();
+        assert_eq!(err.code(), "CONFLICT");
+        assert_eq!(lot_quantities(state, id), vec![("L2".to_string(), q(0)), ("L1".to_string(), q(8)), ("INICIAL".to_string(), q(5))]);
+
+        VendaService::cancel_venda(state, created.id, "CFe1canc".to_string(), "2024-06-15T11:00:00-03:00".to_string(), None).unwrap();
+        assert_eq!(lot_quantities(state, id), vec![("L2".to_string(), q(4)), ("L1".to_string(), q(10)), ("INICIAL".to_string(), q(5))]);
+
+        let expiring = LotService::expiring(state, &ExpiringLotsQuery { days: Some(5) }).unwrap();
+        assert_eq!(expiring.iter().map(|l| (l.lot.code.as_str(), l.days_left)).collect::<Vec<_>>(), vec![("L2", 3)]);
+        assert_eq!(LotService::expiring(state, &ExpiringLotsQuery::default()).unwrap().len(), 2);
+
+        let missing = BalanceChangeDto { amount: q(1), kind: Some(MovementType::Loss), lot: Some("XX".to_string()), ..Default::default() };
+        assert_eq!(ProductService::decrement_balance(state, id, missing).unwrap_err().code(), "VALIDATION_ERROR");
+        let loss = BalanceChangeDto { amount: q(5), kind: Some(MovementType::Loss), lot: Some("INICIAL".to_string()), ..Default::default() };
+        ProductService::decrement_balance(state, id, loss).unwrap();
+
+        // Ajuste de saldo pelo cadastro também segue a validade
+        let product = ProductService::update(state, id, UpdateProductDto { balance: Some(q(9)), ..Default::default() }).unwrap();
+        assert_eq!(product.balance, q(9));
+        assert_eq!(lot_quantities(state, id), vec![("L2".to_string(), q(0)), ("L1".to_string(), q(9)), ("INICIAL".to_string(), q(0))]);
+
+        let renamed = LotService::update(state, 1, UpdateLotDto { code: Some("L2".to_string()), ..Default::default() });
+        assert_eq!(renamed.unwrap_err().code(), "CONFLICT");
+    }
+
+    #[test]
+    fn test_lots_flow() {
+        on_both_backends(lots_flow);
+    }
+}
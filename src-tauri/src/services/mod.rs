@@ -2,8 +2,26 @@ pub mod config_service;
 pub mod product_service;
 pub mod venda_service;
 pub mod resume_service;
+pub mod history_service;
+pub mod stock_service;
+pub mod catalog_service;
+pub mod category_service;
+pub mod promotion_service;
+pub mod inventory_service;
+pub mod unit_service;
+pub mod lot_service;
+pub mod cart_service;
 
 pub use config_service::ConfigService;
 pub use product_service::ProductService;
 pub use venda_service::VendaService;
 pub use resume_service::ResumeService;
+pub use history_service::HistoryService;
+pub use stock_service::StockService;
+pub use catalog_service::CatalogService;
+pub use category_service::CategoryService;
+pub use promotion_service::PromotionService;
+pub use inventory_service::InventoryService;
+pub use unit_service::UnitService;
+pub use lot_service::LotService;
+pub use cart_service::CartService;
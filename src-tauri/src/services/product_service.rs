@@ -1,272 +1,602 @@
-use crate::database::SqliteDbService;
-use crate::entities::ProductEntity;
-use rusqlite::{params, Result};
-use chrono::Utc;
+use crate::barcode::{decode_scale_label, is_valid_gtin, GtinKind, ScaleLabel, ScaleValue};
+use crate::error::{AppError, AppResult, FieldError};
+use crate::dtos::{
+    BalanceChangeDto, BarcodeLookupDto, BarcodeMatch, ComponentDto, CreateProductDto, KitComponentDto, KitDto, ProductFiscalDto,
+    ProductPageDto, ProductSearchQuery, UpdateProductDto,
+};
+use crate::entities::{
+    CompositionItemEntity, Money, MovementType, ProductEntity, Quantity, StockMovementEntity, UnitEntity, VendaItemEntity,
+};
+use crate::repositories::{search_terms, ProductSearch};
+use crate::services::ConfigService;
+use crate::services::history_service::{HistoryService, ENTITY_PRODUCT};
+use crate::services::{LotService, StockService, UnitService};
+use crate::state::AppState;
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Item de venda de uma etiqueta de balança. Nas etiquetas de preço a
+/// quantidade é o total dividido pelo preço do cadastro (ou 1, se o produto
+/// não tem preço) e o total da etiqueta é mantido: a diferença de
+/// arredondamento entre ele e `quantidade * preco_unitario` vai para o
+/// `acrescimo` ou o `desconto` do item, que a venda conserva.
+fn scale_item(product: &ProductEntity, label: &ScaleLabel) -> VendaItemEntity {
+    match label.value {
+        ScaleValue::Weight(weight) => VendaItemEntity::from_product(0, product, weight),
+        ScaleValue::Price(total) if product.price.is_zero() => {
+            let mut item = VendaItemEntity::new(0, product.code.clone(), String::new(), String::new(), Quantity::from_int(1), total);
+            item.inherit(product);
+            item
+        }
+        ScaleValue::Price(total) => {
+            let (total_cents, price_cents) = (total.cents(), product.price.cents());
+            let quantidade = Quantity::from_units((total_cents * 1000 + price_cents / 2) / price_cents);
+            let mut item = VendaItemEntity::from_product(0, product, quantidade);
+            let rounding = total - item.gross_total();
+            if rounding.is_negative() {
+                item.desconto = -rounding;
+            } else {
+                item.acrescimo = rounding;
+            }
+            item.preco_total = item.net_total();
+            item
+        }
+    }
+}
+
+/// Produto da etiqueta de balança: pelo código impresso e, se não houver,
+/// pelo mesmo código sem os zeros à esquerda
+fn find_scale_product(state: &AppState, code: &str) -> AppResult<Option<ProductEntity>> {
+    if let Some(product) = state.products.find_by_code(code)? {
+        return Ok(Some(product));
+    }
+    match code.trim_start_matches('0') {
+        trimmed if trimmed.is_empty() || trimmed == code => Ok(None),
+        trimmed => state.products.find_by_code(trimmed),
+    }
+}
 
 pub struct ProductService;
 
 impl ProductService {
     /// POST /products - Cria um novo produto
-    pub fn create(code: String, name: String) -> Result<ProductEntity, String> {
-        let product = ProductEntity::new(code, name);
-        Self::save(&product)
+    pub fn create(state: &AppState, dto: CreateProductDto) -> AppResult<ProductEntity> {
+        let now = state.clock.now();
+        let mut product = ProductEntity { created_at: now, updated_at: now, ..ProductEntity::new(dto.code, dto.name) };
+        Self::apply_fiscal(&mut product, dto.fiscal);
+        product.validate()?;
+        Self::check_category(state, &product)?;
+        Self::check_units(state, &product)?;
+
+        let saved = Self::save(state, &product)?;
+
+        Self::record(state, "create", None, Some(&saved));
+        Ok(saved)
     }
 
     /// GET /products/:id - Busca um produto por ID
-    pub fn find_by_id(id: i64) -> Result<Option<ProductEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, code, name, active, balance, created_at, updated_at 
-             FROM produtos WHERE id = ?1"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let product = stmt.query_row(params![id], |row| {
-            let created_at_str: String = row.get(5)?;
-            let updated_at_str: String = row.get(6)?;
-            
-            Ok(ProductEntity {
-                id: row.get(0)?,
-                code: row.get(1)?,
-                name: row.get(2)?,
-                active: row.get(3)?,
-                balance: row.get(4)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        });
-
-        match product {
-            Ok(p) => Ok(Some(p)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(format!("Failed to query product: {}", e)),
-        }
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<ProductEntity>> {
+        state.products.find_by_id(id)
     }
 
     /// GET /products/code/:code - Busca um produto por código
-    pub fn find_by_code(code: &str) -> Result<Option<ProductEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, code, name, active, balance, created_at, updated_at 
-             FROM produtos WHERE code = ?1"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let product = stmt.query_row(params![code], |row| {
-            let created_at_str: String = row.get(5)?;
-            let updated_at_str: String = row.get(6)?;
-            
-            Ok(ProductEntity {
-                id: row.get(0)?,
-                code: row.get(1)?,
-                name: row.get(2)?,
-                active: row.get(3)?,
-                balance: row.get(4)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        });
-
-        match product {
-            Ok(p) => Ok(Some(p)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(format!("Failed to query product: {}", e)),
-        }
+    pub fn find_by_code(state: &AppState, code: &str) -> AppResult<Option<ProductEntity>> {
+        state.products.find_by_code(code)
     }
 
     /// GET /products - Lista todos os produtos
-    pub fn find_all() -> Result<Vec<ProductEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, code, name, active, balance, created_at, updated_at 
-             FROM produtos ORDER BY name"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let products = stmt.query_map([], |row| {
-            let created_at_str: String = row.get(5)?;
-            let updated_at_str: String = row.get(6)?;
-            
-            Ok(ProductEntity {
-                id: row.get(0)?,
-                code: row.get(1)?,
-                name: row.get(2)?,
-                active: row.get(3)?,
-                balance: row.get(4)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query products: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect products: {}", e))?;
-
-        Ok(products)
+    pub fn find_all(state: &AppState) -> AppResult<Vec<ProductEntity>> {
+        state.products.find_all()
     }
 
     /// Lista todos os produtos ativos
-    pub fn find_all_active() -> Result<Vec<ProductEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, code, name, active, balance, created_at, updated_at 
-             FROM produtos WHERE active = 1 ORDER BY name"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let products = stmt.query_map([], |row| {
-            let created_at_str: String = row.get(5)?;
-            let updated_at_str: String = row.get(6)?;
-            
-            Ok(ProductEntity {
-                id: row.get(0)?,
-                code: row.get(1)?,
-                name: row.get(2)?,
-                active: row.get(3)?,
-                balance: row.get(4)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query products: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect products: {}", e))?;
+    pub fn find_all_active(state: &AppState) -> AppResult<Vec<ProductEntity>> {
+        state.products.find_all_active()
+    }
+
+    /// GET /products?active= - Lista produtos, opcionalmente filtrando pelo status
+    pub fn list(state: &AppState, active: Option<bool>) -> AppResult<Vec<ProductEntity>> {
+        match active {
+            None => Self::find_all(state),
+            Some(true) => Self::find_all_active(state),
+            Some(false) => Ok(Self::find_all(state)?.into_iter().filter(|p| p.active == 0).collect()),
+        }
+    }
+
+    /// GET /products/search?q= - Busca por parte do código ou do nome, sem
+    /// distinção de acentos e maiúsculas, paginada
+    pub fn search(state: &AppState, query: &ProductSearchQuery) -> AppResult<ProductPageDto> {
+        let terms = search_terms(&query.q);
+        if terms.is_empty() {
+            return Err(AppError::validation("q", "Informe o código ou o nome do produto"));
+        }
 
-        Ok(products)
+        let page = query.page.unwrap_or(1).max(1);
+        let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let search = ProductSearch {
+            query: query.q.trim().to_string(),
+            terms,
+            active: query.active,
+            offset: (page - 1).saturating_mul(per_page),
+            limit: per_page,
+        };
+
+        let (items, total) = state.products.search(&search)?;
+        Ok(ProductPageDto { items, total, page, per_page })
     }
 
-    /// PUT /products/:id - Atualiza um produto
-    pub fn update(id: i64, code: Option<String>, name: Option<String>, active: Option<i32>, balance: Option<f64>) -> Result<ProductEntity, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
+    /// GET /products/barcode/:barcode - Encontra o produto de um código lido no
+    /// caixa, pelo código, pelo GTIN ou como etiqueta de balança (nessa ordem)
+    pub fn lookup_barcode(state: &AppState, barcode: &str) -> AppResult<BarcodeLookupDto> {
+        let barcode = barcode.trim();
+        let found = |matched_by, product: ProductEntity, label: Option<ScaleLabel>| {
+            let item = match &label {
+                Some(label) => scale_item(&product, label),
+                None => VendaItemEntity::from_product(0, &product, Quantity::from_int(1)),
+            };
+            BarcodeLookupDto { barcode: barcode.to_string(), matched_by, product, label, item }
+        };
+
+        if let Some(product) = state.products.find_by_code(barcode)? {
+            return Ok(found(BarcodeMatch::Code, product, None));
+        }
+
+        if GtinKind::of(barcode).is_some() {
+            if let Some(product) = state.products.find_by_gtin(barcode)? {
+                return Ok(found(BarcodeMatch::Gtin, product, None));
+            }
+
+            let layout = ConfigService::find_by_id(state, "default")?.unwrap_or_default().balanca;
+            if let Some(label) = decode_scale_label(barcode, &layout) {
+                if let Some(product) = find_scale_product(state, &label.code)? {
+                    return Ok(found(BarcodeMatch::Scale, product, Some(label)));
+                }
+            }
+
+            if !is_valid_gtin(barcode) {
+                return Err(AppError::validation("barcode", "Dígito verificador inválido"));
+            }
+        }
 
+        Err(AppError::not_found(format!("Product with barcode {} not found", barcode)))
+    }
+
+    /// PUT /products/:id - Atualiza um produto.
+    /// Uma mudança de `balance` vira um movimento de ajuste no livro de estoque.
+    pub fn update(state: &AppState, id: i64, dto: UpdateProductDto) -> AppResult<ProductEntity> {
         // Busca o produto existente
-        let existing = Self::find_by_id(id)?
-            .ok_or_else(|| format!("Product with id {} not found", id))?;
+        let existing = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id)))?;
 
         // Atualiza apenas os campos fornecidos
-        let updated_code = code.unwrap_or(existing.code);
-        let updated_name = name.unwrap_or(existing.name);
-        let updated_active = active.unwrap_or(existing.active);
-        let updated_balance = balance.unwrap_or(existing.balance);
-
-        conn.execute(
-            "UPDATE produtos SET code = ?1, name = ?2, active = ?3, balance = ?4, updated_at = ?5 WHERE id = ?6",
-            params![
-                updated_code,
-                updated_name,
-                updated_active,
-                updated_balance,
-                Utc::now().to_rfc3339(),
-                id
-            ],
-        ).map_err(|e| format!("Failed to update product: {}", e))?;
-
-        Ok(ProductEntity {
-            id: Some(id),
-            code: updated_code,
-            name: updated_name,
-            active: updated_active,
-            balance: updated_balance,
-            created_at: existing.created_at,
-            updated_at: Utc::now(),
-        })
+        let mut updated = ProductEntity {
+            code: dto.code.unwrap_or_else(|| existing.code.clone()),
+            name: dto.name.unwrap_or_else(|| existing.name.clone()),
+            active: dto.active.unwrap_or(existing.active),
+            updated_at: state.clock.now(),
+            ..existing.clone()
+        };
+        Self::apply_fiscal(&mut updated, dto.fiscal);
+        updated.validate()?;
+        Self::check_category(state, &updated)?;
+        Self::check_units(state, &updated)?;
+        if let Some(balance) = dto.balance {
+            UnitService::check_quantity(state, &updated.unit, balance, "balance")?;
+        }
+
+        // Ao ligar o controle de lotes, o saldo atual vira o lote inicial
+        let opening_lot = match updated.track_lots == 1 && existing.track_lots != 1 {
+            true => LotService::opening_lot(state, &existing)?,
+            false => None,
+        };
+
+        let mut saved = Self::save(state, &updated)?;
+        if let Some(lot) = opening_lot {
+            LotService::insert(state, lot)?;
+        }
+        if let Some(delta) = dto.balance.map(|b| b - existing.balance).filter(|d| !d.is_zero()) {
+            let movement = StockMovementEntity {
+                origin: state.origin,
+                created_at: updated.updated_at,
+                ..StockMovementEntity::new(id, MovementType::Adjustment, delta, None, None)
+            };
+            if let Some(last) = state.stock.record_all(&LotService::allocate(state, movement)?)?.pop() {
+                saved.balance = last.balance_after;
+            }
+            StockService::notify_low_stock(state, StockService::crossed_min(&existing, saved.balance).as_slice());
+        }
+
+        Self::record(state, "update", Some(&existing), Some(&saved));
+        Ok(saved)
     }
 
     /// Salva ou atualiza um produto (interno)
-    pub fn save(product: &ProductEntity) -> Result<ProductEntity, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        if let Some(id) = product.id {
-            // Update
-            conn.execute(
-                "UPDATE produtos SET code = ?1, name = ?2, active = ?3, balance = ?4, updated_at = ?5 WHERE id = ?6",
-                params![
-                    product.code,
-                    product.name,
-                    product.active,
-                    product.balance,
-                    Utc::now().to_rfc3339(),
-                    id
-                ],
-            ).map_err(|e| format!("Failed to update product: {}", e))?;
-
-            Ok(ProductEntity { id: Some(id), updated_at: Utc::now(), ..product.clone() })
-        } else {
-            // Insert
-            conn.execute(
-                "INSERT INTO produtos (code, name, active, balance, created_at, updated_at) 
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![
-                    product.code,
-                    product.name,
-                    product.active,
-                    product.balance,
-                    product.created_at.to_rfc3339(),
-                    product.updated_at.to_rfc3339()
-                ],
-            ).map_err(|e| format!("Failed to insert product: {}", e))?;
-
-            let id = conn.last_insert_rowid();
-            Ok(ProductEntity { id: Some(id), ..product.clone() })
+    pub fn save(state: &AppState, product: &ProductEntity) -> AppResult<ProductEntity> {
+        state.products.save(product)
+    }
+
+    /// PATCH /products/:id/increment - Registra uma entrada no estoque
+    pub fn increment_balance(state: &AppState, id: i64, change: BalanceChangeDto) -> AppResult<ProductEntity> {
+        Self::change_balance(state, "increment_balance", id, true, change)
+    }
+
+    /// PATCH /products/:id/decrement - Registra uma saída do estoque
+    pub fn decrement_balance(state: &AppState, id: i64, change: BalanceChangeDto) -> AppResult<ProductEntity> {
+        Self::change_balance(state, "decrement_balance", id, false, change)
+    }
+
+    /// Ajusta o saldo de um produto (interno - genérico)
+    pub fn update_balance(state: &AppState, id: i64, quantity: Quantity) -> AppResult<()> {
+        let movement = StockMovementEntity::new(id, MovementType::Adjustment, quantity, None, None);
+        StockService::apply(state, "update_balance", movement).map(|_| ())
+    }
+
+    /// DELETE /products/:id - Deleta um produto (soft delete - marca como inativo)
+    pub fn delete(state: &AppState, id: i64) -> AppResult<()> {
+        let before = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id)))?;
+        state.products.deactivate(id, state.clock.now())?;
+
+        let after = Self::find_by_id(state, id)?;
+        Self::record(state, "delete", Some(&before), after.as_ref());
+        Ok(())
+    }
+
+    /// GET /products/:id/components - Composição do kit, com custo e
+    /// disponibilidade calculados pelos componentes
+    pub fn components(state: &AppState, id: i64) -> AppResult<KitDto> {
+        if Self::find_by_id(state, id)?.is_none() {
+            return Err(AppError::not_found(format!("Product with id {} not found", id)));
+        }
+
+        let mut components = Vec::new();
+        for item in state.compositions.find_by_kit(id)? {
+            let product = Self::find_by_id(state, item.component_id)?
+                .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", item.component_id)))?;
+            // Só kits completos: saldo inteiro dividido pela quantidade por kit
+            let available = Quantity::from_int((product.balance.units() / item.quantity.units()).max(0));
+            components.push(KitComponentDto {
+                product_id: item.component_id,
+                code: product.code,
+                name: product.name,
+                unit: product.unit,
+                quantity: item.quantity,
+                price: product.price,
+                balance: product.balance,
+                available,
+            });
         }
+
+        Ok(KitDto {
+            kit_id: id,
+            cost: components.iter().map(|c| c.price * c.quantity).sum::<Money>(),
+            available: components.iter().map(|c| c.available).min().unwrap_or(Quantity::ZERO),
+            components,
+        })
     }
 
-    /// PATCH /products/:id/increment - Incrementa o saldo de um produto
-    pub fn increment_balance(id: i64, amount: f64) -> Result<ProductEntity, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
+    /// PUT /products/:id/components - Define os componentes do kit; lista
+    /// vazia desfaz o kit. Kits não podem ter outros kits como componentes.
+    pub fn set_components(state: &AppState, id: i64, components: Vec<ComponentDto>) -> AppResult<KitDto> {
+        if Self::find_by_id(state, id)?.is_none() {
+            return Err(AppError::not_found(format!("Product with id {} not found", id)));
+        }
+
+        let mut errors = Vec::new();
+        if !components.is_empty() {
+            if let Some(parent) = state.compositions.find_by_component(id)?.first() {
+                errors.push(FieldError::new("components", format!("Produto já é componente do kit {}", parent.kit_id)));
+            }
+        }
+        for (i, component) in components.iter().enumerate() {
+            let field = format!("components[{}]", i);
+            if component.quantity <= Quantity::ZERO {
+                errors.push(FieldError::new(&format!("{}.quantity", field), "Quantidade deve ser maior que zero"));
+            }
+            let message = if component.product_id == id {
+                Some("Kit não pode ser componente dele mesmo".to_string())
+            } else if components[..i].iter().any(|c| c.product_id == component.product_id) {
+                Some("Componente repetido".to_string())
+            } else if Self::find_by_id(state, component.product_id)?.is_none() {
+                Some(format!("Produto {} não encontrado", component.product_id))
+            } else if !state.compositions.find_by_kit(component.product_id)?.is_empty() {
+                Some("Componente não pode ser outro kit".to_string())
+            } else {
+                None
+            };
+            if let Some(message) = message {
+                errors.push(FieldError::new(&format!("{}.product_id", field), message));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AppError::invalid_fields(errors));
+        }
+
+        let before = state.compositions.find_by_kit(id)?;
+        let after: Vec<CompositionItemEntity> = components.into_iter()
+            .map(|c| CompositionItemEntity { kit_id: id, component_id: c.product_id, quantity: c.quantity })
+            .collect();
+        state.compositions.replace(id, &after)?;
 
-        conn.execute(
-            "UPDATE produtos SET balance = balance + ?1, updated_at = ?2 WHERE id = ?3",
-            params![amount, Utc::now().to_rfc3339(), id],
-        ).map_err(|e| format!("Failed to increment balance: {}", e))?;
+        HistoryService::record(state, "update_components", ENTITY_PRODUCT, Some(id.to_string()), Some(&before), Some(&after));
+        Self::components(state, id)
+    }
+
+    /// Produtos que saem do estoque ao vender `quantity` de `product`: o
+    /// próprio produto ou, num kit, cada componente na proporção da composição
+    pub(crate) fn stock_components(state: &AppState, product: ProductEntity, quantity: Quantity) -> AppResult<Vec<(ProductEntity, Quantity)>> {
+        let Some(id) = product.id else {
+            return Ok(vec![(product, quantity)]);
+        };
+        let composition = state.compositions.find_by_kit(id)?;
+        if composition.is_empty() {
+            return Ok(vec![(product, quantity)]);
+        }
 
-        Self::find_by_id(id)?
-            .ok_or_else(|| format!("Product with id {} not found after update", id))
+        composition.into_iter().map(|item| {
+            let component = Self::find_by_id(state, item.component_id)?
+                .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", item.component_id)))?;
+            Ok((component, quantity * item.quantity))
+        }).collect()
     }
 
-    /// PATCH /products/:id/decrement - Decrementa o saldo de um produto
-    pub fn decrement_balance(id: i64, amount: f64) -> Result<ProductEntity, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
+    /// Registra no livro uma entrada (`entry`) ou saída de `change.amount`
+    fn change_balance(state: &AppState, action: &str, id: i64, entry: bool, change: BalanceChangeDto) -> AppResult<ProductEntity> {
+        let kind = change.kind.unwrap_or(MovementType::Adjustment);
+        if kind.is_entry().is_some_and(|is_entry| is_entry != entry) {
+            return Err(AppError::validation("type", format!("Movimento '{}' não combina com esta operação", kind.as_str())));
+        }
+        if change.amount.is_negative() || change.amount.is_zero() {
+            return Err(AppError::validation("amount", "Quantidade deve ser positiva"));
+        }
+        let product = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id)))?;
+        let amount = UnitService::to_stock(state, &product, change.unit.as_deref(), change.amount, "amount")?;
+        let lot_id = LotService::resolve(state, &product, change.lot.as_deref(), change.expires_at, entry)?;
 
-        conn.execute(
-            "UPDATE produtos SET balance = balance - ?1, updated_at = ?2 WHERE id = ?3",
-            params![amount, Utc::now().to_rfc3339(), id],
-        ).map_err(|e| format!("Failed to decrement balance: {}", e))?;
+        let delta = if entry { amount } else { -amount };
+        let movement = StockMovementEntity { lot_id, ..StockMovementEntity::new(id, kind, delta, change.reference, change.user) };
+        StockService::apply(state, action, movement)?;
 
-        Self::find_by_id(id)?
-            .ok_or_else(|| format!("Product with id {} not found after update", id))
+        Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", id)))
     }
 
-    /// Atualiza o saldo de um produto (interno - genérico)
-    pub fn update_balance(id: i64, quantity: f64) -> Result<(), String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
+    /// Copia os dados fiscais informados para o produto; texto vazio limpa o campo
+    pub(crate) fn apply_fiscal(product: &mut ProductEntity, fiscal: ProductFiscalDto) {
+        let text = |value: Option<String>, current: &mut Option<String>| {
+            if let Some(value) = value {
+                let value = value.trim();
+                *current = (!value.is_empty()).then(|| value.to_string());
+            }
+        };
+
+        if let Some(price) = fiscal.price {
+            product.price = price;
+        }
+        if let Some(unit) = fiscal.unit {
+            product.unit = UnitEntity::normalize_code(&unit);
+        }
+        if let Some(purchase_unit) = fiscal.purchase_unit {
+            let purchase_unit = UnitEntity::normalize_code(&purchase_unit);
+            product.purchase_unit = (!purchase_unit.is_empty()).then_some(purchase_unit);
+        }
+        if let Some(purchase_factor) = fiscal.purchase_factor {
+            product.purchase_factor = purchase_factor;
+        }
+        if let Some(origem) = fiscal.origem {
+            product.origem = origem;
+        }
+        if let Some(category_id) = fiscal.category_id {
+            product.category_id = (category_id != 0).then_some(category_id);
+        }
+        if let Some(track_lots) = fiscal.track_lots {
+            product.track_lots = track_lots;
+        }
+        if let Some(min_stock) = fiscal.min_stock {
+            product.min_stock = (!min_stock.is_zero()).then_some(min_stock);
+        }
+        if let Some(max_stock) = fiscal.max_stock {
+            product.max_stock = (!max_stock.is_zero()).then_some(max_stock);
+        }
+        text(fiscal.gtin, &mut product.gtin);
+        text(fiscal.ncm, &mut product.ncm);
+        text(fiscal.cest, &mut product.cest);
+        text(fiscal.cfop, &mut product.cfop);
+        text(fiscal.cst, &mut product.cst);
+    }
 
-        conn.execute(
-            "UPDATE produtos SET balance = balance + ?1, updated_at = ?2 WHERE id = ?3",
-            params![quantity, Utc::now().to_rfc3339(), id],
-        ).map_err(|e| format!("Failed to update balance: {}", e))?;
+    /// A categoria informada precisa existir
+    pub(crate) fn check_category(state: &AppState, product: &ProductEntity) -> AppResult<()> {
+        match product.category_id {
+            Some(id) if state.categories.find_by_id(id)?.is_none() => {
+                Err(AppError::validation("category_id", format!("Categoria {} não encontrada", id)))
+            }
+            _ => Ok(()),
+        }
+    }
 
-        Ok(())
+    /// As unidades de venda e de compra precisam estar cadastradas
+    pub(crate) fn check_units(state: &AppState, product: &ProductEntity) -> AppResult<()> {
+        let mut errors = Vec::new();
+        for (field, unit) in [("unit", Some(&product.unit)), ("purchase_unit", product.purchase_unit.as_ref())] {
+            if let Some(unit) = unit {
+                if state.units.find_by_code(unit)?.is_none() {
+                    errors.push(FieldError::new(field, format!("Unidade {} não cadastrada", unit)));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::invalid_fields(errors))
+        }
     }
 
-    /// DELETE /products/:id - Deleta um produto (soft delete - marca como inativo)
-    pub fn delete(id: i64) -> Result<(), String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
+    pub(crate) fn record(state: &AppState, action: &str, before: Option<&ProductEntity>, after: Option<&ProductEntity>) {
+        let id = after.or(before).and_then(|p| p.id).map(|id| id.to_string());
+        HistoryService::record(state, action, ENTITY_PRODUCT, id, before, after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::barcode::{ScaleLayout, ScaleValueKind};
+    use crate::entities::{ConfigEntity, Money, NegativeStockPolicy};
+    use crate::services::VendaService;
+    use crate::test_support::{cash, item, new_venda, on_both_backends, save_product};
+
+    fn search_flow(state: &AppState) {
+        for (code, name) in [("001", "Arroz Tio João 5kg"), ("002", "Feijão Carioca"), ("7891000100103", "Biscoito de Arroz"), ("003", "Açúcar")] {
+            ProductService::create(state, CreateProductDto { code: code.to_string(), name: name.to_string(), fiscal: Default::default() }).unwrap();
+        }
+        let search = |q: &str| {
+            let query = ProductSearchQuery { q: q.to_string(), ..Default::default() };
+            let page = ProductService::search(state, &query).unwrap();
+            page.items.into_iter().map(|p| p.code).collect::<Vec<_>>()
+        };
+
+        // Acentos e maiúsculas não importam; quem começa pelo termo vem antes
+        assert_eq!(search("ARR"), vec!["001", "7891000100103"]);
+        assert_eq!(search("joao"), vec!["001"]);
+        assert_eq!(search("acuc"), vec!["003"]);
+        assert_eq!(search("feij car"), vec!["002"]);
+        assert_eq!(search("78910"), vec!["7891000100103"]);
+        assert_eq!(search("002"), vec!["002"]);
+        assert!(search("arroz feijão").is_empty());
+
+        let query = ProductSearchQuery { q: "a".to_string(), page: Some(2), per_page: Some(2), active: None };
+        let page = ProductService::search(state, &query).unwrap();
+        assert_eq!((page.total, page.items.len(), page.page, page.per_page), (3, 1, 2, 2));
+
+        // O índice acompanha as alterações do cadastro
+        let product = ProductService::find_by_code(state, "002").unwrap().unwrap();
+        let dto = UpdateProductDto { name: Some("Feijão Preto".to_string()), ..Default::default() };
+        ProductService::update(state, product.id.unwrap(), dto).unwrap();
+        assert!(search("carioca").is_empty());
+        assert_eq!(search("preto"), vec!["002"]);
+
+        ProductService::delete(state, product.id.unwrap()).unwrap();
+        let query = ProductSearchQuery { q: "feijao".to_string(), active: Some(true), ..Default::default() };
+        assert_eq!(ProductService::search(state, &query).unwrap().total, 0);
+
+        let err = ProductService::search(state, &ProductSearchQuery { q: " - ".to_string(), ..Default::default() }).unwrap_err();
+        assert_eq!(err.code(), "VALIDATION_ERROR");
+    }
 
-        conn.execute(
-            "UPDATE produtos SET active = 0, updated_at = ?1 WHERE id = ?2",
-            params![Utc::now().to_rfc3339(), id],
-        ).map_err(|e| format!("Failed to delete product: {}", e))?;
+    /// Queijo vendido por quilo, código de balança 123
+    fn create_cheese(state: &AppState) {
+        let mut dto = CreateProductDto { code: "123".to_string(), name: "Queijo Minas".to_string(), fiscal: Default::default() };
+        dto.fiscal.price = Some(Money::from_cents(4990));
+        dto.fiscal.unit = Some("KG".to_string());
+        dto.fiscal.gtin = Some("7891000100103".to_string());
+        ProductService::create(state, dto).unwrap();
+    }
 
-        Ok(())
+    fn barcode_flow(state: &AppState) {
+        create_cheese(state);
+
+        let found = ProductService::lookup_barcode(state, "7891000100103").unwrap();
+        assert_eq!((found.matched_by, found.item.quantidade), (BarcodeMatch::Gtin, Quantity::from_int(1)));
+
+        // 2 0123 0 002495 D: R$ 24,95 de queijo a R$ 49,90/kg
+        let label = format!("201230002495{}", crate::barcode::check_digit("201230002495").unwrap());
+        let found = ProductService::lookup_barcode(state, &label).unwrap();
+        assert_eq!(found.matched_by, BarcodeMatch::Scale);
+        assert_eq!((found.item.quantidade, found.item.preco_total), (Quantity::from_units(500), Money::from_cents(2495)));
+        assert_eq!((found.item.produto_code.as_str(), found.item.produto_medida.as_str()), ("123", "KG"));
+
+        // Etiqueta de peso (5 dígitos de código e 5 de gramas)
+        let config = ConfigEntity {
+            balanca: ScaleLayout { code_digits: 5, value_digits: 5, kind: ScaleValueKind::Weight },
+            ..Default::default()
+        };
+        ConfigService::save(state, &config).unwrap();
+        let label = format!("200123001250{}", crate::barcode::check_digit("200123001250").unwrap());
+        let found = ProductService::lookup_barcode(state, &label).unwrap();
+        assert_eq!((found.item.quantidade, found.item.preco_total), (Quantity::from_units(1250), Money::from_cents(6238)));
+
+        assert_eq!(ProductService::lookup_barcode(state, "7891000100110").unwrap_err().code(), "NOT_FOUND");
+        assert_eq!(ProductService::lookup_barcode(state, "7891000100111").unwrap_err().code(), "VALIDATION_ERROR");
+    }
+
+    fn scale_label_sale(state: &AppState) {
+        create_cheese(state);
+
+        // R$ 10,00 a R$ 49,90/kg: 0,200 kg (R$ 9,98) e R$ 0,02 de acréscimo,
+        // para que a venda cobre o valor impresso na etiqueta
+        let label = format!("201230001000{}", crate::barcode::check_digit("201230001000").unwrap());
+        let found = ProductService::lookup_barcode(state, &label).unwrap();
+        assert_eq!((found.item.quantidade, found.item.acrescimo), (Quantity::from_units(200), Money::from_cents(2)));
+        let total = Money::from_int(10);
+        let created = VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", total), vec![found.item], cash(total)).unwrap();
+        let itens = VendaService::find_items_by_venda_id(state, created.id).unwrap();
+        assert_eq!((itens[0].preco_unitario, itens[0].preco_total), (Money::from_cents(4990), total));
+        assert_eq!(VendaService::find_by_id(state, created.id).unwrap().unwrap().total, total);
+    }
+
+    fn kit_flow(state: &AppState) {
+        let save = |code: &str, price: i64, balance: i64| save_product(state, code, Money::from_int(price), Quantity::from_int(balance));
+        let cesta = save("CESTA", 50, 0);
+        let vinho = save("VINHO", 30, 5);
+        let queijo = save("QUEIJO", 12, 3);
+        let component = |product_id: i64, quantity: i64| ComponentDto { product_id, quantity: Quantity::from_int(quantity) };
+
+        let kit = ProductService::set_components(state, cesta, vec![component(vinho, 1), component(queijo, 2)]).unwrap();
+        assert_eq!((kit.cost, kit.available), (Money::from_int(54), Quantity::from_int(1)));
+        assert_eq!(kit.components.iter().map(|c| c.available).collect::<Vec<_>>(), vec![Quantity::from_int(5), Quantity::from_int(1)]);
+
+        let err = ProductService::set_components(state, vinho, vec![component(cesta, 1), component(vinho, 1), component(queijo, 0)]).unwrap_err();
+        let AppError::Validation { fields, .. } = err else { panic!("expected validation error") };
+        let fields: Vec<&str> = fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, vec!["components", "components[0].product_id", "components[1].product_id", "components[2].quantity"]);
+
+        // Kit não tem estoque próprio
+        let change = BalanceChangeDto { amount: Quantity::from_int(1), ..Default::default() };
+        assert_eq!(ProductService::increment_balance(state, cesta, change).unwrap_err().code(), "VALIDATION_ERROR");
+
+        let config = ConfigEntity { id: "default".to_string(), controle_estoque: 1, estoque_negativo: NegativeStockPolicy::Warn, ..Default::default() };
+        ConfigService::save(state, &config).unwrap();
+        let balance = |id: i64| ProductService::find_by_id(state, id).unwrap().unwrap().balance;
+
+        let items = vec![item("CESTA", Quantity::from_int(2)), item("VINHO", Quantity::from_int(1))];
+        let total = Money::from_int(130);
+        let created = VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", total), items, cash(total)).unwrap();
+        assert_eq!((balance(vinho), balance(queijo), balance(cesta)), (Quantity::from_int(2), Quantity::from_int(-1), Quantity::ZERO));
+        assert_eq!(created.stock_warnings.iter().map(|w| w.product_id).collect::<Vec<_>>(), vec![queijo]);
+
+        // A cesta continua sendo uma linha da venda, com o preço do kit
+        let itens = VendaService::find_items_by_venda_id(state, created.id).unwrap();
+        assert_eq!((itens[0].produto_code.as_str(), itens[0].preco_total), ("CESTA", Money::from_int(100)));
+
+        VendaService::cancel_venda(state, created.id, "CFe2".to_string(), "2024-06-15T11:00:00".to_string(), None).unwrap();
+        assert_eq!((balance(vinho), balance(queijo)), (Quantity::from_int(5), Quantity::from_int(3)));
+
+        let kit = ProductService::set_components(state, cesta, vec![]).unwrap();
+        assert!(kit.components.is_empty());
+        assert_eq!(kit.available, Quantity::ZERO);
+    }
+
+    #[test]
+    fn test_kits() {
+        on_both_backends(kit_flow);
+    }
+
+    #[test]
+    fn test_barcode_lookup() {
+        on_both_backends(barcode_flow);
+    }
+
+    #[test]
+    fn test_scale_label_sale() {
+        on_both_backends(scale_label_sale);
+    }
+
+    #[test]
+    fn test_search() {
+        on_both_backends(search_flow);
     }
 }
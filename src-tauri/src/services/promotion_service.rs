@@ -0,0 +1,300 @@
+use crate::dtos::{PricingDto, PricingPreviewDto, PromotionListQuery, SavePromotionDto};
+use crate::error::{AppError, AppResult};
+use crate::entities::{Money, ProductEntity, PromotionEntity, PromotionRule, Quantity, VendaItemEntity};
+use crate::services::category_service::CategoryTree;
+use crate::services::history_service::{HistoryService, ENTITY_PROMOTION};
+use crate::services::VendaService;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+pub struct PromotionService;
+
+impl PromotionService {
+    /// POST /promotions - Cria uma promoção
+    pub fn create(state: &AppState, dto: SavePromotionDto) -> AppResult<PromotionEntity> {
+        let now = state.clock.now();
+        let promotion = PromotionEntity { created_at: now, updated_at: now, ..Self::from_dto(state, dto)? };
+        Self::check(state, &promotion)?;
+
+        let saved = state.promotions.save(&promotion)?;
+        Self::record(state, "create", None, Some(&saved));
+        Ok(saved)
+    }
+
+    /// GET /promotions/:id - Busca uma promoção por ID
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<PromotionEntity>> {
+        state.promotions.find_by_id(id)
+    }
+
+    /// GET /promotions - Lista as promoções (ou só as em vigor)
+    pub fn find_all(state: &AppState, query: &PromotionListQuery) -> AppResult<Vec<PromotionEntity>> {
+        if query.current.unwrap_or(false) {
+            state.promotions.find_valid_at(state.clock.now())
+        } else {
+            state.promotions.find_all()
+        }
+    }
+
+    /// PUT /promotions/:id - Substitui uma promoção
+    pub fn update(state: &AppState, id: i64, dto: SavePromotionDto) -> AppResult<PromotionEntity> {
+        let existing = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Promotion with id {} not found", id)))?;
+
+        let updated = PromotionEntity {
+            id: Some(id),
+            created_at: existing.created_at,
+            updated_at: state.clock.now(),
+            ..Self::from_dto(state, dto)?
+        };
+        Self::check(state, &updated)?;
+
+        let saved = state.promotions.save(&updated)?;
+        Self::record(state, "update", Some(&existing), Some(&saved));
+        Ok(saved)
+    }
+
+    /// DELETE /promotions/:id - Remove uma promoção. Itens já vendidos
+    /// continuam com o `promotion_id`; os dados dela ficam no histórico.
+    pub fn delete(state: &AppState, id: i64) -> AppResult<()> {
+        let existing = Self::find_by_id(state, id)?
+            .ok_or_else(|| AppError::not_found(format!("Promotion with id {} not found", id)))?;
+
+        state.promotions.delete(id)?;
+        Self::record(state, "delete", Some(&existing), None);
+        Ok(())
+    }
+
+    /// POST /promotions/preview - Precifica os itens como na venda, sem gravar
+    pub fn preview(state: &AppState, dto: PricingPreviewDto) -> AppResult<PricingDto> {
+        let at = match dto.dh_emi.as_deref() {
+            Some(dh_emi) => state.clock
+                .parse_instant(dh_emi)
+                .ok_or_else(|| AppError::validation("dh_emi", format!("Invalid dh_emi '{}'", dh_emi)))?,
+            None => state.clock.now(),
+        };
+        let items = VendaService::price_items(state, dto.items, at)?;
+
+        let subtotal: Money = items.iter().map(VendaItemEntity::gross_total).sum();
+        let desconto: Money = items.iter().map(|i| i.desconto).sum();
+        let acrescimo: Money = items.iter().map(|i| i.acrescimo).sum();
+        Ok(PricingDto { items, subtotal, desconto, total: subtotal - desconto + acrescimo })
+    }
+
+    /// Aplica as promoções em vigor em `at` aos itens de produtos cadastrados.
+    ///
+    /// Itens iguais (mesmo produto e preço) são somados antes do cálculo, para
+    /// que "leve 3, pague 2" valha com o produto passado um a um no caixa; o
+    /// desconto é então rateado entre eles pelo valor bruto. Entre as
+    /// promoções do produto vale a de maior desconto, sem acumular. Itens com
+    /// desconto manual ficam como vieram; os que já trazem `promotion_id` são
+    /// recalculados.
+    pub(crate) fn apply(state: &AppState, items: &mut [VendaItemEntity], at: DateTime<Utc>) -> AppResult<()> {
+        for item in items.iter_mut().filter(|i| i.promotion_id.is_some()) {
+            item.desconto = Money::ZERO;
+            item.promotion_id = None;
+        }
+
+        let promotions = state.promotions.find_valid_at(at)?;
+        if promotions.is_empty() {
+            return Ok(());
+        }
+        let tree = CategoryTree::load(state)?;
+
+        let mut groups: Vec<(ProductEntity, Money, Vec<usize>)> = Vec::new();
+        let mut products: HashMap<String, Option<ProductEntity>> = HashMap::new();
+        for (i, item) in items.iter().enumerate() {
+            if !item.desconto.is_zero() {
+                continue;
+            }
+            let product = match products.get(&item.produto_code) {
+                Some(product) => product.clone(),
+                None => {
+                    let product = state.products.find_by_code(&item.produto_code)?;
+                    products.insert(item.produto_code.clone(), product.clone());
+                    product
+                }
+            };
+            let Some(product) = product else { continue };
+
+            match groups.iter_mut().find(|(p, price, _)| p.id == product.id && *price == item.preco_unitario) {
+                Some((_, _, indexes)) => indexes.push(i),
+                None => groups.push((product, item.preco_unitario, vec![i])),
+            }
+        }
+
+        for (product, unit_price, indexes) in groups {
+            let quantity: Quantity = indexes.iter().map(|&i| items[i].quantidade).sum();
+            let total: Money = indexes.iter().map(|&i| items[i].gross_total()).sum();
+
+            let mut best: Option<(&PromotionEntity, Money)> = None;
+            for promotion in promotions.iter().filter(|p| applies_to(&p.rule, &product, &tree)) {
+                let discount = promotion.rule.discount(quantity, unit_price, total);
+                if discount > best.map(|(_, d)| d).unwrap_or(Money::ZERO) {
+                    best = Some((promotion, discount));
+                }
+            }
+            let Some((promotion, discount)) = best else { continue };
+
+            // Rateio proporcional; o último item fica com a diferença dos arredondamentos
+            let mut remaining = discount;
+            for (n, &i) in indexes.iter().enumerate() {
+                let share = if n + 1 == indexes.len() {
+                    remaining
+                } else {
+                    let cents = discount.cents() as i128 * items[i].gross_total().cents() as i128 / total.cents() as i128;
+                    Money::from_cents(cents as i64)
+                };
+                remaining -= share;
+                items[i].desconto = share;
+                items[i].promotion_id = promotion.id;
+            }
+        }
+        Ok(())
+    }
+
+    fn from_dto(state: &AppState, dto: SavePromotionDto) -> AppResult<PromotionEntity> {
+        let instant = |field: &str, value: Option<String>| match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+            Some(value) => state.clock
+                .parse_instant(value)
+                .map(Some)
+                .ok_or_else(|| AppError::validation(field, format!("Data/hora inválida: '{}'", value))),
+            None => Ok(None),
+        };
+
+        Ok(PromotionEntity {
+            starts_at: instant("starts_at", dto.starts_at)?,
+            ends_at: instant("ends_at", dto.ends_at)?,
+            active: dto.active.unwrap_or(true) as i32,
+            ..PromotionEntity::new(dto.name.trim().to_string(), dto.rule)
+        })
+    }
+
+    /// Valida a promoção e a existência do produto ou da categoria
+    fn check(state: &AppState, promotion: &PromotionEntity) -> AppResult<()> {
+        promotion.validate()?;
+
+        match &promotion.rule {
+            PromotionRule::CategoryPercent { category_id, .. } => {
+                if state.categories.find_by_id(*category_id)?.is_none() {
+                    return Err(AppError::validation("category_id", format!("Categoria {} não encontrada", category_id)));
+                }
+            }
+            rule => {
+                let product_id = rule.product_id().unwrap_or_default();
+                if state.products.find_by_id(product_id)?.is_none() {
+                    return Err(AppError::validation("product_id", format!("Produto {} não encontrado", product_id)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn record(state: &AppState, action: &str, before: Option<&PromotionEntity>, after: Option<&PromotionEntity>) {
+        let id = after.or(before).and_then(|p| p.id).map(|id| id.to_string());
+        HistoryService::record(state, action, ENTITY_PROMOTION, id, before, after);
+    }
+}
+
+/// A promoção vale para o produto (direto ou pela categoria, incluindo subcategorias)
+fn applies_to(rule: &PromotionRule, product: &ProductEntity, tree: &CategoryTree) -> bool {
+    match rule {
+        PromotionRule::CategoryPercent { category_id, .. } => product.category_id
+            .is_some_and(|c| tree.subtree(*category_id).contains(&c)),
+        rule => rule.product_id().is_some() && rule.product_id() == product.id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{CategoryEntity, Percent, PromotionTier};
+    use crate::test_support::{cash, item, new_venda, on_both_backends};
+
+    fn save_promotion(state: &AppState, name: &str, rule: PromotionRule, starts_at: Option<&str>, ends_at: Option<&str>) -> AppResult<i64> {
+        let dto = SavePromotionDto {
+            name: name.to_string(),
+            rule,
+            starts_at: starts_at.map(str::to_string),
+            ends_at: ends_at.map(str::to_string),
+            active: None,
+        };
+        Ok(PromotionService::create(state, dto)?.id.unwrap())
+    }
+
+    fn promotion_flow(state: &AppState) {
+        let bebidas = state.categories.save(&CategoryEntity::new("Bebidas".to_string(), None)).unwrap().id;
+        let refri = state.categories.save(&CategoryEntity::new("Refrigerantes".to_string(), bebidas)).unwrap().id;
+        let save_in_category = |code: &str, price: i64, category_id: Option<i64>| {
+            let product = ProductEntity { price: Money::from_int(price), category_id, ..ProductEntity::new(code.to_string(), code.to_string()) };
+            state.products.save(&product).unwrap().id.unwrap()
+        };
+        let arroz = save_in_category("001", 10, None);
+        let guarana = save_in_category("002", 4, refri);
+        let sabao = save_in_category("003", 5, None);
+
+        let oferta = save_promotion(state, "Oferta do dia", PromotionRule::Price { product_id: arroz, price: Money::from_int(8) },
+            Some("2024-06-15T08:00"), Some("2024-06-16")).unwrap();
+        let leve3 = save_promotion(state, "Leve 3 pague 2", PromotionRule::BuyPay { product_id: guarana, buy: 3, pay: 2 }, None, None).unwrap();
+        let atacado = save_promotion(state, "Atacado", PromotionRule::Progressive {
+            product_id: sabao,
+            tiers: vec![
+                PromotionTier { min_quantity: Quantity::from_int(10), percent: Percent::from_int(20) },
+                PromotionTier { min_quantity: Quantity::from_int(5), percent: Percent::from_int(10) },
+            ],
+        }, None, None).unwrap();
+        save_promotion(state, "Semana das bebidas", PromotionRule::CategoryPercent { category_id: bebidas.unwrap(), percent: Percent::from_int(10) }, None, None).unwrap();
+
+        let invalid = save_promotion(state, "Leve 2", PromotionRule::BuyPay { product_id: guarana, buy: 2, pay: 2 }, None, None);
+        assert_eq!(invalid.unwrap_err().code(), "VALIDATION_ERROR");
+        let unknown = save_promotion(state, "?", PromotionRule::Price { product_id: 99, price: Money::from_int(1) }, None, None);
+        assert_eq!(unknown.unwrap_err().code(), "VALIDATION_ERROR");
+        let reversed = save_promotion(state, "?", PromotionRule::Price { product_id: arroz, price: Money::from_int(1) }, Some("2024-06-16"), Some("2024-06-15"));
+        assert_eq!(reversed.unwrap_err().code(), "VALIDATION_ERROR");
+
+        // Guaraná passado um a um: leve 3 pague 2 (R$ 4) ganha dos 10% da categoria (R$ 1,20)
+        let q = Quantity::from_int;
+        let manual = VendaItemEntity { desconto: Money::from_int(1), ..item("001", q(1)) };
+        let items = vec![item("001", q(2)), item("002", q(1)), item("002", q(1)), item("002", q(1)), item("003", q(6)), manual];
+        let venda = new_venda("2024-06-15T10:30:00-03:00", Money::from_int(60));
+        let venda_id = VendaService::create_venda(state, &venda, items.clone(), cash(Money::from_int(60))).unwrap().id;
+
+        let saved = VendaService::find_items_by_venda_id(state, venda_id).unwrap();
+        let applied: Vec<(Money, Option<i64>)> = saved.iter().map(|i| (i.desconto, i.promotion_id)).collect();
+        assert_eq!(applied, vec![
+            (Money::from_int(4), Some(oferta)),
+            (Money::from_cents(133), Some(leve3)),
+            (Money::from_cents(133), Some(leve3)),
+            (Money::from_cents(134), Some(leve3)),
+            (Money::from_int(3), Some(atacado)),
+            (Money::from_int(1), None),
+        ]);
+
+        // Fora do período a oferta não vale; a prévia recalcula descontos já aplicados
+        let preview = PromotionService::preview(state, PricingPreviewDto { items: saved, dh_emi: Some("2024-06-16T09:00".to_string()) }).unwrap();
+        assert_eq!((preview.items[0].desconto, preview.items[0].promotion_id), (Money::ZERO, None));
+        assert_eq!(preview.subtotal, Money::from_int(20 + 12 + 30 + 10));
+        assert_eq!(preview.desconto, Money::from_int(4 + 3 + 1));
+        assert_eq!(preview.total, Money::from_int(64));
+
+        PromotionService::update(state, leve3, SavePromotionDto {
+            name: "Leve 3 pague 2".to_string(),
+            rule: PromotionRule::BuyPay { product_id: guarana, buy: 3, pay: 2 },
+            starts_at: None,
+            ends_at: None,
+            active: Some(false),
+        }).unwrap();
+        let preview = PromotionService::preview(state, PricingPreviewDto { items: items[1..4].to_vec(), dh_emi: None }).unwrap();
+        assert_eq!(preview.desconto, Money::from_cents(120));
+        assert!(PromotionService::find_all(state, &PromotionListQuery { current: Some(true) }).unwrap().iter().all(|p| p.id != Some(leve3)));
+
+        PromotionService::delete(state, atacado).unwrap();
+        assert!(PromotionService::find_by_id(state, atacado).unwrap().is_none());
+        assert_eq!(PromotionService::find_all(state, &PromotionListQuery::default()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_promotion_flow() {
+        on_both_backends(promotion_flow);
+    }
+}
@@ -0,0 +1,418 @@
+use crate::clock::parse_date;
+use crate::dtos::{
+    KardexDto, KardexQueryDto, LowStockDto, ReorderQueryDto, ReorderReportDto, ReorderSuggestionDto, StockDiscrepancyDto,
+    StockMovementDto,
+};
+use crate::error::{AppError, AppResult};
+use crate::entities::{MovementType, ProductEntity, Quantity, StockMovementEntity};
+use crate::repositories::StockMovementFilter;
+use crate::services::history_service::{HistoryService, ENTITY_PRODUCT};
+use crate::services::{LotService, UnitService};
+use crate::state::AppState;
+use chrono::Days;
+use std::collections::{HashMap, HashSet};
+
+/// Dias de venda usados na sugestão de compra quando o período não é informado
+pub const DEFAULT_REORDER_DAYS: u32 = 30;
+
+pub struct StockService;
+
+/// Aplica à quantidade informada (sem sinal) o sinal do tipo de movimento
+fn signed_quantity(kind: MovementType, quantity: Quantity) -> AppResult<Quantity> {
+    if quantity.is_zero() {
+        return Err(AppError::validation("quantity", "Quantidade deve ser diferente de zero"));
+    }
+
+    match kind.is_entry() {
+        None => Ok(quantity),
+        Some(_) if quantity.is_negative() => Err(AppError::validation(
+            "quantity",
+            format!("Quantidade de '{}' deve ser positiva; só ajustes aceitam valores negativos", kind.as_str()),
+        )),
+        Some(true) => Ok(quantity),
+        Some(false) => Ok(-quantity),
+    }
+}
+
+/// Divisão inteira arredondando para cima (divisor positivo)
+fn ceil_div(value: i128, divisor: i128) -> i128 {
+    value.div_euclid(divisor) + (value.rem_euclid(divisor) != 0) as i128
+}
+
+impl StockService {
+    /// Item de estoque baixo para um produto com saldo `balance`, se estiver
+    /// abaixo do mínimo
+    pub(crate) fn low_stock_entry(product: &ProductEntity, balance: Quantity) -> Option<LowStockDto> {
+        let min_stock = product.min_stock?;
+        (balance < min_stock).then(|| LowStockDto {
+            product_id: product.id.unwrap_or(0),
+            code: product.code.clone(),
+            name: product.name.clone(),
+            unit: product.unit.clone(),
+            balance,
+            min_stock,
+            max_stock: product.max_stock,
+        })
+    }
+
+    /// Item de estoque baixo só se o saldo cruzou o mínimo agora: quem já
+    /// estava abaixo dele não é avisado de novo
+    pub(crate) fn crossed_min(before: &ProductEntity, balance_after: Quantity) -> Option<LowStockDto> {
+        if Self::low_stock_entry(before, before.balance).is_some() {
+            return None;
+        }
+        Self::low_stock_entry(before, balance_after)
+    }
+
+    /// Avisa o frontend (`state.events`) dos produtos que ficaram abaixo do mínimo
+    pub(crate) fn notify_low_stock(state: &AppState, low_stock: &[LowStockDto]) {
+        if !low_stock.is_empty() {
+            state.events.low_stock(low_stock);
+        }
+    }
+
+    /// GET /stock/low - Produtos ativos com saldo abaixo do estoque mínimo
+    pub fn low_stock(state: &AppState) -> AppResult<Vec<LowStockDto>> {
+        let kits: HashSet<i64> = state.compositions.kit_ids()?.into_iter().collect();
+        Ok(state.products.find_all_active()?
+            .iter()
+            .filter(|p| p.id.is_some_and(|id| !kits.contains(&id)))
+            .filter_map(|p| Self::low_stock_entry(p, p.balance))
+            .collect())
+    }
+
+    /// GET /stock/reorder - Sugestão de compra pela média diária de vendas no
+    /// período. Só entram produtos ativos (exceto kits) com sugestão positiva.
+    pub fn reorder(state: &AppState, query: &ReorderQueryDto) -> AppResult<ReorderReportDto> {
+        let dt_fim = query.dt_fim.as_deref()
+            .map(|d| parse_date(d).map_err(|e| AppError::validation("dtFim", e)))
+            .transpose()?
+            .unwrap_or_else(|| state.clock.today());
+        let dt_init = query.dt_init.as_deref()
+            .map(|d| parse_date(d).map_err(|e| AppError::validation("dtInit", e)))
+            .transpose()?
+            .unwrap_or_else(|| dt_fim.checked_sub_days(Days::new(u64::from(DEFAULT_REORDER_DAYS) - 1)).unwrap_or(dt_fim));
+        if dt_init > dt_fim {
+            return Err(AppError::validation("dtInit", "Data inicial deve ser anterior ou igual à final"));
+        }
+        let coverage_days = query.coverage_days.unwrap_or(DEFAULT_REORDER_DAYS);
+        if coverage_days == 0 {
+            return Err(AppError::validation("coverageDays", "Dias de cobertura devem ser maior que zero"));
+        }
+        let days = (dt_fim - dt_init).num_days() + 1;
+
+        // Vendido no período: saídas por venda menos as devolvidas por cancelamento
+        let (start, end) = state.clock.day_range(dt_init, dt_fim);
+        let mut sold: HashMap<i64, Quantity> = HashMap::new();
+        for movement in state.stock.find(&StockMovementFilter { start: Some(start), end: Some(end), ..Default::default() })? {
+            if matches!(movement.kind, MovementType::Sale | MovementType::Cancellation) {
+                *sold.entry(movement.product_id).or_default() -= movement.quantity;
+            }
+        }
+
+        let kits: HashSet<i64> = state.compositions.kit_ids()?.into_iter().collect();
+        let units: HashMap<String, _> = state.units.find_all()?.into_iter().map(|u| (u.code.clone(), u)).collect();
+        let mut items = Vec::new();
+        for product in state.products.find_all_active()? {
+            let Some(product_id) = product.id.filter(|id| !kits.contains(id)) else { continue };
+            let sold = sold.get(&product_id).copied().unwrap_or_default().max(Quantity::ZERO);
+            let daily_average = Quantity::from_units((sold.units() as i128 * 2 + days as i128).div_euclid(days as i128 * 2) as i64);
+
+            let demand = Quantity::from_units(ceil_div(sold.units() as i128 * i128::from(coverage_days), days as i128) as i64);
+            let mut target = demand.max(product.min_stock.unwrap_or_default());
+            if let Some(max_stock) = product.max_stock {
+                target = target.min(max_stock);
+            }
+            let shortage = target - product.balance;
+            if shortage <= Quantity::ZERO {
+                continue;
+            }
+
+            let suggested = units.get(&product.unit).map(|u| u.round_up(shortage)).unwrap_or(shortage);
+            let purchase_quantity = product.purchase_unit.as_ref().map(|purchase_unit| {
+                let exact = Quantity::from_units(ceil_div(suggested.units() as i128 * 1000, product.purchase_factor.units() as i128) as i64);
+                units.get(purchase_unit).map(|u| u.round_up(exact)).unwrap_or(exact)
+            });
+
+            items.push(ReorderSuggestionDto {
+                product_id,
+                code: product.code,
+                name: product.name,
+                unit: product.unit,
+                balance: product.balance,
+                min_stock: product.min_stock,
+                max_stock: product.max_stock,
+                sold,
+                daily_average,
+                target,
+                suggested,
+                purchase_unit: product.purchase_unit,
+                purchase_quantity,
+            });
+        }
+
+        Ok(ReorderReportDto { dt_init, dt_fim, days, coverage_days, items })
+    }
+
+    /// POST /stock/movements - Registra um movimento de estoque, convertendo a
+    /// quantidade da unidade informada para a de estoque
+    pub fn register(state: &AppState, dto: StockMovementDto) -> AppResult<StockMovementEntity> {
+        let product = state.products.find_by_id(dto.product_id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", dto.product_id)))?;
+        let quantity = signed_quantity(dto.kind, dto.quantity)?;
+        let quantity = UnitService::to_stock(state, &product, dto.unit.as_deref(), quantity, "quantity")?;
+        let lot_id = LotService::resolve(state, &product, dto.lot.as_deref(), dto.expires_at, !quantity.is_negative())?;
+        let movement = StockMovementEntity {
+            lot_id,
+            ..StockMovementEntity::new(dto.product_id, dto.kind, quantity, dto.reference, dto.user)
+        };
+        Self::apply(state, &format!("stock_{}", dto.kind.as_str()), movement)
+    }
+
+    /// Grava o movimento (atualizando o saldo) e registra a alteração do
+    /// produto no histórico com a ação `action`. Kits não têm estoque próprio.
+    /// Nos produtos com controle de lotes, uma saída que atravessa lotes vira
+    /// uma linha por lote; o retorno é a última, com o saldo final.
+    pub fn apply(state: &AppState, action: &str, movement: StockMovementEntity) -> AppResult<StockMovementEntity> {
+        if !state.compositions.find_by_kit(movement.product_id)?.is_empty() {
+            return Err(AppError::validation("product_id", "Kit não tem estoque próprio; movimente os componentes"));
+        }
+        let movement = StockMovementEntity { origin: state.origin, created_at: state.clock.now(), ..movement };
+
+        let before = state.products.find_by_id(movement.product_id)?;
+        let saved = state.stock.record_all(&LotService::allocate(state, movement.clone())?)?
+            .pop()
+            .ok_or_else(|| AppError::Internal("Movement without stock lines".to_string()))?;
+        // O movimento já foi gravado; o estado depois só serve ao histórico e ao aviso
+        let after = state.products.find_by_id(movement.product_id).ok().flatten();
+
+        HistoryService::record(state, action, ENTITY_PRODUCT, Some(movement.product_id.to_string()), before.as_ref(), after.as_ref());
+        if let (Some(before), Some(after)) = (&before, &after) {
+            Self::notify_low_stock(state, Self::crossed_min(before, after.balance).as_slice());
+        }
+        Ok(saved)
+    }
+
+    /// GET /stock/kardex/:product_id - Movimentos do produto no período, com
+    /// saldo inicial e final
+    pub fn kardex(state: &AppState, product_id: i64, query: &KardexQueryDto) -> AppResult<KardexDto> {
+        let product = state.products.find_by_id(product_id)?
+            .ok_or_else(|| AppError::not_found(format!("Product with id {} not found", product_id)))?;
+
+        let start = query.dt_init.as_deref()
+            .map(|d| parse_date(d).map_err(|e| AppError::validation("dtInit", e)))
+            .transpose()?
+            .map(|d| state.clock.start_of_day(d));
+        let end = query.dt_fim.as_deref()
+            .map(|d| parse_date(d).map_err(|e| AppError::validation("dtFim", e)))
+            .transpose()?
+            .map(|d| state.clock.day_range(d, d).1);
+
+        let opening_balance = match start {
+            Some(start) => state.stock.sum_quantity(product_id, Some(start))?,
+            None => Quantity::ZERO,
+        };
+        let movements = state.stock.find(&StockMovementFilter { product_id: Some(product_id), start, end, ..Default::default() })?;
+
+        let total_in = movements.iter().filter(|m| !m.quantity.is_negative()).fold(Quantity::ZERO, |sum, m| sum + m.quantity);
+        let total_out = movements.iter().filter(|m| m.quantity.is_negative()).fold(Quantity::ZERO, |sum, m| sum - m.quantity);
+
+        Ok(KardexDto {
+            product_id,
+            code: product.code,
+            name: product.name,
+            opening_balance,
+            total_in,
+            total_out,
+            closing_balance: opening_balance + total_in - total_out,
+            movements,
+        })
+    }
+
+    /// GET/POST /stock/reconcile - Lista os produtos cujo saldo não bate com o
+    /// livro de movimentos. Com `fix`, grava no produto o saldo do livro.
+    pub fn reconcile(state: &AppState, fix: bool) -> AppResult<Vec<StockDiscrepancyDto>> {
+        let ledger: HashMap<i64, Quantity> = state.stock.ledger_balances()?.into_iter().collect();
+
+        let discrepancies: Vec<(ProductEntity, Quantity)> = state.products.find_all()?
+            .into_iter()
+            .filter_map(|p| {
+                let ledger_balance = p.id.and_then(|id| ledger.get(&id).copied()).unwrap_or_default();
+                (p.balance != ledger_balance).then_some((p, ledger_balance))
+            })
+            .collect();
+
+        if fix {
+            for (product, ledger_balance) in &discrepancies {
+                let id = product.id.unwrap_or(0);
+                state.products.set_balance(id, *ledger_balance, state.clock.now())?;
+
+                let after = state.products.find_by_id(id)?;
+                HistoryService::record(state, "reconcile_balance", ENTITY_PRODUCT, Some(id.to_string()), Some(product), after.as_ref());
+                Self::notify_low_stock(state, Self::crossed_min(product, *ledger_balance).as_slice());
+            }
+        }
+
+        Ok(discrepancies.into_iter()
+            .map(|(p, ledger_balance)| StockDiscrepancyDto {
+                product_id: p.id.unwrap_or(0),
+                code: p.code,
+                balance: p.balance,
+                ledger_balance,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::dtos::{BalanceChangeDto, CreateProductDto, ProductFiscalDto, UpdateProductDto};
+    use crate::entities::{ConfigEntity, Money, NegativeStockPolicy};
+    use crate::events::RecordedEvents;
+    use crate::services::{ConfigService, ProductService, VendaService};
+    use crate::test_support::{item, new_venda, on_both_backends};
+    use chrono::{TimeZone, Utc};
+    use std::sync::Arc;
+
+    fn at(state: &AppState, day: u32) -> AppState {
+        let now = Utc.with_ymd_and_hms(2024, 6, day, 15, 0, 0).unwrap();
+        state.clone().with_clock(Arc::new(FixedClock { now, timezone: state.clock.timezone() }))
+    }
+
+    fn movement(product_id: i64, kind: MovementType, quantity: i64) -> StockMovementDto {
+        StockMovementDto {
+            product_id,
+            kind,
+            quantity: Quantity::from_int(quantity),
+            reference: Some("NF 123".to_string()),
+            user: Some("maria".to_string()),
+            unit: None,
+            lot: None,
+            expires_at: None,
+        }
+    }
+
+    fn ledger_flow(state: &AppState) {
+        let product = ProductService::create(state, CreateProductDto {
+            code: "001".to_string(),
+            name: "Arroz".to_string(),
+            fiscal: Default::default(),
+        }).unwrap();
+        let id = product.id.unwrap();
+
+        StockService::register(&at(state, 1), movement(id, MovementType::Purchase, 10)).unwrap();
+        StockService::register(&at(state, 2), movement(id, MovementType::Loss, 1)).unwrap();
+        let adjusted = StockService::register(&at(state, 3), movement(id, MovementType::Adjustment, -2)).unwrap();
+        assert_eq!((adjusted.quantity, adjusted.balance_after), (Quantity::from_int(-2), Quantity::from_int(7)));
+
+        let change = BalanceChangeDto { amount: Quantity::from_int(3), kind: Some(MovementType::Return), ..Default::default() };
+        let product = ProductService::increment_balance(&at(state, 4), id, change).unwrap();
+        assert_eq!(product.balance, Quantity::from_int(10));
+
+        assert!(StockService::register(state, movement(id, MovementType::Sale, -1)).is_err());
+        assert!(StockService::register(state, movement(id, MovementType::Purchase, 0)).is_err());
+        assert!(StockService::register(state, movement(999, MovementType::Purchase, 1)).is_err());
+        let wrong_way = BalanceChangeDto { amount: Quantity::from_int(1), kind: Some(MovementType::Sale), ..Default::default() };
+        assert!(ProductService::increment_balance(state, id, wrong_way).is_err());
+
+        let query = KardexQueryDto { dt_init: Some("2024-06-02".to_string()), dt_fim: Some("2024-06-03".to_string()) };
+        let kardex = StockService::kardex(state, id, &query).unwrap();
+        assert_eq!(kardex.opening_balance, Quantity::from_int(10));
+        assert_eq!((kardex.total_in, kardex.total_out), (Quantity::ZERO, Quantity::from_int(3)));
+        assert_eq!(kardex.closing_balance, Quantity::from_int(7));
+        let kinds: Vec<MovementType> = kardex.movements.iter().map(|m| m.kind).collect();
+        assert_eq!(kinds, vec![MovementType::Loss, MovementType::Adjustment]);
+        assert_eq!(kardex.movements[0].user.as_deref(), Some("maria"));
+
+        // Saldo gravado fora do livro é detectado e corrigido pela reconciliação
+        assert!(StockService::reconcile(state, false).unwrap().is_empty());
+        state.products.set_balance(id, Quantity::from_int(50), state.clock.now()).unwrap();
+        let found = StockService::reconcile(state, true).unwrap();
+        assert_eq!((found[0].balance, found[0].ledger_balance), (Quantity::from_int(50), Quantity::from_int(10)));
+        assert_eq!(state.products.find_by_id(id).unwrap().unwrap().balance, Quantity::from_int(10));
+        assert!(StockService::reconcile(state, false).unwrap().is_empty());
+
+        // As partes de um movimento entram juntas: uma parte inválida desfaz as outras
+        let part = StockMovementEntity::new(id, MovementType::Loss, Quantity::from_int(-1), None, None);
+        let parts = vec![part.clone(), StockMovementEntity { lot_id: Some(999), ..part }];
+        assert_eq!(state.stock.record_all(&parts).unwrap_err().code(), "NOT_FOUND");
+        assert_eq!(state.products.find_by_id(id).unwrap().unwrap().balance, Quantity::from_int(10));
+        assert!(StockService::reconcile(state, false).unwrap().is_empty());
+    }
+
+    fn stock_levels_flow(state: &AppState) {
+        let events = Arc::new(RecordedEvents::default());
+        let state = &at(state, 30).with_events(events.clone());
+        let config = ConfigEntity { id: "default".to_string(), controle_estoque: 1, estoque_negativo: NegativeStockPolicy::Allow, ..Default::default() };
+        ConfigService::save(state, &config).unwrap();
+
+        let levels = |min: i64, max: i64| ProductFiscalDto {
+            min_stock: Some(Quantity::from_int(min)),
+            max_stock: Some(Quantity::from_int(max)),
+            ..Default::default()
+        };
+        let invalid = ProductService::create(state, CreateProductDto { code: "001".to_string(), name: "Arroz".to_string(), fiscal: levels(10, 5) });
+        assert_eq!(invalid.unwrap_err().code(), "VALIDATION_ERROR");
+
+        let fiscal = ProductFiscalDto { purchase_unit: Some("CX".to_string()), purchase_factor: Some(Quantity::from_int(12)), ..levels(5, 20) };
+        let id = ProductService::create(state, CreateProductDto { code: "001".to_string(), name: "Arroz".to_string(), fiscal }).unwrap().id.unwrap();
+        ProductService::create(state, CreateProductDto { code: "002".to_string(), name: "Feijão".to_string(), fiscal: Default::default() }).unwrap();
+        StockService::register(state, movement(id, MovementType::Purchase, 10)).unwrap();
+
+        // Só a venda que cruza o mínimo avisa; as seguintes não repetem o aviso
+        let sell = |quantidade: i64| {
+            let items = vec![item("001", Quantity::from_int(quantidade))];
+            VendaService::create_venda(state, &new_venda("2024-06-30T10:00:00-03:00", Money::ZERO), items, vec![]).unwrap().low_stock
+        };
+        assert!(sell(4).is_empty());
+        let low = sell(2);
+        assert_eq!(low.iter().map(|l| (l.product_id, l.balance)).collect::<Vec<_>>(), vec![(id, Quantity::from_int(4))]);
+        assert!(sell(1).is_empty());
+        assert_eq!(events.low_stock_codes(), vec!["001"]);
+        let low = StockService::low_stock(state).unwrap();
+        assert_eq!(low.iter().map(|l| (l.product_id, l.balance, l.min_stock)).collect::<Vec<_>>(), vec![(id, Quantity::from_int(3), Quantity::from_int(5))]);
+
+        // 7 vendidos em 30 dias: cobrir 30 dias pede 7 (faltam 4, 1 caixa)
+        let report = StockService::reorder(state, &ReorderQueryDto::default()).unwrap();
+        assert_eq!((report.dt_init.to_string(), report.days), ("2024-06-01".to_string(), 30));
+        assert_eq!(report.items.len(), 1);
+        let item = &report.items[0];
+        assert_eq!((item.sold, item.daily_average, item.target), (Quantity::from_int(7), Quantity::from_units(233), Quantity::from_int(7)));
+        assert_eq!((item.suggested, item.purchase_quantity), (Quantity::from_int(4), Some(Quantity::from_int(1))));
+
+        // A sugestão não passa do estoque máximo
+        let query = ReorderQueryDto { coverage_days: Some(90), ..Default::default() };
+        let item = &StockService::reorder(state, &query).unwrap().items[0];
+        assert_eq!((item.target, item.suggested, item.purchase_quantity), (Quantity::from_int(20), Quantity::from_int(17), Some(Quantity::from_int(2))));
+
+        let query = ReorderQueryDto { dt_init: Some("2024-06-30".to_string()), dt_fim: Some("2024-06-01".to_string()), ..Default::default() };
+        assert_eq!(StockService::reorder(state, &query).unwrap_err().code(), "VALIDATION_ERROR");
+        let query = ReorderQueryDto { coverage_days: Some(0), ..Default::default() };
+        assert_eq!(StockService::reorder(state, &query).unwrap_err().code(), "VALIDATION_ERROR");
+
+        // Perdas e ajustes também avisam ao cruzar o mínimo
+        StockService::register(state, movement(id, MovementType::Purchase, 10)).unwrap();
+        StockService::register(state, movement(id, MovementType::Loss, 8)).unwrap();
+        assert_eq!(events.low_stock_codes(), vec!["001"]);
+        StockService::register(state, movement(id, MovementType::Adjustment, -1)).unwrap();
+        assert_eq!(events.low_stock_codes(), vec!["001", "001"]);
+
+        // Mínimo 0 remove o limite
+        let cleared = ProductFiscalDto { min_stock: Some(Quantity::ZERO), ..Default::default() };
+        let product = ProductService::update(state, id, UpdateProductDto { fiscal: cleared, ..Default::default() }).unwrap();
+        assert_eq!((product.min_stock, product.max_stock), (None, Some(Quantity::from_int(20))));
+        assert!(StockService::low_stock(state).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stock_levels() {
+        on_both_backends(stock_levels_flow);
+    }
+
+    #[test]
+    fn test_ledger() {
+        on_both_backends(ledger_flow);
+    }
+}
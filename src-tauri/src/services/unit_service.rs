@@ -0,0 +1,190 @@
+use crate::dtos::{CreateUnitDto, UpdateUnitDto};
+use crate::error::{AppError, AppResult};
+use crate::entities::{ProductEntity, Quantity, UnitEntity};
+use crate::services::history_service::{HistoryService, ENTITY_UNIT};
+use crate::state::AppState;
+
+pub struct UnitService;
+
+impl UnitService {
+    /// POST /units - Cadastra uma unidade de medida
+    pub fn create(state: &AppState, dto: CreateUnitDto) -> AppResult<UnitEntity> {
+        let code = UnitEntity::normalize_code(&dto.code);
+        if state.units.find_by_code(&code)?.is_some() {
+            return Err(AppError::Conflict(format!("Unidade {} já cadastrada", code)));
+        }
+
+        let now = state.clock.now();
+        let unit = UnitEntity {
+            created_at: now,
+            updated_at: now,
+            ..UnitEntity::new(code, dto.description.trim().to_string(), dto.decimals.unwrap_or(0))
+        };
+        unit.validate()?;
+
+        let saved = state.units.save(&unit)?;
+        Self::record(state, "create", None, Some(&saved));
+        Ok(saved)
+    }
+
+    /// GET /units/:code - Busca uma unidade pelo código
+    pub fn find_by_code(state: &AppState, code: &str) -> AppResult<Option<UnitEntity>> {
+        state.units.find_by_code(&UnitEntity::normalize_code(code))
+    }
+
+    /// GET /units - Lista as unidades por código
+    pub fn find_all(state: &AppState) -> AppResult<Vec<UnitEntity>> {
+        state.units.find_all()
+    }
+
+    /// PUT /units/:code - Atualiza a descrição ou as casas decimais. Reduzir
+    /// as casas não altera saldos já gravados; vale para as próximas quantidades.
+    pub fn update(state: &AppState, code: &str, dto: UpdateUnitDto) -> AppResult<UnitEntity> {
+        let existing = Self::find_by_code(state, code)?
+            .ok_or_else(|| AppError::not_found(format!("Unit with code {} not found", code)))?;
+
+        let updated = UnitEntity {
+            description: dto.description.map(|d| d.trim().to_string()).unwrap_or_else(|| existing.description.clone()),
+            decimals: dto.decimals.unwrap_or(existing.decimals),
+            updated_at: state.clock.now(),
+            ..existing.clone()
+        };
+        updated.validate()?;
+
+        let saved = state.units.save(&updated)?;
+        Self::record(state, "update", Some(&existing), Some(&saved));
+        Ok(saved)
+    }
+
+    /// DELETE /units/:code - Remove uma unidade que nenhum produto usa
+    pub fn delete(state: &AppState, code: &str) -> AppResult<()> {
+        let existing = Self::find_by_code(state, code)?
+            .ok_or_else(|| AppError::not_found(format!("Unit with code {} not found", code)))?;
+
+        let in_use = state.products.find_all()?
+            .iter()
+            .filter(|p| p.unit == existing.code || p.purchase_unit.as_deref() == Some(existing.code.as_str()))
+            .count();
+        if in_use > 0 {
+            return Err(AppError::Conflict(format!("Unidade {} está em uso por {} produto(s)", existing.code, in_use)));
+        }
+
+        state.units.delete(&existing.code)?;
+        Self::record(state, "delete", Some(&existing), None);
+        Ok(())
+    }
+
+    /// Recusa `quantity` se a unidade `code` não aceita tantas casas decimais.
+    /// Unidades sem cadastro (ex. itens avulsos) não são conferidas.
+    pub(crate) fn check_quantity(state: &AppState, code: &str, quantity: Quantity, field: &str) -> AppResult<()> {
+        match Self::find_by_code(state, code)? {
+            Some(unit) if !unit.accepts(quantity) => Err(AppError::validation(field, unit.decimals_message())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Converte `quantity`, expressa em `unit` (ausente = unidade de venda),
+    /// para a unidade de estoque do produto, conferindo as casas decimais
+    /// antes e depois da conversão
+    pub(crate) fn to_stock(state: &AppState, product: &ProductEntity, unit: Option<&str>, quantity: Quantity, field: &str) -> AppResult<Quantity> {
+        let unit = unit.map(UnitEntity::normalize_code).unwrap_or_else(|| product.unit.clone());
+        let factor = product.unit_factor(&unit)
+            .ok_or_else(|| AppError::validation("unit", format!("Produto {} não usa a unidade {}", product.code, unit)))?;
+
+        Self::check_quantity(state, &unit, quantity, field)?;
+        let converted = quantity * factor;
+        if unit != product.unit {
+            Self::check_quantity(state, &product.unit, converted, field)?;
+        }
+        Ok(converted)
+    }
+
+    fn record(state: &AppState, action: &str, before: Option<&UnitEntity>, after: Option<&UnitEntity>) {
+        let code = after.or(before).map(|u| u.code.clone());
+        HistoryService::record(state, action, ENTITY_UNIT, code, before, after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dtos::{BalanceChangeDto, CreateProductDto, ProductFiscalDto, StockMovementDto, UpdateProductDto};
+    use crate::entities::{ConfigEntity, Money, MovementType, NegativeStockPolicy, VendaItemEntity};
+    use crate::services::{ConfigService, ProductService, StockService, VendaService};
+    use crate::test_support::{cash, item, new_venda, on_both_backends};
+
+    fn units_flow(state: &AppState) {
+        let created = UnitService::create(state, CreateUnitDto { code: " sc ".to_string(), description: "Saco".to_string(), decimals: None }).unwrap();
+        assert_eq!((created.code.as_str(), created.decimals), ("SC", 0));
+        let repeated = UnitService::create(state, CreateUnitDto { code: "SC".to_string(), description: "Saco".to_string(), decimals: None });
+        assert_eq!(repeated.unwrap_err().code(), "CONFLICT");
+        let invalid = UnitService::create(state, CreateUnitDto { code: "KG/L".to_string(), description: String::new(), decimals: Some(4) });
+        let AppError::Validation { fields, .. } = invalid.unwrap_err() else { panic!("expected validation error") };
+        assert_eq!(fields.iter().map(|f| f.field.as_str()).collect::<Vec<_>>(), vec!["code", "description", "decimals"]);
+
+        // Refrigerante vendido por unidade e comprado em caixas de 12
+        let fiscal = ProductFiscalDto {
+            price: Some(Money::from_int(3)),
+            purchase_unit: Some("cx".to_string()),
+            purchase_factor: Some(Quantity::from_int(12)),
+            ..Default::default()
+        };
+        let product = ProductService::create(state, CreateProductDto { code: "001".to_string(), name: "Guaraná".to_string(), fiscal }).unwrap();
+        let id = product.id.unwrap();
+        assert_eq!(product.purchase_unit.as_deref(), Some("CX"));
+
+        let unknown = ProductFiscalDto { unit: Some("XX".to_string()), ..Default::default() };
+        let unknown = ProductService::update(state, id, UpdateProductDto { fiscal: unknown, ..Default::default() });
+        assert_eq!(unknown.unwrap_err().code(), "VALIDATION_ERROR");
+
+        let purchase = StockMovementDto {
+            product_id: id,
+            kind: MovementType::Purchase,
+            quantity: Quantity::from_int(2),
+            reference: None,
+            user: None,
+            unit: Some("CX".to_string()),
+            lot: None,
+            expires_at: None,
+        };
+        assert_eq!(StockService::register(state, purchase.clone()).unwrap().quantity, Quantity::from_int(24));
+        let half_box = StockMovementDto { quantity: "0.5".parse().unwrap(), ..purchase.clone() };
+        assert_eq!(StockService::register(state, half_box).unwrap_err().code(), "VALIDATION_ERROR");
+        let wrong_unit = StockMovementDto { unit: Some("KG".to_string()), ..purchase };
+        assert_eq!(StockService::register(state, wrong_unit).unwrap_err().code(), "VALIDATION_ERROR");
+        let fraction = BalanceChangeDto { amount: "1.5".parse().unwrap(), ..Default::default() };
+        assert_eq!(ProductService::decrement_balance(state, id, fraction).unwrap_err().code(), "VALIDATION_ERROR");
+
+        // Uma caixa fechada no caixa: preço de 12 unidades e baixa de 12
+        let config = ConfigEntity { id: "default".to_string(), controle_estoque: 1, estoque_negativo: NegativeStockPolicy::Allow, ..Default::default() };
+        ConfigService::save(state, &config).unwrap();
+        let line = |medida: &str, quantidade: &str| VendaItemEntity {
+            produto_medida: medida.to_string(),
+            ..item("001", quantidade.parse().unwrap())
+        };
+        let priced = VendaService::price_items(state, vec![line("cx", "1"), line("", "2")], state.clock.now()).unwrap();
+        assert_eq!((priced[0].produto_medida.as_str(), priced[0].preco_unitario), ("CX", Money::from_int(36)));
+        assert_eq!((priced[1].produto_medida.as_str(), priced[1].preco_unitario), ("UN", Money::from_int(3)));
+
+        let venda = new_venda("2024-06-15T10:30:00-03:00", Money::from_int(42));
+        VendaService::create_venda(state, &venda, vec![line("CX", "1"), line("UN", "2")], cash(Money::from_int(42))).unwrap();
+        assert_eq!(state.products.find_by_id(id).unwrap().unwrap().balance, Quantity::from_int(10));
+
+        let fractional = VendaService::create_venda(state, &venda, vec![line("UN", "0.5")], vec![]);
+        let AppError::Validation { fields, .. } = fractional.unwrap_err() else { panic!("expected validation error") };
+        assert_eq!(fields[0].field, "items[0].quantidade");
+        let other_unit = VendaService::create_venda(state, &venda, vec![line("KG", "1")], vec![]);
+        assert_eq!(other_unit.unwrap_err().code(), "VALIDATION_ERROR");
+
+        assert_eq!(UnitService::delete(state, "cx").unwrap_err().code(), "CONFLICT");
+        UnitService::update(state, "SC", UpdateUnitDto { decimals: Some(2), ..Default::default() }).unwrap();
+        assert!(UnitService::find_by_code(state, "sc").unwrap().unwrap().accepts("1.25".parse().unwrap()));
+        UnitService::delete(state, "SC").unwrap();
+        assert!(UnitService::find_all(state).unwrap().iter().all(|u| u.code != "SC"));
+    }
+
+    #[test]
+    fn test_units_flow() {
+        on_both_backends(units_flow);
+    }
+}
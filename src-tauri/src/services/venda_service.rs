@@ -1,315 +1,361 @@
-use crate::database::SqliteDbService;
-use crate::entities::{VendaEntity, VendaItemEntity, VendaPagamentoEntity};
-use rusqlite::{params, Result, Transaction};
-use chrono::Utc;
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VendaWithRelations {
-    #[serde(flatten)]
-    pub venda: VendaEntity,
-    pub itens: Vec<VendaItemEntity>,
-    pub pagamentos: Vec<VendaPagamentoEntity>,
+use crate::error::{AppError, AppResult, FieldError};
+use crate::clock::parse_date;
+use crate::dtos::{CategorySalesDto, LowStockDto, StockWarningDto, VendaCreatedDto, VendaResumo, VendaWithRelations};
+use crate::entities::{
+    ConfigEntity, Money, MovementType, NegativeStockPolicy, ProductEntity, Quantity, StockMovementEntity, UnitEntity, VendaEntity,
+    VendaItemEntity, VendaPagamentoEntity,
+};
+use crate::repositories::StockMovementFilter;
+use crate::services::category_service::CategoryTree;
+use crate::services::config_service::ConfigService;
+use crate::services::product_service::ProductService;
+use crate::services::history_service::{HistoryService, ENTITY_VENDA};
+use crate::services::promotion_service::PromotionService;
+use crate::services::stock_service::StockService;
+use crate::services::unit_service::UnitService;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+pub struct VendaService;
+
+/// Converte o filtro `YYYY-MM-DD`..`YYYY-MM-DD` (dias locais da loja, inclusivo)
+/// no intervalo UTC semiaberto usado pelo repositório
+fn interval(state: &AppState, dt_init: &str, dt_end: &str) -> AppResult<(DateTime<Utc>, DateTime<Utc>)> {
+    let from = parse_date(dt_init).map_err(|e| AppError::validation("dtInit", e))?;
+    let to = parse_date(dt_end).map_err(|e| AppError::validation("dtFim", e))?;
+    Ok(state.clock.day_range(from, to))
+}
+
+/// Completa os itens com descrição, unidade, preço e dados fiscais do cadastro
+/// de produtos e confere a unidade e as casas decimais da quantidade. Itens de
+/// produtos sem cadastro precisam trazer a descrição.
+fn resolve_items(state: &AppState, items: Vec<VendaItemEntity>) -> AppResult<Vec<VendaItemEntity>> {
+    let now = state.clock.now();
+
+    items.into_iter().enumerate().map(|(i, mut item)| {
+        item.produto_medida = UnitEntity::normalize_code(&item.produto_medida);
+        match state.products.find_by_code(&item.produto_code)? {
+            Some(product) if !item.produto_medida.is_empty() && product.unit_factor(&item.produto_medida).is_none() => {
+                return Err(AppError::validation(
+                    &format!("items[{}].produto_medida", i),
+                    format!("Produto {} não usa a unidade {}", product.code, item.produto_medida),
+                ));
+            }
+            Some(product) => item.inherit(&product),
+            None if item.produto_description.trim().is_empty() => {
+                return Err(AppError::validation(
+                    &format!("items[{}].produto_code", i),
+                    format!("Produto {} não encontrado", item.produto_code),
+                ));
+            }
+            None => {}
+        }
+        UnitService::check_quantity(state, &item.produto_medida, item.quantidade, &format!("items[{}].quantidade", i))?;
+        item.created_at = now;
+        item.updated_at = now;
+        Ok(item)
+    }).collect()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VendaResumo {
-    pub total_vendas: i64,
-    pub total_valor: f64,
-    pub total_desconto: f64,
-    pub total_acrescimo: f64,
-    pub total_canceladas: i64,
+/// Política de estoque da loja, ou `None` quando o controle de estoque está desligado
+fn stock_policy(config: &ConfigEntity) -> Option<NegativeStockPolicy> {
+    (config.controle_estoque == 1).then_some(config.estoque_negativo)
 }
 
-pub struct VendaService;
+/// Confere os valores da venda já precificada e recalcula todos os totais no
+/// backend, devolvendo o total da venda:
+/// - `preco_total` de cada item = `quantidade * preco_unitario - desconto + acrescimo`;
+///   o enviado pelo cliente (`informed`, na ordem dos itens) precisa conferir,
+///   e zero quer dizer "não informado"
+/// - `total` = soma de `preco_total` dos itens `- discount + addition`
+/// - `desconto_rat` e `acrescimo_rat` são rateados pelo valor líquido dos itens
+///
+/// `tolerance` vale para cada `preco_total`, para o `total` enviado e para os
+/// pagamentos. Todos os campos inconsistentes voltam juntos no erro de validação.
+fn check_totals(
+    venda: &VendaEntity,
+    items: &mut [VendaItemEntity],
+    informed: &[Money],
+    payments: &[VendaPagamentoEntity],
+    tolerance: Money,
+) -> AppResult<Money> {
+    let mut errors = Vec::new();
+
+    if items.is_empty() {
+        errors.push(FieldError::new("items", "Venda sem itens"));
+    }
+    for (i, item) in items.iter_mut().enumerate() {
+        let field = |name: &str| format!("items[{}].{}", i, name);
+        if item.quantidade.is_negative() || item.quantidade.is_zero() {
+            errors.push(FieldError::new(&field("quantidade"), "Quantidade deve ser maior que zero"));
+        }
+        if item.preco_unitario.is_negative() {
+            errors.push(FieldError::new(&field("preco_unitario"), "Preço não pode ser negativo"));
+        }
+        if item.desconto.is_negative() {
+            errors.push(FieldError::new(&field("desconto"), "Desconto não pode ser negativo"));
+        } else if item.desconto > item.gross_total() {
+            errors.push(FieldError::new(&field("desconto"), "Desconto maior que o valor do item"));
+        }
+        if item.acrescimo.is_negative() {
+            errors.push(FieldError::new(&field("acrescimo"), "Acréscimo não pode ser negativo"));
+        }
+
+        item.preco_total = item.net_total();
+        let sent = informed.get(i).copied().unwrap_or_default();
+        if !sent.is_zero() && (sent - item.preco_total).abs() > tolerance {
+            errors.push(FieldError::new(
+                &field("preco_total"),
+                format!("Total do item {} difere de quantidade × preço - desconto + acréscimo ({})", sent, item.preco_total),
+            ));
+        }
+    }
+
+    if venda.discount.is_negative() {
+        errors.push(FieldError::new("discount", "Desconto não pode ser negativo"));
+    }
+    if venda.addition.is_negative() {
+        errors.push(FieldError::new("addition", "Acréscimo não pode ser negativo"));
+    }
+
+    let net: Vec<Money> = items.iter().map(|i| i.preco_total).collect();
+    let total = net.iter().copied().sum::<Money>() - venda.discount + venda.addition;
+    if total.is_negative() {
+        errors.push(FieldError::new("discount", "Desconto maior que o valor dos itens"));
+    } else if (venda.total - total).abs() > tolerance {
+        errors.push(FieldError::new("total", format!("Total {} difere da soma dos itens ({})", venda.total, total)));
+    }
+
+    for (i, payment) in payments.iter().enumerate() {
+        if payment.total_pagamento.is_negative() {
+            errors.push(FieldError::new(&format!("payments[{}].total_pagamento", i), "Valor não pode ser negativo"));
+        }
+    }
+    let paid: Money = payments.iter().map(|p| p.total_pagamento).sum();
+    if paid < total - tolerance {
+        errors.push(FieldError::new("payments", format!("Pagamentos ({}) não cobrem o total ({})", paid, total)));
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::invalid_fields(errors));
+    }
+
+    let discounts = prorate(venda.discount, &net);
+    let additions = prorate(venda.addition, &net);
+    for ((item, desconto_rat), acrescimo_rat) in items.iter_mut().zip(discounts).zip(additions) {
+        item.desconto_rat = desconto_rat;
+        item.acrescimo_rat = acrescimo_rat;
+    }
+    Ok(total)
+}
+
+/// Rateia `amount` na proporção de `weights`; o último fica com a diferença
+/// dos arredondamentos
+fn prorate(amount: Money, weights: &[Money]) -> Vec<Money> {
+    let total: i128 = weights.iter().map(|w| w.cents() as i128).sum();
+    let mut remaining = amount;
+
+    weights.iter().enumerate().map(|(n, weight)| {
+        let share = if n + 1 == weights.len() {
+            remaining
+        } else if total == 0 {
+            Money::ZERO
+        } else {
+            Money::from_cents((amount.cents() as i128 * weight.cents() as i128 / total) as i64)
+        };
+        remaining -= share;
+        share
+    }).collect()
+}
+
+/// Baixas de estoque de uma venda, com os avisos de saldo negativo e os
+/// produtos que a venda levou para baixo do estoque mínimo
+struct SaleStock {
+    movements: Vec<StockMovementEntity>,
+    warnings: Vec<StockWarningDto>,
+    low_stock: Vec<LowStockDto>,
+}
+
+/// Monta as baixas de estoque da venda, uma por produto cadastrado (itens
+/// repetidos são somados e kits baixam os componentes), com os avisos da
+/// política `warn`. A política `block` não é conferida aqui, e sim na
+/// gravação da venda (`create_venda` com `block_negative`), na mesma
+/// transação que baixa o saldo.
+fn sale_movements(
+    state: &AppState,
+    items: &[VendaItemEntity],
+    policy: NegativeStockPolicy,
+) -> AppResult<SaleStock> {
+    let mut sold: Vec<(ProductEntity, Quantity)> = Vec::new();
+    for item in items {
+        let Some(product) = state.products.find_by_code(&item.produto_code)? else {
+            continue;
+        };
+        // Itens na unidade de compra baixam o equivalente na unidade de estoque
+        let quantity = item.quantidade * product.unit_factor(&item.produto_medida).unwrap_or(Quantity::from_int(1));
+        for (product, quantity) in ProductService::stock_components(state, product, quantity)? {
+            match sold.iter_mut().find(|(p, _)| p.id == product.id) {
+                Some((_, total)) => *total += quantity,
+                None => sold.push((product, quantity)),
+            }
+        }
+    }
+
+    let now = state.clock.now();
+    let mut movements = Vec::new();
+    let mut warnings = Vec::new();
+    let mut low_stock = Vec::new();
+    for (product, quantity) in sold {
+        let Some(product_id) = product.id else { continue };
+        if quantity.is_zero() {
+            continue;
+        }
+
+        let balance_after = product.balance - quantity;
+        if balance_after.is_negative() && policy == NegativeStockPolicy::Warn {
+            warnings.push(StockWarningDto { product_id, code: product.code.clone(), balance: product.balance, quantity, balance_after });
+        }
+        low_stock.extend(StockService::crossed_min(&product, balance_after));
+
+        // Os lotes são escolhidos na gravação da venda, com os saldos lidos na transação
+        let movement = StockMovementEntity::new(product_id, MovementType::Sale, -quantity, None, None);
+        movements.push(StockMovementEntity { origin: state.origin, created_at: now, ..movement });
+    }
+
+    Ok(SaleStock { movements, warnings, low_stock })
+}
+
+/// Movimentos que devolvem ao estoque (e aos mesmos lotes) as baixas feitas pela venda
+fn cancellation_movements(state: &AppState, venda_id: i64) -> AppResult<Vec<StockMovementEntity>> {
+    let filter = StockMovementFilter {
+        reference: Some(StockMovementEntity::venda_reference(venda_id)),
+        ..Default::default()
+    };
+    let now = state.clock.now();
+
+    Ok(state.stock.find(&filter)?
+        .into_iter()
+        .filter(|m| m.kind == MovementType::Sale)
+        .map(|m| {
+            let movement = StockMovementEntity::new(m.product_id, MovementType::Cancellation, -m.quantity, None, None);
+            StockMovementEntity { lot_id: m.lot_id, origin: state.origin, created_at: now, ..movement }
+        })
+        .collect())
+}
 
 impl VendaService {
     /// Busca uma venda por ID
-    pub fn find_by_id(id: i64) -> Result<Option<VendaEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, tip, mod, serie_origin, serie, nr_nf_origin, nr_nf, cnpj, doc_destinatario, 
-             dh_emi, dh_emi_canc, total, addition, discount, chave, chave_canc, file_path, 
-             cancel_file_path, protocolo, cancelled, created_at, updated_at 
-             FROM vendas WHERE id = ?1"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let venda = stmt.query_row(params![id], |row| {
-            let created_at_str: String = row.get(20)?;
-            let updated_at_str: String = row.get(21)?;
-            
-            Ok(VendaEntity {
-                id: row.get(0)?,
-                tip: row.get(1)?,
-                mod_: row.get(2)?,
-                serie_origin: row.get(3)?,
-                serie: row.get(4)?,
-                nr_nf_origin: row.get(5)?,
-                nr_nf: row.get(6)?,
-                cnpj: row.get(7)?,
-                doc_destinatario: row.get(8)?,
-                dh_emi: row.get(9)?,
-                dh_emi_canc: row.get(10)?,
-                total: row.get(11)?,
-                addition: row.get(12)?,
-                discount: row.get(13)?,
-                chave: row.get(14)?,
-                chave_canc: row.get(15)?,
-                file_path: row.get(16)?,
-                cancel_file_path: row.get(17)?,
-                protocolo: row.get(18)?,
-                cancelled: row.get(19)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        });
-
-        match venda {
-            Ok(v) => Ok(Some(v)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(format!("Failed to query venda: {}", e)),
+    pub fn find_by_id(state: &AppState, id: i64) -> AppResult<Option<VendaEntity>> {
+        state.vendas.find_by_id(id)
+    }
+
+    /// Completa os itens com os dados do cadastro e aplica as promoções em
+    /// vigor em `at` (desconto em `desconto`, origem em `promotion_id`)
+    pub fn price_items(state: &AppState, items: Vec<VendaItemEntity>, at: DateTime<Utc>) -> AppResult<Vec<VendaItemEntity>> {
+        let mut items = resolve_items(state, items)?;
+        PromotionService::apply(state, &mut items, at)?;
+        for item in items.iter_mut() {
+            item.preco_total = item.net_total();
         }
+        Ok(items)
     }
 
-    /// Cria uma nova venda com itens e pagamentos
+    /// Cria uma nova venda com itens e pagamentos.
+    /// `dh_emi` sem fuso é interpretado no fuso da loja; os itens herdam preço
+    /// e dados fiscais do produto (ver `VendaItemEntity::inherit`) e recebem as
+    /// promoções em vigor na emissão. Os totais são conferidos e recalculados
+    /// (ver `check_totals`) com a tolerância `tolerancia_total` da configuração.
+    /// Com `controle_estoque` ligado, baixa o estoque na mesma transação.
     pub fn create_venda(
+        state: &AppState,
         venda: &VendaEntity,
         items: Vec<VendaItemEntity>,
         payments: Vec<VendaPagamentoEntity>,
-    ) -> Result<i64, String> {
-        let db = SqliteDbService::get_instance()?;
-        let mut conn = db.get_connection()?;
-
-        let tx = conn.transaction()
-            .map_err(|e| format!("Failed to start transaction: {}", e))?;
-
-        // Insere a venda
-        tx.execute(
-            "INSERT INTO vendas (tip, mod, serie_origin, serie, nr_nf_origin, nr_nf, cnpj, 
-             doc_destinatario, dh_emi, dh_emi_canc, total, addition, discount, chave, chave_canc, 
-             file_path, cancel_file_path, protocolo, cancelled, created_at, updated_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
-            params![
-                venda.tip,
-                venda.mod_,
-                venda.serie_origin,
-                venda.serie,
-                venda.nr_nf_origin,
-                venda.nr_nf,
-                venda.cnpj,
-                venda.doc_destinatario,
-                venda.dh_emi,
-                venda.dh_emi_canc,
-                venda.total,
-                venda.addition,
-                venda.discount,
-                venda.chave,
-                venda.chave_canc,
-                venda.file_path,
-                venda.cancel_file_path,
-                venda.protocolo,
-                venda.cancelled,
-                venda.created_at.to_rfc3339(),
-                venda.updated_at.to_rfc3339()
-            ],
-        ).map_err(|e| format!("Failed to insert venda: {}", e))?;
-
-        let venda_id = tx.last_insert_rowid();
-
-        // Insere os itens
-        for item in items {
-            Self::insert_item_in_transaction(&tx, venda_id, &item)?;
-        }
-
-        // Insere os pagamentos
-        for payment in payments {
-            Self::insert_payment_in_transaction(&tx, venda_id, &payment)?;
-        }
-
-        tx.commit()
-            .map_err(|e| format!("Failed to commit transaction: {}", e))?;
-
-        Ok(venda_id)
-    }
-
-    /// Insere um item de venda dentro de uma transação
-    fn insert_item_in_transaction(
-        tx: &Transaction,
-        venda_id: i64,
-        item: &VendaItemEntity,
-    ) -> Result<(), String> {
-        tx.execute(
-            "INSERT INTO venda_itens (venda_id, produto_code, produto_description, produto_medida, 
-             quantidade, preco_unitario, desconto, desconto_rat, acrescimo, acrescimo_rat, 
-             preco_total, created_at, updated_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
-            params![
-                venda_id,
-                item.produto_code,
-                item.produto_description,
-                item.produto_medida,
-                item.quantidade,
-                item.preco_unitario,
-                item.desconto,
-                item.desconto_rat,
-                item.acrescimo,
-                item.acrescimo_rat,
-                item.preco_total,
-                item.created_at.to_rfc3339(),
-                item.updated_at.to_rfc3339()
-            ],
-        ).map_err(|e| format!("Failed to insert venda_item: {}", e))?;
+    ) -> AppResult<VendaCreatedDto> {
+        Self::create(state, venda, items, payments, None)
+    }
 
-        Ok(())
+    /// Cria a venda do carrinho `cart_id`, encerrando-o na mesma transação.
+    /// Se o carrinho já não está em pagamento (outra finalização chegou antes),
+    /// nada é gravado e o erro é `Conflict`.
+    pub(crate) fn create_from_cart(
+        state: &AppState,
+        cart_id: i64,
+        venda: &VendaEntity,
+        items: Vec<VendaItemEntity>,
+        payments: Vec<VendaPagamentoEntity>,
+    ) -> AppResult<VendaCreatedDto> {
+        Self::create(state, venda, items, payments, Some(cart_id))
     }
 
-    /// Insere um pagamento dentro de uma transação
-    fn insert_payment_in_transaction(
-        tx: &Transaction,
-        venda_id: i64,
-        payment: &VendaPagamentoEntity,
-    ) -> Result<(), String> {
-        tx.execute(
-            "INSERT INTO venda_pagamentos (venda_id, code, name, total_pagamento, created_at, updated_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                venda_id,
-                payment.code,
-                payment.name,
-                payment.total_pagamento,
-                payment.created_at.to_rfc3339(),
-                payment.updated_at.to_rfc3339()
-            ],
-        ).map_err(|e| format!("Failed to insert venda_pagamento: {}", e))?;
+    fn create(
+        state: &AppState,
+        venda: &VendaEntity,
+        items: Vec<VendaItemEntity>,
+        payments: Vec<VendaPagamentoEntity>,
+        cart_id: Option<i64>,
+    ) -> AppResult<VendaCreatedDto> {
+        let emitted_at = state.clock
+            .parse_instant(&venda.dh_emi)
+            .ok_or_else(|| AppError::validation("dh_emi", format!("Invalid dh_emi '{}'", venda.dh_emi)))?;
+
+        let config = ConfigService::find_by_id(state, "default")?.unwrap_or_default();
+        let informed: Vec<Money> = items.iter().map(|i| i.preco_total).collect();
+        let mut items = Self::price_items(state, items, emitted_at)?;
+        let total = check_totals(venda, &mut items, &informed, &payments, config.tolerancia_total)?;
+        let venda = &VendaEntity { total, ..venda.clone() };
+
+        let policy = stock_policy(&config);
+        let stock = match policy {
+            Some(policy) => sale_movements(state, &items, policy)?,
+            None => SaleStock { movements: Vec::new(), warnings: Vec::new(), low_stock: Vec::new() },
+        };
+        let block_negative = policy == Some(NegativeStockPolicy::Block);
+        let venda_id = state.vendas.create_venda(venda, emitted_at, &items, &payments, &stock.movements, block_negative, cart_id)?;
+
+        // A venda já foi gravada: daqui em diante nenhuma falha volta ao cliente,
+        // senão uma nova tentativa duplicaria a venda
+        let after = Self::find_with_relations(state, venda_id).ok().flatten();
+        HistoryService::record(state, "create", ENTITY_VENDA, Some(venda_id.to_string()), None, after.as_ref());
+        StockService::notify_low_stock(state, &stock.low_stock);
+        Ok(VendaCreatedDto { id: venda_id, stock_warnings: stock.warnings, low_stock: stock.low_stock })
+    }
 
-        Ok(())
+    /// Busca uma venda com seus itens e pagamentos
+    pub fn find_with_relations(state: &AppState, id: i64) -> AppResult<Option<VendaWithRelations>> {
+        let Some(venda) = Self::find_by_id(state, id)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(VendaWithRelations {
+            venda,
+            itens: Self::find_items_by_venda_id(state, id)?,
+            pagamentos: Self::find_payments_by_venda_id(state, id)?,
+        }))
     }
 
     /// Busca itens de uma venda
-    pub fn find_items_by_venda_id(venda_id: i64) -> Result<Vec<VendaItemEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, produto_code, produto_description, produto_medida, quantidade, preco_unitario, 
-             desconto, desconto_rat, acrescimo, acrescimo_rat, preco_total, created_at, updated_at 
-             FROM venda_itens WHERE venda_id = ?1"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let items = stmt.query_map(params![venda_id], |row| {
-            let created_at_str: String = row.get(11)?;
-            let updated_at_str: String = row.get(12)?;
-            
-            Ok(VendaItemEntity {
-                id: row.get(0)?,
-                venda_id,
-                produto_code: row.get(1)?,
-                produto_description: row.get(2)?,
-                produto_medida: row.get(3)?,
-                quantidade: row.get(4)?,
-                preco_unitario: row.get(5)?,
-                desconto: row.get(6)?,
-                desconto_rat: row.get(7)?,
-                acrescimo: row.get(8)?,
-                acrescimo_rat: row.get(9)?,
-                preco_total: row.get(10)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query venda_items: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect venda_items: {}", e))?;
-
-        Ok(items)
+    pub fn find_items_by_venda_id(state: &AppState, venda_id: i64) -> AppResult<Vec<VendaItemEntity>> {
+        state.vendas.find_items_by_venda_id(venda_id)
     }
 
     /// Busca pagamentos de uma venda
-    pub fn find_payments_by_venda_id(venda_id: i64) -> Result<Vec<VendaPagamentoEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, code, name, total_pagamento, created_at, updated_at 
-             FROM venda_pagamentos WHERE venda_id = ?1"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let payments = stmt.query_map(params![venda_id], |row| {
-            let created_at_str: String = row.get(4)?;
-            let updated_at_str: String = row.get(5)?;
-            
-            Ok(VendaPagamentoEntity {
-                id: row.get(0)?,
-                venda_id,
-                code: row.get(1)?,
-                name: row.get(2)?,
-                total_pagamento: row.get(3)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query venda_pagamentos: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect venda_pagamentos: {}", e))?;
-
-        Ok(payments)
+    pub fn find_payments_by_venda_id(state: &AppState, venda_id: i64) -> AppResult<Vec<VendaPagamentoEntity>> {
+        state.vendas.find_payments_by_venda_id(venda_id)
     }
 
     /// Busca vendas por intervalo de datas
-    pub fn get_vendas_by_interval(dt_init: &str, dt_end: &str) -> Result<Vec<VendaWithRelations>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT id, tip, mod, serie_origin, serie, nr_nf_origin, nr_nf, cnpj, doc_destinatario, 
-             dh_emi, dh_emi_canc, total, addition, discount, chave, chave_canc, file_path, 
-             cancel_file_path, protocolo, cancelled, created_at, updated_at 
-             FROM vendas 
-             WHERE DATE(dh_emi) BETWEEN ?1 AND ?2 
-             ORDER BY dh_emi DESC"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let vendas = stmt.query_map(params![dt_init, dt_end], |row| {
-            let created_at_str: String = row.get(20)?;
-            let updated_at_str: String = row.get(21)?;
-            
-            Ok(VendaEntity {
-                id: row.get(0)?,
-                tip: row.get(1)?,
-                mod_: row.get(2)?,
-                serie_origin: row.get(3)?,
-                serie: row.get(4)?,
-                nr_nf_origin: row.get(5)?,
-                nr_nf: row.get(6)?,
-                cnpj: row.get(7)?,
-                doc_destinatario: row.get(8)?,
-                dh_emi: row.get(9)?,
-                dh_emi_canc: row.get(10)?,
-                total: row.get(11)?,
-                addition: row.get(12)?,
-                discount: row.get(13)?,
-                chave: row.get(14)?,
-                chave_canc: row.get(15)?,
-                file_path: row.get(16)?,
-                cancel_file_path: row.get(17)?,
-                protocolo: row.get(18)?,
-                cancelled: row.get(19)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query vendas: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect vendas: {}", e))?;
+    pub fn get_vendas_by_interval(state: &AppState, dt_init: &str, dt_end: &str) -> AppResult<Vec<VendaWithRelations>> {
+        let (start, end) = interval(state, dt_init, dt_end)?;
+        let vendas = state.vendas.find_by_interval(start, end)?;
 
         let mut result = Vec::new();
         for venda in vendas {
             let venda_id = venda.id.unwrap_or(0);
-            let itens = Self::find_items_by_venda_id(venda_id)?;
-            let pagamentos = Self::find_payments_by_venda_id(venda_id)?;
-            
+            let itens = Self::find_items_by_venda_id(state, venda_id)?;
+            let pagamentos = Self::find_payments_by_venda_id(state, venda_id)?;
+
             result.push(VendaWithRelations {
                 venda,
                 itens,
@@ -321,122 +367,391 @@ impl VendaService {
     }
 
     /// Busca itens de vendas por intervalo de datas
-    pub fn get_items_by_interval(dt_init: &str, dt_end: &str) -> Result<Vec<VendaItemEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT vi.id, vi.venda_id, vi.produto_code, vi.produto_description, vi.produto_medida, 
-             vi.quantidade, vi.preco_unitario, vi.desconto, vi.desconto_rat, vi.acrescimo, 
-             vi.acrescimo_rat, vi.preco_total, vi.created_at, vi.updated_at 
-             FROM venda_itens vi
-             INNER JOIN vendas v ON vi.venda_id = v.id
-             WHERE DATE(v.dh_emi) BETWEEN ?1 AND ?2
-             ORDER BY v.dh_emi DESC, vi.id"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let items = stmt.query_map(params![dt_init, dt_end], |row| {
-            let created_at_str: String = row.get(12)?;
-            let updated_at_str: String = row.get(13)?;
-            
-            Ok(VendaItemEntity {
-                id: row.get(0)?,
-                venda_id: row.get(1)?,
-                produto_code: row.get(2)?,
-                produto_description: row.get(3)?,
-                produto_medida: row.get(4)?,
-                quantidade: row.get(5)?,
-                preco_unitario: row.get(6)?,
-                desconto: row.get(7)?,
-                desconto_rat: row.get(8)?,
-                acrescimo: row.get(9)?,
-                acrescimo_rat: row.get(10)?,
-                preco_total: row.get(11)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query items: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect items: {}", e))?;
-
-        Ok(items)
+    pub fn get_items_by_interval(state: &AppState, dt_init: &str, dt_end: &str) -> AppResult<Vec<VendaItemEntity>> {
+        let (start, end) = interval(state, dt_init, dt_end)?;
+        state.vendas.find_items_by_interval(start, end)
     }
 
     /// Busca pagamentos de vendas por intervalo de datas
-    pub fn get_payments_by_interval(dt_init: &str, dt_end: &str) -> Result<Vec<VendaPagamentoEntity>, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT vp.id, vp.venda_id, vp.code, vp.name, vp.total_pagamento, vp.created_at, vp.updated_at 
-             FROM venda_pagamentos vp
-             INNER JOIN vendas v ON vp.venda_id = v.id
-             WHERE DATE(v.dh_emi) BETWEEN ?1 AND ?2
-             ORDER BY v.dh_emi DESC, vp.id"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let payments = stmt.query_map(params![dt_init, dt_end], |row| {
-            let created_at_str: String = row.get(5)?;
-            let updated_at_str: String = row.get(6)?;
-            
-            Ok(VendaPagamentoEntity {
-                id: row.get(0)?,
-                venda_id: row.get(1)?,
-                code: row.get(2)?,
-                name: row.get(3)?,
-                total_pagamento: row.get(4)?,
-                created_at: created_at_str.parse().unwrap_or(Utc::now()),
-                updated_at: updated_at_str.parse().unwrap_or(Utc::now()),
-            })
-        })
-        .map_err(|e| format!("Failed to query payments: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect payments: {}", e))?;
-
-        Ok(payments)
+    pub fn get_payments_by_interval(state: &AppState, dt_init: &str, dt_end: &str) -> AppResult<Vec<VendaPagamentoEntity>> {
+        let (start, end) = interval(state, dt_init, dt_end)?;
+        state.vendas.find_payments_by_interval(start, end)
     }
 
     /// Busca resumo de vendas por intervalo de datas
-    pub fn get_resumo_by_interval(dt_init: &str, dt_end: &str) -> Result<VendaResumo, String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
-
-        let mut stmt = conn.prepare(
-            "SELECT 
-                COUNT(*) as total_vendas,
-                COALESCE(SUM(total), 0) as total_valor,
-                COALESCE(SUM(discount), 0) as total_desconto,
-                COALESCE(SUM(addition), 0) as total_acrescimo,
-                COALESCE(SUM(CASE WHEN cancelled = 1 THEN 1 ELSE 0 END), 0) as total_canceladas
-             FROM vendas
-             WHERE DATE(dh_emi) BETWEEN ?1 AND ?2"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-        let resumo = stmt.query_row(params![dt_init, dt_end], |row| {
-            Ok(VendaResumo {
-                total_vendas: row.get(0)?,
-                total_valor: row.get(1)?,
-                total_desconto: row.get(2)?,
-                total_acrescimo: row.get(3)?,
-                total_canceladas: row.get(4)?,
-            })
-        })
-        .map_err(|e| format!("Failed to query resumo: {}", e))?;
+    pub fn get_resumo_by_interval(state: &AppState, dt_init: &str, dt_end: &str) -> AppResult<VendaResumo> {
+        let (start, end) = interval(state, dt_init, dt_end)?;
+        state.vendas.resumo_by_interval(start, end)
+    }
+
+    /// Totais dos itens vendidos no período por categoria, na ordem da árvore,
+    /// com o subtotal de cada seção somando as subcategorias. Vendas canceladas ficam de fora;
+    /// itens sem categoria (ou de produtos sem cadastro) vêm numa linha final
+    /// com `category_id` nulo.
+    pub fn get_category_totals_by_interval(state: &AppState, dt_init: &str, dt_end: &str) -> AppResult<Vec<CategorySalesDto>> {
+        let (start, end) = interval(state, dt_init, dt_end)?;
+        let cancelled: HashSet<i64> = state.vendas.find_by_interval(start, end)?
+            .into_iter()
+            .filter(|v| v.cancelled != 0)
+            .filter_map(|v| v.id)
+            .collect();
+
+        let mut categories: HashMap<String, Option<i64>> = HashMap::new();
+        let mut own: HashMap<Option<i64>, (i64, Quantity, Money)> = HashMap::new();
+        for item in state.vendas.find_items_by_interval(start, end)? {
+            if cancelled.contains(&item.venda_id) {
+                continue;
+            }
+            let category = match categories.get(&item.produto_code) {
+                Some(category) => *category,
+                None => {
+                    let category = state.products.find_by_code(&item.produto_code)?.and_then(|p| p.category_id);
+                    categories.insert(item.produto_code.clone(), category);
+                    category
+                }
+            };
+            let entry = own.entry(category).or_default();
+            entry.0 += 1;
+            entry.1 += item.quantidade;
+            entry.2 += item.preco_total;
+        }
+
+        let tree = CategoryTree::load(state)?;
+        let mut rows: Vec<CategorySalesDto> = Vec::new();
+        for category in tree.preorder() {
+            let id = category.id.unwrap_or_default();
+            let subtree = tree.subtree(id);
+            if !subtree.iter().any(|c| own.contains_key(&Some(*c))) {
+                continue;
+            }
+            let (items, quantidade, total) = own.get(&Some(id)).copied().unwrap_or_default();
+            rows.push(CategorySalesDto {
+                category_id: Some(id),
+                parent_id: category.parent_id,
+                name: category.name.clone(),
+                path: tree.path(id),
+                items,
+                quantidade,
+                total,
+                total_with_children: subtree.iter().filter_map(|c| own.get(&Some(*c))).map(|o| o.2).sum(),
+            });
+        }
 
-        Ok(resumo)
+        if let Some(&(items, quantidade, total)) = own.get(&None) {
+            let name = "Sem categoria".to_string();
+            rows.push(CategorySalesDto {
+                category_id: None,
+                parent_id: None,
+                path: name.clone(),
+                name,
+                items,
+                quantidade,
+                total,
+                total_with_children: total,
+            });
+        }
+        Ok(rows)
     }
 
-    /// Atualiza o status de cancelamento de uma venda
-    pub fn cancel_venda(venda_id: i64, chave_canc: String, dh_emi_canc: String, cancel_file_path: Option<String>) -> Result<(), String> {
-        let db = SqliteDbService::get_instance()?;
-        let conn = db.get_connection()?;
+    /// Cancela uma venda e devolve ao estoque o que ela baixou. Cancelar de
+    /// novo não altera nada; o status é conferido na transação, então dois
+    /// cancelamentos simultâneos devolvem o estoque uma vez só.
+    pub fn cancel_venda(state: &AppState, venda_id: i64, chave_canc: String, dh_emi_canc: String, cancel_file_path: Option<String>) -> AppResult<()> {
+        let before = Self::find_by_id(state, venda_id)?
+            .ok_or_else(|| AppError::not_found(format!("Venda with id {} not found", venda_id)))?;
+        if before.cancelled != 0 {
+            return Ok(());
+        }
 
-        conn.execute(
-            "UPDATE vendas SET cancelled = 1, chave_canc = ?1, dh_emi_canc = ?2, cancel_file_path = ?3, updated_at = ?4 WHERE id = ?5",
-            params![chave_canc, dh_emi_canc, cancel_file_path, Utc::now().to_rfc3339(), venda_id],
-        ).map_err(|e| format!("Failed to cancel venda: {}", e))?;
+        let movements = cancellation_movements(state, venda_id)?;
+        if !state.vendas.cancel(venda_id, &chave_canc, &dh_emi_canc, cancel_file_path.as_deref(), state.clock.now(), &movements)? {
+            return Ok(());
+        }
 
+        let after = Self::find_by_id(state, venda_id).ok().flatten();
+        HistoryService::record(state, "cancel", ENTITY_VENDA, Some(venda_id.to_string()), Some(&before), after.as_ref());
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, DEFAULT_TIMEZONE};
+    use crate::entities::{CategoryEntity, ConfigEntity, Money, ProductEntity, Quantity};
+    use crate::test_support::{backends, cash, item, new_venda, on_both_backends, save_product};
+    use std::sync::Arc;
+
+    fn sale_flow(state: &AppState) {
+        let mut venda = new_venda("2024-06-15T10:30:00-03:00", Money::from_int(24));
+        venda.discount = Money::from_int(1);
+        let items = vec![
+            VendaItemEntity::new(0, "001".to_string(), "Arroz".to_string(), "UN".to_string(), Quantity::from_int(2), Money::from_int(10)),
+            VendaItemEntity::new(0, "002".to_string(), "Feijão".to_string(), "UN".to_string(), Quantity::from_int(1), Money::from_int(5)),
+        ];
+        let payments = cash(Money::from_int(25));
+
+        let venda_id = VendaService::create_venda(state, &venda, items, payments).unwrap().id;
+
+        let vendas = VendaService::get_vendas_by_interval(state, "2024-06-15", "2024-06-15").unwrap();
+        assert_eq!(vendas.len(), 1);
+        assert_eq!(vendas[0].venda.id, Some(venda_id));
+        assert_eq!(vendas[0].itens.len(), 2);
+        assert!(vendas[0].itens.iter().all(|i| i.venda_id == venda_id));
+        assert_eq!(vendas[0].pagamentos[0].total_pagamento, Money::from_int(25));
+
+        assert!(VendaService::get_items_by_interval(state, "2024-06-16", "2024-06-30").unwrap().is_empty());
+
+        VendaService::cancel_venda(state, venda_id, "CFe2".to_string(), "2024-06-15T11:00:00".to_string(), None).unwrap();
+
+        let resumo = VendaService::get_resumo_by_interval(state, "2024-06-01", "2024-06-30").unwrap();
+        assert_eq!(resumo.total_vendas, 1);
+        assert_eq!(resumo.total_valor, Money::from_int(24));
+        assert_eq!(resumo.total_desconto, Money::from_int(1));
+        assert_eq!(resumo.total_canceladas, 1);
+    }
+
+    fn items_inherit_product_data(state: &AppState) {
+        let mut product = ProductEntity::new("789".to_string(), "Café 500g".to_string());
+        product.price = Money::from_cents(1890);
+        product.unit = "PCT".to_string();
+        product.ncm = Some("09012100".to_string());
+        product.cfop = Some("5102".to_string());
+        product.cst = Some("102".to_string());
+        let product = state.products.save(&product).unwrap();
+
+        // O frontend envia só código e quantidade; preço e dados fiscais informados não valem
+        let items: Vec<VendaItemEntity> = serde_json::from_value(serde_json::json!([
+            { "produto_code": "789", "quantidade": 2 },
+            { "produto_code": "789", "quantidade": 1, "preco_unitario": 15, "produto_description": "Outro", "ncm": "22030000", "cfop": "5405" },
+            { "produto_code": "789", "quantidade": 1, "preco_unitario": 0 },
+        ])).unwrap();
+        let total = Money::from_cents(7560);
+        let venda_id = VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", total), items, cash(total)).unwrap().id;
+
+        let itens = VendaService::find_items_by_venda_id(state, venda_id).unwrap();
+        assert_eq!(itens[0].produto_description, product.name);
+        assert_eq!(itens[0].produto_medida, "PCT");
+        assert_eq!((itens[0].preco_unitario, itens[0].preco_total), (Money::from_cents(1890), Money::from_cents(3780)));
+        assert_eq!((itens[0].ncm.as_deref(), itens[0].cfop.as_deref(), itens[0].origem), (Some("09012100"), Some("5102"), Some(0)));
+        assert_eq!((itens[1].preco_unitario, itens[1].preco_total), (Money::from_cents(1890), Money::from_cents(1890)));
+        assert_eq!((itens[1].produto_description.as_str(), itens[1].ncm.as_deref(), itens[1].cfop.as_deref()), ("Café 500g", Some("09012100"), Some("5102")));
+        assert_eq!((itens[2].preco_unitario, itens[2].preco_total), (Money::from_cents(1890), Money::from_cents(1890)));
+
+        // Produto sem preço no cadastro é de preço aberto: vale o preço do item
+        state.products.save(&ProductEntity::new("790".to_string(), "Bolo".to_string())).unwrap();
+        let aberto = vec![VendaItemEntity { produto_code: "790".to_string(), quantidade: Quantity::from_int(1), preco_unitario: Money::from_int(12), ..Default::default() }];
+        let venda_id = VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", Money::from_int(12)), aberto, cash(Money::from_int(12))).unwrap().id;
+        assert_eq!(VendaService::find_items_by_venda_id(state, venda_id).unwrap()[0].preco_total, Money::from_int(12));
+
+        let unknown = vec![item("000", Quantity::from_int(1))];
+        let err = VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", Money::ZERO), unknown, vec![]).unwrap_err();
+        assert_eq!(err.code(), "VALIDATION_ERROR");
+    }
+
+    /// Produto "001" com 3 unidades em estoque; devolve o ID
+    fn stocked_product(state: &AppState) -> i64 {
+        let product_id = save_product(state, "001", Money::ZERO, Quantity::ZERO);
+        state.stock.record(&StockMovementEntity::new(product_id, MovementType::Purchase, Quantity::from_int(3), None, None)).unwrap();
+        product_id
+    }
+
+    /// Vende `quantidade` + 1 unidades do produto "001", em dois itens
+    fn sell(state: &AppState, quantidade: i64) -> AppResult<VendaCreatedDto> {
+        let items = vec![item("001", Quantity::from_int(quantidade)), item("001", Quantity::from_int(1))];
+        VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", Money::ZERO), items, vec![])
+    }
+
+    fn set_policy(state: &AppState, controle_estoque: i32, estoque_negativo: NegativeStockPolicy) {
+        let config = ConfigEntity { id: "default".to_string(), controle_estoque, estoque_negativo, ..Default::default() };
+        ConfigService::save(state, &config).unwrap();
+    }
+
+    fn stock_follows_sales(state: &AppState) {
+        let product_id = stocked_product(state);
+        let balance = || state.products.find_by_id(product_id).unwrap().unwrap().balance;
+        let sell = |quantidade| sell(state, quantidade);
+
+        // Sem controle de estoque o saldo não muda
+        sell(1).unwrap();
+        assert_eq!(balance(), Quantity::from_int(3));
+
+        set_policy(state, 1, NegativeStockPolicy::Warn);
+        let created = sell(3).unwrap();
+        assert_eq!(balance(), Quantity::from_int(-1));
+        assert_eq!(created.stock_warnings.len(), 1);
+        assert_eq!((created.stock_warnings[0].quantity, created.stock_warnings[0].balance_after), (Quantity::from_int(4), Quantity::from_int(-1)));
+
+        let filter = StockMovementFilter { reference: Some(StockMovementEntity::venda_reference(created.id)), ..Default::default() };
+        let movements = state.stock.find(&filter).unwrap();
+        assert_eq!(movements.len(), 1);
+        assert_eq!((movements[0].kind, movements[0].quantity), (MovementType::Sale, Quantity::from_int(-4)));
+
+        // Cancelar devolve o estoque uma única vez
+        VendaService::cancel_venda(state, created.id, "CFe2".to_string(), "2024-06-15T11:00:00".to_string(), None).unwrap();
+        VendaService::cancel_venda(state, created.id, "CFe2".to_string(), "2024-06-15T11:00:00".to_string(), None).unwrap();
+        assert_eq!(balance(), Quantity::from_int(3));
+        assert_eq!(state.stock.find(&filter).unwrap().last().map(|m| m.kind), Some(MovementType::Cancellation));
+
+        // Um cancelamento concorrente que leu a venda ainda ativa não devolve de novo
+        let returned = state.stock.find(&filter).unwrap();
+        let late = state.vendas.cancel(created.id, "CFe2", "2024-06-15T11:00:00", None, state.clock.now(), &returned[..1]).unwrap();
+        assert!(!late);
+        assert_eq!(balance(), Quantity::from_int(3));
+        let err = VendaService::cancel_venda(state, 9999, "CFe2".to_string(), "2024-06-15T11:00:00".to_string(), None).unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+    }
+
+    fn block_policy_rejects_oversales(state: &AppState) {
+        let product_id = stocked_product(state);
+        let balance = || state.products.find_by_id(product_id).unwrap().unwrap().balance;
+        let vendas = || VendaService::get_vendas_by_interval(state, "2024-06-15", "2024-06-15").unwrap().len();
+        set_policy(state, 1, NegativeStockPolicy::Block);
+
+        let err = sell(state, 3).unwrap_err();
+        assert_eq!(err.code(), "CONFLICT");
+        assert_eq!((balance(), vendas()), (Quantity::from_int(3), 0));
+
+        // Outra venda gravada depois da leitura do saldo: a baixa é conferida na transação
+        let stale = StockMovementEntity::new(product_id, MovementType::Sale, Quantity::from_int(-4), None, None);
+        let venda = new_venda("2024-06-15T10:30:00-03:00", Money::ZERO);
+        let err = state.vendas.create_venda(&venda, state.clock.now(), &[], &[], &[stale], true, None).unwrap_err();
+        assert_eq!(err.code(), "CONFLICT");
+        assert_eq!((balance(), vendas()), (Quantity::from_int(3), 0));
+
+        assert!(sell(state, 2).unwrap().stock_warnings.is_empty());
+        assert_eq!(balance(), Quantity::ZERO);
+    }
+
+    fn totals_by_category(state: &AppState) {
+        let save_category = |name: &str, parent_id: Option<i64>| {
+            state.categories.save(&CategoryEntity::new(name.to_string(), parent_id)).unwrap().id.unwrap()
+        };
+        let bebidas = save_category("Bebidas", None);
+        let refri = save_category("Refrigerantes", Some(bebidas));
+        save_category("Limpeza", None);
+        let save_in_category = |code: &str, category_id: Option<i64>| {
+            let product = ProductEntity { category_id, ..ProductEntity::new(code.to_string(), code.to_string()) };
+            state.products.save(&product).unwrap();
+        };
+        save_in_category("001", Some(bebidas));
+        save_in_category("002", Some(refri));
+        save_in_category("003", None);
+
+        let priced = |code: &str, quantidade: i64, preco: i64| VendaItemEntity {
+            preco_unitario: Money::from_int(preco),
+            ..item(code, Quantity::from_int(quantidade))
+        };
+        let sell = |items, total: i64| {
+            let total = Money::from_int(total);
+            VendaService::create_venda(state, &new_venda("2024-06-15T10:30:00-03:00", total), items, cash(total)).unwrap().id
+        };
+        sell(vec![priced("001", 1, 10), priced("002", 2, 5), priced("003", 1, 3)], 23);
+        let cancelled = sell(vec![priced("002", 1, 100)], 100);
+        VendaService::cancel_venda(state, cancelled, "CFe2".to_string(), "2024-06-15T11:00:00".to_string(), None).unwrap();
+
+        let rows = VendaService::get_category_totals_by_interval(state, "2024-06-15", "2024-06-15").unwrap();
+        let summary: Vec<(&str, Money, Money)> = rows.iter().map(|r| (r.path.as_str(), r.total, r.total_with_children)).collect();
+        assert_eq!(summary, vec![
+            ("Bebidas", Money::from_int(10), Money::from_int(20)),
+            ("Bebidas > Refrigerantes", Money::from_int(10), Money::from_int(10)),
+            ("Sem categoria", Money::from_int(3), Money::from_int(3)),
+        ]);
+        assert_eq!((rows[1].items, rows[1].quantidade, rows[1].parent_id), (1, Quantity::from_int(2), Some(bebidas)));
+        assert_eq!(rows[2].category_id, None);
+
+        assert!(VendaService::get_category_totals_by_interval(state, "2024-06-16", "2024-06-16").unwrap().is_empty());
+    }
+
+    fn late_sale_counts_on_local_day(state: &AppState) {
+        // 22:30 em São Paulo já é 01:30 do dia seguinte em UTC
+        let avulso = VendaItemEntity::new(0, "999".to_string(), "Avulso".to_string(), "UN".to_string(), Quantity::from_int(1), Money::from_int(10));
+        VendaService::create_venda(state, &new_venda("2024-06-15T22:30:00-03:00", Money::from_int(10)), vec![avulso], cash(Money::from_int(10))).unwrap();
+
+        assert_eq!(VendaService::get_vendas_by_interval(state, "2024-06-15", "2024-06-15").unwrap().len(), 1);
+        assert!(VendaService::get_vendas_by_interval(state, "2024-06-16", "2024-06-16").unwrap().is_empty());
+        assert!(VendaService::get_vendas_by_interval(state, "15/06/2024", "2024-06-15").is_err());
+        assert!(VendaService::create_venda(state, &new_venda("ontem", Money::ZERO), vec![], vec![]).is_err());
+    }
+
+    fn totals_are_checked(state: &AppState) {
+        state.products.save(&ProductEntity { price: Money::from_int(10), ..ProductEntity::new("001".to_string(), "Arroz".to_string()) }).unwrap();
+        let line = |quantidade: i64, preco_total: Money| VendaItemEntity { preco_total, ..item("001", Quantity::from_int(quantidade)) };
+        let fields = |err: AppError| match err {
+            AppError::Validation { fields, .. } => fields.into_iter().map(|f| f.field).collect::<Vec<_>>(),
+            other => panic!("expected validation error, got {:?}", other),
+        };
+        let dh_emi = "2024-06-15T10:30:00-03:00";
+
+        // Tudo que não confere volta junto
+        let err = VendaService::create_venda(state, &new_venda(dh_emi, Money::from_int(25)), vec![line(0, Money::ZERO), line(1, Money::ZERO)], cash(Money::from_int(5))).unwrap_err();
+        assert_eq!(fields(err), vec!["items[0].quantidade", "total", "payments"]);
+        let err = VendaService::create_venda(state, &new_venda(dh_emi, Money::ZERO), vec![], vec![]).unwrap_err();
+        assert_eq!(fields(err), vec!["items"]);
+
+        // O rateio enviado é descartado: o desconto é rateado pelo valor dos itens
+        let mut venda = new_venda(dh_emi, Money::from_int(29));
+        venda.discount = Money::from_int(1);
+        let items = vec![VendaItemEntity { desconto_rat: Money::from_cents(50), ..line(2, Money::ZERO) }, line(1, Money::ZERO)];
+        let venda_id = VendaService::create_venda(state, &venda, items, cash(Money::from_int(29))).unwrap().id;
+        let rateio: Vec<Money> = VendaService::find_items_by_venda_id(state, venda_id).unwrap().iter().map(|i| i.desconto_rat).collect();
+        assert_eq!(rateio, vec![Money::from_cents(66), Money::from_cents(34)]);
+
+        // O preco_total enviado precisa conferir com quantidade × preço - desconto + acréscimo
+        let config = ConfigEntity { id: "default".to_string(), tolerancia_total: Money::from_cents(10), ..Default::default() };
+        ConfigService::save(state, &config).unwrap();
+        let err = VendaService::create_venda(state, &new_venda(dh_emi, Money::from_int(20)), vec![line(2, Money::from_cents(2500))], cash(Money::from_int(20))).unwrap_err();
+        assert_eq!(fields(err), vec!["items[0].preco_total"]);
+
+        // Dentro da tolerância, os valores gravados são os recalculados
+        let discounted = VendaItemEntity { desconto: Money::from_int(2), acrescimo: Money::from_int(1), ..line(2, Money::from_cents(1905)) };
+        let venda_id = VendaService::create_venda(state, &new_venda(dh_emi, Money::from_cents(1905)), vec![discounted], cash(Money::from_int(19))).unwrap().id;
+        let saved = VendaService::find_with_relations(state, venda_id).unwrap().unwrap();
+        assert_eq!((saved.itens[0].preco_total, saved.venda.total), (Money::from_int(19), Money::from_int(19)));
+    }
+
+    #[test]
+    fn test_sale_flow() {
+        on_both_backends(sale_flow);
+    }
+
+    #[test]
+    fn test_items_inherit_product_data() {
+        on_both_backends(items_inherit_product_data);
+    }
+
+    #[test]
+    fn test_stock_follows_sales() {
+        on_both_backends(stock_follows_sales);
+    }
+
+    #[test]
+    fn test_block_policy_rejects_oversales() {
+        on_both_backends(block_policy_rejects_oversales);
+    }
+
+    #[test]
+    fn test_totals_by_category() {
+        on_both_backends(totals_by_category);
+    }
+
+    #[test]
+    fn test_interval_uses_store_timezone() {
+        let clock = FixedClock { now: Utc::now(), timezone: DEFAULT_TIMEZONE };
+        for state in backends() {
+            late_sale_counts_on_local_day(&state.with_clock(Arc::new(clock)));
+        }
+    }
+
+    #[test]
+    fn test_totals_are_checked() {
+        on_both_backends(totals_are_checked);
+    }
+
+    #[test]
+    fn test_prorate_keeps_rounding_on_last_item() {
+        let weights = [Money::from_cents(100), Money::from_cents(100), Money::from_cents(100)];
+        assert_eq!(prorate(Money::from_cents(100), &weights), vec![Money::from_cents(33), Money::from_cents(33), Money::from_cents(34)]);
+        assert_eq!(prorate(Money::from_cents(10), &[Money::ZERO, Money::ZERO]), vec![Money::ZERO, Money::from_cents(10)]);
+    }
+}
@@ -0,0 +1,80 @@
+use super::Cell;
+
+/// Separador usado na exportação (padrão do Excel em português)
+const DELIMITER: char = ';';
+
+/// Lê um CSV (RFC 4180). O separador (`;`, `,` ou tab) é o mais frequente na
+/// primeira linha; arquivos que não são UTF-8 são lidos como Windows-1252/Latin-1.
+pub fn read(content: &[u8]) -> Vec<Vec<String>> {
+    let text = decode(content);
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    let delimiter = detect_delimiter(text);
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Grava com `;`, vírgula decimal e BOM, para abrir direto no Excel
+pub fn write(rows: &[Vec<Cell>]) -> Vec<u8> {
+    let mut out = String::from('\u{feff}');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|cell| match cell {
+            Cell::Text(text) => quote(text),
+            Cell::Number(number) => number.replace('.', ","),
+        }).collect();
+        out.push_str(&fields.join(&DELIMITER.to_string()));
+        out.push_str("\r\n");
+    }
+    out.into_bytes()
+}
+
+fn decode(content: &[u8]) -> String {
+    match std::str::from_utf8(content) {
+        Ok(text) => text.to_string(),
+        Err(_) => content.iter().map(|&b| b as char).collect(),
+    }
+}
+
+fn detect_delimiter(text: &str) -> char {
+    let header = text.lines().next().unwrap_or("");
+    [';', ',', '\t']
+        .into_iter()
+        .max_by_key(|d| (header.matches(*d).count(), *d == DELIMITER))
+        .unwrap_or(DELIMITER)
+}
+
+fn quote(text: &str) -> String {
+    if text.contains([DELIMITER, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,147 @@
+//! Leitura e escrita de planilhas simples (uma aba, primeira linha de
+//! cabeçalho) em CSV e XLSX, usadas na importação e exportação do cadastro.
+
+mod csv;
+mod xlsx;
+mod zip;
+
+use serde::{Deserialize, Serialize};
+
+/// Formato do arquivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SheetFormat {
+    Csv,
+    Xlsx,
+}
+
+impl SheetFormat {
+    /// XLSX é um ZIP (começa com `PK`); qualquer outra coisa é lida como CSV
+    pub fn detect(content: &[u8]) -> Self {
+        if content.starts_with(b"PK\x03\x04") {
+            SheetFormat::Xlsx
+        } else {
+            SheetFormat::Csv
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SheetFormat::Csv => "csv",
+            SheetFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SheetFormat::Csv => "text/csv; charset=utf-8",
+            SheetFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+/// Célula a gravar; números vão como número no XLSX e com vírgula decimal no CSV
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Text(String),
+    Number(String),
+}
+
+/// Lê todas as linhas da primeira aba como texto, preservando as linhas vazias
+/// para que o índice de cada linha corresponda à sua posição no arquivo
+pub fn read_rows(content: &[u8], format: SheetFormat) -> Result<Vec<Vec<String>>, String> {
+    match format {
+        SheetFormat::Csv => Ok(csv::read(content)),
+        SheetFormat::Xlsx => xlsx::read(content),
+    }
+}
+
+/// Grava as linhas no formato pedido
+pub fn write_rows(rows: &[Vec<Cell>], format: SheetFormat) -> Result<Vec<u8>, String> {
+    match format {
+        SheetFormat::Csv => Ok(csv::write(rows)),
+        SheetFormat::Xlsx => xlsx::write(rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Vec<Cell>> {
+        vec![
+            vec![Cell::Text("code".to_string()), Cell::Text("name".to_string()), Cell::Text("price".to_string())],
+            vec![Cell::Text("001".to_string()), Cell::Text("Feijão; \"carioca\"".to_string()), Cell::Number("12.9".to_string())],
+            vec![Cell::Text("002".to_string()), Cell::Text("Arroz\n5kg".to_string()), Cell::Number("-0.5".to_string())],
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [SheetFormat::Csv, SheetFormat::Xlsx] {
+            let content = write_rows(&sample(), format).unwrap();
+            assert_eq!(SheetFormat::detect(&content), format);
+
+            let rows = read_rows(&content, format).unwrap();
+            let price = if format == SheetFormat::Csv { "12,9" } else { "12.9" };
+            assert_eq!(rows[1], vec!["001", "Feijão; \"carioca\"", price], "{:?}", format);
+            assert_eq!(rows[2][1], "Arroz\n5kg");
+            assert_eq!(rows.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_reads_excel_csv() {
+        // Excel em português: ponto e vírgula, Windows-1252 e CRLF
+        let content = b"C\xf3digo;Descri\xe7\xe3o\r\n1;A\xe7\xfacar\r\n\r\n2;\"Sal\"\r\n";
+        let rows = read_rows(content, SheetFormat::Csv).unwrap();
+        assert_eq!(rows, vec![vec!["Código", "Descrição"], vec!["1", "Açúcar"], vec![""], vec!["2", "Sal"]]);
+    }
+
+    #[test]
+    fn test_reads_excel_xlsx() {
+        // Como o Excel grava: textos compartilhados, células vazias omitidas e aba em outro caminho
+        let workbook = r#"<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Produtos" sheetId="1" r:id="rId3"/></sheets></workbook>"#;
+        let rels = r#"<Relationships><Relationship Id="rId3" Target="/xl/worksheets/produtos.xml"/></Relationships>"#;
+        let shared = r#"<sst><si><t>code</t></si><si><t>price</t></si><si><r><t>Arroz </t></r><r><t>&amp; Feijão</t></r><rPh><t>x</t></rPh></si></sst>"#;
+        let sheet = concat!(
+            r#"<worksheet><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c><c r="C1" t="s"><v>1</v></c></row>"#,
+            r#"<row r="3"><c r="A3" t="s"><v>2</v></c><c r="C3"><v>1.2E1</v></c></row></sheetData></worksheet>"#
+        );
+        let content = zip::write(&[
+            ("xl/workbook.xml", workbook.as_bytes().to_vec()),
+            ("xl/_rels/workbook.xml.rels", rels.as_bytes().to_vec()),
+            ("xl/sharedStrings.xml", shared.as_bytes().to_vec()),
+            ("xl/worksheets/produtos.xml", sheet.as_bytes().to_vec()),
+        ])
+        .unwrap();
+
+        let rows = read_rows(&content, SheetFormat::Xlsx).unwrap();
+        assert_eq!(rows, vec![vec!["code", "", "price"], vec![], vec!["Arroz & Feijão", "", "12"]]);
+        assert!(read_rows(b"PK\x03\x04lixo", SheetFormat::Xlsx).is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_xlsx() {
+        let workbook = |cell: &str| {
+            let sheet = format!(r#"<worksheet><sheetData>{}</sheetData></worksheet>"#, cell);
+            zip::write(&[("xl/worksheets/sheet1.xml", sheet.into_bytes())]).unwrap()
+        };
+        let read = |cell: &str| read_rows(&workbook(cell), SheetFormat::Xlsx);
+
+        assert!(read(r#"<row r="1048576"><c r="XFD1048576"><v>1</v></c></row>"#).is_ok());
+        assert!(read(r#"<row r="1048577"><c r="A1048577"><v>1</v></c></row>"#).unwrap_err().contains("Linha"));
+        assert!(read(r#"<row r="1"><c r="XFE1"><v>1</v></c></row>"#).unwrap_err().contains("Coluna"));
+        // Referência longa demais estouraria o cálculo da coluna
+        assert!(read(r#"<row r="1"><c r="ZZZZZZZZZZZZZZZZ1"><v>1</v></c></row>"#).unwrap_err().contains("inválida"));
+
+        let compressed = {
+            use std::io::Write;
+            let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&[0u8; 4096]).unwrap();
+            encoder.finish().unwrap()
+        };
+        assert_eq!(zip::inflate(&compressed, 4096).unwrap().len(), 4096);
+        assert!(zip::inflate(&compressed, 4095).is_err());
+    }
+}
@@ -0,0 +1,326 @@
+//! XLSX (SpreadsheetML) reduzido ao necessário: leitura da primeira aba, com
+//! textos compartilhados ou embutidos, e escrita de uma aba sem estilos.
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesRef, Event};
+use quick_xml::Reader;
+
+use super::zip::{self, ZipReader};
+use super::Cell;
+
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const RELS_NS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+const DOC_RELS_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Limites de uma aba do Excel: 1.048.576 linhas e 16.384 colunas (XFD)
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+/// Células alocadas na leitura, contando as vazias que preenchem lacunas
+const MAX_CELLS: usize = 10_000_000;
+
+/// Lê a primeira aba da pasta de trabalho
+pub fn read(content: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let zip = ZipReader::new(content)?;
+    let sheet = first_sheet_path(&zip)?;
+    let xml = zip.read(&sheet)?.ok_or_else(|| "Arquivo XLSX sem planilhas".to_string())?;
+    let shared = match zip.read("xl/sharedStrings.xml")? {
+        Some(xml) => shared_strings(&xml)?,
+        None => Vec::new(),
+    };
+    sheet_rows(&xml, &shared)
+}
+
+/// Grava as linhas numa pasta de trabalho com uma única aba
+pub fn write(rows: &[Vec<Cell>]) -> Result<Vec<u8>, String> {
+    let content_types = format!(
+        concat!(
+            r#"{}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+            r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+            r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            r#"</Types>"#
+        ),
+        XML_HEADER
+    );
+    let rels = format!(
+        r#"{}<Relationships xmlns="{}"><Relationship Id="rId1" Type="{}/officeDocument" Target="xl/workbook.xml"/></Relationships>"#,
+        XML_HEADER, RELS_NS, DOC_RELS_NS
+    );
+    let workbook = format!(
+        r#"{}<workbook xmlns="{}" xmlns:r="{}"><sheets><sheet name="Planilha1" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+        XML_HEADER, MAIN_NS, DOC_RELS_NS
+    );
+    let workbook_rels = format!(
+        r#"{}<Relationships xmlns="{}"><Relationship Id="rId1" Type="{}/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#,
+        XML_HEADER, RELS_NS, DOC_RELS_NS
+    );
+
+    let mut sheet = format!(r#"{}<worksheet xmlns="{}"><sheetData>"#, XML_HEADER, MAIN_NS);
+    for (r, row) in rows.iter().enumerate() {
+        sheet.push_str(&format!(r#"<row r="{}">"#, r + 1));
+        for (c, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Text(text) => sheet.push_str(&format!(
+                    r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                    reference,
+                    escape(text.as_str())
+                )),
+                Cell::Number(number) => sheet.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, number)),
+            }
+        }
+        sheet.push_str("</row>");
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    zip::write(&[
+        ("[Content_Types].xml", content_types.into_bytes()),
+        ("_rels/.rels", rels.into_bytes()),
+        ("xl/workbook.xml", workbook.into_bytes()),
+        ("xl/_rels/workbook.xml.rels", workbook_rels.into_bytes()),
+        ("xl/worksheets/sheet1.xml", sheet.into_bytes()),
+    ])
+}
+
+/// Caminho da primeira aba, pelo relacionamento declarado em `xl/workbook.xml`
+fn first_sheet_path(zip: &ZipReader) -> Result<String, String> {
+    const FALLBACK: &str = "xl/worksheets/sheet1.xml";
+
+    let (Some(workbook), Some(rels)) = (zip.read("xl/workbook.xml")?, zip.read("xl/_rels/workbook.xml.rels")?) else {
+        return Ok(FALLBACK.to_string());
+    };
+
+    let mut id = None;
+    parse(&workbook, |event| {
+        if let Event::Start(e) | Event::Empty(e) = event {
+            if id.is_none() && e.local_name().as_ref() == b"sheet" {
+                id = attribute(&e, b"id")?;
+            }
+        }
+        Ok(())
+    })?;
+    let Some(id) = id else {
+        return Ok(FALLBACK.to_string());
+    };
+
+    let mut target = None;
+    parse(&rels, |event| {
+        if let Event::Start(e) | Event::Empty(e) = event {
+            if e.local_name().as_ref() == b"Relationship" && attribute(&e, b"Id")?.as_deref() == Some(id.as_str()) {
+                target = attribute(&e, b"Target")?;
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(match target {
+        Some(target) => match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        },
+        None => FALLBACK.to_string(),
+    })
+}
+
+/// Tabela de textos compartilhados; textos com formatação (`<r>`) são concatenados
+/// e a guia fonética (`<rPh>`) é ignorada
+fn shared_strings(xml: &[u8]) -> Result<Vec<String>, String> {
+    let mut strings = Vec::new();
+    let mut in_text = false;
+    let mut in_phonetic = false;
+
+    parse(xml, |event| {
+        match event {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"si" => strings.push(String::new()),
+                b"t" => in_text = true,
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                _ => {}
+            },
+            event if in_text && !in_phonetic => {
+                if let (Some(text), Some(current)) = (text_of(&event)?, strings.last_mut()) {
+                    current.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(strings)
+}
+
+/// Tipo da célula (atributo `t`)
+enum CellType {
+    Number,
+    Shared,
+    Other,
+}
+
+fn sheet_rows(xml: &[u8], shared: &[String]) -> Result<Vec<Vec<String>>, String> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row = 0usize;
+    let mut column = 0usize;
+    let mut cell_type = CellType::Other;
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut cell_count = 0usize;
+
+    parse(xml, |event| {
+        match event {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                row = match attribute(&e, b"r")? {
+                    Some(r) => r.parse().map_err(|_| format!("Linha {} inválida no XLSX", r))?,
+                    None => rows.len() + 1,
+                };
+                if row == 0 || row > MAX_ROWS {
+                    return Err(format!("Linha {} fora dos limites do XLSX (1 a {})", row, MAX_ROWS));
+                }
+                column = 0;
+                if rows.len() < row {
+                    rows.resize(row, Vec::new());
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"c" => {
+                column = cell_column(&e, column)?;
+                cell_type = match attribute(&e, b"t")?.as_deref() {
+                    None | Some("n") => CellType::Number,
+                    Some("s") => CellType::Shared,
+                    Some(_) => CellType::Other,
+                };
+                value.clear();
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                column = cell_column(&e, column)?;
+            }
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"v" | b"t") => in_value = true,
+            Event::End(e) => match e.local_name().as_ref() {
+                b"v" | b"t" => in_value = false,
+                b"c" if row > 0 && column > 0 => {
+                    let text = match cell_type {
+                        CellType::Number => match value.trim().parse::<f64>() {
+                            Ok(number) => number.to_string(),
+                            Err(_) => value.clone(),
+                        },
+                        CellType::Shared => value
+                            .trim()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|i| shared.get(i))
+                            .cloned()
+                            .ok_or_else(|| format!("Texto compartilhado {} inexistente no XLSX", value))?,
+                        CellType::Other => value.clone(),
+                    };
+                    let cells = &mut rows[row - 1];
+                    if cells.len() < column {
+                        cell_count += column - cells.len();
+                        if cell_count > MAX_CELLS {
+                            return Err(format!("Planilha XLSX com mais de {} células", MAX_CELLS));
+                        }
+                        cells.resize(column, String::new());
+                    }
+                    cells[column - 1] = text;
+                }
+                _ => {}
+            },
+            event if in_value => {
+                if let Some(text) = text_of(&event)? {
+                    value.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// Coluna de uma célula `c`: a da referência `r` ou a seguinte à anterior
+fn cell_column(e: &quick_xml::events::BytesStart, previous: usize) -> Result<usize, String> {
+    let column = match attribute(e, b"r")? {
+        Some(r) => column_index(&r).ok_or_else(|| format!("Célula {} inválida no XLSX", r))?,
+        None => previous + 1,
+    };
+    if column > MAX_COLUMNS {
+        return Err(format!("Coluna {} fora dos limites do XLSX (máximo {})", column, MAX_COLUMNS));
+    }
+    Ok(column)
+}
+
+fn parse(xml: &[u8], mut handle: impl FnMut(Event) -> Result<(), String>) -> Result<(), String> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf).map_err(|e| format!("XML inválido no XLSX: {}", e))? {
+            Event::Eof => return Ok(()),
+            event => handle(event)?,
+        }
+        buf.clear();
+    }
+}
+
+fn attribute(e: &quick_xml::events::BytesStart, name: &[u8]) -> Result<Option<String>, String> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| format!("XML inválido no XLSX: {}", e))?;
+        if attr.key.local_name().as_ref() == name {
+            let value = attr.unescape_value().map_err(|e| format!("XML inválido no XLSX: {}", e))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Texto de um nó de texto ou de uma referência (`&amp;`, `&#10;`)
+fn text_of(event: &Event) -> Result<Option<String>, String> {
+    let invalid = |e: &dyn std::fmt::Display| format!("XML inválido no XLSX: {}", e);
+    match event {
+        Event::Text(e) => Ok(Some(e.xml_content().map_err(|e| invalid(&e))?.into_owned())),
+        Event::CData(e) => Ok(Some(String::from_utf8_lossy(e).into_owned())),
+        Event::GeneralRef(e) => reference(e).map(|c| Some(c.to_string())).map_err(|e| invalid(&e)),
+        _ => Ok(None),
+    }
+}
+
+fn reference(e: &BytesRef) -> Result<char, String> {
+    if let Some(c) = e.resolve_char_ref().map_err(|e| e.to_string())? {
+        return Ok(c);
+    }
+    match e.as_ref() {
+        b"lt" => Ok('<'),
+        b"gt" => Ok('>'),
+        b"amp" => Ok('&'),
+        b"quot" => Ok('"'),
+        b"apos" => Ok('\''),
+        other => Err(format!("entidade desconhecida &{};", String::from_utf8_lossy(other))),
+    }
+}
+
+/// Coluna (1 = A) a partir de uma referência como `AB12`
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference.bytes().take_while(|b| b.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    letters.iter().try_fold(0usize, |n, b| {
+        n.checked_mul(26)?.checked_add((b.to_ascii_uppercase() - b'A') as usize + 1)
+    })
+}
+
+/// Nome da coluna (0 = A)
+fn column_name(index: usize) -> String {
+    let mut n = index + 1;
+    let mut name = Vec::new();
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
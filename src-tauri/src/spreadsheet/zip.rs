@@ -0,0 +1,149 @@
+//! O mínimo de ZIP necessário para o XLSX: leitura de entradas armazenadas ou
+//! comprimidas com deflate e escrita com deflate.
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Tamanho máximo de uma entrada descomprimida (protege contra "zip bombs")
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Arquivo ZIP já carregado na memória
+pub struct ZipReader<'a> {
+    data: &'a [u8],
+    /// Nome, método, tamanho comprimido e posição do cabeçalho local de cada entrada
+    entries: Vec<(String, u16, usize, usize)>,
+}
+
+impl<'a> ZipReader<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, String> {
+        let invalid = || "Arquivo XLSX inválido".to_string();
+
+        // O registro final tem 22 bytes mais um comentário opcional
+        let end = (0..data.len().saturating_sub(21))
+            .rev()
+            .find(|&i| u32_at(data, i) == Some(END_OF_CENTRAL_DIRECTORY))
+            .ok_or_else(invalid)?;
+        let count = u16_at(data, end + 10).ok_or_else(invalid)? as usize;
+        let mut offset = u32_at(data, end + 16).ok_or_else(invalid)? as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            if u32_at(data, offset) != Some(CENTRAL_HEADER) {
+                return Err(invalid());
+            }
+            let method = u16_at(data, offset + 10).ok_or_else(invalid)?;
+            let compressed = u32_at(data, offset + 20).ok_or_else(invalid)? as usize;
+            let name_len = u16_at(data, offset + 28).ok_or_else(invalid)? as usize;
+            let extra_len = u16_at(data, offset + 30).ok_or_else(invalid)? as usize;
+            let comment_len = u16_at(data, offset + 32).ok_or_else(invalid)? as usize;
+            let local = u32_at(data, offset + 42).ok_or_else(invalid)? as usize;
+            let name = data.get(offset + 46..offset + 46 + name_len).ok_or_else(invalid)?;
+
+            entries.push((String::from_utf8_lossy(name).into_owned(), method, compressed, local));
+            offset += 46 + name_len + extra_len + comment_len;
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    /// Conteúdo descomprimido da entrada, se existir
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let Some(&(_, method, compressed, local)) = self.entries.iter().find(|(n, ..)| n == name) else {
+            return Ok(None);
+        };
+        let invalid = || format!("Entrada {} inválida no XLSX", name);
+
+        if u32_at(self.data, local) != Some(LOCAL_HEADER) {
+            return Err(invalid());
+        }
+        let name_len = u16_at(self.data, local + 26).ok_or_else(invalid)? as usize;
+        let extra_len = u16_at(self.data, local + 28).ok_or_else(invalid)? as usize;
+        let start = local + 30 + name_len + extra_len;
+        let raw = self.data.get(start..start + compressed).ok_or_else(invalid)?;
+
+        match method {
+            STORED => Ok(Some(raw.to_vec())),
+            DEFLATED => inflate(raw, MAX_ENTRY_SIZE).map(Some).map_err(|e| format!("{}: {}", invalid(), e)),
+            _ => Err(format!("Compressão {} não suportada no XLSX", method)),
+        }
+    }
+}
+
+/// Descomprime `raw`, recusando conteúdo com mais de `limit` bytes
+pub(super) fn inflate(raw: &[u8], limit: u64) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    DeflateDecoder::new(raw).take(limit + 1).read_to_end(&mut out).map_err(|e| e.to_string())?;
+    if out.len() as u64 > limit {
+        return Err(format!("conteúdo maior que {} bytes", limit));
+    }
+    Ok(out)
+}
+
+/// Monta um ZIP com as entradas informadas, comprimidas com deflate
+pub fn write(entries: &[(&str, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+
+    for (name, content) in entries {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).map_err(|e| e.to_string())?;
+        let compressed = encoder.finish().map_err(|e| e.to_string())?;
+        let crc = crc32fast::hash(content);
+        let offset = out.len() as u32;
+
+        // Versão 2.0, nomes em UTF-8, data 1980-01-01 00:00
+        let common = |buf: &mut Vec<u8>| {
+            buf.extend_from_slice(&20u16.to_le_bytes());
+            buf.extend_from_slice(&0x0800u16.to_le_bytes());
+            buf.extend_from_slice(&DEFLATED.to_le_bytes());
+            buf.extend_from_slice(&0u16.to_le_bytes());
+            buf.extend_from_slice(&0x21u16.to_le_bytes());
+            buf.extend_from_slice(&crc.to_le_bytes());
+            buf.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&0u16.to_le_bytes());
+        };
+
+        out.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        common(&mut out);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&compressed);
+
+        central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        common(&mut central);
+        central.extend_from_slice(&[0; 6]); // comentário, disco e atributos internos
+        central.extend_from_slice(&0u32.to_le_bytes()); // atributos externos
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    Ok(out)
+}
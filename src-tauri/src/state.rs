@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use crate::clock::{Clock, SystemClock};
+use crate::database::SqliteDbService;
+use crate::entities::Origin;
+use crate::events::{EventSink, NoEvents};
+use crate::repositories::{
+    CartRepository, CategoryRepository, CompositionRepository, ConfigRepository, HistoryRepository, InventoryRepository, LotRepository,
+    ProductRepository, PromotionRepository, ResumeRepository, StockMovementRepository, UnitRepository, VendaRepository,
+};
+use crate::repositories::sqlite::{
+    SqliteCartRepository, SqliteCategoryRepository, SqliteCompositionRepository, SqliteConfigRepository, SqliteHistoryRepository,
+    SqliteInventoryRepository, SqliteLotRepository, SqliteProductRepository, SqlitePromotionRepository, SqliteResumeRepository,
+    SqliteStockMovementRepository, SqliteUnitRepository, SqliteVendaRepository,
+};
+use crate::repositories::memory::{
+    InMemoryCartRepository, InMemoryCategoryRepository, InMemoryCompositionRepository, InMemoryConfigRepository, InMemoryHistoryRepository,
+    InMemoryInventoryRepository, InMemoryLotRepository, InMemoryProductRepository, InMemoryPromotionRepository, InMemoryResumeRepository,
+    InMemoryStockMovementRepository, InMemoryUnitRepository, InMemoryVendaRepository,
+};
+
+/// Estado compartilhado pelos comandos Tauri, pelos handlers HTTP e pelos use cases.
+///
+/// Cada repositório é um trait object, então o mesmo fluxo roda sobre o SQLite
+/// em produção ou inteiramente em memória nos testes.
+#[derive(Clone)]
+pub struct AppState {
+    pub products: Arc<dyn ProductRepository>,
+    pub categories: Arc<dyn CategoryRepository>,
+    pub promotions: Arc<dyn PromotionRepository>,
+    /// Unidades de medida
+    pub units: Arc<dyn UnitRepository>,
+    /// Componentes dos kits
+    pub compositions: Arc<dyn CompositionRepository>,
+    pub vendas: Arc<dyn VendaRepository>,
+    /// Sessões de inventário (contagem física)
+    pub inventories: Arc<dyn InventoryRepository>,
+    /// Lotes com validade dos produtos com controle de lotes
+    pub lots: Arc<dyn LotRepository>,
+    /// Carrinhos do caixa (vendas em andamento)
+    pub carts: Arc<dyn CartRepository>,
+    pub configs: Arc<dyn ConfigRepository>,
+    pub resumes: Arc<dyn ResumeRepository>,
+    pub history: Arc<dyn HistoryRepository>,
+    /// Livro de movimentos de estoque; é por ele que o saldo dos produtos muda
+    pub stock: Arc<dyn StockMovementRepository>,
+    pub clock: Arc<dyn Clock>,
+    /// Para onde vão os avisos ao frontend (estoque baixo)
+    pub events: Arc<dyn EventSink>,
+    /// De onde vêm as alterações feitas com este estado (gravado no histórico)
+    pub origin: Origin,
+}
+
+impl AppState {
+    /// Estado apoiado no banco SQLite
+    pub fn sqlite(db: SqliteDbService) -> Self {
+        Self {
+            products: Arc::new(SqliteProductRepository::new(db.clone())),
+            categories: Arc::new(SqliteCategoryRepository::new(db.clone())),
+            promotions: Arc::new(SqlitePromotionRepository::new(db.clone())),
+            units: Arc::new(SqliteUnitRepository::new(db.clone())),
+            compositions: Arc::new(SqliteCompositionRepository::new(db.clone())),
+            vendas: Arc::new(SqliteVendaRepository::new(db.clone())),
+            inventories: Arc::new(SqliteInventoryRepository::new(db.clone())),
+            lots: Arc::new(SqliteLotRepository::new(db.clone())),
+            carts: Arc::new(SqliteCartRepository::new(db.clone())),
+            configs: Arc::new(SqliteConfigRepository::new(db.clone())),
+            resumes: Arc::new(SqliteResumeRepository::new(db.clone())),
+            history: Arc::new(SqliteHistoryRepository::new(db.clone())),
+            stock: Arc::new(SqliteStockMovementRepository::new(db)),
+            clock: Arc::new(SystemClock::default()),
+            events: Arc::new(NoEvents),
+            origin: Origin::default(),
+        }
+    }
+
+    /// Estado totalmente em memória, sem banco de dados
+    pub fn in_memory() -> Self {
+        let products = Arc::new(InMemoryProductRepository::new());
+        let lots = Arc::new(InMemoryLotRepository::new());
+        let stock = Arc::new(InMemoryStockMovementRepository::new(products.clone(), lots.clone()));
+        let carts: Arc<dyn CartRepository> = Arc::new(InMemoryCartRepository::new());
+        Self {
+            products,
+            categories: Arc::new(InMemoryCategoryRepository::new()),
+            promotions: Arc::new(InMemoryPromotionRepository::new()),
+            units: Arc::new(InMemoryUnitRepository::new()),
+            compositions: Arc::new(InMemoryCompositionRepository::new()),
+            vendas: Arc::new(InMemoryVendaRepository::new(stock.clone(), carts.clone())),
+            inventories: Arc::new(InMemoryInventoryRepository::new(stock.clone())),
+            lots,
+            carts,
+            stock,
+            configs: Arc::new(InMemoryConfigRepository::new()),
+            resumes: Arc::new(InMemoryResumeRepository::new()),
+            history: Arc::new(InMemoryHistoryRepository::new()),
+            clock: Arc::new(SystemClock::default()),
+            events: Arc::new(NoEvents),
+            origin: Origin::default(),
+        }
+    }
+
+    /// Substitui o relógio (ex. `FixedClock` nos testes)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Substitui o destino dos avisos ao frontend
+    pub fn with_events(mut self, events: Arc<dyn EventSink>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Define a origem registrada no histórico (Tauri ou HTTP)
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+}
@@ -0,0 +1,44 @@
+//! Apoio aos testes dos serviços: os dois backends do `AppState` e as
+//! entidades que várias suítes montam do mesmo jeito
+
+use crate::database::SqliteDbService;
+use crate::entities::{Money, ProductEntity, Quantity, VendaEntity, VendaItemEntity, VendaPagamentoEntity};
+use crate::state::AppState;
+
+/// Estado em memória e estado SQLite (banco em memória), para testes que
+/// ajustam o estado (relógio, origem) antes de rodar
+pub fn backends() -> [AppState; 2] {
+    let db = SqliteDbService::new_in_memory().expect("Failed to create in-memory database");
+    [AppState::in_memory(), AppState::sqlite(db)]
+}
+
+/// Roda `test` em cada backend, com um estado novo para cada um
+pub fn on_both_backends(test: impl Fn(&AppState)) {
+    for state in backends() {
+        test(&state);
+    }
+}
+
+/// Cadastra direto no repositório um produto com preço e saldo; devolve o ID
+pub fn save_product(state: &AppState, code: &str, price: Money, balance: Quantity) -> i64 {
+    let product = ProductEntity { price, balance, ..ProductEntity::new(code.to_string(), code.to_string()) };
+    state.products.save(&product).unwrap().id.unwrap()
+}
+
+/// NFC-e emitida em `dh_emi` com o `total` informado
+pub fn new_venda(dh_emi: &str, total: Money) -> VendaEntity {
+    VendaEntity::new(
+        1, 65, "1".to_string(), 10, "28095955000199".to_string(),
+        dh_emi.to_string(), total, "CFe1".to_string(),
+    )
+}
+
+/// Item só com código e quantidade; o resto vem do cadastro do produto
+pub fn item(code: &str, quantidade: Quantity) -> VendaItemEntity {
+    VendaItemEntity { produto_code: code.to_string(), quantidade, ..Default::default() }
+}
+
+/// Pagamento único em dinheiro
+pub fn cash(total: Money) -> Vec<VendaPagamentoEntity> {
+    vec![VendaPagamentoEntity::new(0, "01".to_string(), "Dinheiro".to_string(), total)]
+}
@@ -1,14 +1,17 @@
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::clock::parse_timezone;
 use crate::entities::ConfigEntity;
 use crate::services::ConfigService;
+use crate::state::AppState;
 use crate::dtos::{CreateOrUpdateConfigDto, UpdatePercentConfigDto, CnpjResponseDto};
 
 pub struct GetFirstConfigUseCase;
 
 impl GetFirstConfigUseCase {
     /// Busca a primeira configuração (default)
-    pub fn execute() -> Result<Option<ConfigEntity>, String> {
+    pub fn execute(state: &AppState) -> AppResult<Option<ConfigEntity>> {
         // Busca pela configuração com id "default"
-        ConfigService::find_by_id("default")
+        ConfigService::find_by_id(state, "default")
     }
 }
 
@@ -16,12 +19,13 @@ pub struct CreateOrUpdateConfigUseCase;
 
 impl CreateOrUpdateConfigUseCase {
     /// Cria ou atualiza uma configuração
-    pub fn execute(dto: CreateOrUpdateConfigDto) -> Result<ConfigEntity, String> {
+    pub fn execute(state: &AppState, dto: CreateOrUpdateConfigDto) -> AppResult<ConfigEntity> {
         let id = dto.id.unwrap_or_else(|| "default".to_string());
         
         // Busca a configuração existente ou cria uma nova com valores padrão
-        let mut config = ConfigService::find_by_id(&id)?
-            .unwrap_or_else(|| ConfigEntity::default());
+        let existing = ConfigService::find_by_id(state, &id)?;
+        let is_new = existing.is_none();
+        let mut config = existing.unwrap_or_default();
         
         // Atualiza os campos fornecidos no DTO
         if let Some(flow_base_url) = dto.flow_base_url {
@@ -123,20 +127,34 @@ impl CreateOrUpdateConfigUseCase {
         if let Some(modelo) = dto.modelo {
             config.modelo = modelo;
         }
+        if let Some(estoque_negativo) = dto.estoque_negativo {
+            config.estoque_negativo = estoque_negativo;
+        }
+        if let Some(balanca) = dto.balanca {
+            balanca.validate().map_err(|e| AppError::validation("balanca", e))?;
+            config.balanca = balanca;
+        }
+        if let Some(tolerancia_total) = dto.tolerancia_total {
+            if tolerancia_total.is_negative() {
+                return Err(AppError::validation("tolerancia_total", "Tolerância não pode ser negativa"));
+            }
+            config.tolerancia_total = tolerancia_total;
+        }
+        if let Some(timezone) = dto.timezone {
+            let timezone = parse_timezone(&timezone).map_err(|e| AppError::validation("timezone", e))?;
+            config.timezone = timezone.name().to_string();
+        }
 
         // Atualiza o timestamp
-        config.updated_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        config.updated_at = state.clock.now();
         
-        // Se for uma nova configuração, define o created_at
-        if config.id.is_empty() {
+        // Se for uma nova configuração, define o id e o created_at
+        if is_new {
             config.id = id;
             config.created_at = config.updated_at;
         }
 
-        ConfigService::save(&config)
+        ConfigService::save(state, &config)
     }
 }
 
@@ -144,17 +162,14 @@ pub struct UpdatePercentUseCase;
 
 impl UpdatePercentUseCase {
     /// Atualiza apenas o percentual de desconto da configuração
-    pub fn execute(dto: UpdatePercentConfigDto) -> Result<ConfigEntity, String> {
-        let mut config = ConfigService::find_by_id("default")?
-            .ok_or_else(|| "Configuração não encontrada".to_string())?;
+    pub fn execute(state: &AppState, dto: UpdatePercentConfigDto) -> AppResult<ConfigEntity> {
+        let mut config = ConfigService::find_by_id(state, "default")?
+            .ok_or_else(|| AppError::not_found("Configuração não encontrada"))?;
         
         config.percent_s = dto.percent_s;
-        config.updated_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        config.updated_at = state.clock.now();
 
-        ConfigService::save(&config)
+        ConfigService::save_as(state, &config, Some("update_percent"))
     }
 }
 
@@ -162,12 +177,12 @@ pub struct GetCnpjUseCase;
 
 impl GetCnpjUseCase {
     /// Consulta informações de um CNPJ em API externa
-    pub async fn execute(cnpj: String) -> Result<CnpjResponseDto, String> {
+    pub async fn execute(cnpj: String) -> AppResult<CnpjResponseDto> {
         // Remove caracteres não numéricos do CNPJ
         let cnpj_clean = cnpj.chars().filter(|c| c.is_numeric()).collect::<String>();
         
         if cnpj_clean.len() != 14 {
-            return Err("CNPJ inválido".to_string());
+            return Err(AppError::validation("cnpj", "CNPJ inválido"));
         }
 
         // Consulta API pública de CNPJ (exemplo: ReceitaWS)
@@ -175,15 +190,15 @@ impl GetCnpjUseCase {
         
         let response = reqwest::get(&url)
             .await
-            .map_err(|e| format!("Erro ao consultar CNPJ: {}", e))?;
+            .context("Erro ao consultar CNPJ")?;
 
         if !response.status().is_success() {
-            return Err(format!("Erro na consulta: status {}", response.status()));
+            return Err(AppError::ExternalService(format!("Erro na consulta: status {}", response.status())));
         }
 
         let json: serde_json::Value = response.json()
             .await
-            .map_err(|e| format!("Erro ao parsear resposta: {}", e))?;
+            .context("Erro ao parsear resposta")?;
 
         // Verifica se houve erro na API
         if let Some(status) = json.get("status").and_then(|s| s.as_str()) {
@@ -191,7 +206,7 @@ impl GetCnpjUseCase {
                 let message = json.get("message")
                     .and_then(|m| m.as_str())
                     .unwrap_or("Erro desconhecido");
-                return Err(message.to_string());
+                return Err(AppError::ExternalService(message.to_string()));
             }
         }
 
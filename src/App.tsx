@@ -1,11 +1,14 @@
 import { useState } from "react";
 import reactLogo from "./assets/react.svg";
 import { invoke } from "@tauri-apps/api/core";
+import { Cart } from "./api/carts";
+import { CartRecoveryPrompt } from "./components/CartRecoveryPrompt";
 import "./App.css";
 
 function App() {
   const [greetMsg, setGreetMsg] = useState("");
   const [name, setName] = useState("");
+  const [cart, setCart] = useState<Cart | null>(null);
 
   async function greet() {
     // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -14,6 +17,10 @@ function App() {
 
   return (
     <main className="container">
+      {/* Carrinho que sobrou de uma queda ou reinício: retomar ou abortar */}
+      <CartRecoveryPrompt onResume={setCart} />
+      {cart && <p>Venda em andamento: carrinho #{cart.id}</p>}
+
       <h1>Welcome to Tauri + React</h1>
 
       <div className="row">
@@ -0,0 +1,155 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { PricingItem } from './promotions';
+import type { LowStock } from './stock';
+
+export type CartStatus = 'open' | 'payment' | 'finalized' | 'aborted';
+
+export interface CartPayment {
+    id: number;
+    cart_id: number;
+    code: string;              // forma de pagamento ('01' dinheiro, '03' crédito...)
+    name: string;
+    total_pagamento: number;
+    created_at: string;
+}
+
+export interface Cart {
+    id: number;
+    status: CartStatus;
+    user?: string | null;
+    doc_destinatario?: string | null;
+    discount: number;
+    addition: number;
+    venda_id: number | null;   // preenchido na finalização
+    opened_at: string;
+    updated_at: string;
+    closed_at: string | null;
+    items: PricingItem[];      // id = ID do item no carrinho
+    payments: CartPayment[];
+    subtotal: number;
+    items_discount: number;
+    total: number;             // subtotal - items_discount - discount + addition
+    paid: number;
+    remaining: number;
+    change: number;            // troco
+}
+
+export interface CartItemInput {
+    barcode: string;
+    quantidade?: number;       // padrão 1 (ou o peso da etiqueta)
+    produto_medida?: string;   // unidade de venda (padrão) ou de compra
+    desconto?: number;
+}
+
+export interface CartFiscal {
+    tip: number;
+    mod_: number;
+    serie: string;
+    nr_nf: number;
+    cnpj: string;
+    chave: string;
+    dh_emi?: string;           // padrão: agora
+    protocolo?: string;
+    file_path?: string;
+}
+
+export interface CartFinalized {
+    cart: Cart;
+    venda: { id: number; stock_warnings: unknown[]; low_stock: LowStock[] };
+}
+
+/**
+ * API de Carrinho - venda em andamento no caixa, gravada a cada passo
+ */
+export class CartsApi {
+    /**
+     * POST /carts - Abre um carrinho
+     */
+    static async open(user?: string, docDestinatario?: string): Promise<Cart> {
+        return await invoke<Cart>('open_cart', { cart: { user, doc_destinatario: docDestinatario } });
+    }
+
+    /**
+     * GET /carts/current - Carrinho em andamento, se houver (chamar ao iniciar o caixa)
+     */
+    static async current(): Promise<Cart | null> {
+        return await invoke<Cart | null>('get_current_cart');
+    }
+
+    /**
+     * GET /carts/:id - Busca carrinho por ID
+     */
+    static async findById(id: number): Promise<Cart | null> {
+        return await invoke<Cart | null>('get_cart', { id });
+    }
+
+    /**
+     * POST /carts/:id/items - Lê um produto
+     */
+    static async addItem(id: number, item: CartItemInput): Promise<Cart> {
+        return await invoke<Cart>('add_cart_item', { id, item });
+    }
+
+    /**
+     * PUT /carts/:id/items/:item_id - Altera quantidade ou desconto de um item
+     */
+    static async updateItem(id: number, itemId: number, item: { quantidade?: number; desconto?: number }): Promise<Cart> {
+        return await invoke<Cart>('update_cart_item', { id, itemId, item });
+    }
+
+    /**
+     * DELETE /carts/:id/items/:item_id - Remove um item
+     */
+    static async removeItem(id: number, itemId: number): Promise<Cart> {
+        return await invoke<Cart>('remove_cart_item', { id, itemId });
+    }
+
+    /**
+     * PUT /carts/:id/discount - Desconto e acréscimo no total
+     */
+    static async setDiscount(id: number, discount?: number, addition?: number): Promise<Cart> {
+        return await invoke<Cart>('set_cart_discount', { id, discount: { discount, addition } });
+    }
+
+    /**
+     * POST /carts/:id/checkout - Passa para pagamento
+     */
+    static async checkout(id: number): Promise<Cart> {
+        return await invoke<Cart>('checkout_cart', { id });
+    }
+
+    /**
+     * POST /carts/:id/reopen - Volta a aceitar itens (sem pagamentos)
+     */
+    static async reopen(id: number): Promise<Cart> {
+        return await invoke<Cart>('reopen_cart', { id });
+    }
+
+    /**
+     * POST /carts/:id/payments - Registra um pagamento
+     */
+    static async addPayment(id: number, code: string, totalPagamento: number, name?: string): Promise<Cart> {
+        return await invoke<Cart>('add_cart_payment', { id, payment: { code, name, total_pagamento: totalPagamento } });
+    }
+
+    /**
+     * DELETE /carts/:id/payments/:payment_id - Remove um pagamento
+     */
+    static async removePayment(id: number, paymentId: number): Promise<Cart> {
+        return await invoke<Cart>('remove_cart_payment', { id, paymentId });
+    }
+
+    /**
+     * POST /carts/:id/finalize - Grava a venda e encerra o carrinho
+     */
+    static async finalize(id: number, fiscal: CartFiscal): Promise<CartFinalized> {
+        return await invoke<CartFinalized>('finalize_cart', { id, fiscal });
+    }
+
+    /**
+     * POST /carts/:id/abort - Descarta o carrinho
+     */
+    static async abort(id: number): Promise<Cart> {
+        return await invoke<Cart>('abort_cart', { id });
+    }
+}
@@ -0,0 +1,78 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Product } from './products';
+
+export interface Category {
+    id?: number;
+    name: string;
+    parent_id?: number | null;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface CategoryTree extends Category {
+    /** Nomes desde a raiz, ex. 'Bebidas > Refrigerantes' */
+    path: string;
+    children: CategoryTree[];
+}
+
+/**
+ * API de Categorias - árvore de seções e grupos de produtos
+ */
+export class CategoriesApi {
+    /**
+     * POST /categories - Cria uma categoria (sem pai = seção principal)
+     */
+    static async create(name: string, parentId?: number): Promise<Category> {
+        return await invoke<Category>('create_category', { name, parentId });
+    }
+
+    /**
+     * GET /categories - Lista todas as categorias, por nome
+     */
+    static async findAll(): Promise<Category[]> {
+        return await invoke<Category[]>('get_categories');
+    }
+
+    /**
+     * GET /categories/tree - Árvore a partir das seções principais
+     */
+    static async tree(): Promise<CategoryTree[]> {
+        return await invoke<CategoryTree[]>('get_category_tree');
+    }
+
+    /**
+     * GET /categories/:id - Busca categoria por ID
+     */
+    static async findById(id: number): Promise<Category | null> {
+        return await invoke<Category | null>('get_category', { id });
+    }
+
+    /**
+     * PUT /categories/:id - Renomeia ou move (parentId 0 = raiz)
+     */
+    static async update(id: number, data: { name?: string; parentId?: number }): Promise<Category> {
+        return await invoke<Category>('update_category', { id, ...data });
+    }
+
+    /**
+     * DELETE /categories/:id - Remove; produtos e subcategorias vão para
+     * `reassignTo` ou para a categoria pai
+     */
+    static async delete(id: number, reassignTo?: number): Promise<void> {
+        await invoke('delete_category', { id, reassignTo });
+    }
+
+    /**
+     * GET /categories/:id/products - Produtos da categoria e das subcategorias
+     */
+    static async products(id: number, query?: { subcategories?: boolean; active?: boolean }): Promise<Product[]> {
+        return await invoke<Product[]>('get_category_products', { id, query });
+    }
+
+    /**
+     * POST /categories/reassign - Move produtos (categoryId ausente ou 0 tira da categoria)
+     */
+    static async reassign(productIds: number[], categoryId?: number): Promise<Product[]> {
+        return await invoke<Product[]>('reassign_products', { productIds, categoryId });
+    }
+}
@@ -1,6 +1,17 @@
 import { invoke } from "@tauri-apps/api/core";
 
 // Types
+/** O que fazer quando uma venda deixaria o saldo negativo (com `controle_estoque = 1`) */
+export type NegativeStockPolicy = 'block' | 'warn' | 'allow';
+
+/** Leiaute das etiquetas de balança: 2 + código + ... + valor + dígito verificador */
+export interface ScaleLayout {
+  code_digits: number;
+  value_digits: number;
+  /** price: total em centavos; weight: peso em gramas */
+  kind: 'price' | 'weight';
+}
+
 export interface ConfigEntity {
   id: string;
   flow_base_url: string;
@@ -38,6 +49,12 @@ export interface ConfigEntity {
   habilitar_contador_nao: number;
   controle_estoque: number;
   modelo: number;
+  estoque_negativo: NegativeStockPolicy;
+  balanca: ScaleLayout;
+  /** Diferença aceita como arredondamento na conferência dos totais da venda (padrão 0.01) */
+  tolerancia_total: number;
+  /** Fuso horário da loja, nome IANA (padrão America/Sao_Paulo); vale a partir do próximo início do app */
+  timezone: string;
 }
 
 export interface CreateOrUpdateConfigDto {
@@ -75,6 +92,10 @@ export interface CreateOrUpdateConfigDto {
   habilitar_contador_nao?: number;
   controle_estoque?: number;
   modelo?: number;
+  estoque_negativo?: NegativeStockPolicy;
+  balanca?: ScaleLayout;
+  tolerancia_total?: number;
+  timezone?: string;
 }
 
 export interface UpdatePercentConfigDto {
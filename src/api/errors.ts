@@ -0,0 +1,30 @@
+/**
+ * Erro retornado pelos comandos Tauri e pelo servidor HTTP
+ * (veja docs/API_ERROS.md)
+ */
+export type AppErrorCode =
+    | 'NOT_FOUND'
+    | 'VALIDATION_ERROR'
+    | 'CONFLICT'
+    | 'FORBIDDEN'
+    | 'DATABASE_ERROR'
+    | 'EXTERNAL_SERVICE_ERROR'
+    | 'INTERNAL_ERROR';
+
+export interface FieldError {
+    field: string;
+    message: string;
+}
+
+export interface AppError {
+    code: AppErrorCode;
+    message: string;
+    fields?: FieldError[];
+}
+
+export function isAppError(error: unknown): error is AppError {
+    return typeof error === 'object'
+        && error !== null
+        && typeof (error as AppError).code === 'string'
+        && typeof (error as AppError).message === 'string';
+}
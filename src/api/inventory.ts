@@ -0,0 +1,103 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type InventoryStatus = 'open' | 'closed' | 'cancelled';
+
+export interface InventorySession {
+    id?: number;
+    name: string;
+    status: InventoryStatus;
+    user?: string | null;
+    opened_at: string;
+    closed_at?: string | null;
+}
+
+export interface InventoryLine {
+    product_id: number;
+    code: string;
+    name: string;
+    unit: string;
+    price: number;
+    expected: number;          // saldo congelado na abertura
+    counted: number | null;    // null enquanto não contado
+    scans: number;
+    variance: number | null;   // counted - expected
+    variance_value: number | null;
+}
+
+export interface InventoryReport {
+    session: InventorySession;
+    lines: InventoryLine[];
+    total_products: number;
+    counted_products: number;
+    divergent_products: number;
+    surplus_value: number;
+    shortage_value: number;
+    variance_value: number;
+}
+
+export interface InventoryScan {
+    barcode: string;
+    quantity?: number;   // padrão 1 (ou o peso da etiqueta); negativa desfaz
+    replace?: boolean;
+}
+
+/**
+ * API de Inventário - contagem física do estoque com ajuste das diferenças
+ */
+export class InventoryApi {
+    /**
+     * POST /inventory - Abre uma sessão de inventário
+     */
+    static async open(name?: string, user?: string): Promise<InventorySession> {
+        return await invoke<InventorySession>('open_inventory', { inventory: { name, user } });
+    }
+
+    /**
+     * GET /inventory - Lista as sessões
+     */
+    static async findAll(status?: InventoryStatus): Promise<InventorySession[]> {
+        return await invoke<InventorySession[]>('get_inventories', { query: { status } });
+    }
+
+    /**
+     * GET /inventory/current - Sessão aberta, se houver
+     */
+    static async current(): Promise<InventorySession | null> {
+        return await invoke<InventorySession | null>('get_current_inventory');
+    }
+
+    /**
+     * GET /inventory/:id - Busca sessão por ID
+     */
+    static async findById(id: number): Promise<InventorySession | null> {
+        return await invoke<InventorySession | null>('get_inventory', { id });
+    }
+
+    /**
+     * POST /inventory/:id/counts - Registra a leitura de um produto
+     */
+    static async scan(id: number, scan: InventoryScan): Promise<InventoryLine> {
+        return await invoke<InventoryLine>('scan_inventory_count', { id, scan });
+    }
+
+    /**
+     * GET /inventory/:id/report - Relatório de divergências
+     */
+    static async report(id: number, differencesOnly = false): Promise<InventoryReport> {
+        return await invoke<InventoryReport>('get_inventory_report', { id, query: { differencesOnly } });
+    }
+
+    /**
+     * POST /inventory/:id/close - Encerra a sessão lançando os ajustes
+     */
+    static async close(id: number, zeroUncounted = false, user?: string): Promise<InventoryReport> {
+        return await invoke<InventoryReport>('close_inventory', { id, options: { zero_uncounted: zeroUncounted, user } });
+    }
+
+    /**
+     * POST /inventory/:id/cancel - Descarta a sessão sem mexer no estoque
+     */
+    static async cancel(id: number): Promise<InventorySession> {
+        return await invoke<InventorySession>('cancel_inventory', { id });
+    }
+}
@@ -0,0 +1,66 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface Lot {
+    id?: number;
+    product_id: number;
+    code: string;              // número do lote, único por produto
+    expires_at?: string | null; // YYYY-MM-DD
+    quantity: number;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface ProductLots {
+    product_id: number;
+    code: string;
+    track_lots: number;
+    balance: number;
+    lots_total: number;        // igual a balance com o controle de lotes ligado
+    lots: Lot[];               // na ordem de saída (vence antes, sai antes)
+}
+
+export interface ExpiringLot extends Lot {
+    product_code: string;
+    product_name: string;
+    days_left: number;         // negativo para lotes vencidos
+}
+
+/**
+ * API de Lotes - validade e saída FEFO dos produtos com controle de lotes
+ */
+export class LotsApi {
+    /**
+     * POST /lots - Cadastra um lote sem quantidade (a quantidade entra por movimentos)
+     */
+    static async create(productId: number, code: string, expiresAt?: string): Promise<Lot> {
+        return await invoke<Lot>('create_lot', { lot: { product_id: productId, code, expires_at: expiresAt } });
+    }
+
+    /**
+     * GET /lots/:id - Busca lote por ID
+     */
+    static async findById(id: number): Promise<Lot | null> {
+        return await invoke<Lot | null>('get_lot', { id });
+    }
+
+    /**
+     * PUT /lots/:id - Corrige o código ou a validade
+     */
+    static async update(id: number, data: { code?: string; expires_at?: string }): Promise<Lot> {
+        return await invoke<Lot>('update_lot', { id, lot: data });
+    }
+
+    /**
+     * GET /products/:id/lots - Lotes do produto com saldo e soma dos lotes
+     */
+    static async byProduct(productId: number): Promise<ProductLots> {
+        return await invoke<ProductLots>('get_product_lots', { productId });
+    }
+
+    /**
+     * GET /lots/expiring - Lotes com saldo que vencem em até `days` dias (padrão 30), incluindo os vencidos
+     */
+    static async expiring(days?: number): Promise<ExpiringLot[]> {
+        return await invoke<ExpiringLot[]>('get_expiring_lots', { query: { days } });
+    }
+}
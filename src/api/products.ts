@@ -6,10 +6,156 @@ export interface Product {
     name: string;
     active: number;
     balance: number;
+    price: number;
+    unit: string;
+    purchase_unit?: string | null;
+    purchase_factor: number;
+    gtin?: string | null;
+    ncm?: string | null;
+    cest?: string | null;
+    cfop?: string | null;
+    origem: number;
+    cst?: string | null;
+    category_id?: number | null;
+    track_lots: number;      // 1 = saldo controlado por lotes com validade
+    min_stock?: number | null; // estoque mínimo (GET /stock/low)
+    max_stock?: number | null; // estoque máximo (limita a sugestão de compra)
     created_at: string;
     updated_at: string;
 }
 
+/**
+ * Dados comerciais e fiscais do produto.
+ * Campos ausentes não são alterados; string vazia limpa gtin/ncm/cest/cfop/cst.
+ */
+export interface ProductFiscal {
+    price?: number;
+    /** Unidade de venda e de estoque */
+    unit?: string;
+    /** Unidade de compra ('' limpa); `purchase_factor` unidades de venda cada */
+    purchase_unit?: string;
+    purchase_factor?: number;
+    gtin?: string;
+    ncm?: string;
+    cest?: string;
+    cfop?: string;
+    origem?: number;
+    cst?: string;
+    /** 0 tira o produto da categoria */
+    category_id?: number;
+    /** 1 liga o controle de lotes; o saldo atual vira o lote 'INICIAL' */
+    track_lots?: number;
+    /** Estoque mínimo na unidade de venda (0 remove) */
+    min_stock?: number;
+    /** Estoque máximo na unidade de venda (0 remove) */
+    max_stock?: number;
+}
+
+/** Componente informado ao definir um kit */
+export interface KitComponentInput {
+    product_id: number;
+    quantity: number;        // por kit
+}
+
+export interface KitComponent extends KitComponentInput {
+    code: string;
+    name: string;
+    unit: string;
+    price: number;
+    balance: number;
+    available: number;       // kits que este saldo permite montar
+}
+
+export interface Kit {
+    kit_id: number;
+    components: KitComponent[];
+    cost: number;            // soma dos componentes pelo preço de cadastro
+    available: number;       // kits completos disponíveis
+}
+
+export type MovementType = 'sale' | 'cancellation' | 'purchase' | 'adjustment' | 'loss' | 'return';
+
+/** Dados opcionais do movimento de estoque gravado ao alterar o saldo */
+export interface BalanceMovement {
+    type?: MovementType;
+    reference?: string;
+    user?: string;
+    /** Unidade da quantidade (padrão: a de venda); a de compra é convertida */
+    unit?: string;
+    /** Lote (produtos com controle de lotes); entradas criam o lote */
+    lot?: string;
+    /** Validade do lote criado, YYYY-MM-DD */
+    expiresAt?: string;
+}
+
+/** Parâmetros da busca por código ou nome */
+export interface ProductSearchQuery {
+    q: string;
+    /** A partir de 1 (padrão 1) */
+    page?: number;
+    /** Padrão 20, máximo 100 */
+    perPage?: number;
+    active?: boolean;
+}
+
+export interface ProductPage {
+    items: Product[];
+    /** Total encontrado, somando todas as páginas */
+    total: number;
+    page: number;
+    per_page: number;
+}
+
+/** Valor embutido numa etiqueta de balança: preço total ou peso em kg */
+export type ScaleValue = { kind: 'price'; value: number } | { kind: 'weight'; value: number };
+
+export interface BarcodeLookup {
+    barcode: string;
+    matched_by: 'code' | 'gtin' | 'scale';
+    product: Product;
+    /** Etiqueta decodificada, quando matched_by = 'scale' */
+    label: { code: string; value: ScaleValue } | null;
+    /** Item pronto para create_venda (quantidade e total vindos da etiqueta) */
+    item: Record<string, unknown>;
+}
+
+export type SheetFormat = 'csv' | 'xlsx';
+
+export interface ProductImportOptions {
+    /** Detectado pelo conteúdo quando ausente */
+    format?: SheetFormat;
+    /** Campo -> cabeçalho da coluna, para planilhas com nomes diferentes */
+    columns?: Partial<Record<Exclude<keyof ProductFiscal, 'category_id'> | 'code' | 'name' | 'active' | 'balance', string>>;
+    /** Só valida, sem gravar */
+    dryRun?: boolean;
+}
+
+export interface ProductImportRow {
+    /** Linha na planilha (o cabeçalho é a linha 1) */
+    line: number;
+    code: string;
+    action: 'create' | 'update' | 'unchanged' | 'error';
+    product_id: number | null;
+    errors: { field: string; message: string }[];
+}
+
+export interface ProductImportReport {
+    dry_run: boolean;
+    total: number;
+    created: number;
+    updated: number;
+    unchanged: number;
+    failed: number;
+    rows: ProductImportRow[];
+}
+
+export interface ExportFile {
+    filename: string;
+    content_type: string;
+    /** Conteúdo em base64 */
+    content: string;
+}
+
 /**
  * API de Produtos - Ponte com Rust (Tauri Commands)
  * Corresponde ao ProdutosController do NestJS
@@ -18,8 +164,8 @@ export class ProductsApi {
     /**
      * POST /products - Cria um novo produto
      */
-    static async create(code: string, name: string): Promise<Product> {
-        return await invoke<Product>('create_product', { code, name });
+    static async create(code: string, name: string, fiscal?: ProductFiscal): Promise<Product> {
+        return await invoke<Product>('create_product', { code, name, fiscal: fiscal ?? null });
     }
 
     /**
@@ -36,6 +182,41 @@ export class ProductsApi {
         return await invoke<Product | null>('get_product_by_code', { code });
     }
 
+    /**
+     * GET /products/search - Busca por parte do código ou do nome (sem acentos), paginada
+     */
+    static async search(query: ProductSearchQuery): Promise<ProductPage> {
+        return await invoke<ProductPage>('search_products', { query });
+    }
+
+    /**
+     * GET /products/barcode/:barcode - Lê um código de barras: código, GTIN ou etiqueta de balança
+     */
+    static async lookupBarcode(barcode: string): Promise<BarcodeLookup> {
+        return await invoke<BarcodeLookup>('lookup_barcode', { barcode });
+    }
+
+    /**
+     * POST /products/import - Importa produtos de um CSV ou XLSX, criando ou atualizando pelo código
+     */
+    static async importFile(file: Blob, options: ProductImportOptions = {}): Promise<ProductImportReport> {
+        const bytes = new Uint8Array(await file.arrayBuffer());
+        let binary = '';
+        for (let i = 0; i < bytes.length; i += 0x8000) {
+            binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+        }
+        return await invoke<ProductImportReport>('import_products', {
+            dto: { ...options, content: btoa(binary) },
+        });
+    }
+
+    /**
+     * GET /products/export - Exporta o cadastro com saldos
+     */
+    static async exportFile(format: SheetFormat = 'csv'): Promise<ExportFile> {
+        return await invoke<ExportFile>('export_products', { format });
+    }
+
     /**
      * GET /products - Lista todos os produtos
      */
@@ -55,19 +236,21 @@ export class ProductsApi {
      */
     static async update(
         id: number,
-        data: {
+        data: ProductFiscal & {
             code?: string;
             name?: string;
             active?: number;
             balance?: number;
         }
     ): Promise<Product> {
+        const { code, name, active, balance, ...fiscal } = data;
         return await invoke<Product>('update_product', {
             id,
-            code: data.code ?? null,
-            name: data.name ?? null,
-            active: data.active ?? null,
-            balance: data.balance ?? null,
+            code: code ?? null,
+            name: name ?? null,
+            active: active ?? null,
+            balance: balance ?? null,
+            fiscal,
         });
     }
 
@@ -81,14 +264,46 @@ export class ProductsApi {
     /**
      * PATCH /products/:id/increment - Incrementa saldo
      */
-    static async incrementBalance(id: number, amount: number): Promise<Product> {
-        return await invoke<Product>('increment_product_balance', { id, amount });
+    static async incrementBalance(id: number, amount: number, movement: BalanceMovement = {}): Promise<Product> {
+        return await invoke<Product>('increment_product_balance', {
+            id,
+            amount,
+            kind: movement.type ?? null,
+            reference: movement.reference ?? null,
+            user: movement.user ?? null,
+            unit: movement.unit ?? null,
+            lot: movement.lot ?? null,
+            expiresAt: movement.expiresAt ?? null,
+        });
     }
 
     /**
      * PATCH /products/:id/decrement - Decrementa saldo
      */
-    static async decrementBalance(id: number, amount: number): Promise<Product> {
-        return await invoke<Product>('decrement_product_balance', { id, amount });
+    static async decrementBalance(id: number, amount: number, movement: BalanceMovement = {}): Promise<Product> {
+        return await invoke<Product>('decrement_product_balance', {
+            id,
+            amount,
+            kind: movement.type ?? null,
+            reference: movement.reference ?? null,
+            user: movement.user ?? null,
+            unit: movement.unit ?? null,
+            lot: movement.lot ?? null,
+            expiresAt: movement.expiresAt ?? null,
+        });
+    }
+
+    /**
+     * GET /products/:id/components - Composição do kit com custo e disponibilidade
+     */
+    static async getComponents(id: number): Promise<Kit> {
+        return await invoke<Kit>('get_product_components', { id });
+    }
+
+    /**
+     * PUT /products/:id/components - Define os componentes (lista vazia desfaz o kit)
+     */
+    static async setComponents(id: number, components: KitComponentInput[]): Promise<Kit> {
+        return await invoke<Kit>('set_product_components', { id, components });
     }
 }
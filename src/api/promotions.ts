@@ -0,0 +1,94 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface PromotionTier {
+    min_quantity: number;
+    percent: number;
+}
+
+export type PromotionRule =
+    | { type: 'price'; product_id: number; price: number }
+    | { type: 'buy_pay'; product_id: number; buy: number; pay: number }
+    | { type: 'progressive'; product_id: number; tiers: PromotionTier[] }
+    | { type: 'category_percent'; category_id: number; percent: number };
+
+export type Promotion = PromotionRule & {
+    id?: number;
+    name: string;
+    starts_at?: string | null;
+    ends_at?: string | null;   // exclusivo
+    active: number;
+    created_at: string;
+    updated_at: string;
+};
+
+/** Corpo de criação/substituição; datas sem fuso são do fuso da loja */
+export type SavePromotion = PromotionRule & {
+    name: string;
+    starts_at?: string;
+    ends_at?: string;
+    active?: boolean;
+};
+
+/** Item como enviado na venda; os campos ausentes vêm do cadastro */
+export interface PricingItem {
+    produto_code: string;
+    quantidade: number;
+    preco_unitario?: number;
+    desconto?: number;
+    promotion_id?: number | null;
+    [field: string]: unknown;
+}
+
+export interface Pricing {
+    items: PricingItem[];
+    subtotal: number;
+    desconto: number;
+    total: number;
+}
+
+/**
+ * API de Promoções - preços programados e descontos por quantidade
+ */
+export class PromotionsApi {
+    /**
+     * POST /promotions - Cria uma promoção
+     */
+    static async create(promotion: SavePromotion): Promise<Promotion> {
+        return await invoke<Promotion>('create_promotion', { promotion });
+    }
+
+    /**
+     * GET /promotions - Lista as promoções (current = só as em vigor)
+     */
+    static async findAll(current = false): Promise<Promotion[]> {
+        return await invoke<Promotion[]>('get_promotions', { query: { current } });
+    }
+
+    /**
+     * GET /promotions/:id - Busca promoção por ID
+     */
+    static async findById(id: number): Promise<Promotion | null> {
+        return await invoke<Promotion | null>('get_promotion', { id });
+    }
+
+    /**
+     * PUT /promotions/:id - Substitui uma promoção
+     */
+    static async update(id: number, promotion: SavePromotion): Promise<Promotion> {
+        return await invoke<Promotion>('update_promotion', { id, promotion });
+    }
+
+    /**
+     * DELETE /promotions/:id - Remove uma promoção
+     */
+    static async delete(id: number): Promise<void> {
+        await invoke('delete_promotion', { id });
+    }
+
+    /**
+     * POST /promotions/preview - Precifica os itens do caixa sem gravar
+     */
+    static async preview(items: PricingItem[], dhEmi?: string): Promise<Pricing> {
+        return await invoke<Pricing>('preview_pricing', { preview: { items, dh_emi: dhEmi } });
+    }
+}
@@ -0,0 +1,133 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { MovementType } from './products';
+
+export interface StockMovement {
+    id?: number;
+    product_id: number;
+    type: MovementType;
+    quantity: number;        // negativa nas saídas
+    balance_after: number;
+    reference?: string | null;
+    user?: string | null;
+    lot_id?: number | null;  // lote movimentado (produtos com controle de lotes)
+    origin: 'tauri' | 'http' | 'system';
+    created_at: string;
+}
+
+export interface Kardex {
+    product_id: number;
+    code: string;
+    name: string;
+    opening_balance: number;
+    total_in: number;
+    total_out: number;
+    closing_balance: number;
+    movements: StockMovement[];
+}
+
+export interface StockDiscrepancy {
+    product_id: number;
+    code: string;
+    balance: number;
+    ledger_balance: number;
+}
+
+/** Produto com saldo abaixo do estoque mínimo */
+export interface LowStock {
+    product_id: number;
+    code: string;
+    name: string;
+    unit: string;
+    balance: number;
+    min_stock: number;
+    max_stock?: number | null;
+}
+
+export interface ReorderSuggestion {
+    product_id: number;
+    code: string;
+    name: string;
+    unit: string;
+    balance: number;
+    min_stock?: number | null;
+    max_stock?: number | null;
+    sold: number;            // vendas menos cancelamentos no período
+    daily_average: number;
+    target: number;          // média x coverage_days, entre o mínimo e o máximo
+    suggested: number;       // na unidade de venda
+    purchase_unit?: string | null;
+    purchase_quantity?: number | null; // na unidade de compra, arredondada para cima
+}
+
+export interface ReorderReport {
+    dt_init: string;
+    dt_fim: string;
+    days: number;
+    coverage_days: number;
+    items: ReorderSuggestion[];
+}
+
+/** Evento emitido quando uma operação (venda, perda, ajuste...) deixa produtos abaixo do mínimo */
+export const LOW_STOCK_EVENT = 'low-stock';
+
+/**
+ * API de Estoque - livro de movimentos e kardex
+ */
+export class StockApi {
+    /**
+     * POST /stock/movements - Registra um movimento (quantidade sem sinal)
+     */
+    static async register(movement: {
+        productId: number;
+        type: MovementType;
+        quantity: number;
+        reference?: string;
+        user?: string;
+        /** Unidade da quantidade (padrão: a de venda do produto) */
+        unit?: string;
+        /** Lote; entradas criam o lote, saídas sem lote seguem a validade */
+        lot?: string;
+        /** Validade do lote criado, YYYY-MM-DD */
+        expiresAt?: string;
+    }): Promise<StockMovement> {
+        return await invoke<StockMovement>('register_stock_movement', { movement });
+    }
+
+    /**
+     * GET /stock/kardex/:product_id - Kardex do produto no período (YYYY-MM-DD)
+     */
+    static async kardex(productId: number, dtInit?: string, dtFim?: string): Promise<Kardex> {
+        return await invoke<Kardex>('get_kardex', { productId, query: { dtInit, dtFim } });
+    }
+
+    /**
+     * GET/POST /stock/reconcile - Confere saldos contra o livro; `fix` corrige
+     */
+    static async reconcile(fix = false): Promise<StockDiscrepancy[]> {
+        return await invoke<StockDiscrepancy[]>('reconcile_stock', { fix });
+    }
+
+    /**
+     * GET /stock/low - Produtos com saldo abaixo do estoque mínimo
+     */
+    static async lowStock(): Promise<LowStock[]> {
+        return await invoke<LowStock[]>('get_low_stock');
+    }
+
+    /**
+     * GET /stock/reorder - Sugestão de compra pela média diária de vendas
+     * (período padrão: últimos 30 dias; cobertura padrão: 30 dias)
+     */
+    static async reorder(query?: { dtInit?: string; dtFim?: string; coverageDays?: number }): Promise<ReorderReport> {
+        return await invoke<ReorderReport>('get_reorder_suggestions', { query });
+    }
+
+    /**
+     * Escuta o evento `low-stock`, emitido quando uma venda ou movimento de
+     * estoque deixa produtos abaixo do mínimo. Retorna a função que cancela a escuta.
+     */
+    static async onLowStock(handler: (products: LowStock[]) => void): Promise<UnlistenFn> {
+        return await listen<LowStock[]>(LOW_STOCK_EVENT, (event) => handler(event.payload));
+    }
+}
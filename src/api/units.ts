@@ -0,0 +1,49 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface Unit {
+    code: string;            // 'UN', 'KG', 'CX'... até 6 letras ou dígitos, em maiúsculas
+    description: string;
+    decimals: number;        // casas decimais aceitas nas quantidades (0 a 3)
+    created_at: string;
+    updated_at: string;
+}
+
+/**
+ * API de Unidades de Medida
+ */
+export class UnitsApi {
+    /**
+     * POST /units - Cadastra uma unidade (decimals padrão 0)
+     */
+    static async create(code: string, description: string, decimals?: number): Promise<Unit> {
+        return await invoke<Unit>('create_unit', { code, description, decimals });
+    }
+
+    /**
+     * GET /units - Lista as unidades por código
+     */
+    static async findAll(): Promise<Unit[]> {
+        return await invoke<Unit[]>('get_units');
+    }
+
+    /**
+     * GET /units/:code - Busca uma unidade pelo código
+     */
+    static async findByCode(code: string): Promise<Unit | null> {
+        return await invoke<Unit | null>('get_unit', { code });
+    }
+
+    /**
+     * PUT /units/:code - Atualiza a descrição ou as casas decimais
+     */
+    static async update(code: string, data: { description?: string; decimals?: number }): Promise<Unit> {
+        return await invoke<Unit>('update_unit', { code, ...data });
+    }
+
+    /**
+     * DELETE /units/:code - Remove uma unidade que nenhum produto usa
+     */
+    static async delete(code: string): Promise<void> {
+        await invoke('delete_unit', { code });
+    }
+}
@@ -0,0 +1,71 @@
+import { useState, useEffect } from 'react';
+import { CartsApi, Cart } from '../api/carts';
+
+interface CartRecoveryPromptProps {
+    /** Chamado com o carrinho quando o operador decide continuar a venda */
+    onResume: (cart: Cart) => void;
+}
+
+/**
+ * Ao iniciar o caixa, procura um carrinho que ficou em andamento (queda ou
+ * reinício do aplicativo) e pergunta se a venda deve ser retomada ou abortada
+ */
+export function CartRecoveryPrompt({ onResume }: CartRecoveryPromptProps) {
+    const [cart, setCart] = useState<Cart | null>(null);
+    const [busy, setBusy] = useState(false);
+
+    // Consulta o carrinho em andamento ao montar o componente
+    useEffect(() => {
+        loadCurrent();
+    }, []);
+
+    const loadCurrent = async () => {
+        try {
+            setCart(await CartsApi.current());
+        } catch (error) {
+            console.error('Erro ao buscar carrinho em andamento:', error);
+        }
+    };
+
+    const handleResume = () => {
+        if (!cart) return;
+        onResume(cart);
+        setCart(null);
+    };
+
+    const handleAbort = async () => {
+        if (!cart) return;
+        try {
+            setBusy(true);
+            await CartsApi.abort(cart.id);
+            setCart(null);
+        } catch (error) {
+            console.error('Erro ao abortar carrinho:', error);
+            // O carrinho pode ter mudado (ex. finalizado em outro caixa)
+            loadCurrent();
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    if (!cart) return null;
+
+    return (
+        <div role="dialog" aria-modal="true">
+            <h2>Venda em andamento</h2>
+            <p>
+                O carrinho #{cart.id}
+                {cart.user ? ` de ${cart.user}` : ''} ficou aberto desde{' '}
+                {new Date(cart.opened_at).toLocaleString()}, com {cart.items.length} item(ns)
+                e total de {cart.total.toFixed(2)}
+                {cart.status === 'payment' ? `, já em pagamento (pago ${cart.paid.toFixed(2)})` : ''}.
+            </p>
+            <button onClick={handleResume} disabled={busy}>
+                Retomar venda
+            </button>
+            <button onClick={handleAbort} disabled={busy}>
+                Abortar venda
+            </button>
+        </div>
+    );
+}